@@ -0,0 +1,34 @@
+use nanocl_error::io::IoResult;
+use nanocld_client::stubs::audit::AuditLog;
+
+use crate::{
+  config::CliConfig,
+  models::{AuditArg, AuditFilter, AuditRow, GenericListOpts},
+};
+
+use super::{GenericCommand, GenericCommandLs};
+
+impl GenericCommand for AuditArg {
+  fn object_name() -> &'static str {
+    "audit"
+  }
+}
+
+impl GenericCommandLs for AuditArg {
+  type Item = AuditRow;
+  type Args = AuditArg;
+  type ApiItem = AuditLog;
+
+  fn get_key(item: &Self::Item) -> String {
+    item.key.clone()
+  }
+}
+
+/// Function that execute when running `nanocl audit`
+pub async fn exec_audit(
+  cli_conf: &CliConfig,
+  opts: &GenericListOpts<AuditFilter>,
+) -> IoResult<()> {
+  let args = &AuditArg;
+  AuditArg::exec_ls(&cli_conf.client, args, opts).await
+}
@@ -1,87 +1,41 @@
-use nanocl_error::io::{IoError, IoResult};
+use std::{
+  io::Write,
+  path::{Path, PathBuf},
+};
+
+use futures::StreamExt;
+use ntex::util::Bytes;
+
+use nanocl_error::{
+  http::HttpResult,
+  io::{FromIo, IoError, IoResult},
+};
 use nanocld_client::stubs::{
-  cargo_spec::CargoSpecPartial, generic::GenericFilterNsp, job::JobPartial,
-  resource::ResourcePartial, secret::SecretPartial, statefile::Statefile,
+  auth::RolePartial,
+  cargo_spec::CargoSpecPartial,
+  generic::{GenericClause, GenericFilter, GenericFilterNsp},
+  job::JobPartial,
+  namespace::NamespacePartial,
+  resource::ResourcePartial,
+  resource_kind::ResourceKindPartial,
+  secret::SecretPartial,
+  statefile::Statefile,
   vm_spec::VmSpecPartial,
+  volume::VolumePartial,
 };
 
-use crate::{config::CliConfig, models::BackupOpts, utils};
+use crate::{
+  config::CliConfig,
+  models::{BackupManifest, BackupOpts, BACKUP_VERSION},
+  utils,
+};
 
-pub async fn exec_backup(
-  cli_conf: &CliConfig,
-  opts: &BackupOpts,
-) -> IoResult<()> {
-  let dir_path = opts
-    .output_dir
-    .clone()
-    .unwrap_or(std::env::current_dir()?.to_string_lossy().to_string());
-  std::fs::create_dir_all(&dir_path)?;
-  // Backup namespaces
-  let namespaces = cli_conf.client.list_namespace(None).await?;
-  for namespace in namespaces {
-    let file_path = format!("{}/{}.yml", dir_path, namespace.name);
-    let token = format!("namespace/{}", namespace.name);
-    let pg_style = utils::progress::create_spinner_style(&token, "green");
-    let pg = utils::progress::create_progress("(processing)", &pg_style);
-    pg.set_message("(processing: cargoes)");
-    let cargoes = cli_conf
-      .client
-      .list_cargo(Some(&GenericFilterNsp {
-        namespace: Some(namespace.name.clone()),
-        ..Default::default()
-      }))
-      .await?
-      .iter()
-      .map(|cargo| cargo.spec.clone().into())
-      .collect::<Vec<CargoSpecPartial>>();
-    pg.set_message("(processing: virtual machines)");
-    let vms = cli_conf
-      .client
-      .list_vm(Some(&GenericFilterNsp {
-        namespace: Some(namespace.name.clone()),
-        ..Default::default()
-      }))
-      .await?
-      .iter()
-      .map(|vm| vm.spec.clone().into())
-      .collect::<Vec<VmSpecPartial>>();
-    pg.set_message(format!("(writing statefile: {}.yml)", namespace.name));
-    let state_file = Statefile {
-      api_version: cli_conf.client.version.clone(),
-      sub_states: None,
-      args: None,
-      group: None,
-      namespace: Some(namespace.name.clone()),
-      secrets: None,
-      resources: None,
-      cargoes: Some(cargoes),
-      virtual_machines: Some(vms),
-      jobs: None,
-    };
-    let data = serde_yaml::to_string(&state_file).map_err(|err| {
-      IoError::interrupted("Backup state", err.to_string().as_str())
-    })?;
-    pg.finish_with_message(format!("(backup: {}.yml)", namespace.name));
-    if std::path::Path::new(&file_path).exists() && !opts.skip_confirm {
-      utils::dialog::confirm("File already exist override ?")?;
-    }
-    std::fs::write(&file_path, data)?;
-  }
-  // Backup jobs
-  let file_path = format!("{}/jobs.yml", dir_path);
-  let pg_style = utils::progress::create_spinner_style("jobs", "green");
-  let pg = utils::progress::create_progress("(processing)", &pg_style);
-  let jobs = cli_conf
-    .client
-    .list_job(None)
-    .await?
-    .iter()
-    .map(|job| job.spec.clone().into())
-    .collect::<Vec<JobPartial>>();
-  if std::path::Path::new(&file_path).exists() && !opts.skip_confirm {
-    utils::dialog::confirm("File already exist override ?")?;
-  }
-  let state_file = Statefile {
+/// Name of the directory holding the content of the archive
+pub const ARCHIVE_ROOT: &str = "backup";
+
+/// Create an empty statefile with the api version of the client
+pub fn empty_statefile(cli_conf: &CliConfig) -> Statefile {
+  Statefile {
     api_version: cli_conf.client.version.clone(),
     sub_states: None,
     args: None,
@@ -91,74 +45,270 @@ pub async fn exec_backup(
     resources: None,
     cargoes: None,
     virtual_machines: None,
-    jobs: Some(jobs),
-  };
-  let data = serde_yaml::to_string(&state_file).map_err(|err| {
+    jobs: None,
+    volumes: None,
+  }
+}
+
+/// Serialize an object in yaml into a file
+pub fn write_yaml<T>(path: &Path, data: &T) -> IoResult<()>
+where
+  T: serde::Serialize,
+{
+  let data = serde_yaml::to_string(data).map_err(|err| {
     IoError::interrupted("Backup state", err.to_string().as_str())
   })?;
-  std::fs::write(&file_path, data)?;
-  pg.finish_with_message("(backup: jobs.yml)");
-  // Backup secrets
-  let file_path = format!("{}/secrets.yml", dir_path);
-  let pg_style = utils::progress::create_spinner_style("secrets", "green");
+  std::fs::write(path, data)
+    .map_err(|err| err.map_err_context(|| path.display().to_string()))?;
+  Ok(())
+}
+
+/// Write a stream of bytes downloaded from the daemon into a file
+async fn download<S>(stream: S, path: &Path) -> IoResult<()>
+where
+  S: futures::Stream<Item = HttpResult<Bytes>>,
+{
+  let mut file = std::fs::File::create(path)
+    .map_err(|err| err.map_err_context(|| path.display().to_string()))?;
+  let mut stream = Box::pin(stream);
+  while let Some(chunk) = stream.next().await {
+    let chunk = chunk.map_err(|err| {
+      IoError::interrupted("Download", err.to_string().as_str())
+    })?;
+    file.write_all(&chunk)?;
+  }
+  Ok(())
+}
+
+/// Write every object of the daemon and the data of the volumes
+/// and vm images into a directory
+async fn backup_dir(
+  cli_conf: &CliConfig,
+  opts: &BackupOpts,
+  dir: &Path,
+) -> IoResult<BackupManifest> {
+  let client = &cli_conf.client;
+  let passphrase = match &opts.passphrase_file {
+    Some(path) => Some(utils::crypto::read_passphrase(path)?),
+    None => None,
+  };
+  // Backup resource kinds with all their versions
+  let pg_style = utils::progress::create_spinner_style("kinds", "green");
   let pg = utils::progress::create_progress("(processing)", &pg_style);
-  let secrets = cli_conf
-    .client
-    .list_secret(None)
+  let mut kinds = Vec::new();
+  for kind in client.list_resource_kind(None).await? {
+    let inspect = client.inspect_resource_kind(&kind.name).await?;
+    for version in inspect.versions.into_iter().rev() {
+      kinds.push(ResourceKindPartial {
+        name: kind.name.clone(),
+        version: version.version,
+        metadata: version.metadata,
+        data: version.data,
+      });
+    }
+  }
+  write_yaml(&dir.join("resource_kinds.yml"), &kinds)?;
+  pg.finish_with_message("(backup: resource_kinds.yml)");
+  // Backup namespaces with their quota and limit range
+  let pg_style = utils::progress::create_spinner_style("namespaces", "green");
+  let pg = utils::progress::create_progress("(processing)", &pg_style);
+  let mut namespaces = Vec::new();
+  for namespace in client.list_namespace(None).await? {
+    let inspect = client.inspect_namespace(&namespace.name).await?;
+    namespaces.push(NamespacePartial {
+      name: namespace.name,
+      metadata: None,
+      quota: inspect.quota,
+      limit_range: inspect.limit_range,
+    });
+  }
+  write_yaml(&dir.join("namespaces.yml"), &namespaces)?;
+  pg.finish_with_message("(backup: namespaces.yml)");
+  // Backup roles, the built-in admin role is created by the daemon
+  let roles = client
+    .list_role(None)
     .await?
     .into_iter()
-    .map(|secret| secret.into())
-    .collect::<Vec<SecretPartial>>();
-  if std::path::Path::new(&file_path).exists() && !opts.skip_confirm {
-    utils::dialog::confirm("File already exist override ?")?;
+    .filter(|role| role.name != "admin")
+    .map(|role| RolePartial {
+      name: role.name,
+      rules: role.rules,
+    })
+    .collect::<Vec<_>>();
+  write_yaml(&dir.join("roles.yml"), &roles)?;
+  // Backup secrets, they are listed encrypted, backup their decrypted data
+  let pg_style = utils::progress::create_spinner_style("secrets", "green");
+  let pg = utils::progress::create_progress("(processing)", &pg_style);
+  let mut secrets = Vec::new();
+  for secret in client.list_secret(None).await? {
+    let secret = client.reveal_secret(&secret.name).await?;
+    secrets.push(SecretPartial::from(secret));
   }
   let state_file = Statefile {
-    api_version: cli_conf.client.version.clone(),
-    sub_states: None,
-    args: None,
-    group: None,
-    namespace: None,
     secrets: Some(secrets),
-    resources: None,
-    cargoes: None,
-    virtual_machines: None,
-    jobs: None,
+    ..empty_statefile(cli_conf)
   };
-  let data = serde_yaml::to_string(&state_file).map_err(|err| {
-    IoError::interrupted("Backup state", err.to_string().as_str())
-  })?;
-  std::fs::write(&file_path, data)?;
-  pg.finish_with_message("(backup: secrets.yml)");
+  match &passphrase {
+    Some(passphrase) => {
+      let data = serde_yaml::to_string(&state_file).map_err(|err| {
+        IoError::interrupted("Backup state", err.to_string().as_str())
+      })?;
+      let sealed = utils::crypto::seal(passphrase, data.as_bytes())?;
+      std::fs::write(dir.join("secrets.yml.enc"), sealed)?;
+      pg.finish_with_message("(backup: secrets.yml.enc)");
+    }
+    None => {
+      write_yaml(&dir.join("secrets.yml"), &state_file)?;
+      pg.finish_with_message("(backup: secrets.yml)");
+    }
+  }
   // Backup resources
-  let file_path = format!("{}/resources.yml", dir_path);
-  let pg_style = utils::progress::create_spinner_style("resources", "green");
-  let pg = utils::progress::create_progress("(processing)", &pg_style);
-  let resources = cli_conf
-    .client
+  let resources = client
     .list_resource(None)
     .await?
+    .into_iter()
+    .map(ResourcePartial::from)
+    .collect::<Vec<_>>();
+  let state_file = Statefile {
+    resources: Some(resources),
+    ..empty_statefile(cli_conf)
+  };
+  write_yaml(&dir.join("resources.yml"), &state_file)?;
+  // Backup jobs
+  let jobs = client
+    .list_job(None)
+    .await?
     .iter()
-    .map(|resource| resource.clone().into())
-    .collect::<Vec<ResourcePartial>>();
-  if std::path::Path::new(&file_path).exists() && !opts.skip_confirm {
+    .map(|job| job.spec.clone().into())
+    .collect::<Vec<JobPartial>>();
+  let state_file = Statefile {
+    jobs: Some(jobs),
+    ..empty_statefile(cli_conf)
+  };
+  write_yaml(&dir.join("jobs.yml"), &state_file)?;
+  // Backup base vm images, snapshots are recreated with their vm
+  let filter =
+    GenericFilter::new().r#where("kind", GenericClause::Eq("Base".to_owned()));
+  let images = client.list_vm_image(Some(&filter)).await?;
+  std::fs::create_dir_all(dir.join("vm_images"))?;
+  for image in &images {
+    let token = format!("vm_image/{}", image.name);
+    let pg_style = utils::progress::create_spinner_style(&token, "green");
+    let pg = utils::progress::create_progress("(downloading)", &pg_style);
+    let stream = client.export_vm_image(&image.name).await?;
+    download(stream, &dir.join(format!("vm_images/{}.img", image.name)))
+      .await?;
+    pg.finish_with_message("(done)");
+  }
+  // Backup the objects of each namespace and the data of their volumes
+  std::fs::create_dir_all(dir.join("namespaces"))?;
+  for namespace in &namespaces {
+    let name = &namespace.name;
+    let token = format!("namespace/{name}");
+    let pg_style = utils::progress::create_spinner_style(&token, "green");
+    let pg = utils::progress::create_progress("(processing)", &pg_style);
+    let filter = GenericFilterNsp {
+      namespace: Some(name.clone()),
+      ..Default::default()
+    };
+    pg.set_message("(processing: cargoes)");
+    let cargoes = client
+      .list_cargo(Some(&filter))
+      .await?
+      .iter()
+      .map(|cargo| cargo.spec.clone().into())
+      .collect::<Vec<CargoSpecPartial>>();
+    pg.set_message("(processing: virtual machines)");
+    let vms = client
+      .list_vm(Some(&filter))
+      .await?
+      .iter()
+      .map(|vm| vm.spec.clone().into())
+      .collect::<Vec<VmSpecPartial>>();
+    pg.set_message("(processing: volumes)");
+    let volumes = client.list_volume(Some(&filter)).await?;
+    let volume_dir = dir.join(format!("volumes/{name}"));
+    std::fs::create_dir_all(&volume_dir)?;
+    for volume in &volumes {
+      pg.set_message(format!("(downloading: volume {})", volume.name));
+      let snapshot = client.snapshot_volume(&volume.name, Some(name)).await?;
+      let stream = client
+        .export_volume_snapshot(&volume.name, &snapshot.name, Some(name))
+        .await?;
+      download(stream, &volume_dir.join(format!("{}.tar", volume.name)))
+        .await?;
+    }
+    let state_file = Statefile {
+      namespace: Some(name.clone()),
+      cargoes: Some(cargoes),
+      virtual_machines: Some(vms),
+      volumes: Some(volumes.into_iter().map(VolumePartial::from).collect()),
+      ..empty_statefile(cli_conf)
+    };
+    write_yaml(&dir.join(format!("namespaces/{name}.yml")), &state_file)?;
+    pg.finish_with_message(format!("(backup: namespaces/{name}.yml)"));
+  }
+  let manifest = BackupManifest {
+    version: BACKUP_VERSION,
+    api_version: client.version.clone(),
+    created_at: chrono::Utc::now().naive_utc(),
+    encrypted: passphrase.is_some(),
+    namespaces: namespaces.into_iter().map(|n| n.name).collect(),
+  };
+  write_yaml(&dir.join("manifest.yml"), &manifest)?;
+  Ok(manifest)
+}
+
+/// Create a temporary directory to build or extract an archive
+pub fn create_work_dir(prefix: &str) -> IoResult<PathBuf> {
+  let dir = std::env::temp_dir().join(format!(
+    "{prefix}-{}-{}",
+    std::process::id(),
+    chrono::Utc::now().timestamp_millis()
+  ));
+  std::fs::create_dir_all(&dir)
+    .map_err(|err| err.map_err_context(|| dir.display().to_string()))?;
+  Ok(dir)
+}
+
+/// Compress a directory into a tar.gz archive
+fn write_archive(dir: &Path, path: &str) -> IoResult<()> {
+  let file = std::fs::File::create(path)
+    .map_err(|err| err.map_err_context(|| path.to_owned()))?;
+  let encoder =
+    flate2::write::GzEncoder::new(file, flate2::Compression::default());
+  let mut builder = tar::Builder::new(encoder);
+  builder
+    .append_dir_all(ARCHIVE_ROOT, dir)
+    .map_err(|err| err.map_err_context(|| path.to_owned()))?;
+  builder
+    .into_inner()
+    .and_then(|encoder| encoder.finish())
+    .map_err(|err| err.map_err_context(|| path.to_owned()))?;
+  Ok(())
+}
+
+/// Function that execute when running `nanocl backup`
+pub async fn exec_backup(
+  cli_conf: &CliConfig,
+  opts: &BackupOpts,
+) -> IoResult<()> {
+  let path = opts.output.clone().unwrap_or_else(|| {
+    format!(
+      "nanocl-backup-{}.tar.gz",
+      chrono::Local::now().format("%Y%m%d%H%M%S")
+    )
+  });
+  if Path::new(&path).exists() && !opts.skip_confirm {
     utils::dialog::confirm("File already exist override ?")?;
   }
-  let state_file = Statefile {
-    api_version: cli_conf.client.version.clone(),
-    sub_states: None,
-    args: None,
-    group: None,
-    namespace: None,
-    secrets: None,
-    resources: Some(resources),
-    cargoes: None,
-    virtual_machines: None,
-    jobs: None,
+  let dir = create_work_dir("nanocl-backup")?;
+  let res = match backup_dir(cli_conf, opts, &dir).await {
+    Ok(_) => write_archive(&dir, &path),
+    Err(err) => Err(err),
   };
-  let data = serde_yaml::to_string(&state_file).map_err(|err| {
-    IoError::interrupted("Backup state", err.to_string().as_str())
-  })?;
-  std::fs::write(&file_path, data)?;
-  pg.finish_with_message("(backup: resources.yml)");
+  let _ = std::fs::remove_dir_all(&dir);
+  res?;
+  println!("{path}");
   Ok(())
 }
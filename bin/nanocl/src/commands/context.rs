@@ -1,7 +1,9 @@
 use nanocl_error::io::IoResult;
 
 use crate::config::CliConfig;
-use crate::models::{Context, ContextArg, ContextCommand, ContextRow};
+use crate::models::{
+  Context, ContextArg, ContextCommand, ContextCreateOpts, ContextRow,
+};
 use crate::utils;
 
 /// Function that execute when running `nanocl context ls`
@@ -41,6 +43,14 @@ fn exec_context_from(path: &str) -> IoResult<()> {
   Ok(())
 }
 
+/// Function that execute when running `nanocl context create`
+/// Will create a context from the given host and token
+fn exec_context_create(opts: &ContextCreateOpts) -> IoResult<()> {
+  let context: Context = opts.clone().into();
+  Context::write(&context)?;
+  Ok(())
+}
+
 /// Function that execute when running `nanocl context`
 pub async fn exec_context(
   cli_conf: &CliConfig,
@@ -51,6 +61,7 @@ pub async fn exec_context(
     ContextCommand::List => exec_context_list(context)?,
     ContextCommand::Use { name } => exec_context_use(name)?,
     ContextCommand::From { path } => exec_context_from(path)?,
+    ContextCommand::Create(opts) => exec_context_create(opts)?,
   }
   Ok(())
 }
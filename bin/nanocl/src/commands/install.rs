@@ -188,6 +188,7 @@ pub async fn exec_install(args: &InstallOpts) -> IoResult<()> {
           ContextEndpoint {
             host: format!("unix://{home_dir}/.nanocl/run/nanocl.sock"),
             ssl: None,
+            token: None,
           },
         );
         map
@@ -1,3 +1,4 @@
+mod audit;
 mod backup;
 mod cargo;
 mod context;
@@ -12,16 +13,21 @@ mod namespace;
 mod node;
 mod process;
 mod resource;
+mod restore;
+mod role;
 mod secret;
 mod state;
+mod token;
 #[cfg(not(target_os = "windows"))]
 mod uninstall;
 mod version;
 mod vm;
 mod vm_image;
+mod volume;
 
 pub use generic::*;
 
+pub use audit::exec_audit;
 pub use backup::exec_backup;
 pub use cargo::exec_cargo;
 pub use context::exec_context;
@@ -35,9 +41,13 @@ pub use namespace::exec_namespace;
 pub use node::exec_node;
 pub use process::exec_process;
 pub use resource::exec_resource;
+pub use restore::exec_restore;
+pub use role::exec_role;
 pub use secret::exec_secret;
 pub use state::exec_state;
+pub use token::exec_token;
 #[cfg(not(target_os = "windows"))]
 pub use uninstall::exec_uninstall;
 pub use version::exec_version;
 pub use vm::exec_vm;
+pub use volume::exec_volume;
@@ -5,7 +5,7 @@ use crate::{
   config::CliConfig,
   models::{
     GenericDefaultOpts, NamespaceArg, NamespaceCommand, NamespaceCreateOpts,
-    NamespaceRow,
+    NamespaceRow, NamespaceUpdateOpts,
   },
 };
 use nanocld_client::stubs::namespace::NamespaceSummary;
@@ -46,6 +46,16 @@ async fn exec_namespace_create(
   Ok(())
 }
 
+/// Function that execute when running `nanocl namespace update`
+async fn exec_namespace_update(
+  client: &NanocldClient,
+  opts: &NamespaceUpdateOpts,
+) -> IoResult<()> {
+  let item = client.put_namespace(&opts.name, &opts.into()).await?;
+  println!("{}", item.name);
+  Ok(())
+}
+
 /// Function that execute when running `nanocl namespace`
 pub async fn exec_namespace(
   cli_conf: &CliConfig,
@@ -57,6 +67,7 @@ pub async fn exec_namespace(
       NamespaceArg::exec_ls(client, args, opts).await
     }
     NamespaceCommand::Create(opts) => exec_namespace_create(client, opts).await,
+    NamespaceCommand::Update(opts) => exec_namespace_update(client, opts).await,
     NamespaceCommand::Inspect(opts) => {
       NamespaceArg::exec_inspect(cli_conf, opts, None).await
     }
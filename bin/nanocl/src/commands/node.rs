@@ -1,8 +1,12 @@
-use nanocl_error::io::IoResult;
+use std::time::Duration;
+
+use nanocl_error::io::{IoError, IoResult};
+use nanocld_client::NanocldClient;
 
 use crate::{
   config::CliConfig,
-  models::{NodeArg, NodeCommand, NodeRow},
+  models::{NodeArg, NodeCommand, NodeDrainOpts, NodeRow},
+  utils,
 };
 
 use super::{GenericCommand, GenericCommandLs};
@@ -23,10 +27,50 @@ impl GenericCommandLs for NodeArg {
   }
 }
 
+/// Function that execute when running `nanocl node drain`
+/// it waits for the node to be drained unless detached
+async fn exec_node_drain(
+  client: &NanocldClient,
+  opts: &NodeDrainOpts,
+) -> IoResult<()> {
+  client.drain_node(&opts.name).await?;
+  if opts.detach {
+    return Ok(());
+  }
+  let token = format!("node/{}", opts.name);
+  let pg_style = utils::progress::create_spinner_style(&token, "green");
+  let pg = utils::progress::create_progress("(draining)", &pg_style);
+  loop {
+    ntex::time::sleep(Duration::from_secs(2)).await;
+    let node = client
+      .list_node()
+      .await?
+      .into_iter()
+      .find(|node| node.name == opts.name)
+      .ok_or_else(|| {
+        IoError::not_found("Node", &format!("{} was removed", opts.name))
+      })?;
+    if !node.draining {
+      // The outcome of the drain is reported in the events of the node
+      pg.finish_with_message("(done)");
+      return Ok(());
+    }
+  }
+}
+
 /// Function that execute when running `nanocl node`
 pub async fn exec_node(cli_conf: &CliConfig, args: &NodeArg) -> IoResult<()> {
   let client = &cli_conf.client;
   match &args.command {
     NodeCommand::List(opts) => NodeArg::exec_ls(client, args, opts).await,
+    NodeCommand::Cordon(opts) => {
+      client.cordon_node(&opts.name).await?;
+      Ok(())
+    }
+    NodeCommand::Uncordon(opts) => {
+      client.uncordon_node(&opts.name).await?;
+      Ok(())
+    }
+    NodeCommand::Drain(opts) => exec_node_drain(client, opts).await,
   }
 }
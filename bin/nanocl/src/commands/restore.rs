@@ -0,0 +1,536 @@
+use std::path::{Path, PathBuf};
+
+use futures::StreamExt;
+use indicatif::ProgressBar;
+use tokio_util::codec;
+
+use nanocl_error::io::{FromIo, IoError, IoResult};
+use nanocld_client::{
+  stubs::{
+    auth::RolePartial,
+    cargo_spec::CargoSpecPartial,
+    job::JobPartial,
+    namespace::{NamespacePartial, NamespaceUpdate},
+    resource::{ResourcePartial, ResourceUpdate},
+    resource_kind::ResourceKindPartial,
+    secret::{SecretPartial, SecretUpdate},
+    statefile::Statefile,
+    system::{EventActorKind, NativeEventAction},
+    vm_spec::{VmSpecPartial, VmSpecUpdate},
+    volume::{VolumePartial, VolumeRestore},
+  },
+  NanocldClient,
+};
+
+use crate::{
+  config::CliConfig,
+  models::{
+    BackupManifest, RestoreAction, RestoreOpts, RestoreRow, BACKUP_VERSION,
+  },
+  utils,
+};
+
+use super::backup::{create_work_dir, ARCHIVE_ROOT};
+
+/// State of a restore shared by the steps replaying each kind of object
+struct RestoreCtx<'a> {
+  client: &'a NanocldClient,
+  opts: &'a RestoreOpts,
+  /// Directory where the archive have been extracted
+  dir: PathBuf,
+  /// Actions planned when running with `--dry-run`
+  rows: Vec<RestoreRow>,
+}
+
+impl RestoreCtx<'_> {
+  /// Record what restoring an object does
+  /// and return a progress bar when the action must be applied
+  fn plan(
+    &mut self,
+    kind: &str,
+    namespace: Option<&str>,
+    name: &str,
+    action: RestoreAction,
+  ) -> Option<ProgressBar> {
+    if self.opts.dry_run {
+      self.rows.push(RestoreRow {
+        kind: kind.to_owned(),
+        namespace: namespace.unwrap_or_default().to_owned(),
+        name: name.to_owned(),
+        action,
+      });
+      return None;
+    }
+    let token = format!("{kind}/{name}");
+    let pg_style = utils::progress::create_spinner_style(&token, "green");
+    let pg = utils::progress::create_progress("(submitting)", &pg_style);
+    if action == RestoreAction::Unchanged {
+      pg.finish_with_message("(unchanged)");
+      return None;
+    }
+    Some(pg)
+  }
+
+  /// Read a yaml file of the archive
+  fn read<T>(&self, path: &str) -> IoResult<T>
+  where
+    T: serde::de::DeserializeOwned,
+  {
+    let path = self.dir.join(path);
+    let data = std::fs::read_to_string(&path)
+      .map_err(|err| err.map_err_context(|| path.display().to_string()))?;
+    serde_yaml::from_str(&data).map_err(|err| {
+      IoError::invalid_data(path.display().to_string(), err.to_string())
+    })
+  }
+}
+
+/// Compare two objects by their serialized value
+fn same<T>(a: &T, b: &T) -> bool
+where
+  T: serde::Serialize,
+{
+  serde_json::to_value(a).ok() == serde_json::to_value(b).ok()
+}
+
+/// Open a file of the archive as a stream of bytes to upload it
+async fn upload_stream(
+  path: &Path,
+) -> IoResult<
+  impl futures::Stream<Item = Result<ntex::util::Bytes, std::io::Error>>,
+> {
+  let file = tokio::fs::File::open(path)
+    .await
+    .map_err(|err| err.map_err_context(|| path.display().to_string()))?;
+  Ok(
+    codec::FramedRead::new(file, codec::BytesCodec::new()).map(|r| {
+      let r = r?;
+      Ok(ntex::util::Bytes::copy_from_slice(&r))
+    }),
+  )
+}
+
+async fn restore_resource_kinds(ctx: &mut RestoreCtx<'_>) -> IoResult<()> {
+  let kinds: Vec<ResourceKindPartial> = ctx.read("resource_kinds.yml")?;
+  for kind in kinds {
+    let name = format!("{}@{}", kind.name, kind.version);
+    let action = match ctx
+      .client
+      .inspect_resource_kind_version(&kind.name, &kind.version)
+      .await
+    {
+      Ok(_) => RestoreAction::Unchanged,
+      Err(_) => RestoreAction::Create,
+    };
+    let Some(pg) = ctx.plan("kind", None, &name, action) else {
+      continue;
+    };
+    ctx.client.create_resource_kind(&kind).await?;
+    pg.finish_with_message("(created)");
+  }
+  Ok(())
+}
+
+async fn restore_namespaces(ctx: &mut RestoreCtx<'_>) -> IoResult<()> {
+  let namespaces: Vec<NamespacePartial> = ctx.read("namespaces.yml")?;
+  for namespace in namespaces {
+    let update = NamespaceUpdate {
+      quota: namespace.quota.clone(),
+      limit_range: namespace.limit_range.clone(),
+    };
+    let action = match ctx.client.inspect_namespace(&namespace.name).await {
+      Err(_) => RestoreAction::Create,
+      Ok(inspect) => {
+        let current = NamespaceUpdate {
+          quota: inspect.quota,
+          limit_range: inspect.limit_range,
+        };
+        if same(&current, &update) {
+          RestoreAction::Unchanged
+        } else {
+          RestoreAction::Update
+        }
+      }
+    };
+    let Some(pg) = ctx.plan("namespace", None, &namespace.name, action) else {
+      continue;
+    };
+    if action == RestoreAction::Create {
+      ctx.client.create_namespace(&namespace.name).await?;
+    }
+    if update.quota.is_some() || update.limit_range.is_some() {
+      ctx.client.put_namespace(&namespace.name, &update).await?;
+    }
+    pg.finish_with_message(format!("({action}d)"));
+  }
+  Ok(())
+}
+
+async fn restore_roles(ctx: &mut RestoreCtx<'_>) -> IoResult<()> {
+  let roles: Vec<RolePartial> = ctx.read("roles.yml")?;
+  for role in roles {
+    let action = match ctx.client.inspect_role(&role.name).await {
+      Err(_) => RestoreAction::Create,
+      Ok(inspect) if inspect.rules == role.rules => RestoreAction::Unchanged,
+      Ok(_) => RestoreAction::Update,
+    };
+    let Some(pg) = ctx.plan("role", None, &role.name, action) else {
+      continue;
+    };
+    if action == RestoreAction::Update {
+      ctx.client.delete_role(&role.name).await?;
+    }
+    ctx.client.create_role(&role).await?;
+    pg.finish_with_message(format!("({action}d)"));
+  }
+  Ok(())
+}
+
+async fn restore_secrets(
+  ctx: &mut RestoreCtx<'_>,
+  manifest: &BackupManifest,
+) -> IoResult<()> {
+  let state_file: Statefile = if manifest.encrypted {
+    let Some(path) = &ctx.opts.passphrase_file else {
+      return Err(IoError::invalid_input(
+        "Restore",
+        "secrets are encrypted, --passphrase-file is required",
+      ));
+    };
+    let passphrase = utils::crypto::read_passphrase(path)?;
+    let sealed = std::fs::read(ctx.dir.join("secrets.yml.enc"))?;
+    let data = utils::crypto::open(&passphrase, &sealed)?;
+    serde_yaml::from_slice(&data).map_err(|err| {
+      IoError::invalid_data("secrets.yml.enc", err.to_string().as_str())
+    })?
+  } else {
+    ctx.read("secrets.yml")?
+  };
+  for secret in state_file.secrets.unwrap_or_default() {
+    let action = match ctx.client.reveal_secret(&secret.name).await {
+      Err(_) => RestoreAction::Create,
+      Ok(inspect) if same(&SecretPartial::from(inspect.clone()), &secret) => {
+        RestoreAction::Unchanged
+      }
+      Ok(_) => RestoreAction::Update,
+    };
+    let Some(pg) = ctx.plan("secret", None, &secret.name, action) else {
+      continue;
+    };
+    if action == RestoreAction::Create {
+      ctx.client.create_secret(&secret).await?;
+    } else {
+      let update: SecretUpdate = secret.clone().into();
+      ctx.client.patch_secret(&secret.name, &update).await?;
+    }
+    pg.finish_with_message(format!("({action}d)"));
+  }
+  Ok(())
+}
+
+async fn restore_vm_images(ctx: &mut RestoreCtx<'_>) -> IoResult<()> {
+  let images = ctx.client.list_vm_image(None).await?;
+  let mut entries = std::fs::read_dir(ctx.dir.join("vm_images"))?
+    .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+    .filter(|path| path.extension().unwrap_or_default() == "img")
+    .collect::<Vec<_>>();
+  entries.sort();
+  for path in entries {
+    let name = path
+      .file_stem()
+      .unwrap_or_default()
+      .to_string_lossy()
+      .to_string();
+    let action = if images.iter().any(|image| image.name == name) {
+      RestoreAction::Unchanged
+    } else {
+      RestoreAction::Create
+    };
+    let Some(pg) = ctx.plan("vm_image", None, &name, action) else {
+      continue;
+    };
+    pg.set_message("(uploading)");
+    let stream = upload_stream(&path).await?;
+    ctx.client.import_vm_image(&name, Box::pin(stream)).await?;
+    pg.finish_with_message("(created)");
+  }
+  Ok(())
+}
+
+async fn restore_volumes(
+  ctx: &mut RestoreCtx<'_>,
+  namespace: &str,
+  volumes: &[VolumePartial],
+) -> IoResult<()> {
+  for volume in volumes {
+    let exists = ctx
+      .client
+      .inspect_volume(&volume.name, Some(namespace))
+      .await
+      .is_ok();
+    let action = match (exists, ctx.opts.overwrite_volumes) {
+      (false, _) => RestoreAction::Create,
+      (true, true) => RestoreAction::Update,
+      (true, false) => RestoreAction::Unchanged,
+    };
+    let Some(pg) = ctx.plan("volume", Some(namespace), &volume.name, action)
+    else {
+      continue;
+    };
+    if !exists {
+      ctx.client.create_volume(volume, Some(namespace)).await?;
+    }
+    let path = ctx
+      .dir
+      .join(format!("volumes/{namespace}/{}.tar", volume.name));
+    if path.exists() {
+      pg.set_message("(uploading)");
+      let stream = upload_stream(&path).await?;
+      let snapshot = ctx
+        .client
+        .import_volume_snapshot(&volume.name, Box::pin(stream), Some(namespace))
+        .await?;
+      pg.set_message("(restoring)");
+      ctx
+        .client
+        .restore_volume(
+          &volume.name,
+          &VolumeRestore {
+            snapshot: snapshot.name,
+          },
+          Some(namespace),
+        )
+        .await?;
+    }
+    pg.finish_with_message(format!("({action}d)"));
+  }
+  Ok(())
+}
+
+async fn restore_cargoes(
+  ctx: &mut RestoreCtx<'_>,
+  namespace: &str,
+  cargoes: &[CargoSpecPartial],
+) -> IoResult<()> {
+  for cargo in cargoes {
+    let action =
+      match ctx.client.inspect_cargo(&cargo.name, Some(namespace)).await {
+        Err(_) => RestoreAction::Create,
+        Ok(inspect)
+          if CargoSpecPartial::from(inspect.spec.clone()) == *cargo =>
+        {
+          RestoreAction::Unchanged
+        }
+        Ok(_) => RestoreAction::Update,
+      };
+    let Some(pg) = ctx.plan("cargo", Some(namespace), &cargo.name, action)
+    else {
+      continue;
+    };
+    let waiter = utils::process::wait_process_state(
+      &format!("{}.{namespace}", cargo.name),
+      EventActorKind::Cargo,
+      vec![NativeEventAction::Start],
+      ctx.client,
+    )
+    .await?;
+    if action == RestoreAction::Create {
+      ctx.client.create_cargo(cargo, Some(namespace)).await?;
+      pg.set_message("(starting)");
+      ctx
+        .client
+        .start_process("cargo", &cargo.name, Some(namespace))
+        .await?;
+    } else {
+      pg.set_message("(updating)");
+      ctx
+        .client
+        .put_cargo(&cargo.name, cargo, Some(namespace))
+        .await?;
+    }
+    waiter.await??;
+    pg.finish_with_message(format!("({action}d)"));
+  }
+  Ok(())
+}
+
+async fn restore_vms(
+  ctx: &mut RestoreCtx<'_>,
+  namespace: &str,
+  vms: &[VmSpecPartial],
+) -> IoResult<()> {
+  for vm in vms {
+    let action = match ctx.client.inspect_vm(&vm.name, Some(namespace)).await {
+      Err(_) => RestoreAction::Create,
+      Ok(inspect) if VmSpecPartial::from(inspect.spec.clone()) == *vm => {
+        RestoreAction::Unchanged
+      }
+      Ok(_) => RestoreAction::Update,
+    };
+    let Some(pg) = ctx.plan("vm", Some(namespace), &vm.name, action) else {
+      continue;
+    };
+    if action == RestoreAction::Create {
+      ctx.client.create_vm(vm, Some(namespace)).await?;
+      let waiter = utils::process::wait_process_state(
+        &format!("{}.{namespace}", vm.name),
+        EventActorKind::Vm,
+        vec![NativeEventAction::Start],
+        ctx.client,
+      )
+      .await?;
+      pg.set_message("(starting)");
+      ctx
+        .client
+        .start_process("vm", &vm.name, Some(namespace))
+        .await?;
+      waiter.await??;
+    } else {
+      let update: VmSpecUpdate = vm.clone().into();
+      ctx
+        .client
+        .patch_vm(&vm.name, &update, Some(namespace))
+        .await?;
+    }
+    pg.finish_with_message(format!("({action}d)"));
+  }
+  Ok(())
+}
+
+async fn restore_resources(ctx: &mut RestoreCtx<'_>) -> IoResult<()> {
+  let state_file: Statefile = ctx.read("resources.yml")?;
+  for resource in state_file.resources.unwrap_or_default() {
+    let action = match ctx.client.inspect_resource(&resource.name).await {
+      Err(_) => RestoreAction::Create,
+      Ok(inspect) if ResourcePartial::from(inspect.clone()) == resource => {
+        RestoreAction::Unchanged
+      }
+      Ok(_) => RestoreAction::Update,
+    };
+    let Some(pg) = ctx.plan("resource", None, &resource.name, action) else {
+      continue;
+    };
+    if action == RestoreAction::Create {
+      ctx.client.create_resource(&resource).await?;
+    } else {
+      let update: ResourceUpdate = resource.clone().into();
+      ctx.client.put_resource(&resource.name, &update).await?;
+    }
+    pg.finish_with_message(format!("({action}d)"));
+  }
+  Ok(())
+}
+
+async fn restore_jobs(ctx: &mut RestoreCtx<'_>) -> IoResult<()> {
+  let state_file: Statefile = ctx.read("jobs.yml")?;
+  for job in state_file.jobs.unwrap_or_default() {
+    let action = match ctx.client.inspect_job(&job.name).await {
+      Err(_) => RestoreAction::Create,
+      Ok(inspect) if same(&JobPartial::from(inspect.spec.clone()), &job) => {
+        RestoreAction::Unchanged
+      }
+      Ok(_) => RestoreAction::Update,
+    };
+    let Some(pg) = ctx.plan("job", None, &job.name, action) else {
+      continue;
+    };
+    // Jobs can't be updated, they are recreated with their new spec
+    if action == RestoreAction::Update {
+      let waiter = utils::process::wait_process_state(
+        &job.name,
+        EventActorKind::Job,
+        vec![NativeEventAction::Destroy],
+        ctx.client,
+      )
+      .await?;
+      ctx.client.delete_job(&job.name).await?;
+      waiter.await??;
+    }
+    ctx.client.create_job(&job).await?;
+    pg.finish_with_message(format!("({action}d)"));
+  }
+  Ok(())
+}
+
+/// Replay the objects of an extracted archive in dependency order
+async fn restore_dir(ctx: &mut RestoreCtx<'_>) -> IoResult<()> {
+  let manifest: BackupManifest = ctx.read("manifest.yml")?;
+  if manifest.version > BACKUP_VERSION {
+    return Err(IoError::invalid_data(
+      "Restore",
+      &format!(
+        "archive version {} is not supported, upgrade nanocl",
+        manifest.version
+      ),
+    ));
+  }
+  restore_resource_kinds(ctx).await?;
+  restore_namespaces(ctx).await?;
+  restore_roles(ctx).await?;
+  restore_secrets(ctx, &manifest).await?;
+  restore_vm_images(ctx).await?;
+  let mut states = Vec::new();
+  for namespace in &manifest.namespaces {
+    let state_file: Statefile =
+      ctx.read(&format!("namespaces/{namespace}.yml"))?;
+    restore_volumes(
+      ctx,
+      namespace,
+      state_file.volumes.as_deref().unwrap_or_default(),
+    )
+    .await?;
+    states.push((namespace, state_file));
+  }
+  for (namespace, state_file) in &states {
+    restore_cargoes(
+      ctx,
+      namespace,
+      state_file.cargoes.as_deref().unwrap_or_default(),
+    )
+    .await?;
+    restore_vms(
+      ctx,
+      namespace,
+      state_file.virtual_machines.as_deref().unwrap_or_default(),
+    )
+    .await?;
+  }
+  restore_resources(ctx).await?;
+  restore_jobs(ctx).await?;
+  Ok(())
+}
+
+/// Extract a tar.gz archive created by `nanocl backup`
+fn extract_archive(path: &str, dir: &Path) -> IoResult<()> {
+  let file = std::fs::File::open(path)
+    .map_err(|err| err.map_err_context(|| path.to_owned()))?;
+  let decoder = flate2::read::GzDecoder::new(file);
+  tar::Archive::new(decoder)
+    .unpack(dir)
+    .map_err(|err| err.map_err_context(|| path.to_owned()))?;
+  Ok(())
+}
+
+/// Function that execute when running `nanocl restore`
+pub async fn exec_restore(
+  cli_conf: &CliConfig,
+  opts: &RestoreOpts,
+) -> IoResult<()> {
+  let dir = create_work_dir("nanocl-restore")?;
+  let mut ctx = RestoreCtx {
+    client: &cli_conf.client,
+    opts,
+    dir: dir.join(ARCHIVE_ROOT),
+    rows: Vec::new(),
+  };
+  let res = match extract_archive(&opts.path, &dir) {
+    Ok(_) => restore_dir(&mut ctx).await,
+    Err(err) => Err(err),
+  };
+  let _ = std::fs::remove_dir_all(&dir);
+  res?;
+  if opts.dry_run {
+    utils::print::print_table(ctx.rows);
+  }
+  Ok(())
+}
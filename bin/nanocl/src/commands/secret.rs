@@ -1,10 +1,11 @@
 use nanocl_error::io::IoResult;
-use nanocld_client::stubs::secret::Secret;
+use nanocld_client::stubs::secret::{Secret, SecretRotateKey};
 
 use crate::{
   config::CliConfig,
   models::{
-    GenericDefaultOpts, SecretArg, SecretCommand, SecretCreateOpts, SecretRow,
+    GenericDefaultOpts, SecretArg, SecretCommand, SecretCreateOpts,
+    SecretRotateKeyOpts, SecretRow,
   },
 };
 
@@ -43,6 +44,22 @@ async fn exec_secret_create(
   Ok(())
 }
 
+async fn exec_secret_rotate_key(
+  cli_conf: &CliConfig,
+  opts: &SecretRotateKeyOpts,
+) -> IoResult<()> {
+  let key = match &opts.key_file {
+    Some(file) => Some(std::fs::read_to_string(file)?.trim().to_owned()),
+    None => None,
+  };
+  let res = cli_conf
+    .client
+    .rotate_secret_key(&SecretRotateKey { key })
+    .await?;
+  println!("{} secrets encrypted with key {}", res.count, res.key_id);
+  Ok(())
+}
+
 /// Function that execute when running `nanocl secret`
 pub async fn exec_secret(
   cli_conf: &CliConfig,
@@ -59,5 +76,8 @@ pub async fn exec_secret(
       SecretArg::exec_inspect(cli_conf, opts, None).await
     }
     SecretCommand::Create(opts) => exec_secret_create(cli_conf, opts).await,
+    SecretCommand::RotateKey(opts) => {
+      exec_secret_rotate_key(cli_conf, opts).await
+    }
   }
 }
@@ -38,6 +38,7 @@ use nanocld_client::{
     statefile::Statefile,
     system::NativeEventAction,
     vm_spec::{VmSpecPartial, VmSpecUpdate},
+    volume::VolumePartial,
   },
   NanocldClient,
 };
@@ -48,7 +49,7 @@ use crate::{
     CargoArg, Context, DisplayFormat, GenericDefaultOpts,
     GenericRemoveForceOpts, GenericRemoveOpts, JobArg, ResourceArg, SecretArg,
     StateApplyOpts, StateArg, StateCommand, StateLogsOpts, StateRef,
-    StateRemoveOpts, StateRoot, VmArg,
+    StateRemoveOpts, StateRoot, VmArg, VolumeArg,
   },
   utils,
 };
@@ -259,6 +260,7 @@ fn gen_client(
           .ssl
           .clone(),
         version: Some(api_version.clone()),
+        token: cli_conf.client.token.clone(),
       })?
     }
     _ => {
@@ -614,7 +616,7 @@ async fn state_apply(
       let pg = utils::progress::create_progress("(submitting)", &pg_style);
       let metadata = insert_nanocl_group(&secret.metadata, &nanocl_group);
       secret.metadata = Some(metadata);
-      match client.inspect_secret(&secret.name).await {
+      match client.reveal_secret(&secret.name).await {
         Err(_) => {
           client.create_secret(&secret).await?;
           pg.set_message("(created)");
@@ -634,6 +636,27 @@ async fn state_apply(
       pg.finish_with_message("(done)");
     }
   }
+  if let Some(volumes) = &state_file.data.volumes {
+    for volume in volumes.iter() {
+      let mut volume = volume.to_owned();
+      let token = format!("volume/{}", volume.name);
+      let pg_style = utils::progress::create_spinner_style(&token, "green");
+      let pg = utils::progress::create_progress("(submitting)", &pg_style);
+      let metadata = insert_nanocl_group(&volume.metadata, &nanocl_group);
+      volume.metadata = Some(metadata);
+      // Volumes hold data and are never recreated once they exist
+      if client
+        .inspect_volume(&volume.name, Some(&namespace))
+        .await
+        .is_ok()
+      {
+        pg.finish_with_message("(unchanged)");
+        continue;
+      }
+      client.create_volume(&volume, Some(&namespace)).await?;
+      pg.finish_with_message("(created)");
+    }
+  }
   if let Some(jobs) = &state_file.data.jobs {
     for job in jobs.iter() {
       let mut job = job.to_owned();
@@ -844,6 +867,16 @@ async fn remove_orphans(
     .iter()
     .map(|vm| vm.spec.clone().into())
     .collect();
+  let old_volumes: Vec<VolumePartial> = cli_conf
+    .client
+    .list_volume(Some(&GenericFilterNsp {
+      filter: Some(filter.clone()),
+      namespace: state.data.namespace.clone(),
+    }))
+    .await?
+    .iter()
+    .map(|volume| volume.clone().into())
+    .collect();
   let old_resources: Vec<ResourcePartial> = cli_conf
     .client
     .list_resource(Some(&filter))
@@ -869,6 +902,12 @@ async fn remove_orphans(
       .filter(|v| !vms.iter().any(|nv| nv.name == v.name))
       .collect::<Vec<_>>()
   });
+  let removed_volumes = state.data.volumes.as_ref().map(|volumes| {
+    old_volumes
+      .into_iter()
+      .filter(|v| !volumes.iter().any(|nv| nv.name == v.name))
+      .collect::<Vec<_>>()
+  });
   let removed_resources = state.data.resources.as_ref().map(|resources| {
     old_resources
       .into_iter()
@@ -883,6 +922,7 @@ async fn remove_orphans(
       cargoes: removed_cargoes,
       virtual_machines: removed_vms,
       resources: removed_resources,
+      volumes: removed_volumes,
       ..state.data.clone()
     },
     root: state.root.clone(),
@@ -1013,6 +1053,13 @@ async fn state_remove(
     let _ =
       VmArg::exec_rm(client, &gen_rm_opts, Some(namespace.to_owned())).await;
   }
+  if let Some(volumes) = &state_file.data.volumes {
+    gen_rm_opts.keys =
+      volumes.iter().map(|volume| volume.name.clone()).collect();
+    let _ =
+      VolumeArg::exec_rm(client, &gen_rm_opts, Some(namespace.to_owned()))
+        .await;
+  }
   if let Some(resources) = &state_file.data.resources {
     gen_rm_opts.keys = resources
       .iter()
@@ -0,0 +1,53 @@
+use nanocl_error::io::IoResult;
+use nanocld_client::stubs::auth::Token;
+
+use crate::{
+  config::CliConfig,
+  models::{
+    GenericDefaultOpts, TokenArg, TokenCommand, TokenCreateOpts, TokenRow,
+  },
+};
+
+use super::{GenericCommand, GenericCommandLs, GenericCommandRm};
+
+impl GenericCommand for TokenArg {
+  fn object_name() -> &'static str {
+    "tokens"
+  }
+}
+
+impl GenericCommandLs for TokenArg {
+  type Item = TokenRow;
+  type Args = TokenArg;
+  type ApiItem = Token;
+
+  fn get_key(item: &Self::Item) -> String {
+    item.name.clone()
+  }
+}
+
+impl GenericCommandRm<GenericDefaultOpts, String> for TokenArg {}
+
+/// Create a token and print its value, it can't be retrieved later
+async fn exec_token_create(
+  cli_conf: &CliConfig,
+  opts: &TokenCreateOpts,
+) -> IoResult<()> {
+  let token = opts.clone().try_into()?;
+  let created = cli_conf.client.create_token(&token).await?;
+  println!("{}", created.value);
+  Ok(())
+}
+
+/// Function that execute when running `nanocl token`
+pub async fn exec_token(cli_conf: &CliConfig, args: &TokenArg) -> IoResult<()> {
+  match &args.command {
+    TokenCommand::List(opts) => {
+      TokenArg::exec_ls(&cli_conf.client, args, opts).await
+    }
+    TokenCommand::Remove(opts) => {
+      TokenArg::exec_rm(&cli_conf.client, opts, None).await
+    }
+    TokenCommand::Create(opts) => exec_token_create(cli_conf, opts).await,
+  }
+}
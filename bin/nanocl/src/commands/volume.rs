@@ -0,0 +1,165 @@
+use nanocl_error::io::IoResult;
+use nanocld_client::stubs::{
+  generic::{GenericFilter, GenericListQueryNsp, GenericNspQuery},
+  volume::{Volume, VolumeInspect, VolumePartial, VolumeRestore},
+};
+
+use crate::{
+  config::CliConfig,
+  models::{
+    GenericDefaultOpts, GenericRemoveOpts, VolumeArg, VolumeCommand,
+    VolumeCreateOpts, VolumeNameOpts, VolumeRestoreOpts, VolumeRow,
+    VolumeSnapshotRow,
+  },
+  utils,
+};
+
+use super::{
+  GenericCommand, GenericCommandInspect, GenericCommandLs, GenericCommandRm,
+};
+
+impl GenericCommand for VolumeArg {
+  fn object_name() -> &'static str {
+    "volumes"
+  }
+}
+
+impl GenericCommandLs for VolumeArg {
+  type Item = VolumeRow;
+  type Args = VolumeArg;
+  type ApiItem = Volume;
+
+  fn get_key(item: &Self::Item) -> String {
+    item.name.clone()
+  }
+
+  fn transform_filter(
+    args: &Self::Args,
+    filter: &GenericFilter,
+  ) -> impl serde::Serialize {
+    GenericListQueryNsp::try_from(filter.clone())
+      .unwrap()
+      .with_namespace(args.namespace.as_deref())
+  }
+}
+
+impl GenericCommandRm<GenericDefaultOpts, GenericNspQuery> for VolumeArg {
+  fn get_query(
+    _opts: &GenericRemoveOpts<GenericDefaultOpts>,
+    namespace: Option<String>,
+  ) -> Option<GenericNspQuery>
+  where
+    GenericNspQuery: serde::Serialize,
+  {
+    Some(GenericNspQuery::new(namespace.as_deref()))
+  }
+}
+
+impl GenericCommandInspect for VolumeArg {
+  type ApiItem = VolumeInspect;
+}
+
+/// Execute the `nanocl volume create` command to create a new volume
+async fn exec_volume_create(
+  cli_conf: &CliConfig,
+  args: &VolumeArg,
+  opts: &VolumeCreateOpts,
+) -> IoResult<()> {
+  let volume = VolumePartial {
+    name: opts.name.clone(),
+    kind: opts.kind.clone(),
+    metadata: None,
+  };
+  let volume = cli_conf
+    .client
+    .create_volume(&volume, args.namespace.as_deref())
+    .await?;
+  println!("{}", volume.key);
+  Ok(())
+}
+
+/// Execute the `nanocl volume snapshot` command to snapshot a volume
+async fn exec_volume_snapshot(
+  cli_conf: &CliConfig,
+  args: &VolumeArg,
+  opts: &VolumeNameOpts,
+) -> IoResult<()> {
+  let snapshot = cli_conf
+    .client
+    .snapshot_volume(&opts.name, args.namespace.as_deref())
+    .await?;
+  println!("{}", snapshot.name);
+  Ok(())
+}
+
+/// Execute the `nanocl volume snapshots` command to list the snapshots
+async fn exec_volume_snapshots(
+  cli_conf: &CliConfig,
+  args: &VolumeArg,
+  opts: &VolumeNameOpts,
+) -> IoResult<()> {
+  let rows = cli_conf
+    .client
+    .list_volume_snapshot(&opts.name, args.namespace.as_deref())
+    .await?
+    .into_iter()
+    .map(VolumeSnapshotRow::from)
+    .collect::<Vec<_>>();
+  utils::print::print_table(rows);
+  Ok(())
+}
+
+/// Execute the `nanocl volume restore` command to restore a snapshot
+async fn exec_volume_restore(
+  cli_conf: &CliConfig,
+  args: &VolumeArg,
+  opts: &VolumeRestoreOpts,
+) -> IoResult<()> {
+  if !opts.skip_confirm {
+    utils::dialog::confirm(&format!(
+      "Replace the data of volume {} with snapshot {} ?",
+      opts.name, opts.snapshot
+    ))?;
+  }
+  cli_conf
+    .client
+    .restore_volume(
+      &opts.name,
+      &VolumeRestore {
+        snapshot: opts.snapshot.clone(),
+      },
+      args.namespace.as_deref(),
+    )
+    .await?;
+  Ok(())
+}
+
+/// Function that execute when running `nanocl volume`
+pub async fn exec_volume(
+  cli_conf: &CliConfig,
+  args: &VolumeArg,
+) -> IoResult<()> {
+  match &args.command {
+    VolumeCommand::Create(opts) => {
+      exec_volume_create(cli_conf, args, opts).await
+    }
+    VolumeCommand::List(opts) => {
+      VolumeArg::exec_ls(&cli_conf.client, args, opts).await
+    }
+    VolumeCommand::Remove(opts) => {
+      VolumeArg::exec_rm(&cli_conf.client, opts, args.namespace.clone()).await
+    }
+    VolumeCommand::Inspect(opts) => {
+      VolumeArg::exec_inspect(cli_conf, opts, args.namespace.clone()).await
+    }
+    VolumeCommand::Snapshot(opts) => {
+      exec_volume_snapshot(cli_conf, args, opts).await
+    }
+    VolumeCommand::Snapshots(opts) => {
+      exec_volume_snapshots(cli_conf, args, opts).await
+    }
+    VolumeCommand::Restore(opts) => {
+      exec_volume_restore(cli_conf, args, opts).await
+    }
+  }
+}
@@ -63,9 +63,13 @@ fn create_cli_config(cli_args: &Cli) -> IoResult<CliConfig> {
   if let Ok(h) = std::env::var("HOST") {
     host = h;
   }
+  let token = std::env::var("NANOCL_TOKEN")
+    .ok()
+    .or(endpoint.token.clone());
   let client = NanocldClient::connect_to(&ConnectOpts {
     url: host.clone(),
     ssl,
+    token,
     ..Default::default()
   })?;
   Ok(CliConfig {
@@ -85,11 +89,15 @@ async fn execute_arg(cli_args: &Cli) -> IoResult<()> {
     Command::Resource(args) => commands::exec_resource(&cli_conf, args).await,
     Command::Cargo(args) => commands::exec_cargo(&cli_conf, args).await,
     Command::Secret(args) => commands::exec_secret(&cli_conf, args).await,
+    Command::Token(args) => commands::exec_token(&cli_conf, args).await,
+    Command::Role(args) => commands::exec_role(&cli_conf, args).await,
     Command::Event(args) => commands::exec_event(&cli_conf, args).await,
     Command::State(args) => commands::exec_state(&cli_conf, args).await,
     Command::Version => commands::exec_version(&cli_conf).await,
     Command::Vm(args) => commands::exec_vm(&cli_conf, args).await,
+    Command::Volume(args) => commands::exec_volume(&cli_conf, args).await,
     Command::Ps(args) => commands::exec_process(&cli_conf, args).await,
+    Command::Audit(args) => commands::exec_audit(&cli_conf, args).await,
     Command::Install(args) => {
       #[cfg(not(target_os = "windows"))]
       {
@@ -117,6 +125,7 @@ async fn execute_arg(cli_args: &Cli) -> IoResult<()> {
     Command::Info => commands::exec_info(&cli_conf).await,
     Command::Metric(args) => commands::exec_metric(&cli_conf, args).await,
     Command::Backup(opts) => commands::exec_backup(&cli_conf, opts).await,
+    Command::Restore(opts) => commands::exec_restore(&cli_conf, opts).await,
   }
 }
 
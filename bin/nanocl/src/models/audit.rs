@@ -0,0 +1,77 @@
+use chrono::TimeZone;
+use clap::Args;
+use tabled::Tabled;
+
+use nanocld_client::stubs::{
+  audit::AuditLog,
+  generic::{GenericClause, GenericFilter},
+};
+
+pub struct AuditArg;
+
+/// `nanocl audit` available options
+#[derive(Default, Clone, Args)]
+pub struct AuditFilter {
+  /// Show the calls made by the given identity like `token:ci`
+  #[clap(long, short)]
+  pub identity: Option<String>,
+  /// Show the calls made on the given object key
+  #[clap(long = "object")]
+  pub object_key: Option<String>,
+}
+
+impl From<AuditFilter> for GenericFilter {
+  fn from(filter: AuditFilter) -> Self {
+    let mut gen_filter = GenericFilter::new();
+    if let Some(identity) = filter.identity {
+      gen_filter = gen_filter.r#where("identity", GenericClause::Eq(identity));
+    }
+    if let Some(object_key) = filter.object_key {
+      gen_filter =
+        gen_filter.r#where("object_key", GenericClause::Eq(object_key));
+    }
+    gen_filter
+  }
+}
+
+/// A row of the audit table
+#[derive(Tabled)]
+#[tabled(rename_all = "UPPERCASE")]
+pub struct AuditRow {
+  /// When the call have been made
+  pub date: String,
+  /// Who made the call
+  pub identity: String,
+  /// Address of the caller
+  pub source: String,
+  /// Http method of the call
+  pub method: String,
+  /// Path of the call
+  pub route: String,
+  /// Http status code of the response
+  pub status: u16,
+  /// Key of the entry
+  #[tabled(skip)]
+  pub key: String,
+}
+
+impl From<AuditLog> for AuditRow {
+  fn from(entry: AuditLog) -> Self {
+    // Get the current timezone
+    let binding = chrono::Local::now();
+    let tz = binding.offset();
+    let date = tz
+      .timestamp_opt(entry.created_at.and_utc().timestamp(), 0)
+      .unwrap()
+      .format("%Y-%m-%d %H:%M:%S");
+    Self {
+      date: format!("{date}"),
+      identity: entry.identity,
+      source: entry.source,
+      method: entry.method,
+      route: entry.route,
+      status: entry.status,
+      key: entry.key.to_string(),
+    }
+  }
+}
@@ -1,11 +1,83 @@
 use clap::Parser;
+use serde::{Deserialize, Serialize};
+use tabled::Tabled;
+
+/// Version of the layout of the backup archives
+pub const BACKUP_VERSION: u32 = 1;
 
 #[derive(Clone, Parser)]
 pub struct BackupOpts {
-  /// Directory where to write the backup default to the current directory
+  /// Path of the archive to write default to `nanocl-backup-{date}.tar.gz`
   #[clap(short, long)]
-  pub output_dir: Option<String>,
+  pub output: Option<String>,
+  /// File containing a passphrase used to encrypt the secrets
+  #[clap(long)]
+  pub passphrase_file: Option<String>,
   /// Skip confirmation
   #[clap(short = 'y', long = "yes")]
   pub skip_confirm: bool,
 }
+
+/// `nanocl restore` available options
+#[derive(Clone, Parser)]
+pub struct RestoreOpts {
+  /// Path of the archive created by `nanocl backup`
+  pub path: String,
+  /// File containing the passphrase used to encrypt the secrets
+  #[clap(long)]
+  pub passphrase_file: Option<String>,
+  /// Only show what would be created or updated
+  #[clap(long)]
+  pub dry_run: bool,
+  /// Replace the data of the volumes that already exist
+  #[clap(long)]
+  pub overwrite_volumes: bool,
+}
+
+/// Description of a backup archive stored in `manifest.yml`
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct BackupManifest {
+  /// Version of the layout of the archive
+  pub version: u32,
+  /// Version of the api the objects were read from
+  pub api_version: String,
+  /// When the backup was created
+  pub created_at: chrono::NaiveDateTime,
+  /// Whether the secrets are encrypted with a passphrase
+  pub encrypted: bool,
+  /// Names of the namespaces saved in the archive
+  pub namespaces: Vec<String>,
+}
+
+/// What restoring an object of the archive does
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RestoreAction {
+  Create,
+  Update,
+  Unchanged,
+}
+
+impl std::fmt::Display for RestoreAction {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      RestoreAction::Create => write!(f, "create"),
+      RestoreAction::Update => write!(f, "update"),
+      RestoreAction::Unchanged => write!(f, "unchanged"),
+    }
+  }
+}
+
+/// A row of the `nanocl restore --dry-run` table
+#[derive(Tabled)]
+#[tabled(rename_all = "UPPERCASE")]
+pub struct RestoreRow {
+  /// Kind of the object
+  pub kind: String,
+  /// Namespace of the object if any
+  pub namespace: String,
+  /// Name of the object
+  pub name: String,
+  /// What restoring the object does
+  pub action: RestoreAction,
+}
@@ -29,6 +29,43 @@ pub enum ContextCommand {
     /// Path to context file
     path: String,
   },
+  /// Create a new context
+  Create(ContextCreateOpts),
+}
+
+/// `nanocl context create` available options
+#[derive(Clone, Parser)]
+pub struct ContextCreateOpts {
+  /// Context name
+  pub name: String,
+  /// Host of the daemon like `https://my-cluster:8443`
+  #[clap(long)]
+  pub host: String,
+  /// Token sent to authenticate on the daemon
+  #[clap(long)]
+  pub token: Option<String>,
+  /// Description of the context
+  #[clap(long, default_value = "")]
+  pub description: String,
+}
+
+impl From<ContextCreateOpts> for Context {
+  fn from(opts: ContextCreateOpts) -> Self {
+    Self {
+      name: opts.name,
+      meta_data: ContextMetaData {
+        description: opts.description,
+      },
+      endpoints: HashMap::from([(
+        "Nanocl".to_owned(),
+        ContextEndpoint {
+          host: opts.host,
+          ssl: None,
+          token: opts.token,
+        },
+      )]),
+    }
+  }
 }
 
 /// A context endpoint definition
@@ -38,6 +75,9 @@ pub struct ContextEndpoint {
   pub host: String,
   #[serde(skip_serializing_if = "Option::is_none")]
   pub ssl: Option<SslConfig>,
+  /// Token sent to authenticate on the daemon
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub token: Option<String>,
 }
 
 /// A context metadata definition
@@ -72,6 +112,7 @@ impl std::default::Default for Context {
             host: std::env::var("NANOCL_HOST")
               .unwrap_or("unix:///run/nanocl/nanocl.sock".into()),
             ssl: None,
+            token: None,
           },
         );
         map
@@ -1,6 +1,7 @@
 use clap::{Parser, Subcommand, ValueEnum};
 use serde::{Deserialize, Serialize};
 
+mod audit;
 mod backup;
 mod cargo;
 mod context;
@@ -13,13 +14,17 @@ mod namespace;
 mod node;
 mod process;
 mod resource;
+mod role;
 mod secret;
 mod state;
+mod token;
 mod uninstall;
 mod version;
 mod vm;
 mod vm_image;
+mod volume;
 
+pub use audit::*;
 pub use backup::*;
 pub use cargo::*;
 pub use context::*;
@@ -32,11 +37,14 @@ pub use namespace::*;
 pub use node::*;
 pub use process::*;
 pub use resource::*;
+pub use role::*;
 pub use secret::*;
 pub use state::*;
+pub use token::*;
 pub use uninstall::*;
 pub use vm::*;
 pub use vm_image::*;
+pub use volume::*;
 
 /// Cli available options and commands
 #[derive(Parser)]
@@ -63,10 +71,16 @@ pub enum Command {
   Cargo(CargoArg),
   /// Manage virtual machines
   Vm(VmArg),
+  /// Manage volumes
+  Volume(VolumeArg),
   /// Manage resources
   Resource(ResourceArg),
   /// Manage metrics
   Metric(MetricArg),
+  /// Manage api tokens
+  Token(TokenArg),
+  /// Manage roles given to api tokens
+  Role(RoleArg),
   /// Manage contexts
   Context(ContextArg),
   /// Manage nodes (experimental)
@@ -77,6 +91,8 @@ pub enum Command {
   Event(EventArg),
   /// Show processes
   Ps(GenericListOpts<ProcessFilter>),
+  /// Show the audit log of the mutating calls
+  Audit(GenericListOpts<AuditFilter>),
   /// Show nanocl host information
   Info,
   /// Show nanocl version information
@@ -85,8 +101,10 @@ pub enum Command {
   Install(InstallOpts),
   /// Uninstall components
   Uninstall(UninstallOpts),
-  /// Backup the current state
+  /// Backup every object and the data of the volumes into an archive
   Backup(BackupOpts),
+  /// Restore the objects and the data of a backup archive
+  Restore(RestoreOpts),
   // TODO: shell completion
   // Completion {
   //   /// Shell to generate completion for
@@ -2,7 +2,10 @@ use chrono::TimeZone;
 use clap::{Parser, Subcommand};
 use tabled::Tabled;
 
-use nanocld_client::stubs::namespace::NamespaceSummary;
+use nanocld_client::stubs::namespace::{
+  ContainerLimits, NamespaceLimitRange, NamespaceQuota, NamespaceSummary,
+  NamespaceUpdate,
+};
 
 use super::{GenericInspectOpts, GenericListOpts, GenericRemoveOpts};
 
@@ -13,6 +16,8 @@ pub enum NamespaceCommand {
   Create(NamespaceCreateOpts),
   /// Inspect a namespace
   Inspect(GenericInspectOpts),
+  /// Replace the quota and limit range of a namespace
+  Update(NamespaceUpdateOpts),
   /// Remove a namespace
   #[clap(alias("rm"))]
   Remove(GenericRemoveOpts),
@@ -45,6 +50,66 @@ pub struct NamespaceCreateOpts {
   pub name: String,
 }
 
+/// `nanocl namespace update` available options
+#[derive(Clone, Parser)]
+pub struct NamespaceUpdateOpts {
+  /// Total number of cpus of the namespace
+  #[clap(long)]
+  pub quota_cpus: Option<f64>,
+  /// Total memory in bytes of the namespace
+  #[clap(long)]
+  pub quota_memory: Option<i64>,
+  /// Total number of cargo instances, vms and job containers of the namespace
+  #[clap(long)]
+  pub quota_instances: Option<usize>,
+  /// Total size of the vm disks in GB of the namespace
+  #[clap(long)]
+  pub quota_vm_disk_size: Option<u64>,
+  /// Cpus given to the containers without them
+  #[clap(long)]
+  pub default_cpus: Option<f64>,
+  /// Memory in bytes given to the containers without it
+  #[clap(long)]
+  pub default_memory: Option<i64>,
+  /// Maximum cpus of a container
+  #[clap(long)]
+  pub max_cpus: Option<f64>,
+  /// Maximum memory in bytes of a container
+  #[clap(long)]
+  pub max_memory: Option<i64>,
+  /// Name of the namespace to update
+  pub name: String,
+}
+
+/// Convert NamespaceUpdateOpts to a NamespaceUpdate
+impl From<&NamespaceUpdateOpts> for NamespaceUpdate {
+  fn from(opts: &NamespaceUpdateOpts) -> Self {
+    let quota = NamespaceQuota {
+      cpus: opts.quota_cpus,
+      memory: opts.quota_memory,
+      instances: opts.quota_instances,
+      vm_disk_size: opts.quota_vm_disk_size,
+    };
+    let default = ContainerLimits {
+      cpus: opts.default_cpus,
+      memory: opts.default_memory,
+    };
+    let max = ContainerLimits {
+      cpus: opts.max_cpus,
+      memory: opts.max_memory,
+    };
+    let limit_range = NamespaceLimitRange {
+      default: (default != ContainerLimits::default()).then_some(default),
+      max: (max != ContainerLimits::default()).then_some(max),
+    };
+    Self {
+      quota: (quota != NamespaceQuota::default()).then_some(quota),
+      limit_range: (limit_range != NamespaceLimitRange::default())
+        .then_some(limit_range),
+    }
+  }
+}
+
 /// A row of the namespace table
 #[derive(Clone, Tabled)]
 #[tabled(rename_all = "UPPERCASE")]
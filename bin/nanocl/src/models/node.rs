@@ -17,6 +17,29 @@ pub enum NodeCommand {
   /// List nodes
   #[clap(alias = "ls")]
   List(GenericListOpts),
+  /// Stop scheduling new instances on a node
+  Cordon(NodeNameOpts),
+  /// Schedule new instances on a node again and stop its drain
+  Uncordon(NodeNameOpts),
+  /// Cordon a node and move its instances to the other nodes
+  Drain(NodeDrainOpts),
+}
+
+/// `nanocl node cordon` and `nanocl node uncordon` available options
+#[derive(Clone, Parser)]
+pub struct NodeNameOpts {
+  /// Name of the node
+  pub name: String,
+}
+
+/// `nanocl node drain` available options
+#[derive(Clone, Parser)]
+pub struct NodeDrainOpts {
+  /// Return without waiting for the end of the drain
+  #[clap(long, short)]
+  pub detach: bool,
+  /// Name of the node
+  pub name: String,
 }
 
 /// A row of the node table
@@ -31,6 +54,10 @@ pub struct NodeRow {
   pub endpoint: String,
   /// Version of the node
   pub version: String,
+  /// Status of the node
+  pub status: String,
+  #[tabled(rename = "LAST SEEN")]
+  last_seen: String,
   #[tabled(rename = "CREATED AT")]
   created_at: String,
 }
@@ -39,11 +66,18 @@ pub struct NodeRow {
 impl From<Node> for NodeRow {
   fn from(node: Node) -> Self {
     let created_at = node.created_at.format("%Y-%m-%d %H:%M:%S").to_string();
+    let last_seen = node.last_seen.format("%Y-%m-%d %H:%M:%S").to_string();
     Self {
       name: node.name,
       ip_address: node.ip_address.to_string(),
       endpoint: node.endpoint,
       version: node.version,
+      status: match (node.draining, node.cordoned) {
+        (true, _) => format!("{} (draining)", node.status),
+        (false, true) => format!("{} (cordoned)", node.status),
+        _ => node.status.to_string(),
+      },
+      last_seen,
       created_at,
     }
   }
@@ -8,6 +8,7 @@ use bollard_next::{
 
 use nanocld_client::stubs::{
   generic::{GenericClause, GenericFilter},
+  namespace::{get_network_name, SYSTEM_NETWORK},
   process::{Process, ProcessStats},
 };
 
@@ -90,7 +91,13 @@ impl From<Process> for ProcessRow {
     let config = container.config.unwrap_or_default();
     let network = container.network_settings.unwrap_or_default();
     let networks = network.networks.unwrap_or_default();
-    let mut ip_addr = if let Some(network) = networks.get("nanoclbr0") {
+    let network_name = config
+      .labels
+      .as_ref()
+      .and_then(|labels| labels.get("io.nanocl.n"))
+      .map(|namespace| get_network_name(namespace))
+      .unwrap_or(SYSTEM_NETWORK.to_owned());
+    let mut ip_addr = if let Some(network) = networks.get(&network_name) {
       network.ip_address.clone().unwrap_or("<none>".to_owned())
     } else {
       format!(
@@ -0,0 +1,89 @@
+use chrono::TimeZone;
+use clap::{Parser, Subcommand};
+use tabled::Tabled;
+
+use nanocld_client::stubs::auth::{Role, RolePartial, RoleRule, RoleVerb};
+
+use super::{GenericInspectOpts, GenericListOpts, GenericRemoveOpts};
+
+/// `nanocl role` available commands
+#[derive(Clone, Subcommand)]
+pub enum RoleCommand {
+  /// Remove existing role
+  #[clap(alias("rm"))]
+  Remove(GenericRemoveOpts),
+  /// List existing role
+  #[clap(alias("ls"))]
+  List(GenericListOpts),
+  /// Inspect a role
+  Inspect(GenericInspectOpts),
+  /// Create a new role with one rule
+  Create(RoleCreateOpts),
+}
+
+/// `nanocl role` available arguments
+#[derive(Clone, Parser)]
+pub struct RoleArg {
+  /// Role command
+  #[clap(subcommand)]
+  pub command: RoleCommand,
+}
+
+/// `nanocl role create` available options
+#[derive(Clone, Parser)]
+pub struct RoleCreateOpts {
+  /// Name of the role
+  pub name: String,
+  /// Verbs allowed: read, write, delete or all
+  #[clap(long = "verb", required = true)]
+  pub verbs: Vec<RoleVerb>,
+  /// Kinds of objects allowed like cargo, vm, job, secret or `*`
+  #[clap(long = "kind", required = true)]
+  pub kinds: Vec<String>,
+  /// Namespaces allowed, every namespace when not set
+  #[clap(long = "namespace")]
+  pub namespaces: Option<Vec<String>>,
+}
+
+impl From<RoleCreateOpts> for RolePartial {
+  fn from(opts: RoleCreateOpts) -> Self {
+    Self {
+      name: opts.name,
+      rules: vec![RoleRule {
+        verbs: opts.verbs,
+        kinds: opts.kinds,
+        namespaces: opts.namespaces,
+      }],
+    }
+  }
+}
+
+/// A row of the role table
+#[derive(Tabled)]
+#[tabled(rename_all = "UPPERCASE")]
+pub struct RoleRow {
+  /// The name of the role
+  pub name: String,
+  /// The number of rules
+  pub rules: usize,
+  /// When the role have been created
+  #[tabled(rename = "CREATED AT")]
+  pub created_at: String,
+}
+
+impl From<Role> for RoleRow {
+  fn from(role: Role) -> Self {
+    // Get the current timezone
+    let binding = chrono::Local::now();
+    let tz = binding.offset();
+    let created_at = tz
+      .timestamp_opt(role.created_at.and_utc().timestamp(), 0)
+      .unwrap()
+      .format("%Y-%m-%d %H:%M:%S");
+    Self {
+      name: role.name,
+      rules: role.rules.len(),
+      created_at: format!("{created_at}"),
+    }
+  }
+}
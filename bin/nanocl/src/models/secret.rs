@@ -21,6 +21,18 @@ pub enum SecretCommand {
   Inspect(GenericInspectOpts),
   /// Create a new secret
   Create(SecretCreateOpts),
+  /// Rotate the master key used to encrypt the secrets,
+  /// only available on a single node cluster
+  RotateKey(SecretRotateKeyOpts),
+}
+
+/// `nanocl secret rotate-key` available options
+#[derive(Clone, Parser)]
+pub struct SecretRotateKeyOpts {
+  /// File containing the new master key encoded in base64,
+  /// a new key is generated by the daemon when not set
+  #[clap(long)]
+  pub key_file: Option<String>,
 }
 
 /// `nanocl secret` available arguments
@@ -39,6 +51,14 @@ pub struct EnvCreateOpts {
   pub values: Vec<String>,
 }
 
+/// Create a new nanocl.io/file secret
+#[derive(Clone, Parser)]
+pub struct FileCreateOpts {
+  /// Paths of the files to store, they are mounted with their file name
+  #[clap(required = true)]
+  pub paths: Vec<String>,
+}
+
 /// Create a new nanocl.io/tls secret
 #[derive(Clone, Parser, Serialize)]
 #[serde(rename_all = "PascalCase")]
@@ -105,6 +125,20 @@ impl TryFrom<SecretCreateOpts> for SecretPartial {
         };
         ("nanocl.io/tls", serde_json::to_value(tls)?)
       }
+      SecretKindCreateCommand::File(file) => {
+        let mut files = serde_json::Map::new();
+        for path in &file.paths {
+          let name = std::path::Path::new(path)
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .ok_or_else(|| {
+              IoError::invalid_input("File", &format!("invalid path {path}"))
+            })?;
+          let content = std::fs::read_to_string(path)?;
+          files.insert(name, serde_json::Value::String(content));
+        }
+        ("nanocl.io/file", serde_json::Value::Object(files))
+      }
       SecretKindCreateCommand::ContainerRegistry(container_registry) => (
         "nanocl.io/container-registry",
         serde_json::to_value(container_registry)?,
@@ -125,6 +159,7 @@ pub enum SecretKindCreateCommand {
   Env(EnvCreateOpts),
   Tls(TlsCreateOpts),
   ContainerRegistry(ContainerRegistryCreateOpts),
+  File(FileCreateOpts),
 }
 
 /// `nanocl secret create` available options
@@ -0,0 +1,129 @@
+use chrono::TimeZone;
+use clap::{Parser, Subcommand};
+use tabled::Tabled;
+
+use nanocl_error::io::IoError;
+use nanocld_client::stubs::auth::{Token, TokenPartial};
+
+use super::{GenericListOpts, GenericRemoveOpts};
+
+/// `nanocl token` available commands
+#[derive(Clone, Subcommand)]
+pub enum TokenCommand {
+  /// Remove existing token
+  #[clap(alias("rm"))]
+  Remove(GenericRemoveOpts),
+  /// List existing token
+  #[clap(alias("ls"))]
+  List(GenericListOpts),
+  /// Create a new token and print its value
+  Create(TokenCreateOpts),
+}
+
+/// `nanocl token` available arguments
+#[derive(Clone, Parser)]
+pub struct TokenArg {
+  /// Token command
+  #[clap(subcommand)]
+  pub command: TokenCommand,
+}
+
+/// `nanocl token create` available options
+#[derive(Clone, Parser)]
+pub struct TokenCreateOpts {
+  /// Name of the token
+  pub name: String,
+  /// Roles given to the token
+  #[clap(long = "role", required = true)]
+  pub roles: Vec<String>,
+  /// When the token expire in UTC like `2024-12-31` or `2024-12-31 23:59:59`
+  #[clap(long)]
+  pub expires_at: Option<String>,
+}
+
+impl TryFrom<TokenCreateOpts> for TokenPartial {
+  type Error = IoError;
+
+  fn try_from(opts: TokenCreateOpts) -> Result<Self, Self::Error> {
+    let expires_at = match &opts.expires_at {
+      None => None,
+      Some(date) => {
+        let parsed =
+          chrono::NaiveDateTime::parse_from_str(date, "%Y-%m-%d %H:%M:%S")
+            .or_else(|_| {
+              chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                .map(|date| date.and_hms_opt(0, 0, 0).unwrap_or_default())
+            })
+            .map_err(|err| {
+              IoError::invalid_input("ExpiresAt", &format!("{date}: {err}"))
+            })?;
+        Some(parsed)
+      }
+    };
+    Ok(Self {
+      name: opts.name,
+      roles: opts.roles,
+      expires_at,
+    })
+  }
+}
+
+/// A row of the token table
+#[derive(Tabled)]
+#[tabled(rename_all = "UPPERCASE")]
+pub struct TokenRow {
+  /// The name of the token
+  pub name: String,
+  /// The roles of the token
+  pub roles: String,
+  /// When the token have been created
+  #[tabled(rename = "CREATED AT")]
+  pub created_at: String,
+  /// When the token expire
+  #[tabled(rename = "EXPIRES AT")]
+  pub expires_at: String,
+}
+
+impl From<Token> for TokenRow {
+  fn from(token: Token) -> Self {
+    // Get the current timezone
+    let binding = chrono::Local::now();
+    let tz = binding.offset();
+    let format = |date: chrono::NaiveDateTime| {
+      tz.timestamp_opt(date.and_utc().timestamp(), 0)
+        .unwrap()
+        .format("%Y-%m-%d %H:%M:%S")
+        .to_string()
+    };
+    Self {
+      name: token.name,
+      roles: token.roles.join(","),
+      created_at: format(token.created_at),
+      expires_at: token.expires_at.map(format).unwrap_or("never".to_owned()),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn expires_at() {
+    let opts = TokenCreateOpts {
+      name: "ci".to_owned(),
+      roles: vec!["admin".to_owned()],
+      expires_at: Some("2024-12-31".to_owned()),
+    };
+    let token = TokenPartial::try_from(opts.clone()).unwrap();
+    assert_eq!(
+      token.expires_at.unwrap().to_string(),
+      "2024-12-31 00:00:00".to_owned()
+    );
+    let invalid = TokenCreateOpts {
+      expires_at: Some("tomorrow".to_owned()),
+      ..opts
+    };
+    assert!(TokenPartial::try_from(invalid).is_err());
+  }
+}
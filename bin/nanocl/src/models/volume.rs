@@ -0,0 +1,145 @@
+use chrono::TimeZone;
+use clap::{Parser, Subcommand};
+use tabled::Tabled;
+
+use nanocld_client::stubs::volume::{Volume, VolumeKind, VolumeSnapshot};
+
+use super::{GenericInspectOpts, GenericListOpts, GenericRemoveOpts};
+
+/// `nanocl volume` available commands
+#[derive(Clone, Subcommand)]
+pub enum VolumeCommand {
+  /// Create a new volume
+  Create(VolumeCreateOpts),
+  /// List existing volumes
+  #[clap(alias("ls"))]
+  List(GenericListOpts),
+  /// Remove volumes that are not used by any cargo
+  #[clap(alias("rm"))]
+  Remove(GenericRemoveOpts),
+  /// Inspect a volume
+  Inspect(GenericInspectOpts),
+  /// Snapshot the data of a volume into a tarball
+  Snapshot(VolumeNameOpts),
+  /// List the snapshots of a volume
+  Snapshots(VolumeNameOpts),
+  /// Replace the data of a volume with one of its snapshots
+  Restore(VolumeRestoreOpts),
+}
+
+/// `nanocl volume` available arguments
+#[derive(Clone, Parser)]
+pub struct VolumeArg {
+  /// namespace to target by default global is used
+  #[clap(long, short)]
+  pub namespace: Option<String>,
+  /// subcommand to run
+  #[clap(subcommand)]
+  pub command: VolumeCommand,
+}
+
+/// `nanocl volume create` available options
+#[derive(Clone, Parser)]
+pub struct VolumeCreateOpts {
+  /// Name of the volume
+  pub name: String,
+  /// Storage backing the volume `docker` or `host`
+  #[clap(long, default_value = "docker")]
+  pub kind: VolumeKind,
+}
+
+/// `nanocl volume snapshot` available options
+#[derive(Clone, Parser)]
+pub struct VolumeNameOpts {
+  /// Name of the volume
+  pub name: String,
+}
+
+/// `nanocl volume restore` available options
+#[derive(Clone, Parser)]
+pub struct VolumeRestoreOpts {
+  /// Name of the volume
+  pub name: String,
+  /// Name of the snapshot to restore
+  pub snapshot: String,
+  /// Skip confirmation
+  #[clap(short = 'y', long)]
+  pub skip_confirm: bool,
+}
+
+/// A row of the volume table
+#[derive(Tabled)]
+#[tabled(rename_all = "UPPERCASE")]
+pub struct VolumeRow {
+  /// The name of the volume
+  pub name: String,
+  /// The namespace of the volume
+  pub namespace: String,
+  /// The storage backing the volume
+  pub kind: String,
+  /// Name of the docker volume or path of the directory on the host
+  pub source: String,
+  /// When the volume have been created
+  #[tabled(rename = "CREATED AT")]
+  pub created_at: String,
+}
+
+impl From<Volume> for VolumeRow {
+  fn from(volume: Volume) -> Self {
+    // Get the current timezone
+    let binding = chrono::Local::now();
+    let tz = binding.offset();
+    // Convert the created_at to the current timezone
+    let created_at = tz
+      .timestamp_opt(volume.created_at.and_utc().timestamp(), 0)
+      .unwrap()
+      .format("%Y-%m-%d %H:%M:%S");
+    Self {
+      name: volume.name,
+      namespace: volume.namespace_name,
+      kind: volume.kind.to_string(),
+      source: volume.source,
+      created_at: format!("{created_at}"),
+    }
+  }
+}
+
+/// A row of the volume snapshot table
+#[derive(Tabled)]
+#[tabled(rename_all = "UPPERCASE")]
+pub struct VolumeSnapshotRow {
+  /// The name of the snapshot
+  pub name: String,
+  /// Size of the tarball
+  pub size: String,
+  /// When the snapshot have been created
+  #[tabled(rename = "CREATED AT")]
+  pub created_at: String,
+}
+
+/// Convert size to human readable format
+fn convert_size(size: u64) -> String {
+  if size >= 1_000_000_000 {
+    format!("{} GB", size / 1024 / 1024 / 1024)
+  } else if size >= 1_000_000 {
+    format!("{} MB", size / 1024 / 1024)
+  } else {
+    format!("{} KB", size / 1024)
+  }
+}
+
+impl From<VolumeSnapshot> for VolumeSnapshotRow {
+  fn from(snapshot: VolumeSnapshot) -> Self {
+    let binding = chrono::Local::now();
+    let tz = binding.offset();
+    let created_at = tz
+      .timestamp_opt(snapshot.created_at.and_utc().timestamp(), 0)
+      .unwrap()
+      .format("%Y-%m-%d %H:%M:%S");
+    Self {
+      name: snapshot.name,
+      size: convert_size(snapshot.size),
+      created_at: format!("{created_at}"),
+    }
+  }
+}
@@ -0,0 +1,95 @@
+use openssl::{
+  hash::MessageDigest,
+  pkcs5::pbkdf2_hmac,
+  rand::rand_bytes,
+  symm::{decrypt_aead, encrypt_aead, Cipher},
+};
+
+use nanocl_error::io::{IoError, IoResult};
+
+/// Length in bytes of the salt used to derive the key from the passphrase
+const SALT_LEN: usize = 16;
+/// Length in bytes of the AES-GCM nonce
+const NONCE_LEN: usize = 12;
+/// Length in bytes of the AES-GCM authentication tag
+const TAG_LEN: usize = 16;
+/// Number of PBKDF2 iterations to derive the key from the passphrase
+const ITERATIONS: usize = 210_000;
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> IoResult<[u8; 32]> {
+  let mut key = [0; 32];
+  pbkdf2_hmac(
+    passphrase.as_bytes(),
+    salt,
+    ITERATIONS,
+    MessageDigest::sha256(),
+    &mut key,
+  )
+  .map_err(|err| IoError::other("Passphrase", &err.to_string()))?;
+  Ok(key)
+}
+
+/// Encrypt data with a key derived from a passphrase
+/// The output is the salt, the nonce, the encrypted data and the tag
+pub fn seal(passphrase: &str, data: &[u8]) -> IoResult<Vec<u8>> {
+  let mut salt = [0; SALT_LEN];
+  let mut nonce = [0; NONCE_LEN];
+  rand_bytes(&mut salt)
+    .and_then(|_| rand_bytes(&mut nonce))
+    .map_err(|err| IoError::other("Passphrase", &err.to_string()))?;
+  let key = derive_key(passphrase, &salt)?;
+  let mut tag = [0; TAG_LEN];
+  let encrypted = encrypt_aead(
+    Cipher::aes_256_gcm(),
+    &key,
+    Some(&nonce),
+    &[],
+    data,
+    &mut tag,
+  )
+  .map_err(|err| IoError::other("Passphrase", &err.to_string()))?;
+  Ok([&salt[..], &nonce, &encrypted, &tag].concat())
+}
+
+/// Decrypt data sealed with the same passphrase
+pub fn open(passphrase: &str, sealed: &[u8]) -> IoResult<Vec<u8>> {
+  if sealed.len() < SALT_LEN + NONCE_LEN + TAG_LEN {
+    return Err(IoError::invalid_data("Passphrase", "sealed data too short"));
+  }
+  let (salt, rest) = sealed.split_at(SALT_LEN);
+  let (nonce, rest) = rest.split_at(NONCE_LEN);
+  let (encrypted, tag) = rest.split_at(rest.len() - TAG_LEN);
+  let key = derive_key(passphrase, salt)?;
+  decrypt_aead(
+    Cipher::aes_256_gcm(),
+    &key,
+    Some(nonce),
+    &[],
+    encrypted,
+    tag,
+  )
+  .map_err(|_| IoError::invalid_data("Passphrase", "unable to decrypt"))
+}
+
+/// Read a passphrase from a file ignoring the trailing new line
+pub fn read_passphrase(path: &str) -> IoResult<String> {
+  let passphrase = std::fs::read_to_string(path)?.trim_end().to_owned();
+  if passphrase.is_empty() {
+    return Err(IoError::invalid_input("Passphrase", "file is empty"));
+  }
+  Ok(passphrase)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn seal_open() {
+    let sealed = seal("passphrase", b"secret data").unwrap();
+    assert_ne!(&sealed[SALT_LEN + NONCE_LEN..], b"secret data");
+    assert_eq!(open("passphrase", &sealed).unwrap(), b"secret data");
+    assert!(open("wrong", &sealed).is_err());
+    assert!(open("passphrase", &sealed[..8]).is_err());
+  }
+}
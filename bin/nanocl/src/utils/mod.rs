@@ -1,4 +1,5 @@
 pub mod context;
+pub mod crypto;
 pub mod dialog;
 pub mod docker;
 pub mod hash;
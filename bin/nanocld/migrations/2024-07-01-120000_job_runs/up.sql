@@ -0,0 +1,14 @@
+-- Your SQL goes here
+CREATE TABLE IF NOT EXISTS "job_runs" (
+  "key" UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  "created_at" TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  "job_key" VARCHAR NOT NULL REFERENCES jobs("key") ON DELETE CASCADE,
+  "scheduled_at" TIMESTAMPTZ NOT NULL,
+  "status" VARCHAR NOT NULL,
+  "note" VARCHAR
+);
+
+CREATE INDEX "job_runs_key_idx" ON "job_runs" ("key");
+CREATE INDEX "job_runs_created_at_idx" ON "job_runs" ("created_at");
+CREATE INDEX "job_runs_job_key_idx" ON "job_runs" ("job_key");
+CREATE INDEX "job_runs_scheduled_at_idx" ON "job_runs" ("scheduled_at");
@@ -0,0 +1,3 @@
+-- Your SQL goes here
+ALTER TABLE "jobs" ADD COLUMN "attempts" INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE "jobs" ADD COLUMN "last_failure" TEXT;
@@ -0,0 +1,16 @@
+-- Your SQL goes here
+CREATE TABLE IF NOT EXISTS "roles" (
+  "key" VARCHAR NOT NULL UNIQUE PRIMARY KEY,
+  "created_at" TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  "rules" JSONB NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS "tokens" (
+  "key" VARCHAR NOT NULL UNIQUE PRIMARY KEY,
+  "created_at" TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  "expires_at" TIMESTAMPTZ,
+  "hash" VARCHAR NOT NULL UNIQUE,
+  "roles" JSONB NOT NULL
+);
+
+CREATE INDEX "tokens_hash_idx" ON "tokens" ("hash");
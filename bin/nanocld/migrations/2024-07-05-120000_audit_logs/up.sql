@@ -0,0 +1,16 @@
+-- Your SQL goes here
+CREATE TABLE IF NOT EXISTS "audit_logs" (
+  "key" UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  "created_at" TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  "identity" VARCHAR NOT NULL,
+  "source" VARCHAR NOT NULL,
+  "method" VARCHAR NOT NULL,
+  "route" VARCHAR NOT NULL,
+  "object_key" VARCHAR,
+  "status" INTEGER NOT NULL,
+  "payload_hash" VARCHAR
+);
+
+CREATE INDEX "audit_logs_created_at_idx" ON "audit_logs" ("created_at");
+CREATE INDEX "audit_logs_identity_idx" ON "audit_logs" ("identity");
+CREATE INDEX "audit_logs_object_key_idx" ON "audit_logs" ("object_key");
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE "namespaces" DROP COLUMN "limit_range";
+ALTER TABLE "namespaces" DROP COLUMN "quota";
@@ -0,0 +1,3 @@
+-- Your SQL goes here
+ALTER TABLE "namespaces" ADD COLUMN "quota" JSONB;
+ALTER TABLE "namespaces" ADD COLUMN "limit_range" JSONB;
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS "nodes_status_idx";
+ALTER TABLE "nodes" DROP COLUMN "last_seen";
+ALTER TABLE "nodes" DROP COLUMN "status";
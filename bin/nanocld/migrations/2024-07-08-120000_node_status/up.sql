@@ -0,0 +1,5 @@
+-- Your SQL goes here
+ALTER TABLE "nodes" ADD COLUMN "status" VARCHAR NOT NULL DEFAULT 'Ready';
+ALTER TABLE "nodes" ADD COLUMN "last_seen" TIMESTAMPTZ NOT NULL DEFAULT NOW();
+
+CREATE INDEX "nodes_status_idx" ON "nodes" ("status");
@@ -0,0 +1,2 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE "cargoes" DROP COLUMN "settled_spec_key";
@@ -0,0 +1,2 @@
+-- Your SQL goes here
+ALTER TABLE "cargoes" ADD COLUMN "replicas" INTEGER;
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE "cargoes" DROP COLUMN "autoscaler_lease_at";
+ALTER TABLE "cargoes" DROP COLUMN "autoscaler_node";
@@ -0,0 +1,2 @@
+-- Your SQL goes here
+UPDATE "namespaces" SET "quota" = "quota" - 'Secrets' WHERE "quota" ? 'Secrets';
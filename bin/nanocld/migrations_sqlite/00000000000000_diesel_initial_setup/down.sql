@@ -0,0 +1,2 @@
+-- Nothing to setup on SQLite
+SELECT 1;
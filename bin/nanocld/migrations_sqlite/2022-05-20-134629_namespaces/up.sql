@@ -0,0 +1,9 @@
+-- Your SQL goes here
+CREATE TABLE IF NOT EXISTS "namespaces" (
+  "name" VARCHAR NOT NULL UNIQUE PRIMARY KEY,
+  "created_at" TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  "metadata" TEXT
+);
+
+CREATE INDEX "namespaces_name_idx" ON "namespaces" ("name");
+CREATE INDEX "namespaces_created_at_idx" ON "namespaces" ("created_at");
@@ -0,0 +1,16 @@
+-- Your SQL goes here
+CREATE TABLE IF NOT EXISTS "specs" (
+  "key" TEXT NOT NULL UNIQUE PRIMARY KEY,
+  "created_at" TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  "kind_name" VARCHAR NOT NULL,
+  "kind_key" VARCHAR NOT NULL,
+  "version" VARCHAR NOT NULL,
+  "data" TEXT NOT NULL,
+  "metadata" TEXT
+);
+
+CREATE INDEX "specs_key_idx" ON "specs" ("key");
+CREATE INDEX "specs_created_at_idx" ON "specs" ("created_at");
+CREATE INDEX "specs_kind_name_idx" ON "specs" ("kind_name");
+CREATE INDEX "specs_kind_key_idx" ON "specs" ("kind_key");
+CREATE INDEX "specs_version_idx" ON "specs" ("version");
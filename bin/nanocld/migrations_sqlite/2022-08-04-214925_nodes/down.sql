@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE IF EXISTS "node_group_links";
+DROP TABLE IF EXISTS "node_groups";
+DROP TABLE IF EXISTS "nodes";
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE IF EXISTS "resources";
+DROP TABLE IF EXISTS "resource_kinds";
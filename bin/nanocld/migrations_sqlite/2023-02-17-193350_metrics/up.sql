@@ -0,0 +1,16 @@
+-- Your SQL goes here
+CREATE TABLE IF NOT EXISTS "metrics" (
+  "key" TEXT NOT NULL PRIMARY KEY,
+  "created_at" TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  "expires_at" TIMESTAMP NOT NULL DEFAULT (DATETIME('now', '+4 months')),
+  "node_name" VARCHAR NOT NULL,
+  "kind" VARCHAR NOT NULL,
+  "data" TEXT NOT NULL,
+  "note" VARCHAR
+);
+
+CREATE INDEX "metrics_key_idx" ON "metrics" ("key");
+CREATE INDEX "metrics_created_at_idx" ON "metrics" ("created_at");
+CREATE INDEX "metrics_expires_at_idx" ON "metrics" ("expires_at");
+CREATE INDEX "metrics_node_name_idx" ON "metrics" ("node_name");
+CREATE INDEX "metrics_kind_idx" ON "metrics" ("kind");
@@ -0,0 +1,16 @@
+-- Your SQL goes here
+CREATE TABLE IF NOT EXISTS "secrets" (
+  "key" VARCHAR NOT NULL UNIQUE PRIMARY KEY,
+  "created_at" TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  "updated_at" TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  "kind" VARCHAR NOT NULL,
+  "immutable" BOOLEAN NOT NULL DEFAULT FALSE,
+  "data" TEXT NOT NULL,
+  "metadata" TEXT
+);
+
+CREATE INDEX "secrets_key_idx" ON "secrets" ("key");
+CREATE INDEX "secrets_created_at_idx" ON "secrets" ("created_at");
+CREATE INDEX "secrets_updated_at_idx" ON "secrets" ("updated_at");
+CREATE INDEX "secrets_kind_idx" ON "secrets" ("kind");
+CREATE INDEX "secrets_immutable_idx" ON "secrets" ("immutable");
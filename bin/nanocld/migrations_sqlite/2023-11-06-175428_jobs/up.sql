@@ -0,0 +1,14 @@
+-- Your SQL goes here
+CREATE TABLE IF NOT EXISTS "jobs" (
+  "key" VARCHAR NOT NULL UNIQUE PRIMARY KEY,
+  "created_at" TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  "updated_at" TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  "status_key" VARCHAR NOT NULL REFERENCES object_process_statuses("key"),
+  "data" TEXT NOT NULL,
+  "metadata" TEXT
+);
+
+CREATE INDEX "jobs_key_idx" ON "jobs" ("key");
+CREATE INDEX "jobs_created_at_idx" ON "jobs" ("created_at");
+CREATE INDEX "jobs_updated_at_idx" ON "jobs" ("updated_at");
+CREATE INDEX "jobs_status_key_idx" ON "jobs" ("status_key");
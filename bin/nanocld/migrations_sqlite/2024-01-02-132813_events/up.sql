@@ -0,0 +1,25 @@
+-- Your SQL goes here
+CREATE TABLE IF NOT EXISTS "events" (
+  "key" TEXT NOT NULL PRIMARY KEY,
+  "created_at" TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  "expires_at" TIMESTAMP NOT NULL DEFAULT (DATETIME('now', '+4 months')),
+  "reporting_node" VARCHAR NOT NULL,
+  "reporting_controller" VARCHAR NOT NULL,
+  "kind" VARCHAR NOT NULL,
+  "action" VARCHAR NOT NULL,
+  "reason" VARCHAR NOT NULL,
+  "note" VARCHAR,
+  "actor" TEXT,
+  "related" TEXT,
+  "metadata" TEXT
+);
+
+CREATE INDEX "events_key_idx" ON "events" ("key");
+CREATE INDEX "events_created_at_idx" ON "events" ("created_at");
+CREATE INDEX "events_expires_at_idx" ON "events" ("expires_at");
+CREATE INDEX "events_reporting_node_idx" ON "events" ("reporting_node");
+CREATE INDEX "events_reporting_controller_idx" ON "events" ("reporting_controller");
+CREATE INDEX "events_kind_idx" ON "events" ("kind");
+CREATE INDEX "events_action_idx" ON "events" ("action");
+CREATE INDEX "events_reason_idx" ON "events" ("reason");
+CREATE INDEX "events_note_idx" ON "events" ("note");
@@ -0,0 +1,16 @@
+-- Your SQL goes here
+CREATE TABLE IF NOT EXISTS "roles" (
+  "key" VARCHAR NOT NULL UNIQUE PRIMARY KEY,
+  "created_at" TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  "rules" TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS "tokens" (
+  "key" VARCHAR NOT NULL UNIQUE PRIMARY KEY,
+  "created_at" TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  "expires_at" TIMESTAMP,
+  "hash" VARCHAR NOT NULL UNIQUE,
+  "roles" TEXT NOT NULL
+);
+
+CREATE INDEX "tokens_hash_idx" ON "tokens" ("hash");
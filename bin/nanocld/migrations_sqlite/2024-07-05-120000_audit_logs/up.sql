@@ -0,0 +1,16 @@
+-- Your SQL goes here
+CREATE TABLE IF NOT EXISTS "audit_logs" (
+  "key" TEXT NOT NULL PRIMARY KEY,
+  "created_at" TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  "identity" VARCHAR NOT NULL,
+  "source" VARCHAR NOT NULL,
+  "method" VARCHAR NOT NULL,
+  "route" VARCHAR NOT NULL,
+  "object_key" VARCHAR,
+  "status" INTEGER NOT NULL,
+  "payload_hash" VARCHAR
+);
+
+CREATE INDEX "audit_logs_created_at_idx" ON "audit_logs" ("created_at");
+CREATE INDEX "audit_logs_identity_idx" ON "audit_logs" ("identity");
+CREATE INDEX "audit_logs_object_key_idx" ON "audit_logs" ("object_key");
@@ -0,0 +1,3 @@
+-- Your SQL goes here
+ALTER TABLE "namespaces" ADD COLUMN "quota" TEXT;
+ALTER TABLE "namespaces" ADD COLUMN "limit_range" TEXT;
@@ -0,0 +1,16 @@
+-- Your SQL goes here
+CREATE TABLE IF NOT EXISTS "volumes" (
+  "key" VARCHAR NOT NULL UNIQUE PRIMARY KEY,
+  "created_at" TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  "name" VARCHAR NOT NULL,
+  "namespace_name" VARCHAR NOT NULL REFERENCES namespaces("name"),
+  "kind" VARCHAR NOT NULL,
+  "source" VARCHAR NOT NULL,
+  "metadata" TEXT
+);
+
+CREATE INDEX "volumes_key_idx" ON "volumes" ("key");
+CREATE INDEX "volumes_created_at_idx" ON "volumes" ("created_at");
+CREATE INDEX "volumes_name_idx" ON "volumes" ("name");
+CREATE INDEX "volumes_namespace_name_idx" ON "volumes" ("namespace_name");
+CREATE INDEX "volumes_kind_idx" ON "volumes" ("kind");
@@ -0,0 +1,7 @@
+-- Your SQL goes here
+-- SQLite only allows a constant default when adding a column
+ALTER TABLE "nodes" ADD COLUMN "status" VARCHAR NOT NULL DEFAULT 'Ready';
+ALTER TABLE "nodes" ADD COLUMN "last_seen" TIMESTAMP NOT NULL DEFAULT '1970-01-01 00:00:00';
+UPDATE "nodes" SET "last_seen" = CURRENT_TIMESTAMP;
+
+CREATE INDEX "nodes_status_idx" ON "nodes" ("status");
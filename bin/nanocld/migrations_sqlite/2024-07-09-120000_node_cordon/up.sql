@@ -0,0 +1,3 @@
+-- Your SQL goes here
+ALTER TABLE "nodes" ADD COLUMN "cordoned" BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE "nodes" ADD COLUMN "draining" BOOLEAN NOT NULL DEFAULT FALSE;
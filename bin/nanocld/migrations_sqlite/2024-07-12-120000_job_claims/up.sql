@@ -0,0 +1,2 @@
+-- Your SQL goes here
+ALTER TABLE "jobs" ADD COLUMN "last_scheduled_at" TIMESTAMP;
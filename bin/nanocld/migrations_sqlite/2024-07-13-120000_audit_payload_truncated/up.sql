@@ -0,0 +1,2 @@
+-- Your SQL goes here
+ALTER TABLE "audit_logs" ADD COLUMN "payload_truncated" BOOLEAN NOT NULL DEFAULT FALSE;
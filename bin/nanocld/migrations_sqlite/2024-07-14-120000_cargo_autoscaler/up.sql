@@ -0,0 +1,3 @@
+-- Your SQL goes here
+ALTER TABLE "cargoes" ADD COLUMN "autoscaler_node" VARCHAR;
+ALTER TABLE "cargoes" ADD COLUMN "autoscaler_lease_at" TIMESTAMP;
@@ -0,0 +1,2 @@
+-- Your SQL goes here
+UPDATE "namespaces" SET "quota" = json_remove("quota", '$.Secrets') WHERE "quota" IS NOT NULL;
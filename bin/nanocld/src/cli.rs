@@ -15,6 +15,7 @@ pub struct Cli {
   #[clap(long)]
   pub docker_host: Option<String>,
   /// Store address to connect to
+  /// or path of the database file with the `sqlite` feature
   #[clap(long)]
   pub store_addr: Option<String>,
   /// State directory
@@ -42,6 +43,28 @@ pub struct Cli {
   /// Optional ssl options
   #[clap(flatten)]
   pub ssl: Option<SslConfig>,
+  /// File containing the master key used to encrypt the secrets
+  /// A new key is generated when the file doesn't exist
+  #[clap(long)]
+  pub master_key_file: Option<String>,
+  /// Require a token with the right roles for requests over tcp,
+  /// requests over the unix socket without a token are always trusted
+  #[clap(long)]
+  pub auth: bool,
+  /// Number of days the audit log entries are kept, forever when 0
+  /// [default: 90]
+  #[clap(long)]
+  pub audit_retention: Option<u32>,
+  /// Cron expression to snapshot the store like `0 3 * * *`
+  #[clap(long)]
+  pub snapshot_schedule: Option<String>,
+  /// Number of scheduled store snapshots kept, all when 0
+  /// [default: 7]
+  #[clap(long)]
+  pub snapshot_retention: Option<u32>,
+  /// Restore the store from a snapshot archive before reconciling the containers
+  #[clap(long)]
+  pub restore_from: Option<String>,
 }
 
 impl Default for Cli {
@@ -58,6 +81,12 @@ impl Default for Cli {
       advertise_addr: None,
       gid: 0,
       ssl: None,
+      master_key_file: None,
+      auth: false,
+      audit_retention: None,
+      snapshot_schedule: None,
+      snapshot_retention: None,
+      restore_from: None,
     }
   }
 }
@@ -3,7 +3,7 @@ use nanocl_stubs::config::{DaemonConfig, DaemonConfigFile};
 use nanocl_error::io::{FromIo, IoResult};
 use nanocl_utils::unix;
 
-use crate::cli::Cli;
+use crate::{cli::Cli, utils};
 
 /// Merge cli and config file together to generate the daemon config
 fn gen_daemon_conf(
@@ -58,6 +58,18 @@ fn gen_daemon_conf(
   } else {
     config.store_addr.clone()
   };
+  let master_key_file = args
+    .master_key_file
+    .clone()
+    .or(config.master_key_file.clone());
+  let master_key = utils::secret::read_master_key(master_key_file.as_deref())?;
+  let snapshot_schedule = args
+    .snapshot_schedule
+    .clone()
+    .or(config.snapshot_schedule.clone());
+  if let Some(schedule) = &snapshot_schedule {
+    utils::cron::CronSchedule::parse(schedule)?;
+  }
   Ok(DaemonConfig {
     hosts,
     gateway,
@@ -70,6 +82,19 @@ fn gen_daemon_conf(
     nodes: args.nodes.clone(),
     conf_dir: args.conf_dir.clone(),
     ssl: args.ssl.clone(),
+    master_key_file,
+    master_key,
+    auth: args.auth || config.auth.unwrap_or_default(),
+    audit_retention: args
+      .audit_retention
+      .or(config.audit_retention)
+      .unwrap_or(90),
+    snapshot_schedule,
+    snapshot_retention: args
+      .snapshot_retention
+      .or(config.snapshot_retention)
+      .unwrap_or(7),
+    restore_from: args.restore_from.clone(),
   })
 }
 
@@ -124,6 +149,11 @@ mod tests {
       store_addr: None,
       gateway: None,
       hostname: None,
+      master_key_file: None,
+      auth: None,
+      audit_retention: None,
+      snapshot_schedule: None,
+      snapshot_retention: None,
     };
     let merged = gen_daemon_conf(&args, &config).unwrap();
     assert_eq!(merged.hosts, args.hosts.unwrap());
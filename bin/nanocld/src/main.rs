@@ -5,6 +5,7 @@ use nanocl_utils::logger;
 
 mod cli;
 mod config;
+mod middlewares;
 mod models;
 mod objects;
 mod repositories;
@@ -0,0 +1,142 @@
+use futures::{stream, StreamExt};
+use ntex::http::{header, Payload};
+use ntex::util::BytesMut;
+use ntex::web::{Error, ErrorRenderer, WebRequest, WebResponse};
+use ntex::{Middleware, Service, ServiceCtx};
+
+use nanocl_stubs::auth::RoleVerb;
+
+use crate::{
+  models::{AuditLogDb, SystemState},
+  repositories::generic::*,
+  utils::{self, auth::Identity},
+};
+
+/// Maximum size of a json payload buffered to be hashed,
+/// larger payloads are streamed to the handler and recorded as truncated
+const MAX_AUDITED_PAYLOAD: usize = 1024 * 1024;
+
+/// Audit middleware recording every mutating call in the audit log
+/// with who made it, from where, the status code and a hash of its payload.
+/// It must wrap the `Auth` middleware to know the token used
+///
+/// ```no_run,ignore
+/// web::scope("/{version}").wrap(Auth).wrap(Audit);
+/// ```
+pub struct Audit;
+
+impl<S> Middleware<S> for Audit {
+  type Service = AuditMiddleware<S>;
+
+  fn create(&self, service: S) -> Self::Service {
+    AuditMiddleware { service }
+  }
+}
+
+pub struct AuditMiddleware<S> {
+  service: S,
+}
+
+impl<S, Err> Service<WebRequest<Err>> for AuditMiddleware<S>
+where
+  S: Service<WebRequest<Err>, Response = WebResponse, Error = Error>,
+  Err: ErrorRenderer,
+{
+  type Response = WebResponse;
+  type Error = Error;
+
+  ntex::forward_ready!(service);
+
+  async fn call(
+    &self,
+    mut req: WebRequest<Err>,
+    ctx: ServiceCtx<'_, Self>,
+  ) -> Result<Self::Response, Self::Error> {
+    if !utils::audit::is_audited(req.method()) {
+      return ctx.call(&self.service, req).await;
+    }
+    let Some(state) = req.app_state::<SystemState>().cloned() else {
+      return ctx.call(&self.service, req).await;
+    };
+    // Strip the version from the path
+    let route = req
+      .path()
+      .trim_start_matches('/')
+      .split_once('/')
+      .map(|(_, path)| path.to_owned())
+      .unwrap_or_default();
+    let action =
+      utils::auth::get_action(req.method(), &route, req.query_string());
+    let source = match req.peer_addr() {
+      Some(addr) => addr.ip().to_string(),
+      None => "unix".to_owned(),
+    };
+    let cert_identity = utils::audit::get_cert_identity(req.io());
+    let is_json = req
+      .headers()
+      .get(header::CONTENT_TYPE)
+      .and_then(|value| value.to_str().ok())
+      .map(|value| value.contains("json"))
+      .unwrap_or_default();
+    // Only json payloads are buffered to be hashed, others can be streams
+    // like vm images and are not meant to be kept in memory.
+    // The request isn't authenticated yet so at most `MAX_AUDITED_PAYLOAD`
+    // bytes are buffered, the rest is left in the stream for the handler
+    let mut payload = None;
+    let mut payload_truncated = false;
+    if is_json {
+      let mut body = BytesMut::new();
+      let mut stream = req.take_payload();
+      while let Some(chunk) = stream.recv().await {
+        body.extend_from_slice(&chunk?);
+        if body.len() > MAX_AUDITED_PAYLOAD {
+          payload_truncated = true;
+          break;
+        }
+      }
+      let body = body.freeze();
+      if !payload_truncated {
+        payload = Some(body.clone());
+      }
+      req.set_payload(Payload::from_stream(
+        stream::once(async move { Ok(body) }).chain(stream),
+      ));
+    }
+    let res = ctx.call(&self.service, req).await?;
+    let identity = match res.request().extensions().get::<Identity>() {
+      Some(identity) => format!("token:{}", identity.token),
+      None => match (cert_identity, source.as_str()) {
+        (Some(cert), _) => cert,
+        (None, "unix") => "unix".to_owned(),
+        (None, _) => "anonymous".to_owned(),
+      },
+    };
+    let json = payload
+      .as_ref()
+      .and_then(|body| serde_json::from_slice::<serde_json::Value>(body).ok());
+    let object_key = match action.verb {
+      RoleVerb::Read => None,
+      _ => utils::audit::get_object_key(
+        &route,
+        action.namespace.as_deref(),
+        json.as_ref(),
+      ),
+    };
+    let entry = AuditLogDb {
+      key: uuid::Uuid::new_v4(),
+      created_at: chrono::Utc::now().naive_utc(),
+      identity,
+      source,
+      method: res.request().method().to_string(),
+      route: format!("/{route}"),
+      object_key,
+      status: i32::from(res.status().as_u16()),
+      payload_hash: payload.map(|body| utils::auth::hash_bytes(&body)),
+      payload_truncated,
+    };
+    if let Err(err) = AuditLogDb::create_from(entry, &state.inner.pool).await {
+      log::error!("audit::call: {err}");
+    }
+    Ok(res)
+  }
+}
@@ -0,0 +1,68 @@
+use ntex::web::{Error, ErrorRenderer, HttpResponse, WebRequest, WebResponse};
+use ntex::{Middleware, Service, ServiceCtx};
+
+use crate::{models::SystemState, utils};
+
+/// Authentication middleware creator
+/// When the daemon is started with `--auth` every request must send
+/// a token allowed by its roles, except the ones over the unix socket.
+/// The nodes send their own token on the websocket of their heartbeats
+///
+/// ```no_run,ignore
+/// web::scope("/{version}").wrap(Auth);
+/// ```
+pub struct Auth;
+
+impl<S> Middleware<S> for Auth {
+  type Service = AuthMiddleware<S>;
+
+  fn create(&self, service: S) -> Self::Service {
+    AuthMiddleware { service }
+  }
+}
+
+pub struct AuthMiddleware<S> {
+  service: S,
+}
+
+impl<S, Err> Service<WebRequest<Err>> for AuthMiddleware<S>
+where
+  S: Service<WebRequest<Err>, Response = WebResponse, Error = Error>,
+  Err: ErrorRenderer,
+{
+  type Response = WebResponse;
+  type Error = Error;
+
+  ntex::forward_ready!(service);
+
+  async fn call(
+    &self,
+    req: WebRequest<Err>,
+    ctx: ServiceCtx<'_, Self>,
+  ) -> Result<Self::Response, Self::Error> {
+    let Some(state) = req.app_state::<SystemState>().cloned() else {
+      return ctx.call(&self.service, req).await;
+    };
+    if !state.inner.config.auth {
+      return ctx.call(&self.service, req).await;
+    }
+    match utils::auth::authorize(&req, &state).await {
+      Ok(Some(identity)) => {
+        req.extensions_mut().insert(identity);
+      }
+      Ok(None) => {}
+      Err(err) => {
+        return Ok(
+          req.into_response(
+            HttpResponse::build(err.status)
+              .json(&serde_json::json!({
+                "msg": err.msg,
+              }))
+              .into_body(),
+          ),
+        );
+      }
+    }
+    ctx.call(&self.service, req).await
+  }
+}
@@ -0,0 +1,5 @@
+mod audit;
+mod auth;
+
+pub use audit::*;
+pub use auth::*;
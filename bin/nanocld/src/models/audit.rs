@@ -0,0 +1,59 @@
+use diesel::prelude::*;
+
+use nanocl_error::io::IoError;
+
+use nanocl_stubs::audit::AuditLog;
+
+use crate::schema::audit_logs;
+
+#[cfg(feature = "sqlite")]
+use super::sql_types::DbUuid;
+
+/// This structure represent an entry of the audit log in the database.
+/// Entries are only appended and removed once older than the retention.
+#[derive(Clone, Debug, Queryable, Identifiable, Insertable)]
+#[diesel(primary_key(key))]
+#[diesel(table_name = audit_logs)]
+pub struct AuditLogDb {
+  /// The key of the entry
+  #[cfg_attr(feature = "sqlite", diesel(serialize_as = DbUuid))]
+  #[cfg_attr(feature = "sqlite", diesel(deserialize_as = DbUuid))]
+  pub key: uuid::Uuid,
+  /// When the call have been made
+  pub created_at: chrono::NaiveDateTime,
+  /// Who made the call
+  pub identity: String,
+  /// Address of the caller
+  pub source: String,
+  /// Http method of the call
+  pub method: String,
+  /// Path of the call without the version
+  pub route: String,
+  /// Key of the object targeted by the call
+  pub object_key: Option<String>,
+  /// Http status code of the response
+  pub status: i32,
+  /// Sha256 of the json payload
+  pub payload_hash: Option<String>,
+  /// Whether the json payload was too large to be hashed
+  pub payload_truncated: bool,
+}
+
+impl TryFrom<AuditLogDb> for AuditLog {
+  type Error = IoError;
+
+  fn try_from(db: AuditLogDb) -> Result<Self, Self::Error> {
+    Ok(AuditLog {
+      key: db.key,
+      created_at: db.created_at,
+      identity: db.identity,
+      source: db.source,
+      method: db.method,
+      route: db.route,
+      object_key: db.object_key,
+      status: u16::try_from(db.status).unwrap_or_default(),
+      payload_hash: db.payload_hash,
+      payload_truncated: db.payload_truncated,
+    })
+  }
+}
@@ -0,0 +1,86 @@
+use diesel::prelude::*;
+
+use nanocl_error::io::{FromIo, IoError};
+
+use nanocl_stubs::auth::{Role, RolePartial, RoleRule, Token};
+
+use crate::schema::{roles, tokens};
+
+#[cfg(feature = "sqlite")]
+use super::sql_types::DbJson;
+
+/// This structure represent a role in the database.
+/// A role is a set of rules allowing verbs on kinds of objects.
+#[derive(Clone, Debug, Queryable, Identifiable, Insertable)]
+#[diesel(primary_key(key))]
+#[diesel(table_name = roles)]
+pub struct RoleDb {
+  /// The name of the role
+  pub key: String,
+  /// The creation date
+  pub created_at: chrono::NaiveDateTime,
+  /// The rules of the role
+  #[cfg_attr(feature = "sqlite", diesel(serialize_as = DbJson))]
+  #[cfg_attr(feature = "sqlite", diesel(deserialize_as = DbJson))]
+  pub rules: serde_json::Value,
+}
+
+impl TryFrom<&RolePartial> for RoleDb {
+  type Error = IoError;
+
+  fn try_from(role: &RolePartial) -> Result<Self, Self::Error> {
+    Ok(Self {
+      key: role.name.clone(),
+      created_at: chrono::Utc::now().naive_utc(),
+      rules: serde_json::to_value(&role.rules)
+        .map_err(|err| err.map_err_context(|| "RoleRule"))?,
+    })
+  }
+}
+
+impl TryFrom<RoleDb> for Role {
+  type Error = IoError;
+
+  fn try_from(db: RoleDb) -> Result<Self, Self::Error> {
+    Ok(Role {
+      name: db.key,
+      created_at: db.created_at,
+      rules: serde_json::from_value::<Vec<RoleRule>>(db.rules)
+        .map_err(|err| err.map_err_context(|| "RoleRule"))?,
+    })
+  }
+}
+
+/// This structure represent an api token in the database.
+/// Only the sha256 hash of the token value is stored.
+#[derive(Clone, Debug, Queryable, Identifiable, Insertable)]
+#[diesel(primary_key(key))]
+#[diesel(table_name = tokens)]
+pub struct TokenDb {
+  /// The name of the token
+  pub key: String,
+  /// The creation date
+  pub created_at: chrono::NaiveDateTime,
+  /// When the token expire
+  pub expires_at: Option<chrono::NaiveDateTime>,
+  /// The sha256 hash of the token value
+  pub hash: String,
+  /// The roles given to the token
+  #[cfg_attr(feature = "sqlite", diesel(serialize_as = DbJson))]
+  #[cfg_attr(feature = "sqlite", diesel(deserialize_as = DbJson))]
+  pub roles: serde_json::Value,
+}
+
+impl TryFrom<TokenDb> for Token {
+  type Error = IoError;
+
+  fn try_from(db: TokenDb) -> Result<Self, Self::Error> {
+    Ok(Token {
+      name: db.key,
+      created_at: db.created_at,
+      expires_at: db.expires_at,
+      roles: serde_json::from_value::<Vec<String>>(db.roles)
+        .map_err(|err| err.map_err_context(|| "TokenRoles"))?,
+    })
+  }
+}
@@ -5,6 +5,9 @@ use crate::schema::cargoes;
 
 use super::NamespaceDb;
 
+#[cfg(feature = "sqlite")]
+use super::sql_types::DbUuid;
+
 /// This structure represent the cargo in the database.
 /// A cargo is a replicable container that can be used to deploy a service.
 /// His specification is stored as a relation to a `CargoSpecDb`.
@@ -21,11 +24,22 @@ pub struct CargoDb {
   /// The name of the cargo
   pub name: String,
   /// The spec key reference
+  #[cfg_attr(feature = "sqlite", diesel(serialize_as = DbUuid))]
+  #[cfg_attr(feature = "sqlite", diesel(deserialize_as = DbUuid))]
   pub spec_key: uuid::Uuid,
   /// The status key reference
   pub status_key: String,
   /// The namespace name
   pub namespace_name: String,
+  /// Key of the spec whose blue/green or canary deployment was promoted
+  /// or aborted, the instances of the other specs are removed by every node
+  pub settled_spec_key: Option<String>,
+  /// Number of replicas chosen by the autoscaler
+  pub replicas: Option<i32>,
+  /// Node evaluating the autoscaler of the cargo
+  pub autoscaler_node: Option<String>,
+  /// Last time the node evaluating the autoscaler renewed its lease
+  pub autoscaler_lease_at: Option<chrono::NaiveDateTime>,
 }
 
 /// This structure is used to update a cargo in the database.
@@ -39,7 +53,12 @@ pub struct CargoUpdateDb {
   /// The namespace name
   pub namespace_name: Option<String>,
   /// The spec key reference
+  #[cfg_attr(feature = "sqlite", diesel(serialize_as = DbUuid))]
   pub spec_key: Option<uuid::Uuid>,
+  /// Key of the spec whose deployment was promoted or aborted
+  pub settled_spec_key: Option<Option<String>>,
+  /// Number of replicas chosen by the autoscaler
+  pub replicas: Option<Option<i32>>,
 }
 
 /// Arguments to create a new cargo obj
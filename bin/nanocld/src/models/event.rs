@@ -7,11 +7,16 @@ use nanocl_stubs::system::{Event, EventKind, EventPartial};
 
 use crate::schema::events;
 
+#[cfg(feature = "sqlite")]
+use super::sql_types::{DbOptJson, DbUuid};
+
 #[derive(Debug, Queryable, Identifiable, Insertable)]
 #[diesel(primary_key(key))]
 #[diesel(table_name = events)]
 pub struct EventDb {
   /// Unique identifier of this event.
+  #[cfg_attr(feature = "sqlite", diesel(serialize_as = DbUuid))]
+  #[cfg_attr(feature = "sqlite", diesel(deserialize_as = DbUuid))]
   pub key: uuid::Uuid,
   /// When the event was created.
   pub created_at: chrono::NaiveDateTime,
@@ -35,11 +40,17 @@ pub struct EventDb {
   /// Human-readable description of the status of this operation
   pub note: Option<String>,
   /// Actor contains the object this Event is about.
+  #[cfg_attr(feature = "sqlite", diesel(serialize_as = DbOptJson))]
+  #[cfg_attr(feature = "sqlite", diesel(deserialize_as = DbOptJson))]
   pub actor: Option<serde_json::Value>,
   /// Optional secondary actor for more complex actions.
   /// E.g. when regarding actor triggers a creation or deletion of related actor.
+  #[cfg_attr(feature = "sqlite", diesel(serialize_as = DbOptJson))]
+  #[cfg_attr(feature = "sqlite", diesel(deserialize_as = DbOptJson))]
   pub related: Option<serde_json::Value>,
   /// Standard metadata.
+  #[cfg_attr(feature = "sqlite", diesel(serialize_as = DbOptJson))]
+  #[cfg_attr(feature = "sqlite", diesel(deserialize_as = DbOptJson))]
   pub metadata: Option<serde_json::Value>,
 }
 
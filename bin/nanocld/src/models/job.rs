@@ -1,6 +1,14 @@
 use diesel::prelude::*;
 
-use crate::schema::jobs;
+use std::str::FromStr;
+
+use nanocl_error::io::IoError;
+use nanocl_stubs::job::{JobRun, JobRunStatus};
+
+use crate::schema::{job_runs, jobs};
+
+#[cfg(feature = "sqlite")]
+use super::sql_types::{DbJson, DbOptJson, DbUuid};
 
 /// This structure represent a job to run.
 /// It will create and run a list of containers.
@@ -17,15 +25,87 @@ pub struct JobDb {
   /// The status key
   pub status_key: String,
   /// The spec
+  #[cfg_attr(feature = "sqlite", diesel(serialize_as = DbJson))]
+  #[cfg_attr(feature = "sqlite", diesel(deserialize_as = DbJson))]
   pub data: serde_json::Value,
   /// The metadata
+  #[cfg_attr(feature = "sqlite", diesel(serialize_as = DbOptJson))]
+  #[cfg_attr(feature = "sqlite", diesel(deserialize_as = DbOptJson))]
   pub metadata: Option<serde_json::Value>,
+  /// Number of attempts of the last run
+  pub attempts: i32,
+  /// Reason of the last failed attempt
+  pub last_failure: Option<String>,
+  /// State of the steps during the last run
+  #[cfg_attr(feature = "sqlite", diesel(serialize_as = DbJson))]
+  #[cfg_attr(feature = "sqlite", diesel(deserialize_as = DbJson))]
+  pub steps: serde_json::Value,
+  /// Latest scheduled time claimed by a node to run the job
+  pub last_scheduled_at: Option<chrono::NaiveDateTime>,
 }
 
 /// This structure represent the update of a job.
 /// It will update the job with the new data.
-#[derive(Clone, AsChangeset)]
+#[derive(Clone, Default, AsChangeset)]
 #[diesel(table_name = jobs)]
 pub struct JobUpdateDb {
   pub updated_at: Option<chrono::NaiveDateTime>,
+  pub attempts: Option<i32>,
+  pub last_failure: Option<Option<String>>,
+  #[cfg_attr(feature = "sqlite", diesel(serialize_as = DbJson))]
+  pub steps: Option<serde_json::Value>,
+}
+
+/// This structure represent a scheduled run of a job.
+#[derive(Clone, Debug, Queryable, Identifiable, Insertable)]
+#[diesel(primary_key(key))]
+#[diesel(table_name = job_runs)]
+pub struct JobRunDb {
+  /// The key of the run
+  #[cfg_attr(feature = "sqlite", diesel(serialize_as = DbUuid))]
+  #[cfg_attr(feature = "sqlite", diesel(deserialize_as = DbUuid))]
+  pub key: uuid::Uuid,
+  /// When the run have been recorded
+  pub created_at: chrono::NaiveDateTime,
+  /// The key of the job
+  pub job_key: String,
+  /// When the run was scheduled
+  pub scheduled_at: chrono::NaiveDateTime,
+  /// The outcome of the run
+  pub status: String,
+  /// Details about the outcome
+  pub note: Option<String>,
+}
+
+impl JobRunDb {
+  pub fn new(
+    job_key: &str,
+    scheduled_at: chrono::NaiveDateTime,
+    status: &JobRunStatus,
+    note: Option<String>,
+  ) -> Self {
+    Self {
+      key: uuid::Uuid::new_v4(),
+      created_at: chrono::Utc::now().naive_utc(),
+      job_key: job_key.to_owned(),
+      scheduled_at,
+      status: status.to_string(),
+      note,
+    }
+  }
+}
+
+impl TryFrom<JobRunDb> for JobRun {
+  type Error = IoError;
+
+  fn try_from(value: JobRunDb) -> Result<Self, Self::Error> {
+    Ok(JobRun {
+      key: value.key,
+      created_at: value.created_at,
+      job_key: value.job_key,
+      scheduled_at: value.scheduled_at,
+      status: JobRunStatus::from_str(&value.status)?,
+      note: value.note,
+    })
+  }
 }
@@ -8,6 +8,9 @@ use nanocl_stubs::metric::MetricPartial;
 
 use crate::{schema::metrics, utils};
 
+#[cfg(feature = "sqlite")]
+use super::sql_types::{DbJson, DbUuid};
+
 /// This structure represent a metric in the database.
 /// A metric is a data point that can be used to monitor the system.
 /// It is stored as a json object in the database.
@@ -20,6 +23,8 @@ use crate::{schema::metrics, utils};
 #[diesel(table_name = metrics)]
 pub struct MetricDb {
   /// The key of the metric in the database `UUID`
+  #[cfg_attr(feature = "sqlite", diesel(serialize_as = DbUuid))]
+  #[cfg_attr(feature = "sqlite", diesel(deserialize_as = DbUuid))]
   pub key: Uuid,
   /// When the metric was created
   pub created_at: chrono::NaiveDateTime,
@@ -30,6 +35,8 @@ pub struct MetricDb {
   /// The kind of the metric
   pub kind: String,
   /// The data of the metric
+  #[cfg_attr(feature = "sqlite", diesel(serialize_as = DbJson))]
+  #[cfg_attr(feature = "sqlite", diesel(deserialize_as = DbJson))]
   pub data: serde_json::Value,
   /// Optional note about the metric
   pub note: Option<String>,
@@ -75,9 +82,26 @@ impl From<&MetricNodePartial> for MetricDb {
   }
 }
 
-/// Result of the query to find the best node to run workload
-#[derive(Debug, Selectable, Queryable, QueryableByName)]
-#[diesel(table_name = crate::schema::metrics)]
-pub struct MetricNodeDb {
+/// Result of the query reading the latest resources usage of the nodes
+/// It is used by the scheduler to find the best nodes to run workload
+#[derive(Debug, Clone, QueryableByName)]
+pub struct MetricNodeUsageDb {
+  /// The node who saved the metric
+  #[diesel(sql_type = diesel::sql_types::Text)]
   pub node_name: String,
+  /// Average cpu usage in percent
+  #[diesel(sql_type = diesel::sql_types::Double)]
+  pub cpu_usage: f64,
+  /// Memory usage in percent
+  #[diesel(sql_type = diesel::sql_types::Double)]
+  pub memory_usage: f64,
+}
+
+/// Result of the query counting the http requests sent to some addresses
+/// It is used by the autoscaler to compute the requests per second
+#[derive(Debug, Clone, QueryableByName)]
+pub struct MetricCountDb {
+  /// Number of matching metrics
+  #[diesel(sql_type = diesel::sql_types::BigInt)]
+  pub count: i64,
 }
@@ -1,5 +1,4 @@
 use diesel::r2d2::{ConnectionManager, Pool as R2D2Pool, PooledConnection};
-use diesel::PgConnection;
 
 mod ws;
 use nanocl_error::io::{IoError, IoResult};
@@ -35,9 +34,18 @@ pub use resource_kind::*;
 mod secret;
 pub use secret::*;
 
+mod volume;
+pub use volume::*;
+
 mod job;
 pub use job::*;
 
+mod auth;
+pub use auth::*;
+
+mod audit;
+pub use audit::*;
+
 mod spec;
 pub use spec::*;
 
@@ -56,8 +64,18 @@ pub use task_manager::*;
 mod object_process_status;
 pub use object_process_status::*;
 
-pub type Pool = R2D2Pool<ConnectionManager<PgConnection>>;
-pub type DBConn = PooledConnection<ConnectionManager<PgConnection>>;
+pub mod sql_types;
+
+/// Connection to the store, CockroachDB or Postgres by default
+/// and an embedded SQLite database with the `sqlite` feature
+#[cfg(not(feature = "sqlite"))]
+pub type DbConnection = diesel::PgConnection;
+#[cfg(feature = "sqlite")]
+pub type DbConnection = diesel::SqliteConnection;
+pub type DbBackend = <DbConnection as diesel::Connection>::Backend;
+
+pub type Pool = R2D2Pool<ConnectionManager<DbConnection>>;
+pub type DBConn = PooledConnection<ConnectionManager<DbConnection>>;
 
 pub enum ColumnType {
   Text,
@@ -80,10 +98,24 @@ macro_rules! gen_sql_and4json {
         Box::new($query.and($column.is_not_null()))
       }
       nanocl_stubs::generic::GenericClause::Contains(val) => {
-        Box::new($query.and($column.contains(val.clone())))
+        #[cfg(not(feature = "sqlite"))]
+        let condition = $column.contains(val.clone());
+        #[cfg(feature = "sqlite")]
+        let condition = $crate::models::sql_types::json_contains(
+          $column.nullable(),
+          $crate::models::sql_types::DbJson::from(val.clone()),
+        );
+        Box::new($query.and(condition))
       }
       nanocl_stubs::generic::GenericClause::HasKey(val) => {
-        Box::new($query.and($column.has_key(val.clone())))
+        #[cfg(not(feature = "sqlite"))]
+        let condition = $column.has_key(val.clone());
+        #[cfg(feature = "sqlite")]
+        let condition = $crate::models::sql_types::json_has_key(
+          $column.nullable(),
+          val.clone(),
+        );
+        Box::new($query.and(condition))
       }
       _ => {
         panic!("Unsupported clause");
@@ -239,27 +271,27 @@ macro_rules! gen_sql_where4timestamptz {
   ($query: expr, $column: expr, $value: expr) => {
     match $value {
       nanocl_stubs::generic::GenericClause::Eq(val) => {
-        let val = $crate::models::parse_date_string(&val).unwrap();
+        let val = $crate::models::parse_date_string(&val).unwrap().naive_utc();
         $query = $query.filter($column.eq(val.clone()));
       }
       nanocl_stubs::generic::GenericClause::Ne(val) => {
-        let val = $crate::models::parse_date_string(&val).unwrap();
+        let val = $crate::models::parse_date_string(&val).unwrap().naive_utc();
         $query = $query.filter($column.ne(val.clone()));
       }
       nanocl_stubs::generic::GenericClause::Gt(val) => {
-        let val = $crate::models::parse_date_string(&val).unwrap();
+        let val = $crate::models::parse_date_string(&val).unwrap().naive_utc();
         $query = $query.filter($column.gt(val.clone()));
       }
       nanocl_stubs::generic::GenericClause::Lt(val) => {
-        let val = $crate::models::parse_date_string(&val).unwrap();
+        let val = $crate::models::parse_date_string(&val).unwrap().naive_utc();
         $query = $query.filter($column.lt(val.clone()));
       }
       nanocl_stubs::generic::GenericClause::Ge(val) => {
-        let val = $crate::models::parse_date_string(&val).unwrap();
+        let val = $crate::models::parse_date_string(&val).unwrap().naive_utc();
         $query = $query.filter($column.ge(val.clone()));
       }
       nanocl_stubs::generic::GenericClause::Le(val) => {
-        let val = $crate::models::parse_date_string(&val).unwrap();
+        let val = $crate::models::parse_date_string(&val).unwrap().naive_utc();
         $query = $query.filter($column.le(val.clone()));
       }
       nanocl_stubs::generic::GenericClause::IsNull => {
@@ -269,16 +301,20 @@ macro_rules! gen_sql_where4timestamptz {
         $query = $query.filter($column.is_not_null());
       }
       nanocl_stubs::generic::GenericClause::In(items) => {
-        let items: Vec<chrono::DateTime<chrono::Utc>> = items
+        let items: Vec<chrono::NaiveDateTime> = items
           .iter()
-          .map(|item| $crate::models::parse_date_string(item).unwrap())
+          .map(|item| {
+            $crate::models::parse_date_string(item).unwrap().naive_utc()
+          })
           .collect();
         $query = $query.filter($column.eq_any(items));
       }
       nanocl_stubs::generic::GenericClause::NotIn(items) => {
-        let items: Vec<chrono::DateTime<chrono::Utc>> = items
+        let items: Vec<chrono::NaiveDateTime> = items
           .iter()
-          .map(|item| $crate::models::parse_date_string(item).unwrap())
+          .map(|item| {
+            $crate::models::parse_date_string(item).unwrap().naive_utc()
+          })
           .collect();
         $query = $query.filter($column.ne_all(items));
       }
@@ -294,27 +330,27 @@ macro_rules! gen_sql_and4timestamptz {
   ($query: expr, $column: expr, $value: expr) => {
     match $value {
       nanocl_stubs::generic::GenericClause::Eq(val) => {
-        let val = $crate::models::parse_date_string(&val).unwrap();
+        let val = $crate::models::parse_date_string(&val).unwrap().naive_utc();
         Box::new($query.and($column.eq(val.clone())))
       }
       nanocl_stubs::generic::GenericClause::Ne(val) => {
-        let val = $crate::models::parse_date_string(&val).unwrap();
+        let val = $crate::models::parse_date_string(&val).unwrap().naive_utc();
         Box::new($query.and($column.ne(val.clone())))
       }
       nanocl_stubs::generic::GenericClause::Gt(val) => {
-        let val = $crate::models::parse_date_string(&val).unwrap();
+        let val = $crate::models::parse_date_string(&val).unwrap().naive_utc();
         Box::new($query.and($column.gt(val.clone())))
       }
       nanocl_stubs::generic::GenericClause::Lt(val) => {
-        let val = $crate::models::parse_date_string(&val).unwrap();
+        let val = $crate::models::parse_date_string(&val).unwrap().naive_utc();
         Box::new($query.and($column.lt(val.clone())))
       }
       nanocl_stubs::generic::GenericClause::Ge(val) => {
-        let val = $crate::models::parse_date_string(&val).unwrap();
+        let val = $crate::models::parse_date_string(&val).unwrap().naive_utc();
         Box::new($query.and($column.ge(val.clone())))
       }
       nanocl_stubs::generic::GenericClause::Le(val) => {
-        let val = $crate::models::parse_date_string(&val).unwrap();
+        let val = $crate::models::parse_date_string(&val).unwrap().naive_utc();
         Box::new($query.and($column.le(val.clone())))
       }
       nanocl_stubs::generic::GenericClause::IsNull => {
@@ -324,16 +360,20 @@ macro_rules! gen_sql_and4timestamptz {
         Box::new($query.and($column.is_not_null()))
       }
       nanocl_stubs::generic::GenericClause::In(items) => {
-        let items: Vec<chrono::DateTime<chrono::Utc>> = items
+        let items: Vec<chrono::NaiveDateTime> = items
           .iter()
-          .map(|item| $crate::models::parse_date_string(item).unwrap())
+          .map(|item| {
+            $crate::models::parse_date_string(item).unwrap().naive_utc()
+          })
           .collect();
         Box::new($query.and($column.eq_any(items)))
       }
       nanocl_stubs::generic::GenericClause::NotIn(items) => {
-        let items: Vec<chrono::DateTime<chrono::Utc>> = items
+        let items: Vec<chrono::NaiveDateTime> = items
           .iter()
-          .map(|item| $crate::models::parse_date_string(item).unwrap())
+          .map(|item| {
+            $crate::models::parse_date_string(item).unwrap().naive_utc()
+          })
           .collect();
         Box::new($query.and($column.ne_all(items)))
       }
@@ -356,10 +396,24 @@ macro_rules! gen_sql_where4json {
         $query = $query.filter($column.is_not_null());
       }
       nanocl_stubs::generic::GenericClause::Contains(val) => {
-        $query = $query.filter($column.contains(val.clone()));
+        #[cfg(not(feature = "sqlite"))]
+        let condition = $column.contains(val.clone());
+        #[cfg(feature = "sqlite")]
+        let condition = $crate::models::sql_types::json_contains(
+          $column.nullable(),
+          $crate::models::sql_types::DbJson::from(val.clone()),
+        );
+        $query = $query.filter(condition);
       }
       nanocl_stubs::generic::GenericClause::HasKey(val) => {
-        $query = $query.filter($column.has_key(val.clone()));
+        #[cfg(not(feature = "sqlite"))]
+        let condition = $column.has_key(val.clone());
+        #[cfg(feature = "sqlite")]
+        let condition = $crate::models::sql_types::json_has_key(
+          $column.nullable(),
+          val.clone(),
+        );
+        $query = $query.filter(condition);
       }
       _ => {
         // Ignore unsupported clause
@@ -379,7 +433,9 @@ macro_rules! gen_sql_and4uuid {
         Box::new($query.and($column.is_not_null()))
       }
       nanocl_stubs::generic::GenericClause::Eq(val) => {
-        let uuid = uuid::Uuid::parse_str(&val).unwrap_or_default();
+        let uuid = $crate::models::sql_types::DbUuid::from(
+          uuid::Uuid::parse_str(&val).unwrap_or_default(),
+        );
         Box::new($query.and($column.eq(uuid)))
       }
       _ => {
@@ -400,7 +456,9 @@ macro_rules! gen_sql_where4uuid {
         $query = $query.filter($column.is_not_null());
       }
       nanocl_stubs::generic::GenericClause::Eq(val) => {
-        let uuid = uuid::Uuid::parse_str(&val).unwrap_or_default();
+        let uuid = $crate::models::sql_types::DbUuid::from(
+          uuid::Uuid::parse_str(&val).unwrap_or_default(),
+        );
         $query = $query.filter($column.eq(uuid));
       }
       _ => {
@@ -429,12 +487,12 @@ macro_rules! gen_sql_query {
         match s_column.0 {
           ColumnType::Uuid => {
             let column =
-              diesel::dsl::sql::<diesel::sql_types::Uuid>(s_column.1);
+              diesel::dsl::sql::<$crate::models::sql_types::Uuid>(s_column.1);
             $crate::gen_sql_where4uuid!($query, column, value);
           }
           ColumnType::Json => {
             let column =
-              diesel::dsl::sql::<diesel::sql_types::Jsonb>(s_column.1);
+              diesel::dsl::sql::<$crate::models::sql_types::Jsonb>(s_column.1);
             $crate::gen_sql_where4json!($query, column, value);
           }
           ColumnType::Text => {
@@ -443,8 +501,9 @@ macro_rules! gen_sql_query {
             $crate::gen_sql_where4string!($query, column, value);
           }
           ColumnType::Timestamptz => {
-            let column =
-              diesel::dsl::sql::<diesel::sql_types::Timestamptz>(s_column.1);
+            let column = diesel::dsl::sql::<
+              $crate::models::sql_types::Timestamptz,
+            >(s_column.1);
             $crate::gen_sql_where4timestamptz!($query, column, value);
           }
         }
@@ -462,7 +521,7 @@ macro_rules! gen_sql_query {
           match s_column.0 {
             ColumnType::Uuid => {
               let column =
-                diesel::dsl::sql::<diesel::sql_types::Uuid>(s_column.1);
+                diesel::dsl::sql::<$crate::models::sql_types::Uuid>(s_column.1);
               or_condition =
                 $crate::gen_sql_and4uuid!(or_condition, column, value);
             }
@@ -473,14 +532,16 @@ macro_rules! gen_sql_query {
                 $crate::gen_sql_and4string!(or_condition, column, value);
             }
             ColumnType::Json => {
-              let column =
-                diesel::dsl::sql::<diesel::sql_types::Jsonb>(s_column.1);
+              let column = diesel::dsl::sql::<$crate::models::sql_types::Jsonb>(
+                s_column.1,
+              );
               or_condition =
                 $crate::gen_sql_and4json!(or_condition, column, value);
             }
             ColumnType::Timestamptz => {
-              let column =
-                diesel::dsl::sql::<diesel::sql_types::Timestamptz>(s_column.1);
+              let column = diesel::dsl::sql::<
+                $crate::models::sql_types::Timestamptz,
+              >(s_column.1);
               or_condition =
                 $crate::gen_sql_and4timestamptz!(or_condition, column, value);
             }
@@ -506,7 +567,7 @@ macro_rules! gen_sql_order_by {
         match s_column.0 {
           ColumnType::Uuid => {
             let column =
-              diesel::dsl::sql::<diesel::sql_types::Uuid>(s_column.1);
+              diesel::dsl::sql::<$crate::models::sql_types::Uuid>(s_column.1);
             match order {
               nanocl_stubs::generic::GenericOrder::Asc => {
                 $query = $query.order(column.asc());
@@ -518,7 +579,7 @@ macro_rules! gen_sql_order_by {
           }
           ColumnType::Json => {
             let column =
-              diesel::dsl::sql::<diesel::sql_types::Json>(s_column.1);
+              diesel::dsl::sql::<$crate::models::sql_types::Jsonb>(s_column.1);
             match order {
               nanocl_stubs::generic::GenericOrder::Asc => {
                 $query = $query.order(column.asc());
@@ -541,8 +602,9 @@ macro_rules! gen_sql_order_by {
             }
           }
           ColumnType::Timestamptz => {
-            let column =
-              diesel::dsl::sql::<diesel::sql_types::Timestamptz>(s_column.1);
+            let column = diesel::dsl::sql::<
+              $crate::models::sql_types::Timestamptz,
+            >(s_column.1);
             match order {
               nanocl_stubs::generic::GenericOrder::Asc => {
                 $query = $query.order(column.asc());
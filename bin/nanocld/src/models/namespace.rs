@@ -1,10 +1,18 @@
 use diesel::prelude::*;
 use serde::{Deserialize, Serialize};
 
-use nanocl_stubs::namespace::{Namespace, NamespacePartial};
+use nanocl_error::io::{FromIo, IoError, IoResult};
+
+use nanocl_stubs::namespace::{
+  Namespace, NamespaceLimitRange, NamespacePartial, NamespaceQuota,
+  NamespaceUpdate,
+};
 
 use crate::schema::namespaces;
 
+#[cfg(feature = "sqlite")]
+use super::sql_types::DbOptJson;
+
 /// This structure represent the namespace in the database.
 /// A namespace is a group of cargo or virtual machine that share the same network.
 /// It is used to isolate the services.
@@ -20,7 +28,39 @@ pub struct NamespaceDb {
   /// When the namespace was created
   pub created_at: chrono::NaiveDateTime,
   /// User defined metadata
+  #[cfg_attr(feature = "sqlite", diesel(serialize_as = DbOptJson))]
+  #[cfg_attr(feature = "sqlite", diesel(deserialize_as = DbOptJson))]
   pub metadata: Option<serde_json::Value>,
+  /// Caps on the total resources used by the namespace
+  #[cfg_attr(feature = "sqlite", diesel(serialize_as = DbOptJson))]
+  #[cfg_attr(feature = "sqlite", diesel(deserialize_as = DbOptJson))]
+  pub quota: Option<serde_json::Value>,
+  /// Default and maximum resources of each container
+  #[cfg_attr(feature = "sqlite", diesel(serialize_as = DbOptJson))]
+  #[cfg_attr(feature = "sqlite", diesel(deserialize_as = DbOptJson))]
+  pub limit_range: Option<serde_json::Value>,
+}
+
+/// Serialize an optional value to be stored as json
+fn to_json<T: Serialize>(
+  value: Option<&T>,
+  context: &str,
+) -> IoResult<Option<serde_json::Value>> {
+  value
+    .map(serde_json::to_value)
+    .transpose()
+    .map_err(|err| err.map_err_context(|| context).into())
+}
+
+/// Deserialize an optional value stored as json
+fn from_json<T: serde::de::DeserializeOwned>(
+  value: Option<&serde_json::Value>,
+  context: &str,
+) -> IoResult<Option<T>> {
+  value
+    .map(|value| serde_json::from_value(value.clone()))
+    .transpose()
+    .map_err(|err| err.map_err_context(|| context).into())
 }
 
 impl NamespaceDb {
@@ -30,26 +70,71 @@ impl NamespaceDb {
       name: name.to_owned(),
       created_at: chrono::Utc::now().naive_utc(),
       metadata: None,
+      quota: None,
+      limit_range: None,
     }
   }
+
+  /// Quota of the namespace if any
+  pub fn get_quota(&self) -> IoResult<Option<NamespaceQuota>> {
+    from_json(self.quota.as_ref(), "NamespaceQuota")
+  }
+
+  /// Limit range of the namespace if any
+  pub fn get_limit_range(&self) -> IoResult<Option<NamespaceLimitRange>> {
+    from_json(self.limit_range.as_ref(), "NamespaceLimitRange")
+  }
 }
 
-impl From<&NamespacePartial> for NamespaceDb {
-  fn from(p: &NamespacePartial) -> Self {
-    Self {
+impl TryFrom<&NamespacePartial> for NamespaceDb {
+  type Error = IoError;
+
+  fn try_from(p: &NamespacePartial) -> Result<Self, Self::Error> {
+    Ok(Self {
       name: p.name.clone(),
       created_at: chrono::Utc::now().naive_utc(),
       metadata: p.metadata.clone(),
-    }
+      quota: to_json(p.quota.as_ref(), "NamespaceQuota")?,
+      limit_range: to_json(p.limit_range.as_ref(), "NamespaceLimitRange")?,
+    })
   }
 }
 
-impl From<NamespaceDb> for Namespace {
-  fn from(namespace: NamespaceDb) -> Self {
-    Self {
+impl TryFrom<NamespaceDb> for Namespace {
+  type Error = IoError;
+
+  fn try_from(namespace: NamespaceDb) -> Result<Self, Self::Error> {
+    Ok(Self {
+      quota: namespace.get_quota()?,
+      limit_range: namespace.get_limit_range()?,
       name: namespace.name,
       created_at: namespace.created_at,
       metadata: namespace.metadata,
-    }
+    })
+  }
+}
+
+/// This structure is used to update the quota and limit range of a namespace.
+/// Unset values are removed.
+#[derive(Debug, Default, AsChangeset)]
+#[diesel(table_name = namespaces)]
+#[diesel(treat_none_as_null = true)]
+pub struct NamespaceUpdateDb {
+  /// Caps on the total resources used by the namespace
+  #[cfg_attr(feature = "sqlite", diesel(serialize_as = DbOptJson))]
+  pub quota: Option<serde_json::Value>,
+  /// Default and maximum resources of each container
+  #[cfg_attr(feature = "sqlite", diesel(serialize_as = DbOptJson))]
+  pub limit_range: Option<serde_json::Value>,
+}
+
+impl TryFrom<&NamespaceUpdate> for NamespaceUpdateDb {
+  type Error = IoError;
+
+  fn try_from(update: &NamespaceUpdate) -> Result<Self, Self::Error> {
+    Ok(Self {
+      quota: to_json(update.quota.as_ref(), "NamespaceQuota")?,
+      limit_range: to_json(update.limit_range.as_ref(), "NamespaceLimitRange")?,
+    })
   }
 }
@@ -1,7 +1,13 @@
 use diesel::prelude::*;
 use serde::{Deserialize, Serialize};
 
-use crate::schema::nodes;
+use nanocl_error::io::IoError;
+use nanocl_stubs::node::Node;
+
+use crate::schema::{node_group_links, nodes};
+
+#[cfg(feature = "sqlite")]
+use super::sql_types::{DbInet, DbOptJson};
 
 /// This structure represent a node in the database.
 /// A node is a machine that is connected to nanocl network.
@@ -17,6 +23,8 @@ pub struct NodeDb {
   /// The created at date
   pub created_at: chrono::NaiveDateTime,
   /// The ip address of the node
+  #[cfg_attr(feature = "sqlite", diesel(serialize_as = DbInet))]
+  #[cfg_attr(feature = "sqlite", diesel(deserialize_as = DbInet))]
   pub ip_address: ipnet::IpNet,
   /// Endpoint to connect to the node
   pub endpoint: String,
@@ -24,5 +32,61 @@ pub struct NodeDb {
   pub version: String,
   /// User defined metadata
   #[serde(skip_serializing_if = "Option::is_none")]
+  #[cfg_attr(feature = "sqlite", diesel(serialize_as = DbOptJson))]
+  #[cfg_attr(feature = "sqlite", diesel(deserialize_as = DbOptJson))]
   pub metadata: Option<serde_json::Value>,
+  /// The status of the node known from its heartbeats
+  pub status: String,
+  /// When the last heartbeat of the node was received
+  pub last_seen: chrono::NaiveDateTime,
+  /// Whether the node receives no new instances
+  pub cordoned: bool,
+  /// Whether the instances of the node are moved to the other nodes
+  pub draining: bool,
+  /// Hash of the token the node authenticates with on the other nodes
+  #[serde(skip)]
+  pub token_hash: Option<String>,
+}
+
+impl TryFrom<NodeDb> for Node {
+  type Error = IoError;
+
+  fn try_from(node: NodeDb) -> Result<Self, Self::Error> {
+    Ok(Self {
+      status: node.status.parse()?,
+      name: node.name,
+      created_at: node.created_at,
+      ip_address: node.ip_address,
+      endpoint: node.endpoint,
+      version: node.version,
+      metadata: node.metadata,
+      last_seen: node.last_seen,
+      cordoned: node.cordoned,
+      draining: node.draining,
+    })
+  }
+}
+
+/// This structure represent the update of a node.
+#[derive(Clone, Default, AsChangeset)]
+#[diesel(table_name = nodes)]
+pub struct NodeUpdateDb {
+  pub endpoint: Option<String>,
+  pub version: Option<String>,
+  pub status: Option<String>,
+  pub last_seen: Option<chrono::NaiveDateTime>,
+  pub cordoned: Option<bool>,
+  pub draining: Option<bool>,
+  pub token_hash: Option<Option<String>>,
+}
+
+/// This structure represent the link between a node and a node group.
+/// It is used to resolve the nodes targeted by a replication mode.
+#[derive(Debug, Clone, Queryable, Selectable)]
+#[diesel(table_name = node_group_links)]
+pub struct NodeGroupLinkDb {
+  /// The name of the node
+  pub node_name: String,
+  /// The name of the node group
+  pub node_group_name: String,
 }
@@ -6,6 +6,9 @@ use nanocl_stubs::process::{Process, ProcessKind, ProcessPartial};
 
 use crate::schema::processes;
 
+#[cfg(feature = "sqlite")]
+use super::sql_types::DbJson;
+
 /// Represents a process (job, cargo, vm) in the database
 #[derive(Clone, Queryable, Identifiable, Insertable, Selectable)]
 #[diesel(primary_key(key))]
@@ -22,6 +25,8 @@ pub struct ProcessDb {
   /// Kind of the process (Job, Vm, Cargo)
   pub kind: String,
   /// The data of the process a ContainerInspect
+  #[cfg_attr(feature = "sqlite", diesel(serialize_as = DbJson))]
+  #[cfg_attr(feature = "sqlite", diesel(deserialize_as = DbJson))]
   pub data: serde_json::Value,
   /// Id of the node where the container is running
   pub node_name: String,
@@ -39,6 +44,7 @@ pub struct ProcessUpdateDb {
   /// Name of instance
   pub name: Option<String>,
   // The updated at data
+  #[cfg_attr(feature = "sqlite", diesel(serialize_as = DbJson))]
   pub data: Option<serde_json::Value>,
 }
 
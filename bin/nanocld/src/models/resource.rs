@@ -7,6 +7,9 @@ use super::SpecDb;
 
 use crate::schema::resources;
 
+#[cfg(feature = "sqlite")]
+use super::sql_types::DbUuid;
+
 /// This structure represent a resource in the database.
 /// A resource is a representation of a specification for internal nanocl services (controllers).
 /// Custom `kind` can be added to the system.
@@ -26,6 +29,8 @@ pub struct ResourceDb {
   /// The kind of the resource
   pub kind: String,
   /// The spec key reference
+  #[cfg_attr(feature = "sqlite", diesel(serialize_as = DbUuid))]
+  #[cfg_attr(feature = "sqlite", diesel(deserialize_as = DbUuid))]
   pub spec_key: uuid::Uuid,
 }
 
@@ -36,6 +41,7 @@ pub struct ResourceUpdateDb {
   /// The key of the resource
   pub key: Option<String>,
   /// The spec key reference
+  #[cfg_attr(feature = "sqlite", diesel(serialize_as = DbUuid))]
   pub spec_key: Option<uuid::Uuid>,
 }
 
@@ -10,6 +10,9 @@ use crate::{schema::resource_kinds, utils};
 
 use super::SpecDb;
 
+#[cfg(feature = "sqlite")]
+use super::sql_types::DbUuid;
+
 /// This structure represent the resource kind in the database.
 /// A resource kind represent the kind of a resource.
 /// It is stored with a version that containt the schema or and url of a service to call.
@@ -22,12 +25,15 @@ pub struct ResourceKindDb {
   /// When the kind have been created
   pub created_at: chrono::NaiveDateTime,
   /// Last version
+  #[cfg_attr(feature = "sqlite", diesel(serialize_as = DbUuid))]
+  #[cfg_attr(feature = "sqlite", diesel(deserialize_as = DbUuid))]
   pub spec_key: uuid::Uuid,
 }
 
 #[derive(Clone, Debug, AsChangeset)]
 #[diesel(table_name = resource_kinds)]
 pub struct ResourceKindDbUpdate {
+  #[cfg_attr(feature = "sqlite", diesel(serialize_as = DbUuid))]
   pub spec_key: uuid::Uuid,
 }
 
@@ -7,6 +7,9 @@ use nanocl_stubs::secret::{Secret, SecretPartial, SecretUpdate};
 
 use crate::schema::secrets;
 
+#[cfg(feature = "sqlite")]
+use super::sql_types::{DbJson, DbOptJson};
+
 /// This structure represent the secret in the database.
 /// A secret is a key/value pair that can be used by the user to store
 /// sensitive data. It is stored as a json object in the database.
@@ -28,9 +31,13 @@ pub struct SecretDb {
   /// The secret cannot be updated
   pub immutable: bool,
   /// The secret data
+  #[cfg_attr(feature = "sqlite", diesel(serialize_as = DbJson))]
+  #[cfg_attr(feature = "sqlite", diesel(deserialize_as = DbJson))]
   pub data: serde_json::Value,
   // The metadata (user defined)
   #[serde(skip_serializing_if = "Option::is_none")]
+  #[cfg_attr(feature = "sqlite", diesel(serialize_as = DbOptJson))]
+  #[cfg_attr(feature = "sqlite", diesel(deserialize_as = DbOptJson))]
   pub metadata: Option<serde_json::Value>,
 }
 
@@ -69,8 +76,10 @@ impl TryFrom<SecretDb> for Secret {
 #[diesel(table_name = secrets)]
 pub struct SecretUpdateDb {
   /// The secret data
+  #[cfg_attr(feature = "sqlite", diesel(serialize_as = DbJson))]
   pub data: Option<serde_json::Value>,
   // The metadata (user defined)
+  #[cfg_attr(feature = "sqlite", diesel(serialize_as = DbJson))]
   pub metadata: Option<serde_json::Value>,
 }
 
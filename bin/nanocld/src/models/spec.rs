@@ -2,6 +2,9 @@ use diesel::prelude::*;
 
 use crate::schema::specs;
 
+#[cfg(feature = "sqlite")]
+use super::sql_types::{DbJson, DbOptJson, DbUuid};
+
 /// This structure represent the specification of an object. (job, cargo, vm, ...)
 /// We store it with it's version to ensure backward compatibility.
 #[derive(Clone, Debug, Queryable, Identifiable, Insertable)]
@@ -9,6 +12,8 @@ use crate::schema::specs;
 #[diesel(primary_key(key))]
 pub struct SpecDb {
   /// The related resource kind reference
+  #[cfg_attr(feature = "sqlite", diesel(serialize_as = DbUuid))]
+  #[cfg_attr(feature = "sqlite", diesel(deserialize_as = DbUuid))]
   pub key: uuid::Uuid,
   /// When the resource kind version have been created
   pub created_at: chrono::NaiveDateTime,
@@ -19,7 +24,11 @@ pub struct SpecDb {
   /// Version of the resource kind
   pub version: String,
   /// Config of the resource kind version
+  #[cfg_attr(feature = "sqlite", diesel(serialize_as = DbJson))]
+  #[cfg_attr(feature = "sqlite", diesel(deserialize_as = DbJson))]
   pub data: serde_json::Value,
   /// Metadata (user defined) of the resource kind version
+  #[cfg_attr(feature = "sqlite", diesel(serialize_as = DbOptJson))]
+  #[cfg_attr(feature = "sqlite", diesel(deserialize_as = DbOptJson))]
   pub metadata: Option<serde_json::Value>,
 }
@@ -0,0 +1,318 @@
+//! Sql types of the columns stored differently depending on the store backend.
+//! Postgres and CockroachDB have native types for them,
+//! SQLite stores them as text.
+
+#[cfg(not(feature = "sqlite"))]
+pub use diesel::sql_types::{Inet, Jsonb, Timestamptz, Uuid};
+
+/// Uuids bound to a query are converted with `DbUuid::from`
+/// to be stored the same way on every backend
+#[cfg(not(feature = "sqlite"))]
+pub type DbUuid = uuid::Uuid;
+
+#[cfg(feature = "sqlite")]
+pub use sqlite::*;
+
+#[cfg(feature = "sqlite")]
+mod sqlite {
+  use diesel::{
+    deserialize::{self, FromSql, FromSqlRow},
+    expression::AsExpression,
+    query_builder::QueryId,
+    serialize::{self, IsNull, Output, ToSql},
+    sql_types::{Nullable, SqlType, Text},
+    sqlite::{Sqlite, SqliteConnection, SqliteValue},
+  };
+
+  /// Uuid stored as its hyphenated text
+  #[derive(Debug, Clone, Copy, Default, SqlType, QueryId)]
+  #[diesel(sqlite_type(name = "Text"))]
+  pub struct Uuid;
+
+  /// Json stored as its serialized text
+  #[derive(Debug, Clone, Copy, Default, SqlType, QueryId)]
+  #[diesel(sqlite_type(name = "Text"))]
+  pub struct Jsonb;
+
+  /// Ip network stored as its text like `10.0.0.1/32`
+  #[derive(Debug, Clone, Copy, Default, SqlType, QueryId)]
+  #[diesel(sqlite_type(name = "Text"))]
+  pub struct Inet;
+
+  /// SQLite has no timezone, the dates are always saved in UTC
+  pub type Timestamptz = diesel::sql_types::Timestamp;
+
+  /// Result of the conversion of a value from or to its text
+  type TextResult<T> = Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+  /// Ip network operators like postgres,
+  /// required by the `table!` macro for the columns named `Inet`
+  impl diesel::sql_types::ops::Add for Inet {
+    type Rhs = diesel::sql_types::BigInt;
+    type Output = Inet;
+  }
+
+  impl diesel::sql_types::ops::Sub for Inet {
+    type Rhs = diesel::sql_types::BigInt;
+    type Output = Inet;
+  }
+
+  /// Implement the conversion of a wrapper from and to its text
+  macro_rules! text_sql_type {
+    ($wrapper: ident, $rust_type: ty, $sql_type: ty, $to_string: expr, $from_str: expr) => {
+      /// Wrapper used to store a value as text in SQLite
+      #[derive(Debug, Clone, PartialEq, AsExpression, FromSqlRow)]
+      #[diesel(sql_type = $sql_type)]
+      pub struct $wrapper(pub $rust_type);
+
+      impl From<$rust_type> for $wrapper {
+        fn from(value: $rust_type) -> Self {
+          Self(value)
+        }
+      }
+
+      impl From<$wrapper> for $rust_type {
+        fn from(value: $wrapper) -> Self {
+          value.0
+        }
+      }
+
+      impl ToSql<$sql_type, Sqlite> for $wrapper {
+        fn to_sql<'b>(
+          &'b self,
+          out: &mut Output<'b, '_, Sqlite>,
+        ) -> serialize::Result {
+          let to_string: fn(&$rust_type) -> TextResult<String> = $to_string;
+          out.set_value(to_string(&self.0)?);
+          Ok(IsNull::No)
+        }
+      }
+
+      impl FromSql<$sql_type, Sqlite> for $wrapper {
+        fn from_sql(
+          value: SqliteValue<'_, '_, '_>,
+        ) -> deserialize::Result<Self> {
+          let text = <String as FromSql<Text, Sqlite>>::from_sql(value)?;
+          let from_str: fn(&str) -> TextResult<$rust_type> = $from_str;
+          Ok(Self(from_str(&text)?))
+        }
+      }
+    };
+  }
+
+  text_sql_type!(
+    DbUuid,
+    uuid::Uuid,
+    Uuid,
+    |uuid| Ok(uuid.to_string()),
+    |text| Ok(uuid::Uuid::parse_str(text)?)
+  );
+
+  text_sql_type!(
+    DbJson,
+    serde_json::Value,
+    Jsonb,
+    |value| Ok(serde_json::to_string(value)?),
+    |text| Ok(serde_json::from_str(text)?)
+  );
+
+  text_sql_type!(
+    DbInet,
+    ipnet::IpNet,
+    Inet,
+    |net| Ok(net.to_string()),
+    |text| Ok(text.parse::<ipnet::IpNet>()?)
+  );
+
+  /// Wrapper of a nullable json column,
+  /// diesel can't convert an `Option` of a wrapper into an `Option`
+  #[derive(Debug, Clone, PartialEq, AsExpression, FromSqlRow)]
+  #[diesel(sql_type = Nullable<Jsonb>)]
+  pub struct DbOptJson(pub Option<serde_json::Value>);
+
+  impl From<Option<serde_json::Value>> for DbOptJson {
+    fn from(value: Option<serde_json::Value>) -> Self {
+      Self(value)
+    }
+  }
+
+  impl From<DbOptJson> for Option<serde_json::Value> {
+    fn from(value: DbOptJson) -> Self {
+      value.0
+    }
+  }
+
+  impl ToSql<Nullable<Jsonb>, Sqlite> for DbOptJson {
+    fn to_sql<'b>(
+      &'b self,
+      out: &mut Output<'b, '_, Sqlite>,
+    ) -> serialize::Result {
+      match &self.0 {
+        None => Ok(IsNull::Yes),
+        Some(value) => {
+          out.set_value(serde_json::to_string(value)?);
+          Ok(IsNull::No)
+        }
+      }
+    }
+  }
+
+  impl FromSql<Nullable<Jsonb>, Sqlite> for DbOptJson {
+    fn from_sql(value: SqliteValue<'_, '_, '_>) -> deserialize::Result<Self> {
+      let value = <DbJson as FromSql<Jsonb, Sqlite>>::from_sql(value)?;
+      Ok(Self(Some(value.0)))
+    }
+
+    fn from_nullable_sql(
+      value: Option<SqliteValue<'_, '_, '_>>,
+    ) -> deserialize::Result<Self> {
+      match value {
+        None => Ok(Self(None)),
+        Some(value) => Self::from_sql(value),
+      }
+    }
+  }
+
+  diesel::define_sql_function! {
+    /// Whether a json contains another one like the `@>` jsonb operator
+    fn json_contains(
+      target: diesel::sql_types::Nullable<super::Jsonb>,
+      value: super::Jsonb,
+    ) -> diesel::sql_types::Bool;
+  }
+
+  diesel::define_sql_function! {
+    /// Whether a json object has a key or a json array has a string element
+    /// like the `?` jsonb operator
+    fn json_has_key(
+      target: diesel::sql_types::Nullable<super::Jsonb>,
+      key: diesel::sql_types::Text,
+    ) -> diesel::sql_types::Bool;
+  }
+
+  /// Whether the structure and data of a json contains another one
+  fn deep_contains(
+    target: &serde_json::Value,
+    value: &serde_json::Value,
+  ) -> bool {
+    use serde_json::Value;
+    match (target, value) {
+      (Value::Object(target), Value::Object(value)) => {
+        value.iter().all(|(key, value)| {
+          target
+            .get(key)
+            .is_some_and(|target| deep_contains(target, value))
+        })
+      }
+      (Value::Array(target), Value::Array(value)) => value
+        .iter()
+        .all(|value| target.iter().any(|target| deep_contains(target, value))),
+      (target, value) => target == value,
+    }
+  }
+
+  /// Same rules as postgres to tell if a json contains another one,
+  /// a top level array also contains a primitive value
+  fn contains(target: &serde_json::Value, value: &serde_json::Value) -> bool {
+    match target {
+      serde_json::Value::Array(items)
+        if !value.is_array() && !value.is_object() =>
+      {
+        items.iter().any(|item| item == value)
+      }
+      _ => deep_contains(target, value),
+    }
+  }
+
+  /// Same rules as postgres to tell if a json has a key
+  fn has_key(target: &serde_json::Value, key: &str) -> bool {
+    use serde_json::Value;
+    match target {
+      Value::Object(target) => target.contains_key(key),
+      Value::Array(target) => target.iter().any(|value| value == key),
+      Value::String(target) => target == key,
+      _ => false,
+    }
+  }
+
+  /// Same functions declared on their storage type,
+  /// diesel only implements the registration for the builtin sql types
+  mod storage {
+    diesel::define_sql_function! {
+      fn json_contains(
+        target: diesel::sql_types::Nullable<diesel::sql_types::Text>,
+        value: diesel::sql_types::Text,
+      ) -> diesel::sql_types::Bool;
+    }
+
+    diesel::define_sql_function! {
+      fn json_has_key(
+        target: diesel::sql_types::Nullable<diesel::sql_types::Text>,
+        key: diesel::sql_types::Text,
+      ) -> diesel::sql_types::Bool;
+    }
+  }
+
+  /// Parse a json stored as text, invalid json contains nothing
+  fn parse_json(text: Option<String>) -> Option<serde_json::Value> {
+    serde_json::from_str(&text?).ok()
+  }
+
+  /// Register the json functions used by the generic filters
+  /// they must be registered on every new connection
+  pub fn register_functions(
+    conn: &mut SqliteConnection,
+  ) -> diesel::QueryResult<()> {
+    storage::json_contains_utils::register_impl(
+      conn,
+      |target: Option<String>, value: String| match (
+        parse_json(target),
+        parse_json(Some(value)),
+      ) {
+        (Some(target), Some(value)) => contains(&target, &value),
+        _ => false,
+      },
+    )?;
+    storage::json_has_key_utils::register_impl(
+      conn,
+      |target: Option<String>, key: String| {
+        parse_json(target).is_some_and(|target| has_key(&target, &key))
+      },
+    )?;
+    Ok(())
+  }
+
+  /// Sqlite sql types unit test
+  #[cfg(test)]
+  mod tests {
+    use super::*;
+
+    /// Test the json functions follow the postgres rules
+    #[test]
+    fn json_functions() {
+      let target = serde_json::json!({
+        "Name": "test",
+        "Labels": ["a", "b"],
+        "Spec": { "Replicas": 2, "Image": "nginx" },
+      });
+      assert!(contains(&target, &serde_json::json!({ "Name": "test" })));
+      assert!(contains(
+        &target,
+        &serde_json::json!({ "Spec": { "Replicas": 2 } })
+      ));
+      assert!(contains(&target, &serde_json::json!({ "Labels": ["b"] })));
+      assert!(!contains(&target, &serde_json::json!({ "Labels": "a" })));
+      assert!(contains(
+        &serde_json::json!(["a", "b"]),
+        &serde_json::json!("a")
+      ));
+      assert!(!contains(&target, &serde_json::json!({ "Labels": ["c"] })));
+      assert!(!contains(&target, &serde_json::json!({ "Name": "other" })));
+      assert!(!contains(&target, &serde_json::json!({ "Missing": 1 })));
+      assert!(has_key(&target, "Spec"));
+      assert!(!has_key(&target, "Image"));
+      assert!(has_key(&serde_json::json!(["a", "b"]), "a"));
+      assert!(!has_key(&serde_json::json!([1, 2]), "1"));
+    }
+  }
+}
@@ -1,10 +1,12 @@
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
 
 use futures::channel::mpsc;
 use ntex::rt;
 
 use nanocl_stubs::{config::DaemonConfig, system::Event};
 
+use crate::utils::secret::MasterKey;
+
 use super::{Pool, RawEventEmitter, TaskManager};
 
 /// This structure represent the state of the system.
@@ -19,6 +21,10 @@ pub struct SystemStateInner {
   pub config: DaemonConfig,
   /// Manager of the tasks
   pub task_manager: TaskManager,
+  /// Keys used to encrypt the secrets, the current one first
+  pub master_keys: Arc<RwLock<Vec<MasterKey>>>,
+  /// Token of the node sent to the other nodes, only its hash is stored
+  pub node_token: String,
   /// Event emitter
   pub(crate) event_emitter: mpsc::UnboundedSender<Event>,
   /// Http event client
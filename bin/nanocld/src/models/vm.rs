@@ -5,6 +5,9 @@ use crate::schema::vms;
 
 use super::NamespaceDb;
 
+#[cfg(feature = "sqlite")]
+use super::sql_types::DbUuid;
+
 /// This structure represent the vm in the database.
 /// A vm is a virtual machine that is running on the server.
 /// The vm is linked to a namespace.
@@ -26,6 +29,8 @@ pub struct VmDb {
   /// The status key
   pub status_key: String,
   /// The spec key reference
+  #[cfg_attr(feature = "sqlite", diesel(serialize_as = DbUuid))]
+  #[cfg_attr(feature = "sqlite", diesel(deserialize_as = DbUuid))]
   pub spec_key: uuid::Uuid,
 }
 
@@ -40,6 +45,7 @@ pub struct VmUpdateDb {
   /// The name of the vm
   pub name: Option<String>,
   /// The spec key reference
+  #[cfg_attr(feature = "sqlite", diesel(serialize_as = DbUuid))]
   pub spec_key: Option<uuid::Uuid>,
 }
 
@@ -0,0 +1,71 @@
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use nanocl_error::io::{IoError, IoResult};
+
+use nanocl_stubs::volume::{Volume, VolumePartial};
+
+use crate::schema::volumes;
+
+use super::NamespaceDb;
+
+#[cfg(feature = "sqlite")]
+use super::sql_types::DbOptJson;
+
+/// This structure represent the volume in the database.
+/// A volume hold the persistent data of the cargoes of a namespace,
+/// it is backed by a docker named volume or a directory on the host.
+#[derive(
+  Debug,
+  Clone,
+  Serialize,
+  Deserialize,
+  Queryable,
+  Identifiable,
+  Insertable,
+  Associations,
+)]
+#[serde(rename_all = "PascalCase")]
+#[diesel(primary_key(key))]
+#[diesel(table_name = volumes)]
+#[diesel(belongs_to(NamespaceDb, foreign_key = namespace_name))]
+pub struct VolumeDb {
+  /// The key of the volume `{name}.{namespace}`
+  pub key: String,
+  /// The creation date
+  pub created_at: chrono::NaiveDateTime,
+  /// The name of the volume
+  pub name: String,
+  /// The namespace of the volume
+  pub namespace_name: String,
+  /// The storage backing the volume
+  pub kind: String,
+  /// Name of the docker volume or path of the directory on the host
+  pub source: String,
+  /// The metadata (user defined)
+  #[cfg_attr(feature = "sqlite", diesel(serialize_as = DbOptJson))]
+  #[cfg_attr(feature = "sqlite", diesel(deserialize_as = DbOptJson))]
+  pub metadata: Option<serde_json::Value>,
+}
+
+/// Arguments to create a new volume obj
+pub struct VolumeObjCreateIn {
+  pub namespace: String,
+  pub spec: VolumePartial,
+}
+
+impl TryFrom<VolumeDb> for Volume {
+  type Error = IoError;
+
+  fn try_from(db: VolumeDb) -> IoResult<Self> {
+    Ok(Volume {
+      kind: db.kind.parse()?,
+      key: db.key,
+      name: db.name,
+      namespace_name: db.namespace_name,
+      source: db.source,
+      created_at: db.created_at,
+      metadata: db.metadata,
+    })
+  }
+}
@@ -4,6 +4,8 @@ use std::time::{Duration, Instant};
 pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(5);
 /// How long before lack of client response causes a timeout
 pub const CLIENT_TIMEOUT: Duration = Duration::from_secs(10);
+/// Header telling which node opened a connection on the node websocket
+pub const NODE_HEADER: &str = "x-nanocl-node";
 
 /// This structure represent the state of a websocket connection.
 #[derive(Debug, Clone)]
@@ -41,9 +41,20 @@ impl ObjCreate for CargoDb {
         "Cargo name can only contain a-z, A-Z, 0-9, and -_",
       ));
     }
+    utils::container::secret::validate_mounts(
+      obj.spec.secret_mounts.as_deref().unwrap_or_default(),
+    )?;
+    utils::volume::validate_mounts(
+      &obj.namespace,
+      obj.spec.volume_mounts.as_deref().unwrap_or_default(),
+      state,
+    )
+    .await?;
     let key = utils::key::gen_key(&obj.namespace, &obj.spec.name);
-    let new_spec =
-      SpecDb::try_from_cargo_partial(&key, &obj.version, &obj.spec)?;
+    let spec =
+      utils::quota::prepare_cargo(&obj.namespace, None, &obj.spec, state)
+        .await?;
+    let new_spec = SpecDb::try_from_cargo_partial(&key, &obj.version, &spec)?;
     let spec = SpecDb::create_from(new_spec, &state.inner.pool)
       .await?
       .try_to_cargo_spec()?;
@@ -62,6 +73,10 @@ impl ObjCreate for CargoDb {
       namespace_name: obj.namespace.clone(),
       status_key: key,
       spec_key: spec.key,
+      settled_spec_key: None,
+      replicas: None,
+      autoscaler_node: None,
+      autoscaler_lease_at: None,
     };
     let cargo = CargoDb::create_from(new_item, &state.inner.pool)
       .await?
@@ -123,6 +138,23 @@ impl ObjPutByPk for CargoDb {
     obj: &Self::ObjPutIn,
     state: &SystemState,
   ) -> HttpResult<Self::ObjPutOut> {
+    utils::container::secret::validate_mounts(
+      obj.spec.secret_mounts.as_deref().unwrap_or_default(),
+    )?;
+    let cargo = CargoDb::transform_read_by_pk(pk, &state.inner.pool).await?;
+    utils::volume::validate_mounts(
+      &cargo.namespace_name,
+      obj.spec.volume_mounts.as_deref().unwrap_or_default(),
+      state,
+    )
+    .await?;
+    let spec = utils::quota::prepare_cargo(
+      &cargo.namespace_name,
+      Some(pk),
+      &obj.spec,
+      state,
+    )
+    .await?;
     let status = ObjPsStatusDb::read_by_pk(pk, &state.inner.pool).await?;
     let new_status = ObjPsStatusUpdate {
       wanted: Some(ObjPsStatusKind::Start.to_string()),
@@ -131,7 +163,7 @@ impl ObjPutByPk for CargoDb {
       prev_actual: Some(status.actual),
     };
     ObjPsStatusDb::update_pk(pk, new_status, &state.inner.pool).await?;
-    CargoDb::update_from_spec(pk, &obj.spec, &obj.version, &state.inner.pool)
+    CargoDb::update_from_spec(pk, &spec, &obj.version, &state.inner.pool)
       .await
       .map_err(HttpError::from)
   }
@@ -243,6 +275,16 @@ impl ObjPatchByPk for CargoDb {
       } else {
         cargo.spec.secrets
       },
+      secret_mounts: if obj.spec.secret_mounts.is_some() {
+        obj.spec.secret_mounts.clone()
+      } else {
+        cargo.spec.secret_mounts
+      },
+      volume_mounts: if obj.spec.volume_mounts.is_some() {
+        obj.spec.volume_mounts.clone()
+      } else {
+        cargo.spec.volume_mounts
+      },
       metadata: if obj.spec.metadata.is_some() {
         obj.spec.metadata.clone()
       } else {
@@ -258,6 +300,31 @@ impl ObjPatchByPk for CargoDb {
       } else {
         cargo.spec.image_pull_policy
       },
+      node_affinity: if obj.spec.node_affinity.is_some() {
+        obj.spec.node_affinity.clone()
+      } else {
+        cargo.spec.node_affinity
+      },
+      node_anti_affinity: if obj.spec.node_anti_affinity.is_some() {
+        obj.spec.node_anti_affinity.clone()
+      } else {
+        cargo.spec.node_anti_affinity
+      },
+      update_strategy: if obj.spec.update_strategy.is_some() {
+        obj.spec.update_strategy.clone()
+      } else {
+        cargo.spec.update_strategy
+      },
+      autoscale: if obj.spec.autoscale.is_some() {
+        obj.spec.autoscale.clone()
+      } else {
+        cargo.spec.autoscale
+      },
+      disruption_budget: if obj.spec.disruption_budget.is_some() {
+        obj.spec.disruption_budget.clone()
+      } else {
+        cargo.spec.disruption_budget
+      },
     };
     let obj = &CargoObjPutIn {
       spec,
@@ -1,5 +1,11 @@
-use nanocl_error::http::HttpResult;
+use std::collections::HashMap;
+
+use nanocl_error::{
+  http::{HttpError, HttpResult},
+  io::FromIo,
+};
 use nanocl_stubs::{
+  generic::GenericFilter,
   job::{Job, JobInspect, JobPartial},
   system::{NativeEventAction, ObjPsStatusKind, ObjPsStatusPartial},
 };
@@ -12,6 +18,62 @@ use crate::{
 
 use super::generic::*;
 
+/// Ensure the steps of a job and the jobs it depends on form acyclic graphs
+async fn validate_dependencies(
+  obj: &JobPartial,
+  state: &crate::models::SystemState,
+) -> HttpResult<()> {
+  if obj.steps.is_some() && !obj.containers.is_empty() {
+    return Err(HttpError::bad_request(
+      "Steps and Containers cannot be used together",
+    ));
+  }
+  let steps =
+    utils::container::job::get_steps(obj.steps.as_ref(), &obj.containers);
+  let mut graph = HashMap::new();
+  for step in &steps {
+    let deps = step.depends_on.clone().unwrap_or_default();
+    if let Some(dep) = deps
+      .iter()
+      .find(|dep| !steps.iter().any(|step| step.name == **dep))
+    {
+      return Err(HttpError::bad_request(format!(
+        "Step {} depends on unknown step {dep}",
+        step.name
+      )));
+    }
+    if graph.insert(step.name.clone(), deps).is_some() {
+      return Err(HttpError::bad_request(format!(
+        "Step {} is defined more than once",
+        step.name
+      )));
+    }
+  }
+  if let Some(cycle) = utils::dag::find_cycle(&graph) {
+    return Err(HttpError::bad_request(format!(
+      "Cycle in job steps: {}",
+      cycle.join(" -> ")
+    )));
+  }
+  let Some(depends_on) = &obj.depends_on else {
+    return Ok(());
+  };
+  let jobs =
+    JobDb::transform_read_by(&GenericFilter::new(), &state.inner.pool).await?;
+  let mut graph = jobs
+    .into_iter()
+    .map(|job| (job.name, job.depends_on.unwrap_or_default()))
+    .collect::<HashMap<_, _>>();
+  graph.insert(obj.name.clone(), depends_on.clone());
+  if let Some(cycle) = utils::dag::find_cycle(&graph) {
+    return Err(HttpError::bad_request(format!(
+      "Cycle in job dependencies: {}",
+      cycle.join(" -> ")
+    )));
+  }
+  Ok(())
+}
+
 impl ObjCreate for JobDb {
   type ObjCreateIn = JobPartial;
   type ObjCreateOut = Job;
@@ -20,6 +82,14 @@ impl ObjCreate for JobDb {
     obj: &Self::ObjCreateIn,
     state: &crate::models::SystemState,
   ) -> HttpResult<Self::ObjCreateOut> {
+    if let Some(schedule) = &obj.schedule {
+      utils::cron::CronSchedule::parse(schedule)?;
+    }
+    validate_dependencies(obj, state).await?;
+    utils::container::secret::validate_mounts(
+      obj.secret_mounts.as_deref().unwrap_or_default(),
+    )?;
+    let obj = &utils::quota::prepare_job(obj, state).await?;
     let db_model = JobDb::try_from_partial(obj)?;
     let status = ObjPsStatusPartial {
       key: obj.name.clone(),
@@ -32,9 +102,6 @@ impl ObjCreate for JobDb {
     let job = JobDb::create_from(db_model, &state.inner.pool)
       .await?
       .try_to_spec(&status)?;
-    if let Some(schedule) = &job.schedule {
-      utils::cron::add_cron_rule(&job, schedule, state).await?;
-    }
     Ok(job)
   }
 }
@@ -72,7 +139,8 @@ impl ObjInspectByPk for JobDb {
     pk: &str,
     state: &crate::models::SystemState,
   ) -> HttpResult<Self::ObjInspectOut> {
-    let job = JobDb::transform_read_by_pk(pk, &state.inner.pool).await?;
+    let (job_db, status) = JobDb::read_by_pk(pk, &state.inner.pool).await?;
+    let job = job_db.try_to_spec(&status)?;
     let instances =
       ProcessDb::read_by_kind_key(pk, None, &state.inner.pool).await?;
     let (instance_total, instance_failed, instance_success, instance_running) =
@@ -84,6 +152,10 @@ impl ObjInspectByPk for JobDb {
       instance_running,
       instance_failed,
       instances,
+      attempts: job_db.attempts as usize,
+      last_failure: job_db.last_failure,
+      steps: serde_json::from_value(job_db.steps)
+        .map_err(|err| err.map_err_context(|| "JobSteps"))?,
     };
     Ok(job_inspect)
   }
@@ -4,5 +4,6 @@ mod namespace;
 mod resource;
 mod secret;
 mod vm;
+mod volume;
 
 pub mod generic;
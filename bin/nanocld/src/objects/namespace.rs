@@ -1,9 +1,12 @@
 use nanocl_error::http::{HttpError, HttpResult};
-use nanocl_stubs::namespace::{Namespace, NamespaceInspect, NamespacePartial};
+use nanocl_stubs::namespace::{
+  Namespace, NamespaceInspect, NamespacePartial, NamespaceUpdate,
+};
 
 use crate::{
-  models::{CargoDb, NamespaceDb, SystemState},
+  models::{CargoDb, NamespaceDb, NamespaceUpdateDb, SystemState, VolumeDb},
   repositories::generic::*,
+  utils,
 };
 
 use super::generic::*;
@@ -25,9 +28,10 @@ impl ObjCreate for NamespaceDb {
         &obj.name
       )));
     }
-    let item = NamespaceDb::create_from(obj, &state.inner.pool)
+    let item = NamespaceDb::create_try_from(obj, &state.inner.pool)
       .await?
-      .into();
+      .try_into()?;
+    utils::network::create(&obj.name, state).await?;
     Ok(item)
   }
 }
@@ -49,9 +53,13 @@ impl ObjInspectByPk for NamespaceDb {
         CargoDb::inspect_obj_by_pk(&cargo.spec.cargo_key, state).await?;
       cargoes.push(cargo);
     }
+    let usage = utils::quota::get_usage(&namespace.name, None, state).await?;
     Ok(NamespaceInspect {
+      quota: namespace.get_quota()?,
+      limit_range: namespace.get_limit_range()?,
       name: namespace.name,
       cargoes,
+      usage,
     })
   }
 }
@@ -66,11 +74,36 @@ impl ObjDelByPk for NamespaceDb {
     state: &SystemState,
   ) -> HttpResult<Self::ObjDelOut> {
     let item = NamespaceDb::read_by_pk(pk, &state.inner.pool).await?;
+    let volumes = VolumeDb::read_by_namespace(pk, &state.inner.pool).await?;
+    if !volumes.is_empty() {
+      return Err(HttpError::conflict(format!(
+        "Namespace {pk}: still have {} volume(s), remove them first",
+        volumes.len()
+      )));
+    }
     CargoDb::delete_by_namespace(pk, state).await?;
     NamespaceDb::del_by_pk(pk, &state.inner.pool).await?;
-    if let Err(err) = state.inner.docker_api.remove_network(pk).await {
-      log::error!("Unable to remove network {} got error: {}", pk, err);
+    if let Err(err) = utils::network::remove(pk, state).await {
+      log::error!("Unable to remove network of {} got error: {}", pk, err);
     }
-    Ok(item.into())
+    Ok(item.try_into()?)
+  }
+}
+
+impl ObjPutByPk for NamespaceDb {
+  type ObjPutIn = NamespaceUpdate;
+  type ObjPutOut = Namespace;
+
+  async fn fn_put_obj_by_pk(
+    pk: &str,
+    obj: &Self::ObjPutIn,
+    state: &SystemState,
+  ) -> HttpResult<Self::ObjPutOut> {
+    NamespaceDb::read_by_pk(pk, &state.inner.pool).await?;
+    let update = NamespaceUpdateDb::try_from(obj)?;
+    let item = NamespaceDb::update_pk(pk, update, &state.inner.pool)
+      .await?
+      .try_into()?;
+    Ok(item)
   }
 }
@@ -5,8 +5,9 @@ use nanocl_stubs::{
 };
 
 use crate::{
-  models::{SecretDb, SystemState},
+  models::{SecretDb, SecretUpdateDb, SystemState},
   repositories::generic::*,
+  utils,
 };
 
 use super::generic::*;
@@ -19,7 +20,9 @@ impl ObjCreate for SecretDb {
     obj: &Self::ObjCreateIn,
     state: &SystemState,
   ) -> HttpResult<Self::ObjCreateOut> {
-    let secret = SecretDb::create_from(obj, &state.inner.pool).await?;
+    let mut secret = SecretDb::from(obj);
+    secret.data = utils::secret::seal_data(&obj.name, &obj.data, state)?;
+    let secret = SecretDb::create_from(secret, &state.inner.pool).await?;
     let secret: Secret = secret.try_into()?;
     Ok(secret)
   }
@@ -53,7 +56,9 @@ impl ObjPatchByPk for SecretDb {
     obj: &Self::ObjPatchIn,
     state: &SystemState,
   ) -> HttpResult<Self::ObjPatchOut> {
-    let secret = SecretDb::update_pk(pk, obj, &state.inner.pool)
+    let mut update = SecretUpdateDb::from(obj);
+    update.data = Some(utils::secret::seal_data(pk, &obj.data, state)?);
+    let secret = SecretDb::update_pk(pk, update, &state.inner.pool)
       .await?
       .try_into()?;
     Ok(secret)
@@ -43,6 +43,7 @@ impl ObjCreate for VmDb {
     if name.contains('.') {
       return Err(HttpError::bad_request("VM name cannot contain '.'"));
     }
+    utils::quota::check_vm(namespace, None, &vm, state).await?;
     let image =
       VmImageDb::read_by_pk(&vm.disk.image, &state.inner.pool).await?;
     if image.kind.as_str() != "Base" {
@@ -122,6 +123,8 @@ impl ObjPutByPk for VmDb {
     state: &SystemState,
   ) -> HttpResult<Self::ObjPutOut> {
     let vm = VmDb::transform_read_by_pk(pk, &state.inner.pool).await?;
+    utils::quota::check_vm(&vm.namespace_name, Some(pk), &obj.spec, state)
+      .await?;
     let status = ObjPsStatusDb::read_by_pk(pk, &state.inner.pool).await?;
     let new_status = ObjPsStatusUpdate {
       wanted: Some(ObjPsStatusKind::Start.to_string()),
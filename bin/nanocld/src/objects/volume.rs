@@ -0,0 +1,82 @@
+use nanocl_error::http::{HttpError, HttpResult};
+use nanocl_stubs::volume::{Volume, VolumeInspect};
+
+use crate::{
+  models::{NamespaceDb, SystemState, VolumeDb, VolumeObjCreateIn},
+  repositories::generic::*,
+  utils,
+};
+
+use super::generic::*;
+
+impl ObjCreate for VolumeDb {
+  type ObjCreateIn = VolumeObjCreateIn;
+  type ObjCreateOut = Volume;
+
+  async fn fn_create_obj(
+    obj: &Self::ObjCreateIn,
+    state: &SystemState,
+  ) -> HttpResult<Self::ObjCreateOut> {
+    utils::volume::validate_name(&obj.spec.name)?;
+    NamespaceDb::read_by_pk(&obj.namespace, &state.inner.pool).await?;
+    let key = utils::key::gen_key(&obj.namespace, &obj.spec.name);
+    if VolumeDb::read_by_pk(&key, &state.inner.pool).await.is_ok() {
+      return Err(HttpError::conflict(format!("Volume {key}: already exist")));
+    }
+    let volume = VolumeDb {
+      source: utils::volume::get_source(&key, &obj.spec.kind, state),
+      key,
+      created_at: chrono::Utc::now().naive_utc(),
+      name: obj.spec.name.clone(),
+      namespace_name: obj.namespace.clone(),
+      kind: obj.spec.kind.to_string(),
+      metadata: obj.spec.metadata.clone(),
+    };
+    utils::volume::create(&volume, state).await?;
+    let volume = VolumeDb::create_from(volume, &state.inner.pool)
+      .await?
+      .try_into()?;
+    Ok(volume)
+  }
+}
+
+impl ObjDelByPk for VolumeDb {
+  type ObjDelOut = Volume;
+  type ObjDelOpts = ();
+
+  async fn fn_del_obj_by_pk(
+    pk: &str,
+    _opts: &Self::ObjDelOpts,
+    state: &SystemState,
+  ) -> HttpResult<Self::ObjDelOut> {
+    let volume = VolumeDb::transform_read_by_pk(pk, &state.inner.pool).await?;
+    let used_by = utils::volume::get_used_by(&volume, state).await?;
+    if !used_by.is_empty() {
+      return Err(HttpError::conflict(format!(
+        "Volume {pk}: still used by {}",
+        used_by.join(", ")
+      )));
+    }
+    utils::volume::remove(&volume, state).await?;
+    VolumeDb::del_by_pk(pk, &state.inner.pool).await?;
+    Ok(volume)
+  }
+}
+
+impl ObjInspectByPk for VolumeDb {
+  type ObjInspectOut = VolumeInspect;
+
+  async fn inspect_obj_by_pk(
+    pk: &str,
+    state: &SystemState,
+  ) -> HttpResult<Self::ObjInspectOut> {
+    let volume = VolumeDb::transform_read_by_pk(pk, &state.inner.pool).await?;
+    let used_by = utils::volume::get_used_by(&volume, state).await?;
+    let size = utils::volume::get_size(&volume, state).await;
+    Ok(VolumeInspect {
+      volume,
+      size,
+      used_by,
+    })
+  }
+}
@@ -0,0 +1,106 @@
+use std::collections::HashMap;
+
+use diesel::prelude::*;
+
+use nanocl_error::io::IoResult;
+use nanocl_stubs::{audit::AuditLog, generic::GenericFilter};
+
+use crate::{
+  gen_sql_multiple, gen_sql_order_by, gen_sql_query,
+  models::{AuditLogDb, ColumnType},
+  schema::audit_logs,
+};
+
+use super::generic::*;
+
+impl RepositoryBase for AuditLogDb {
+  fn get_columns<'a>() -> HashMap<&'a str, (ColumnType, &'a str)> {
+    HashMap::from([
+      ("key", (ColumnType::Uuid, "audit_logs.key")),
+      (
+        "created_at",
+        (ColumnType::Timestamptz, "audit_logs.created_at"),
+      ),
+      ("identity", (ColumnType::Text, "audit_logs.identity")),
+      ("source", (ColumnType::Text, "audit_logs.source")),
+      ("method", (ColumnType::Text, "audit_logs.method")),
+      ("route", (ColumnType::Text, "audit_logs.route")),
+      ("object_key", (ColumnType::Text, "audit_logs.object_key")),
+      (
+        "payload_hash",
+        (ColumnType::Text, "audit_logs.payload_hash"),
+      ),
+    ])
+  }
+}
+
+impl RepositoryCreate for AuditLogDb {}
+
+impl RepositoryDelBy for AuditLogDb {
+  fn gen_del_query(
+    filter: &GenericFilter,
+  ) -> diesel::query_builder::BoxedDeleteStatement<
+    'static,
+    crate::models::DbBackend,
+    <Self as diesel::associations::HasTable>::Table,
+  >
+  where
+    Self: diesel::associations::HasTable,
+  {
+    let mut query = diesel::delete(audit_logs::table).into_boxed();
+    let columns = Self::get_columns();
+    gen_sql_query!(query, filter, columns)
+  }
+}
+
+impl RepositoryReadBy for AuditLogDb {
+  type Output = AuditLogDb;
+
+  fn get_pk() -> &'static str {
+    "key"
+  }
+
+  fn gen_read_query(
+    filter: &GenericFilter,
+    is_multiple: bool,
+  ) -> impl diesel::query_dsl::methods::LoadQuery<
+    'static,
+    crate::models::DbConnection,
+    Self::Output,
+  > {
+    let mut query = audit_logs::table.into_boxed();
+    let columns = Self::get_columns();
+    query = gen_sql_query!(query, filter, columns);
+    if let Some(orders) = &filter.order_by {
+      query = gen_sql_order_by!(query, orders, columns);
+    } else {
+      query = query.order(audit_logs::created_at.desc());
+    }
+    if is_multiple {
+      gen_sql_multiple!(query, filter);
+    }
+    query
+  }
+}
+
+impl RepositoryCountBy for AuditLogDb {
+  fn gen_count_query(
+    filter: &GenericFilter,
+  ) -> impl diesel::query_dsl::methods::LoadQuery<
+    'static,
+    crate::models::DbConnection,
+    i64,
+  > {
+    let mut query = audit_logs::table.into_boxed();
+    let columns = Self::get_columns();
+    gen_sql_query!(query, filter, columns).count()
+  }
+}
+
+impl RepositoryReadByTransform for AuditLogDb {
+  type NewOutput = AuditLog;
+
+  fn transform(input: Self::Output) -> IoResult<Self::NewOutput> {
+    input.try_into()
+  }
+}
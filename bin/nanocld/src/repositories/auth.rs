@@ -0,0 +1,160 @@
+use std::collections::HashMap;
+
+use diesel::prelude::*;
+
+use nanocl_error::io::IoResult;
+use nanocl_stubs::{
+  auth::{Role, Token},
+  generic::{GenericClause, GenericFilter},
+};
+
+use crate::{
+  gen_sql_multiple, gen_sql_order_by, gen_sql_query,
+  models::{ColumnType, Pool, RoleDb, TokenDb},
+  schema::{roles, tokens},
+};
+
+use super::generic::*;
+
+impl RepositoryBase for RoleDb {
+  fn get_columns<'a>() -> HashMap<&'a str, (ColumnType, &'a str)> {
+    HashMap::from([
+      ("key", (ColumnType::Text, "roles.key")),
+      ("created_at", (ColumnType::Timestamptz, "roles.created_at")),
+      ("rules", (ColumnType::Json, "roles.rules")),
+    ])
+  }
+}
+
+impl RepositoryCreate for RoleDb {}
+
+impl RepositoryDelByPk for RoleDb {}
+
+impl RepositoryReadBy for RoleDb {
+  type Output = RoleDb;
+
+  fn get_pk() -> &'static str {
+    "key"
+  }
+
+  fn gen_read_query(
+    filter: &GenericFilter,
+    is_multiple: bool,
+  ) -> impl diesel::query_dsl::methods::LoadQuery<
+    'static,
+    crate::models::DbConnection,
+    Self::Output,
+  > {
+    let mut query = roles::table.into_boxed();
+    let columns = Self::get_columns();
+    query = gen_sql_query!(query, filter, columns);
+    if let Some(orders) = &filter.order_by {
+      query = gen_sql_order_by!(query, orders, columns);
+    } else {
+      query = query.order(roles::created_at.desc());
+    }
+    if is_multiple {
+      gen_sql_multiple!(query, filter);
+    }
+    query
+  }
+}
+
+impl RepositoryCountBy for RoleDb {
+  fn gen_count_query(
+    filter: &GenericFilter,
+  ) -> impl diesel::query_dsl::methods::LoadQuery<
+    'static,
+    crate::models::DbConnection,
+    i64,
+  > {
+    let mut query = roles::table.into_boxed();
+    let columns = Self::get_columns();
+    gen_sql_query!(query, filter, columns).count()
+  }
+}
+
+impl RepositoryReadByTransform for RoleDb {
+  type NewOutput = Role;
+
+  fn transform(input: Self::Output) -> IoResult<Self::NewOutput> {
+    input.try_into()
+  }
+}
+
+impl RepositoryBase for TokenDb {
+  fn get_columns<'a>() -> HashMap<&'a str, (ColumnType, &'a str)> {
+    HashMap::from([
+      ("key", (ColumnType::Text, "tokens.key")),
+      ("created_at", (ColumnType::Timestamptz, "tokens.created_at")),
+      ("expires_at", (ColumnType::Timestamptz, "tokens.expires_at")),
+      ("hash", (ColumnType::Text, "tokens.hash")),
+      ("roles", (ColumnType::Json, "tokens.roles")),
+    ])
+  }
+}
+
+impl RepositoryCreate for TokenDb {}
+
+impl RepositoryDelByPk for TokenDb {}
+
+impl RepositoryReadBy for TokenDb {
+  type Output = TokenDb;
+
+  fn get_pk() -> &'static str {
+    "key"
+  }
+
+  fn gen_read_query(
+    filter: &GenericFilter,
+    is_multiple: bool,
+  ) -> impl diesel::query_dsl::methods::LoadQuery<
+    'static,
+    crate::models::DbConnection,
+    Self::Output,
+  > {
+    let mut query = tokens::table.into_boxed();
+    let columns = Self::get_columns();
+    query = gen_sql_query!(query, filter, columns);
+    if let Some(orders) = &filter.order_by {
+      query = gen_sql_order_by!(query, orders, columns);
+    } else {
+      query = query.order(tokens::created_at.desc());
+    }
+    if is_multiple {
+      gen_sql_multiple!(query, filter);
+    }
+    query
+  }
+}
+
+impl RepositoryCountBy for TokenDb {
+  fn gen_count_query(
+    filter: &GenericFilter,
+  ) -> impl diesel::query_dsl::methods::LoadQuery<
+    'static,
+    crate::models::DbConnection,
+    i64,
+  > {
+    let mut query = tokens::table.into_boxed();
+    let columns = Self::get_columns();
+    gen_sql_query!(query, filter, columns).count()
+  }
+}
+
+impl RepositoryReadByTransform for TokenDb {
+  type NewOutput = Token;
+
+  fn transform(input: Self::Output) -> IoResult<Self::NewOutput> {
+    input.try_into()
+  }
+}
+
+impl TokenDb {
+  /// Find the token matching the sha256 hash of a token value
+  pub async fn read_by_hash(hash: &str, pool: &Pool) -> IoResult<TokenDb> {
+    let filter =
+      GenericFilter::new().r#where("hash", GenericClause::Eq(hash.to_owned()));
+    TokenDb::read_one_by(&filter, pool).await
+  }
+}
@@ -75,7 +75,7 @@ impl RepositoryReadBy for CargoDb {
     is_multiple: bool,
   ) -> impl diesel::query_dsl::methods::LoadQuery<
     'static,
-    diesel::PgConnection,
+    crate::models::DbConnection,
     Self::Output,
   >
   where
@@ -102,8 +102,11 @@ impl RepositoryReadBy for CargoDb {
 impl RepositoryCountBy for CargoDb {
   fn gen_count_query(
     filter: &GenericFilter,
-  ) -> impl diesel::query_dsl::methods::LoadQuery<'static, diesel::PgConnection, i64>
-  {
+  ) -> impl diesel::query_dsl::methods::LoadQuery<
+    'static,
+    crate::models::DbConnection,
+    i64,
+  > {
     let mut query = cargoes::table
       .inner_join(crate::schema::specs::table)
       .inner_join(crate::schema::object_process_statuses::table)
@@ -134,6 +137,7 @@ impl WithSpec for CargoDb {
     Self::Output {
       namespace_name: self.namespace_name,
       created_at: self.created_at,
+      replicas: self.replicas.map(|replicas| replicas.max(0) as usize),
       spec: r.0.clone(),
       status: r.1.clone(),
     }
@@ -157,9 +161,14 @@ impl CargoDb {
     let new_item = CargoUpdateDb {
       name: Some(item.name.to_owned()),
       spec_key: Some(spec.key),
+      // Forget the replicas of the autoscaler when it's removed
+      replicas: item.autoscale.is_none().then_some(None),
       ..Default::default()
     };
     CargoDb::update_pk(key, new_item, pool).await?;
+    if item.autoscale.is_none() {
+      cargo.replicas = None;
+    }
     cargo.spec = spec;
     Ok(cargo)
   }
@@ -191,6 +200,42 @@ impl CargoDb {
     Ok(count)
   }
 
+  /// Claim or renew the lease to evaluate the autoscaler of a cargo.
+  /// A node holds the lease until it stops renewing it for `lease`
+  /// so a single node scales the cargo for the whole cluster
+  pub async fn claim_autoscaler(
+    key: &str,
+    node: &str,
+    lease: std::time::Duration,
+    pool: &Pool,
+  ) -> IoResult<bool> {
+    let key = key.to_owned();
+    let node = node.to_owned();
+    let pool = pool.clone();
+    let now = chrono::Utc::now().naive_utc();
+    let expired = now - chrono::Duration::from_std(lease).unwrap_or_default();
+    ntex::rt::spawn_blocking(move || {
+      let mut conn = utils::store::get_pool_conn(&pool)?;
+      let count = diesel::update(
+        cargoes::table.filter(cargoes::key.eq(key)).filter(
+          cargoes::autoscaler_node
+            .is_null()
+            .or(cargoes::autoscaler_node.eq(&node))
+            .or(cargoes::autoscaler_lease_at.is_null())
+            .or(cargoes::autoscaler_lease_at.lt(expired)),
+        ),
+      )
+      .set((
+        cargoes::autoscaler_node.eq(&node),
+        cargoes::autoscaler_lease_at.eq(now),
+      ))
+      .execute(&mut conn)
+      .map_err(|err| IoError::interrupted("Cargo", &err.to_string()))?;
+      Ok::<_, IoError>(count > 0)
+    })
+    .await?
+  }
+
   /// This remove all cargo in the given namespace and all their instances (containers)
   /// from the system (database and docker).
   pub async fn delete_by_namespace(
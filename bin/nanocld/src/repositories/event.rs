@@ -51,7 +51,7 @@ impl RepositoryReadBy for EventDb {
     is_multiple: bool,
   ) -> impl diesel::query_dsl::methods::LoadQuery<
     'static,
-    diesel::PgConnection,
+    crate::models::DbConnection,
     Self::Output,
   >
   where
@@ -75,8 +75,11 @@ impl RepositoryReadBy for EventDb {
 impl RepositoryCountBy for EventDb {
   fn gen_count_query(
     filter: &nanocl_stubs::generic::GenericFilter,
-  ) -> impl diesel::query_dsl::methods::LoadQuery<'static, diesel::PgConnection, i64>
-  {
+  ) -> impl diesel::query_dsl::methods::LoadQuery<
+    'static,
+    crate::models::DbConnection,
+    i64,
+  > {
     let mut query = events::table.into_boxed();
     let columns = Self::get_columns();
     gen_sql_query!(query, filter, columns).count()
@@ -17,7 +17,7 @@ pub trait RepositoryCreate: super::RepositoryBase {
     diesel::query_builder::InsertStatement<
       Self::Table,
       <Self as diesel::Insertable<Self::Table>>::Values,
-    >: diesel::query_dsl::LoadQuery<'static, diesel::pg::PgConnection, Self>,
+    >: diesel::query_dsl::LoadQuery<'static, crate::models::DbConnection, Self>,
   {
     let pool = pool.clone();
     let item = Self::from(item);
@@ -44,7 +44,7 @@ pub trait RepositoryCreate: super::RepositoryBase {
     diesel::query_builder::InsertStatement<
       Self::Table,
       <Self as diesel::Insertable<Self::Table>>::Values,
-    >: diesel::query_dsl::LoadQuery<'static, diesel::pg::PgConnection, Self>,
+    >: diesel::query_dsl::LoadQuery<'static, crate::models::DbConnection, Self>,
   {
     let item = Self::try_from(item)?;
     Self::create_from(item, pool).await
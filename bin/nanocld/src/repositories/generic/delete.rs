@@ -20,7 +20,7 @@ pub trait RepositoryDelByPk: super::RepositoryBase {
     query_builder::DeleteStatement<
       <diesel::helper_types::Find<Self::Table, <Pk as ToOwned>::Owned> as HasTable>::Table,
       <diesel::helper_types::Find<Self::Table, <Pk as ToOwned>::Owned> as query_builder::IntoUpdateTarget>::WhereClause,
-    >: query_builder::QueryFragment<diesel::pg::Pg> + query_builder::QueryId,
+    >: query_builder::QueryFragment<crate::models::DbBackend> + query_builder::QueryId,
   {
     log::trace!("{}::delete_by_pk: {pk}", Self::get_name());
     let pool = pool.clone();
@@ -41,7 +41,7 @@ pub trait RepositoryDelBy: super::RepositoryBase {
     filter: &GenericFilter,
   ) -> diesel::query_builder::BoxedDeleteStatement<
     'static,
-    diesel::pg::Pg,
+    crate::models::DbBackend,
     <Self as diesel::associations::HasTable>::Table,
   >
   where
@@ -54,7 +54,7 @@ pub trait RepositoryDelBy: super::RepositoryBase {
   where
     Self: Sized + diesel::associations::HasTable,
     <Self as diesel::associations::HasTable>::Table: diesel::query_builder::QueryId + 'static,
-    <<Self as diesel::associations::HasTable>::Table as diesel::QuerySource>::FromClause: diesel::query_builder::QueryFragment<diesel::pg::Pg>,
+    <<Self as diesel::associations::HasTable>::Table as diesel::QuerySource>::FromClause: diesel::query_builder::QueryFragment<crate::models::DbBackend>,
   {
     log::trace!("{}::delete_by: {filter:?}", Self::get_name());
     let pool = pool.clone();
@@ -16,7 +16,7 @@ pub trait RepositoryReadBy: super::RepositoryBase {
   fn gen_read_query(
     filter: &GenericFilter,
     is_multiple: bool,
-  ) -> impl LoadQuery<'static, diesel::PgConnection, Self::Output>
+  ) -> impl LoadQuery<'static, crate::models::DbConnection, Self::Output>
   where
     Self::Output: Sized;
 
@@ -126,7 +126,7 @@ pub trait RepositoryCountBy: RepositoryBase {
 
   fn gen_count_query(
     filter: &GenericFilter,
-  ) -> impl LoadQuery<'static, diesel::PgConnection, i64>;
+  ) -> impl LoadQuery<'static, crate::models::DbConnection, i64>;
 
   async fn count_by(filter: &GenericFilter, pool: &Pool) -> IoResult<i64> {
     let pool = pool.clone();
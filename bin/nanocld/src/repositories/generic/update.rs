@@ -28,7 +28,7 @@ pub trait RepositoryUpdate: super::RepositoryBase {
       <diesel::helper_types::Find<Self::Table, <Pk as ToOwned>::Owned> as diesel::query_builder::IntoUpdateTarget>::WhereClause,
       <Self::UpdateItem as diesel::AsChangeset>::Changeset,
     >:
-      diesel::query_builder::AsQuery + diesel::query_dsl::LoadQuery<'static, diesel::pg::PgConnection, Self>,
+      diesel::query_builder::AsQuery + diesel::query_dsl::LoadQuery<'static, crate::models::DbConnection, Self>,
   {
     log::trace!("{}::update_by_pk: {pk}", Self::get_name());
     let pool = pool.clone();
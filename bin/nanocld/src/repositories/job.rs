@@ -7,19 +7,20 @@ use futures_util::StreamExt;
 
 use nanocl_error::{
   http::{HttpError, HttpResult},
-  io::IoResult,
+  io::{IoError, IoResult},
 };
 use nanocl_stubs::{
-  generic::GenericFilter,
-  job::{Job, JobPartial, JobSummary},
+  generic::{GenericClause, GenericFilter},
+  job::{Job, JobPartial, JobRun, JobSummary},
 };
 
 use crate::{
   gen_sql_multiple, gen_sql_order_by, gen_sql_query,
   models::{
-    ColumnType, JobDb, JobUpdateDb, ObjPsStatusDb, Pool, ProcessDb, SystemState,
+    sql_types::DbUuid, ColumnType, JobDb, JobRunDb, JobUpdateDb, ObjPsStatusDb,
+    Pool, ProcessDb, SystemState,
   },
-  schema::jobs,
+  schema::{job_runs, jobs},
   utils,
 };
 
@@ -66,7 +67,7 @@ impl RepositoryReadBy for JobDb {
     is_multiple: bool,
   ) -> impl diesel::query_dsl::methods::LoadQuery<
     'static,
-    diesel::PgConnection,
+    crate::models::DbConnection,
     Self::Output,
   > {
     let mut query = jobs::table
@@ -89,8 +90,11 @@ impl RepositoryReadBy for JobDb {
 impl RepositoryCountBy for JobDb {
   fn gen_count_query(
     filter: &GenericFilter,
-  ) -> impl diesel::query_dsl::methods::LoadQuery<'static, diesel::PgConnection, i64>
-  {
+  ) -> impl diesel::query_dsl::methods::LoadQuery<
+    'static,
+    crate::models::DbConnection,
+    i64,
+  > {
     let mut query = jobs::table
       .inner_join(crate::schema::object_process_statuses::table)
       .into_boxed();
@@ -125,9 +129,39 @@ impl JobDb {
       updated_at: chrono::Utc::now().naive_utc(),
       metadata: p.metadata.clone(),
       data,
+      attempts: 0,
+      last_failure: None,
+      steps: serde_json::Value::Array(Vec::new()),
+      last_scheduled_at: None,
     })
   }
 
+  /// Claim the run of a job scheduled at the given time.
+  /// Every node checks the schedule but only the one claiming the run starts it
+  pub async fn claim_run(
+    key: &str,
+    scheduled_at: chrono::NaiveDateTime,
+    pool: &Pool,
+  ) -> IoResult<bool> {
+    let key = key.to_owned();
+    let pool = pool.clone();
+    ntex::rt::spawn_blocking(move || {
+      let mut conn = utils::store::get_pool_conn(&pool)?;
+      let count = diesel::update(
+        jobs::table.filter(jobs::key.eq(key)).filter(
+          jobs::last_scheduled_at
+            .is_null()
+            .or(jobs::last_scheduled_at.lt(scheduled_at)),
+        ),
+      )
+      .set(jobs::last_scheduled_at.eq(scheduled_at))
+      .execute(&mut conn)
+      .map_err(|err| IoError::interrupted("Job", &err.to_string()))?;
+      Ok::<_, IoError>(count > 0)
+    })
+    .await?
+  }
+
   pub fn try_to_spec(&self, status: &ObjPsStatusDb) -> IoResult<Job> {
     let p = serde_json::from_value::<JobPartial>(self.data.clone())?;
     Ok(Job {
@@ -136,8 +170,18 @@ impl JobDb {
       updated_at: self.updated_at,
       metadata: self.metadata.clone(),
       secrets: p.secrets.clone(),
+      secret_mounts: p.secret_mounts.clone(),
       schedule: p.schedule.clone(),
+      concurrency_policy: p.concurrency_policy.clone(),
+      starting_deadline_seconds: p.starting_deadline_seconds,
+      history_limit: p.history_limit,
+      backoff_limit: p.backoff_limit,
+      backoff: p.backoff.clone(),
+      active_deadline_seconds: p.active_deadline_seconds,
+      container_timeout_seconds: p.container_timeout_seconds,
       ttl: p.ttl,
+      depends_on: p.depends_on.clone(),
+      steps: p.steps.clone(),
       status: status.clone().try_into()?,
       containers: p.containers.clone(),
       image_pull_secret: p.image_pull_secret.clone(),
@@ -179,3 +223,110 @@ impl JobDb {
     Ok(job_summaries)
   }
 }
+
+impl RepositoryBase for JobRunDb {
+  fn get_columns<'a>(
+  ) -> std::collections::HashMap<&'a str, (ColumnType, &'a str)> {
+    HashMap::from([
+      ("key", (ColumnType::Uuid, "job_runs.key")),
+      ("job_key", (ColumnType::Text, "job_runs.job_key")),
+      ("status", (ColumnType::Text, "job_runs.status")),
+      (
+        "created_at",
+        (ColumnType::Timestamptz, "job_runs.created_at"),
+      ),
+      (
+        "scheduled_at",
+        (ColumnType::Timestamptz, "job_runs.scheduled_at"),
+      ),
+    ])
+  }
+}
+
+impl RepositoryCreate for JobRunDb {}
+
+impl RepositoryDelBy for JobRunDb {
+  fn gen_del_query(
+    filter: &GenericFilter,
+  ) -> diesel::query_builder::BoxedDeleteStatement<
+    'static,
+    crate::models::DbBackend,
+    <Self as diesel::associations::HasTable>::Table,
+  >
+  where
+    Self: diesel::associations::HasTable,
+  {
+    let mut query = diesel::delete(job_runs::table).into_boxed();
+    let columns = Self::get_columns();
+    gen_sql_query!(query, filter, columns)
+  }
+}
+
+impl RepositoryReadBy for JobRunDb {
+  type Output = JobRunDb;
+
+  fn get_pk() -> &'static str {
+    "key"
+  }
+
+  fn gen_read_query(
+    filter: &GenericFilter,
+    is_multiple: bool,
+  ) -> impl diesel::query_dsl::methods::LoadQuery<
+    'static,
+    crate::models::DbConnection,
+    Self::Output,
+  > {
+    let mut query = job_runs::table.into_boxed();
+    let columns = Self::get_columns();
+    query = gen_sql_query!(query, filter, columns);
+    if let Some(orders) = &filter.order_by {
+      query = gen_sql_order_by!(query, orders, columns);
+    } else {
+      query = query.order(job_runs::scheduled_at.desc());
+    }
+    if is_multiple {
+      gen_sql_multiple!(query, filter);
+    }
+    query
+  }
+}
+
+impl RepositoryReadByTransform for JobRunDb {
+  type NewOutput = JobRun;
+
+  fn transform(item: JobRunDb) -> IoResult<Self::NewOutput> {
+    item.try_into()
+  }
+}
+
+impl JobRunDb {
+  /// Read the scheduled runs of a job, the latest first
+  pub async fn read_by_job(key: &str, pool: &Pool) -> IoResult<Vec<JobRun>> {
+    let filter = GenericFilter::new()
+      .r#where("job_key", GenericClause::Eq(key.to_owned()));
+    JobRunDb::transform_read_by(&filter, pool).await
+  }
+
+  /// Delete the oldest runs of a job to keep only `limit` of them
+  pub async fn prune(key: &str, limit: usize, pool: &Pool) -> IoResult<()> {
+    let keys = JobRunDb::read_by_job(key, pool)
+      .await?
+      .into_iter()
+      .skip(limit)
+      .map(|run| DbUuid::from(run.key))
+      .collect::<Vec<_>>();
+    if keys.is_empty() {
+      return Ok(());
+    }
+    let pool = pool.clone();
+    ntex::rt::spawn_blocking(move || {
+      let mut conn = utils::store::get_pool_conn(&pool)?;
+      diesel::delete(job_runs::table.filter(job_runs::key.eq_any(keys)))
+        .execute(&mut conn)
+        .map_err(|err| IoError::interrupted("JobRun", &err.to_string()))?;
+      Ok::<_, IoError>(())
+    })
+    .await?
+  }
+}
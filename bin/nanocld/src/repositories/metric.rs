@@ -1,10 +1,10 @@
 use diesel::{prelude::*, sql_query};
 use nanocl_error::io::{IoError, IoResult};
-use nanocl_stubs::generic::{GenericClause, GenericFilter};
+use nanocl_stubs::generic::GenericFilter;
 
 use crate::{
   gen_sql_multiple, gen_sql_order_by, gen_sql_query,
-  models::{ColumnType, MetricDb, MetricNodeDb, NodeDb, Pool},
+  models::{ColumnType, MetricCountDb, MetricDb, MetricNodeUsageDb, Pool},
   schema::metrics,
   utils,
 };
@@ -41,7 +41,7 @@ impl RepositoryReadBy for MetricDb {
     is_multiple: bool,
   ) -> impl diesel::query_dsl::methods::LoadQuery<
     'static,
-    diesel::pg::PgConnection,
+    crate::models::DbConnection,
     Self::Output,
   > {
     let mut query = metrics::table.into_boxed();
@@ -62,7 +62,8 @@ impl RepositoryReadBy for MetricDb {
 impl RepositoryCountBy for MetricDb {
   fn gen_count_query(
     filter: &GenericFilter,
-  ) -> impl diesel::query_dsl::LoadQuery<'static, diesel::PgConnection, i64> {
+  ) -> impl diesel::query_dsl::LoadQuery<'static, crate::models::DbConnection, i64>
+  {
     let mut query = metrics::table.into_boxed();
     let columns = Self::get_columns();
     gen_sql_query!(query, filter, columns).count()
@@ -70,14 +71,14 @@ impl RepositoryCountBy for MetricDb {
 }
 
 impl MetricDb {
-  pub async fn find_best_nodes(
-    cpu_threshold: f32,
-    _memory_threshold: f32,
-    limit: usize,
+  /// Read the latest cpu and memory usage in percent of each node
+  /// based on the `nanocl.io/metrs` metrics saved by the nodes
+  pub async fn read_node_usages(
     pool: &Pool,
-  ) -> IoResult<Vec<NodeDb>> {
+  ) -> IoResult<Vec<MetricNodeUsageDb>> {
     let pool_ptr = pool.clone();
-    let node_names = ntex::rt::spawn_blocking(move || {
+    ntex::rt::spawn_blocking(move || {
+      #[cfg(not(feature = "sqlite"))]
       let query = sql_query(
         "
           WITH LatestMetrics AS (
@@ -93,36 +94,152 @@ impl MetricDb {
               jsonb_array_elements(data->'Cpus') AS cpu
             FROM LatestMetrics
             WHERE rn = 1
+          ), MemoryUsages AS (
+            SELECT
+              node_name,
+              COALESCE(
+                (data->'Memory'->>'Used')::float8
+                / NULLIF((data->'Memory'->>'Total')::float8, 0) * 100,
+                0
+              ) AS memory_usage
+            FROM LatestMetrics
+            WHERE rn = 1
           )
           SELECT
-            node_name,
-            AVG((cpu->>'Usage')::float) AS avg_cpu_usage
+            CpuUsages.node_name AS node_name,
+            AVG((CpuUsages.cpu->>'Usage')::float8)::float8 AS cpu_usage,
+            MAX(MemoryUsages.memory_usage)::float8 AS memory_usage
           FROM CpuUsages
-          GROUP BY node_name
-          HAVING AVG((cpu->>'Usage')::float) >= $1
-          ORDER BY avg_cpu_usage DESC
-          LIMIT $2
+          INNER JOIN MemoryUsages
+            ON MemoryUsages.node_name = CpuUsages.node_name
+          GROUP BY CpuUsages.node_name
+        ",
+      );
+      #[cfg(feature = "sqlite")]
+      let query = sql_query(
+        "
+          WITH LatestMetrics AS (
+            SELECT
+              node_name,
+              data,
+              ROW_NUMBER() OVER(PARTITION BY node_name ORDER BY created_at DESC) AS rn
+            FROM metrics
+            WHERE kind = 'nanocl.io/metrs'
+          ), CpuUsages AS (
+            SELECT
+              node_name,
+              cpu.value AS cpu
+            FROM LatestMetrics, json_each(LatestMetrics.data, '$.Cpus') AS cpu
+            WHERE rn = 1
+          ), MemoryUsages AS (
+            SELECT
+              node_name,
+              COALESCE(
+                CAST(json_extract(data, '$.Memory.Used') AS REAL)
+                / NULLIF(CAST(json_extract(data, '$.Memory.Total') AS REAL), 0) * 100,
+                0
+              ) AS memory_usage
+            FROM LatestMetrics
+            WHERE rn = 1
+          )
+          SELECT
+            CpuUsages.node_name AS node_name,
+            CAST(AVG(json_extract(CpuUsages.cpu, '$.Usage')) AS REAL) AS cpu_usage,
+            CAST(MAX(MemoryUsages.memory_usage) AS REAL) AS memory_usage
+          FROM CpuUsages
+          INNER JOIN MemoryUsages
+            ON MemoryUsages.node_name = CpuUsages.node_name
+          GROUP BY CpuUsages.node_name
         ",
       );
       let mut conn = utils::store::get_pool_conn(&pool_ptr)?;
-      let node_names = query
-        .bind::<diesel::sql_types::Float, _>(cpu_threshold)
-        .bind::<diesel::sql_types::BigInt, _>(limit as i64)
-        .get_results::<MetricNodeDb>(&mut conn).map_err(|err| {
-          IoError::interrupted("Find best node", &err.to_string())
+      let usages = query
+        .get_results::<MetricNodeUsageDb>(&mut conn)
+        .map_err(|err| {
+          IoError::interrupted("Read node usages", &err.to_string())
         })?;
-      Ok::<_, IoError>(node_names)
+      Ok::<_, IoError>(usages)
     })
-    .await
-    .map_err(|err| {
-      IoError::interrupted("Find best node", &err.to_string())
-    })??;
-    let filter = GenericFilter::new().r#where(
-      "node_name",
-      GenericClause::In(
-        node_names.iter().map(|x| x.node_name.clone()).collect(),
-      ),
-    );
-    NodeDb::read_by(&filter, pool).await
+    .await?
+  }
+
+  /// Read the metrics of a kind created since the given date
+  /// and delete the expired ones of this kind
+  pub async fn read_recent(
+    kind: &str,
+    since: chrono::NaiveDateTime,
+    pool: &Pool,
+  ) -> IoResult<Vec<MetricDb>> {
+    let kind = kind.to_owned();
+    let pool_ptr = pool.clone();
+    ntex::rt::spawn_blocking(move || {
+      let mut conn = utils::store::get_pool_conn(&pool_ptr)?;
+      let now = chrono::Utc::now().naive_utc();
+      diesel::delete(
+        metrics::table
+          .filter(metrics::kind.eq(&kind))
+          .filter(metrics::expires_at.lt(now)),
+      )
+      .execute(&mut conn)
+      .map_err(|err| IoError::interrupted("Metric", &err.to_string()))?;
+      let metrics = metrics::table
+        .filter(metrics::kind.eq(&kind))
+        .filter(metrics::created_at.ge(since))
+        .get_results::<MetricDb>(&mut conn)
+        .map_err(|err| IoError::interrupted("Metric", &err.to_string()))?;
+      Ok::<_, IoError>(metrics)
+    })
+    .await?
+  }
+
+  /// Count the `ncproxy.io/http` metrics created since the given date
+  /// for the requests sent to one of the given addresses
+  pub async fn count_http_requests(
+    addresses: &[String],
+    since: chrono::NaiveDateTime,
+    pool: &Pool,
+  ) -> IoResult<i64> {
+    let addresses = addresses.to_vec();
+    let pool_ptr = pool.clone();
+    ntex::rt::spawn_blocking(move || {
+      #[cfg(not(feature = "sqlite"))]
+      let query = sql_query(
+        "
+          SELECT COUNT(*)::int8 AS count
+          FROM metrics
+          WHERE kind = 'ncproxy.io/http'
+          AND created_at >= $1
+          AND split_part(data->>'upstream_addr', ':', 1) = ANY($2)
+        ",
+      )
+      .bind::<diesel::sql_types::Timestamptz, _>(since)
+      .bind::<diesel::sql_types::Array<diesel::sql_types::Text>, _>(addresses);
+      // SQLite has no array, the addresses are bound in a json array
+      #[cfg(feature = "sqlite")]
+      let query = sql_query(
+        "
+          SELECT COUNT(*) AS count
+          FROM metrics
+          WHERE kind = 'ncproxy.io/http'
+          AND created_at >= ?
+          AND substr(
+            json_extract(data, '$.upstream_addr'),
+            1,
+            instr(json_extract(data, '$.upstream_addr') || ':', ':') - 1
+          ) IN (SELECT value FROM json_each(?))
+        ",
+      )
+      .bind::<diesel::sql_types::Timestamp, _>(since)
+      .bind::<diesel::sql_types::Text, _>(serde_json::to_string(&addresses)?);
+      let mut conn = utils::store::get_pool_conn(&pool_ptr)?;
+      let res =
+        query
+          .get_result::<MetricCountDb>(&mut conn)
+          .map_err(|err| {
+            IoError::interrupted("Count http requests", &err.to_string())
+          })?;
+      Ok::<_, IoError>(res.count)
+    })
+    .await?
   }
 }
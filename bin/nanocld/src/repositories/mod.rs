@@ -1,3 +1,5 @@
+mod audit;
+mod auth;
 mod cargo;
 mod event;
 mod job;
@@ -12,5 +14,6 @@ mod secret;
 mod spec;
 mod vm;
 mod vm_image;
+mod volume;
 
 pub mod generic;
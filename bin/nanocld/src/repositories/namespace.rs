@@ -7,7 +7,9 @@ use nanocl_stubs::{generic::GenericFilter, namespace::NamespaceSummary};
 
 use crate::{
   gen_sql_multiple, gen_sql_order_by, gen_sql_query,
-  models::{CargoDb, ColumnType, NamespaceDb, ProcessDb, SystemState},
+  models::{
+    CargoDb, ColumnType, NamespaceDb, NamespaceUpdateDb, ProcessDb, SystemState,
+  },
   schema::namespaces,
 };
 
@@ -29,6 +31,10 @@ impl RepositoryCreate for NamespaceDb {}
 
 impl RepositoryDelByPk for NamespaceDb {}
 
+impl RepositoryUpdate for NamespaceDb {
+  type UpdateItem = NamespaceUpdateDb;
+}
+
 impl RepositoryReadBy for NamespaceDb {
   type Output = NamespaceDb;
 
@@ -41,7 +47,7 @@ impl RepositoryReadBy for NamespaceDb {
     is_multiple: bool,
   ) -> impl diesel::query_dsl::methods::LoadQuery<
     'static,
-    diesel::pg::PgConnection,
+    crate::models::DbConnection,
     Self::Output,
   > {
     let mut query = namespaces::table.into_boxed();
@@ -62,7 +68,8 @@ impl RepositoryReadBy for NamespaceDb {
 impl RepositoryCountBy for NamespaceDb {
   fn gen_count_query(
     filter: &GenericFilter,
-  ) -> impl diesel::query_dsl::LoadQuery<'static, diesel::PgConnection, i64> {
+  ) -> impl diesel::query_dsl::LoadQuery<'static, crate::models::DbConnection, i64>
+  {
     let mut query = namespaces::table.into_boxed();
     let columns = Self::get_columns();
     gen_sql_query!(query, filter, columns).count()
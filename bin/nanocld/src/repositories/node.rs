@@ -4,13 +4,15 @@ use diesel::prelude::*;
 
 use nanocl_error::io::{IoError, IoResult};
 
-use nanocl_stubs::generic::GenericFilter;
+use nanocl_stubs::{generic::GenericFilter, node::NodeStatus};
 
 use crate::{
   gen_sql_multiple, gen_sql_order_by, gen_sql_query,
-  models::{ColumnType, NodeDb, Pool, SystemState},
-  schema::nodes,
-  vars,
+  models::{
+    ColumnType, NodeDb, NodeGroupLinkDb, NodeUpdateDb, Pool, SystemState,
+  },
+  schema::{node_group_links, nodes},
+  utils, vars,
 };
 
 use super::generic::*;
@@ -21,6 +23,8 @@ impl RepositoryBase for NodeDb {
       ("name", (ColumnType::Text, "nodes.name")),
       ("ip_address", (ColumnType::Text, "nodes.ip_address")),
       ("created_at", (ColumnType::Timestamptz, "nodes.created_at")),
+      ("status", (ColumnType::Text, "nodes.status")),
+      ("last_seen", (ColumnType::Timestamptz, "nodes.last_seen")),
     ])
   }
 }
@@ -29,6 +33,10 @@ impl RepositoryCreate for NodeDb {}
 
 impl RepositoryDelByPk for NodeDb {}
 
+impl RepositoryUpdate for NodeDb {
+  type UpdateItem = NodeUpdateDb;
+}
+
 impl RepositoryReadBy for NodeDb {
   type Output = NodeDb;
 
@@ -41,7 +49,7 @@ impl RepositoryReadBy for NodeDb {
     is_multiple: bool,
   ) -> impl diesel::query_dsl::methods::LoadQuery<
     'static,
-    diesel::pg::PgConnection,
+    crate::models::DbConnection,
     Self::Output,
   > {
     let mut query = nodes::table.into_boxed();
@@ -62,7 +70,8 @@ impl RepositoryReadBy for NodeDb {
 impl RepositoryCountBy for NodeDb {
   fn gen_count_query(
     filter: &GenericFilter,
-  ) -> impl diesel::query_dsl::LoadQuery<'static, diesel::PgConnection, i64> {
+  ) -> impl diesel::query_dsl::LoadQuery<'static, crate::models::DbConnection, i64>
+  {
     let mut query = nodes::table.into_boxed();
     let columns = Self::get_columns();
     gen_sql_query!(query, filter, columns).count()
@@ -91,15 +100,92 @@ impl NodeDb {
           IoError::invalid_data("Invalid gateway", err.to_string().as_str())
         })?;
     let ip_address = ipnet::IpNet::from(ip_address);
+    let now = chrono::Utc::now().naive_utc();
     let node = NodeDb {
       name: state.inner.config.hostname.clone(),
       ip_address,
       endpoint: state.inner.config.advertise_addr.clone(),
-      created_at: chrono::Utc::now().naive_utc(),
+      created_at: now,
       version: vars::VERSION.to_owned(),
       metadata: None,
+      status: NodeStatus::Ready.to_string(),
+      last_seen: now,
+      cordoned: false,
+      draining: false,
+      token_hash: None,
+    };
+    let node = NodeDb::create_if_not_exists(&node, &state.inner.pool).await?;
+    // A restarted node is back in the cluster with its current version
+    let update = NodeUpdateDb {
+      endpoint: Some(state.inner.config.advertise_addr.clone()),
+      version: Some(vars::VERSION.to_owned()),
+      status: Some(NodeStatus::Ready.to_string()),
+      last_seen: Some(now),
+      token_hash: Some(Some(utils::auth::hash_token(&state.inner.node_token))),
+      ..Default::default()
+    };
+    NodeDb::update_pk(&node.name, update, &state.inner.pool).await?;
+    Ok(())
+  }
+
+  /// Record a heartbeat received from a node
+  pub async fn mark_seen(name: &str, pool: &Pool) -> IoResult<()> {
+    let update = NodeUpdateDb {
+      last_seen: Some(chrono::Utc::now().naive_utc()),
+      ..Default::default()
     };
-    NodeDb::create_if_not_exists(&node, &state.inner.pool).await?;
+    NodeDb::update_pk(name, update, pool).await?;
     Ok(())
   }
+
+  /// Change the status of a node only if it still has the `from` status.
+  /// It returns whether the status changed, so when several nodes detect
+  /// the same change only one of them acts on it.
+  pub async fn update_status(
+    name: &str,
+    from: &NodeStatus,
+    to: &NodeStatus,
+    pool: &Pool,
+  ) -> IoResult<bool> {
+    let pool = pool.clone();
+    let name = name.to_owned();
+    let from = from.to_string();
+    let to = to.to_string();
+    ntex::rt::spawn_blocking(move || {
+      let mut conn = utils::store::get_pool_conn(&pool)?;
+      let count = diesel::update(
+        nodes::table
+          .filter(nodes::name.eq(name))
+          .filter(nodes::status.eq(from)),
+      )
+      .set(nodes::status.eq(to))
+      .execute(&mut conn)
+      .map_err(|err| IoError::interrupted("NodeDb", &err.to_string()))?;
+      Ok::<_, IoError>(count > 0)
+    })
+    .await?
+  }
+}
+
+impl NodeGroupLinkDb {
+  /// Read the group links of the given nodes
+  pub async fn read_by_nodes(
+    nodes: &[String],
+    pool: &Pool,
+  ) -> IoResult<Vec<NodeGroupLinkDb>> {
+    let pool = pool.clone();
+    let nodes = nodes.to_vec();
+    ntex::rt::spawn_blocking(move || {
+      let mut conn = utils::store::get_pool_conn(&pool)?;
+      let items = node_group_links::table
+        .filter(node_group_links::node_name.eq_any(nodes))
+        .select(NodeGroupLinkDb::as_select())
+        .load::<NodeGroupLinkDb>(&mut conn)
+        .map_err(|err| {
+          IoError::interrupted("NodeGroupLinkDb", &err.to_string())
+        })?;
+      Ok::<_, IoError>(items)
+    })
+    .await?
+  }
 }
@@ -64,7 +64,7 @@ impl RepositoryReadBy for ObjPsStatusDb {
     is_multiple: bool,
   ) -> impl diesel::query_dsl::methods::LoadQuery<
     'static,
-    diesel::pg::PgConnection,
+    crate::models::DbConnection,
     Self::Output,
   > {
     let mut query = object_process_statuses::table.into_boxed();
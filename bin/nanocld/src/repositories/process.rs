@@ -13,6 +13,7 @@ use crate::{
   gen_sql_multiple, gen_sql_order_by, gen_sql_query,
   models::{ColumnType, Pool, ProcessDb, ProcessUpdateDb},
   schema::processes,
+  utils,
 };
 
 use super::generic::*;
@@ -48,7 +49,7 @@ impl RepositoryDelBy for ProcessDb {
     filter: &GenericFilter,
   ) -> diesel::query_builder::BoxedDeleteStatement<
     'static,
-    diesel::pg::Pg,
+    crate::models::DbBackend,
     <Self as diesel::associations::HasTable>::Table,
   >
   where
@@ -72,7 +73,7 @@ impl RepositoryReadBy for ProcessDb {
     is_multiple: bool,
   ) -> impl diesel::query_dsl::methods::LoadQuery<
     'static,
-    diesel::pg::PgConnection,
+    crate::models::DbConnection,
     Self::Output,
   > {
     let mut query = processes::table.into_boxed();
@@ -93,8 +94,11 @@ impl RepositoryReadBy for ProcessDb {
 impl RepositoryCountBy for ProcessDb {
   fn gen_count_query(
     filter: &GenericFilter,
-  ) -> impl diesel::query_dsl::methods::LoadQuery<'static, diesel::PgConnection, i64>
-  {
+  ) -> impl diesel::query_dsl::methods::LoadQuery<
+    'static,
+    crate::models::DbConnection,
+    i64,
+  > {
     let mut query = processes::table.into_boxed();
     let columns = Self::get_columns();
     gen_sql_query!(query, filter, columns).count()
@@ -140,3 +144,27 @@ impl ProcessDb {
     ProcessDb::transform_read_by(&filter, pool).await
   }
 }
+
+impl ProcessDb {
+  /// Count the processes running on each node grouped by their kind key
+  /// Return a list of `(node_name, kind_key, count)`
+  pub async fn count_by_node(
+    pool: &Pool,
+  ) -> IoResult<Vec<(String, String, i64)>> {
+    let pool = pool.clone();
+    ntex::rt::spawn_blocking(move || {
+      let mut conn = utils::store::get_pool_conn(&pool)?;
+      let items = processes::table
+        .group_by((processes::node_name, processes::kind_key))
+        .select((
+          processes::node_name,
+          processes::kind_key,
+          diesel::dsl::count_star(),
+        ))
+        .load::<(String, String, i64)>(&mut conn)
+        .map_err(Self::map_err)?;
+      Ok(items)
+    })
+    .await?
+  }
+}
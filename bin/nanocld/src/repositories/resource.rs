@@ -10,6 +10,7 @@ use nanocl_error::{
 
 use nanocl_stubs::{
   generic::GenericFilter,
+  network_policy::NETWORK_POLICY_KIND,
   resource::{Resource, ResourcePartial},
   resource_kind::ResourceKind,
 };
@@ -61,7 +62,7 @@ impl RepositoryReadBy for ResourceDb {
     is_multiple: bool,
   ) -> impl diesel::query_dsl::methods::LoadQuery<
     'static,
-    diesel::PgConnection,
+    crate::models::DbConnection,
     Self::Output,
   > {
     let mut query = resources::table
@@ -84,8 +85,11 @@ impl RepositoryReadBy for ResourceDb {
 impl RepositoryCountBy for ResourceDb {
   fn gen_count_query(
     filter: &GenericFilter,
-  ) -> impl diesel::query_dsl::methods::LoadQuery<'static, diesel::PgConnection, i64>
-  {
+  ) -> impl diesel::query_dsl::methods::LoadQuery<
+    'static,
+    crate::models::DbConnection,
+    i64,
+  > {
     let mut query = resources::table
       .inner_join(crate::schema::specs::table)
       .into_boxed();
@@ -218,6 +222,9 @@ impl ResourceDb {
         HttpError::bad_request(msg)
       })?;
     }
+    if kind.name == NETWORK_POLICY_KIND {
+      utils::network_policy::validate(&resource.data)?;
+    }
     if let Some(url) = &kind.data.url {
       let ctrl_client = utils::ctrl_client::CtrlClient::new(&kind.name, url);
       let config = ctrl_client
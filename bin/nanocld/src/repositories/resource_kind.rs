@@ -53,7 +53,7 @@ impl RepositoryReadBy for ResourceKindDb {
     is_multiple: bool,
   ) -> impl diesel::query_dsl::methods::LoadQuery<
     'static,
-    diesel::PgConnection,
+    crate::models::DbConnection,
     Self::Output,
   > {
     let mut query = resource_kinds::table
@@ -76,7 +76,8 @@ impl RepositoryReadBy for ResourceKindDb {
 impl RepositoryCountBy for ResourceKindDb {
   fn gen_count_query(
     filter: &GenericFilter,
-  ) -> impl diesel::query_dsl::LoadQuery<'static, diesel::PgConnection, i64> {
+  ) -> impl diesel::query_dsl::LoadQuery<'static, crate::models::DbConnection, i64>
+  {
     let mut query = resource_kinds::table.into_boxed();
     let columns = Self::get_columns();
     gen_sql_query!(query, filter, columns).count()
@@ -2,14 +2,16 @@ use std::collections::HashMap;
 
 use diesel::prelude::*;
 
+use nanocl_error::io::IoResult;
 use nanocl_stubs::generic::GenericFilter;
 
 use nanocl_stubs::secret::Secret;
 
 use crate::{
   gen_sql_multiple, gen_sql_order_by, gen_sql_query,
-  models::{ColumnType, SecretDb, SecretUpdateDb},
+  models::{ColumnType, SecretDb, SecretUpdateDb, SystemState},
   schema::secrets,
+  utils,
 };
 
 use super::generic::*;
@@ -53,7 +55,7 @@ impl RepositoryReadBy for SecretDb {
     is_multiple: bool,
   ) -> impl diesel::query_dsl::methods::LoadQuery<
     'static,
-    diesel::pg::PgConnection,
+    crate::models::DbConnection,
     Self::Output,
   > {
     let mut query = secrets::table.into_boxed();
@@ -74,8 +76,11 @@ impl RepositoryReadBy for SecretDb {
 impl RepositoryCountBy for SecretDb {
   fn gen_count_query(
     filter: &GenericFilter,
-  ) -> impl diesel::query_dsl::methods::LoadQuery<'static, diesel::PgConnection, i64>
-  {
+  ) -> impl diesel::query_dsl::methods::LoadQuery<
+    'static,
+    crate::models::DbConnection,
+    i64,
+  > {
     let mut query = secrets::table.into_boxed();
     let columns = Self::get_columns();
     gen_sql_query!(query, filter, columns).count()
@@ -91,3 +96,34 @@ impl RepositoryReadByTransform for SecretDb {
     input.try_into()
   }
 }
+
+impl SecretDb {
+  /// Read the secrets matching the filter with their data decrypted
+  pub async fn read_decrypted_by(
+    filter: &GenericFilter,
+    state: &SystemState,
+  ) -> IoResult<Vec<Secret>> {
+    let keys = utils::secret::get_keys(state);
+    SecretDb::transform_read_by(filter, &state.inner.pool)
+      .await?
+      .into_iter()
+      .map(|mut secret| {
+        secret.data =
+          utils::secret::decrypt(&secret.name, &secret.data, &keys)?;
+        Ok(secret)
+      })
+      .collect()
+  }
+
+  /// Read a secret by its key with its data decrypted
+  pub async fn read_decrypted_by_pk(
+    pk: &str,
+    state: &SystemState,
+  ) -> IoResult<Secret> {
+    let mut secret =
+      SecretDb::transform_read_by_pk(pk, &state.inner.pool).await?;
+    let keys = utils::secret::get_keys(state);
+    secret.data = utils::secret::decrypt(&secret.name, &secret.data, &keys)?;
+    Ok(secret)
+  }
+}
@@ -39,7 +39,7 @@ impl RepositoryDelBy for SpecDb {
     filter: &GenericFilter,
   ) -> diesel::query_builder::BoxedDeleteStatement<
     'static,
-    diesel::pg::Pg,
+    crate::models::DbBackend,
     <Self as diesel::associations::HasTable>::Table,
   >
   where
@@ -63,7 +63,7 @@ impl RepositoryReadBy for SpecDb {
     is_multiple: bool,
   ) -> impl diesel::query_dsl::methods::LoadQuery<
     'static,
-    diesel::pg::PgConnection,
+    crate::models::DbConnection,
     Self::Output,
   > {
     let mut query = specs::table.into_boxed();
@@ -151,10 +151,17 @@ impl SpecDb {
       metadata: self.metadata.clone(),
       init_container: p.init_container,
       secrets: p.secrets,
+      secret_mounts: p.secret_mounts,
+      volume_mounts: p.volume_mounts,
       container: p.container,
       replication: p.replication,
       image_pull_secret: p.image_pull_secret,
       image_pull_policy: p.image_pull_policy,
+      node_affinity: p.node_affinity,
+      node_anti_affinity: p.node_anti_affinity,
+      update_strategy: p.update_strategy,
+      autoscale: p.autoscale,
+      disruption_budget: p.disruption_budget,
     };
     Ok(spec)
   }
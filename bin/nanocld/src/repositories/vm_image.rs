@@ -50,7 +50,7 @@ impl RepositoryReadBy for VmImageDb {
     is_multiple: bool,
   ) -> impl diesel::query_dsl::methods::LoadQuery<
     'static,
-    diesel::pg::PgConnection,
+    crate::models::DbConnection,
     Self::Output,
   > {
     let mut query = vm_images::table.into_boxed();
@@ -71,8 +71,11 @@ impl RepositoryReadBy for VmImageDb {
 impl RepositoryCountBy for VmImageDb {
   fn gen_count_query(
     filter: &GenericFilter,
-  ) -> impl diesel::query_dsl::methods::LoadQuery<'static, diesel::PgConnection, i64>
-  {
+  ) -> impl diesel::query_dsl::methods::LoadQuery<
+    'static,
+    crate::models::DbConnection,
+    i64,
+  > {
     let mut query = vm_images::table.into_boxed();
     let columns = Self::get_columns();
     gen_sql_query!(query, filter, columns).count()
@@ -0,0 +1,104 @@
+use std::collections::HashMap;
+
+use diesel::prelude::*;
+
+use nanocl_error::io::IoResult;
+use nanocl_stubs::{
+  generic::{GenericClause, GenericFilter},
+  volume::Volume,
+};
+
+use crate::{
+  gen_sql_multiple, gen_sql_order_by, gen_sql_query,
+  models::{ColumnType, Pool, VolumeDb},
+  schema::volumes,
+};
+
+use super::generic::*;
+
+impl RepositoryBase for VolumeDb {
+  fn get_columns<'a>() -> HashMap<&'a str, (ColumnType, &'a str)> {
+    HashMap::from([
+      ("key", (ColumnType::Text, "volumes.key")),
+      ("name", (ColumnType::Text, "volumes.name")),
+      (
+        "namespace_name",
+        (ColumnType::Text, "volumes.namespace_name"),
+      ),
+      ("kind", (ColumnType::Text, "volumes.kind")),
+      (
+        "created_at",
+        (ColumnType::Timestamptz, "volumes.created_at"),
+      ),
+      ("metadata", (ColumnType::Json, "volumes.metadata")),
+    ])
+  }
+}
+
+impl RepositoryCreate for VolumeDb {}
+
+impl RepositoryDelByPk for VolumeDb {}
+
+impl RepositoryReadBy for VolumeDb {
+  type Output = VolumeDb;
+
+  fn get_pk() -> &'static str {
+    "key"
+  }
+
+  fn gen_read_query(
+    filter: &GenericFilter,
+    is_multiple: bool,
+  ) -> impl diesel::query_dsl::methods::LoadQuery<
+    'static,
+    crate::models::DbConnection,
+    Self::Output,
+  > {
+    let mut query = volumes::table.into_boxed();
+    let columns = Self::get_columns();
+    query = gen_sql_query!(query, filter, columns);
+    if let Some(orders) = &filter.order_by {
+      query = gen_sql_order_by!(query, orders, columns);
+    } else {
+      query = query.order(volumes::created_at.desc());
+    }
+    if is_multiple {
+      gen_sql_multiple!(query, filter);
+    }
+    query
+  }
+}
+
+impl RepositoryCountBy for VolumeDb {
+  fn gen_count_query(
+    filter: &GenericFilter,
+  ) -> impl diesel::query_dsl::methods::LoadQuery<
+    'static,
+    crate::models::DbConnection,
+    i64,
+  > {
+    let mut query = volumes::table.into_boxed();
+    let columns = Self::get_columns();
+    gen_sql_query!(query, filter, columns).count()
+  }
+}
+
+impl RepositoryReadByTransform for VolumeDb {
+  type NewOutput = Volume;
+
+  fn transform(input: Self::Output) -> IoResult<Self::NewOutput> {
+    input.try_into()
+  }
+}
+
+impl VolumeDb {
+  /// Read the volumes of a namespace
+  pub async fn read_by_namespace(
+    namespace: &str,
+    pool: &Pool,
+  ) -> IoResult<Vec<Volume>> {
+    let filter = GenericFilter::new()
+      .r#where("namespace_name", GenericClause::Eq(namespace.to_owned()));
+    VolumeDb::transform_read_by(&filter, pool).await
+  }
+}
@@ -1,6 +1,27 @@
 // @generated automatically by Diesel CLI.
 
 diesel::table! {
+    use diesel::sql_types::{Bool, Int4, Int8, Nullable, Text, Varchar};
+    use crate::models::sql_types::{Inet, Jsonb, Timestamptz, Uuid};
+
+    audit_logs (key) {
+        key -> Uuid,
+        created_at -> Timestamptz,
+        identity -> Varchar,
+        source -> Varchar,
+        method -> Varchar,
+        route -> Varchar,
+        object_key -> Nullable<Varchar>,
+        status -> Int4,
+        payload_hash -> Nullable<Varchar>,
+        payload_truncated -> Bool,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::{Bool, Int4, Int8, Nullable, Text, Varchar};
+    use crate::models::sql_types::{Inet, Jsonb, Timestamptz, Uuid};
+
     cargoes (key) {
         key -> Varchar,
         created_at -> Timestamptz,
@@ -8,10 +29,17 @@ diesel::table! {
         spec_key -> Uuid,
         status_key -> Varchar,
         namespace_name -> Varchar,
+        settled_spec_key -> Nullable<Varchar>,
+        replicas -> Nullable<Int4>,
+        autoscaler_node -> Nullable<Varchar>,
+        autoscaler_lease_at -> Nullable<Timestamptz>,
     }
 }
 
 diesel::table! {
+    use diesel::sql_types::{Bool, Int4, Int8, Nullable, Text, Varchar};
+    use crate::models::sql_types::{Inet, Jsonb, Timestamptz, Uuid};
+
     events (key) {
         key -> Uuid,
         created_at -> Timestamptz,
@@ -29,6 +57,23 @@ diesel::table! {
 }
 
 diesel::table! {
+    use diesel::sql_types::{Bool, Int4, Int8, Nullable, Text, Varchar};
+    use crate::models::sql_types::{Inet, Jsonb, Timestamptz, Uuid};
+
+    job_runs (key) {
+        key -> Uuid,
+        created_at -> Timestamptz,
+        job_key -> Varchar,
+        scheduled_at -> Timestamptz,
+        status -> Varchar,
+        note -> Nullable<Varchar>,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::{Bool, Int4, Int8, Nullable, Text, Varchar};
+    use crate::models::sql_types::{Inet, Jsonb, Timestamptz, Uuid};
+
     jobs (key) {
         key -> Varchar,
         created_at -> Timestamptz,
@@ -36,10 +81,17 @@ diesel::table! {
         status_key -> Varchar,
         data -> Jsonb,
         metadata -> Nullable<Jsonb>,
+        attempts -> Int4,
+        last_failure -> Nullable<Text>,
+        steps -> Jsonb,
+        last_scheduled_at -> Nullable<Timestamptz>,
     }
 }
 
 diesel::table! {
+    use diesel::sql_types::{Bool, Int4, Int8, Nullable, Text, Varchar};
+    use crate::models::sql_types::{Inet, Jsonb, Timestamptz, Uuid};
+
     metrics (key) {
         key -> Uuid,
         created_at -> Timestamptz,
@@ -52,14 +104,22 @@ diesel::table! {
 }
 
 diesel::table! {
+    use diesel::sql_types::{Bool, Int4, Int8, Nullable, Text, Varchar};
+    use crate::models::sql_types::{Inet, Jsonb, Timestamptz, Uuid};
+
     namespaces (name) {
         name -> Varchar,
         created_at -> Timestamptz,
         metadata -> Nullable<Jsonb>,
+        quota -> Nullable<Jsonb>,
+        limit_range -> Nullable<Jsonb>,
     }
 }
 
 diesel::table! {
+    use diesel::sql_types::{Bool, Int4, Int8, Nullable, Text, Varchar};
+    use crate::models::sql_types::{Inet, Jsonb, Timestamptz, Uuid};
+
     node_group_links (rowid) {
         node_name -> Varchar,
         node_group_name -> Varchar,
@@ -68,12 +128,18 @@ diesel::table! {
 }
 
 diesel::table! {
+    use diesel::sql_types::{Bool, Int4, Int8, Nullable, Text, Varchar};
+    use crate::models::sql_types::{Inet, Jsonb, Timestamptz, Uuid};
+
     node_groups (name) {
         name -> Varchar,
     }
 }
 
 diesel::table! {
+    use diesel::sql_types::{Bool, Int4, Int8, Nullable, Text, Varchar};
+    use crate::models::sql_types::{Inet, Jsonb, Timestamptz, Uuid};
+
     nodes (name) {
         name -> Varchar,
         created_at -> Timestamptz,
@@ -81,10 +147,18 @@ diesel::table! {
         endpoint -> Varchar,
         version -> Varchar,
         metadata -> Nullable<Jsonb>,
+        status -> Varchar,
+        last_seen -> Timestamptz,
+        cordoned -> Bool,
+        draining -> Bool,
+        token_hash -> Nullable<Varchar>,
     }
 }
 
 diesel::table! {
+    use diesel::sql_types::{Bool, Int4, Int8, Nullable, Text, Varchar};
+    use crate::models::sql_types::{Inet, Jsonb, Timestamptz, Uuid};
+
     object_process_statuses (key) {
         key -> Varchar,
         created_at -> Timestamptz,
@@ -97,6 +171,9 @@ diesel::table! {
 }
 
 diesel::table! {
+    use diesel::sql_types::{Bool, Int4, Int8, Nullable, Text, Varchar};
+    use crate::models::sql_types::{Inet, Jsonb, Timestamptz, Uuid};
+
     processes (key) {
         key -> Varchar,
         created_at -> Timestamptz,
@@ -110,6 +187,9 @@ diesel::table! {
 }
 
 diesel::table! {
+    use diesel::sql_types::{Bool, Int4, Int8, Nullable, Text, Varchar};
+    use crate::models::sql_types::{Inet, Jsonb, Timestamptz, Uuid};
+
     resource_kinds (name) {
         name -> Varchar,
         created_at -> Timestamptz,
@@ -118,6 +198,9 @@ diesel::table! {
 }
 
 diesel::table! {
+    use diesel::sql_types::{Bool, Int4, Int8, Nullable, Text, Varchar};
+    use crate::models::sql_types::{Inet, Jsonb, Timestamptz, Uuid};
+
     resources (key) {
         key -> Varchar,
         created_at -> Timestamptz,
@@ -127,6 +210,20 @@ diesel::table! {
 }
 
 diesel::table! {
+    use diesel::sql_types::{Bool, Int4, Int8, Nullable, Text, Varchar};
+    use crate::models::sql_types::{Inet, Jsonb, Timestamptz, Uuid};
+
+    roles (key) {
+        key -> Varchar,
+        created_at -> Timestamptz,
+        rules -> Jsonb,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::{Bool, Int4, Int8, Nullable, Text, Varchar};
+    use crate::models::sql_types::{Inet, Jsonb, Timestamptz, Uuid};
+
     secrets (key) {
         key -> Varchar,
         created_at -> Timestamptz,
@@ -139,6 +236,9 @@ diesel::table! {
 }
 
 diesel::table! {
+    use diesel::sql_types::{Bool, Int4, Int8, Nullable, Text, Varchar};
+    use crate::models::sql_types::{Inet, Jsonb, Timestamptz, Uuid};
+
     specs (key) {
         key -> Uuid,
         created_at -> Timestamptz,
@@ -151,6 +251,22 @@ diesel::table! {
 }
 
 diesel::table! {
+    use diesel::sql_types::{Bool, Int4, Int8, Nullable, Text, Varchar};
+    use crate::models::sql_types::{Inet, Jsonb, Timestamptz, Uuid};
+
+    tokens (key) {
+        key -> Varchar,
+        created_at -> Timestamptz,
+        expires_at -> Nullable<Timestamptz>,
+        hash -> Varchar,
+        roles -> Jsonb,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::{Bool, Int4, Int8, Nullable, Text, Varchar};
+    use crate::models::sql_types::{Inet, Jsonb, Timestamptz, Uuid};
+
     vm_images (name) {
         name -> Varchar,
         node_name -> Varchar,
@@ -165,6 +281,9 @@ diesel::table! {
 }
 
 diesel::table! {
+    use diesel::sql_types::{Bool, Int4, Int8, Nullable, Text, Varchar};
+    use crate::models::sql_types::{Inet, Jsonb, Timestamptz, Uuid};
+
     vms (key) {
         key -> Varchar,
         name -> Varchar,
@@ -175,9 +294,25 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    use diesel::sql_types::{Bool, Int4, Int8, Nullable, Text, Varchar};
+    use crate::models::sql_types::{Inet, Jsonb, Timestamptz, Uuid};
+
+    volumes (key) {
+        key -> Varchar,
+        created_at -> Timestamptz,
+        name -> Varchar,
+        namespace_name -> Varchar,
+        kind -> Varchar,
+        source -> Varchar,
+        metadata -> Nullable<Jsonb>,
+    }
+}
+
 diesel::joinable!(cargoes -> namespaces (namespace_name));
 diesel::joinable!(cargoes -> object_process_statuses (status_key));
 diesel::joinable!(cargoes -> specs (spec_key));
+diesel::joinable!(job_runs -> jobs (job_key));
 diesel::joinable!(jobs -> object_process_statuses (status_key));
 diesel::joinable!(node_group_links -> node_groups (node_group_name));
 diesel::joinable!(node_group_links -> nodes (node_name));
@@ -188,10 +323,13 @@ diesel::joinable!(vm_images -> nodes (node_name));
 diesel::joinable!(vms -> namespaces (namespace_name));
 diesel::joinable!(vms -> object_process_statuses (status_key));
 diesel::joinable!(vms -> specs (spec_key));
+diesel::joinable!(volumes -> namespaces (namespace_name));
 
 diesel::allow_tables_to_appear_in_same_query!(
+  audit_logs,
   cargoes,
   events,
+  job_runs,
   jobs,
   metrics,
   namespaces,
@@ -202,8 +340,11 @@ diesel::allow_tables_to_appear_in_same_query!(
   processes,
   resource_kinds,
   resources,
+  roles,
   secrets,
   specs,
+  tokens,
   vm_images,
   vms,
+  volumes,
 );
@@ -0,0 +1,32 @@
+use ntex::web;
+
+use nanocl_error::http::HttpResult;
+use nanocl_stubs::generic::{GenericCount, GenericListQuery};
+
+use crate::{
+  models::{AuditLogDb, SystemState},
+  repositories::generic::*,
+  utils,
+};
+
+/// Count the audit log entries
+#[cfg_attr(feature = "dev", utoipa::path(
+  get,
+  tag = "Audit",
+  path = "/audit/count",
+  params(
+    ("filter" = Option<String>, Query, description = "Generic filter", example = "{ \"filter\": { \"where\": { \"method\": { \"eq\": \"DELETE\" } } } }"),
+  ),
+  responses(
+    (status = 200, description = "Count result", body = GenericCount),
+  ),
+))]
+#[web::get("/audit/count")]
+pub async fn count_audit(
+  state: web::types::State<SystemState>,
+  qs: web::types::Query<GenericListQuery>,
+) -> HttpResult<web::HttpResponse> {
+  let filter = utils::query_string::parse_qs_filter(&qs)?;
+  let count = AuditLogDb::count_by(&filter, &state.inner.pool).await?;
+  Ok(web::HttpResponse::Ok().json(&GenericCount { count }))
+}
@@ -0,0 +1,32 @@
+use ntex::web;
+
+use nanocl_error::http::HttpResult;
+use nanocl_stubs::generic::GenericListQuery;
+
+use crate::{
+  models::{AuditLogDb, SystemState},
+  repositories::generic::*,
+  utils,
+};
+
+/// List the audit log entries, the most recent first
+#[cfg_attr(feature = "dev", utoipa::path(
+  get,
+  tag = "Audit",
+  path = "/audit",
+  params(
+    ("filter" = Option<String>, Query, description = "Generic filter", example = "{ \"filter\": { \"where\": { \"identity\": { \"eq\": \"token:ci\" } } } }"),
+  ),
+  responses(
+    (status = 200, description = "List of audit log entry", body = [AuditLog]),
+  ),
+))]
+#[web::get("/audit")]
+pub async fn list_audit(
+  state: web::types::State<SystemState>,
+  qs: web::types::Query<GenericListQuery>,
+) -> HttpResult<web::HttpResponse> {
+  let filter = utils::query_string::parse_qs_filter(&qs)?;
+  let items = AuditLogDb::transform_read_by(&filter, &state.inner.pool).await?;
+  Ok(web::HttpResponse::Ok().json(&items))
+}
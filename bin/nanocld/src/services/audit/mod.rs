@@ -0,0 +1,12 @@
+pub use ntex::web;
+
+pub mod count;
+pub mod list;
+
+pub use count::*;
+pub use list::*;
+
+pub fn ntex_config(config: &mut web::ServiceConfig) {
+  config.service(list_audit);
+  config.service(count_audit);
+}
@@ -1,5 +1,6 @@
 use ntex::web;
 
+pub mod abort;
 pub mod count;
 pub mod create;
 pub mod delete;
@@ -7,9 +8,11 @@ pub mod inspect;
 pub mod list;
 pub mod list_history;
 pub mod patch;
+pub mod promote;
 pub mod put;
 pub mod revert;
 
+pub use abort::*;
 pub use count::*;
 pub use create::*;
 pub use delete::*;
@@ -17,6 +20,7 @@ pub use inspect::*;
 pub use list::*;
 pub use list_history::*;
 pub use patch::*;
+pub use promote::*;
 pub use put::*;
 pub use revert::*;
 
@@ -30,6 +34,8 @@ pub fn ntex_config(config: &mut web::ServiceConfig) {
   config.service(list_cargo_history);
   config.service(revert_cargo);
   config.service(count_cargo);
+  config.service(promote_cargo);
+  config.service(abort_cargo);
 }
 
 #[cfg(test)]
@@ -0,0 +1,33 @@
+use ntex::web;
+
+use nanocl_error::http::HttpResult;
+use nanocl_stubs::generic::GenericNspQuery;
+
+use crate::{models::SystemState, utils};
+
+/// Promote the deployment of a cargo by removing his previous instances
+#[cfg_attr(feature = "dev", utoipa::path(
+  post,
+  tag = "Cargoes",
+  path = "/cargoes/{name}/promote",
+  params(
+    ("name" = String, Path, description = "Name of the cargo"),
+    ("namespace" = Option<String>, Query, description = "Namespace where the cargo belongs default to global namespace"),
+  ),
+  responses(
+    (status = 200, description = "Cargo promoted", body = Cargo),
+    (status = 400, description = "No deployment in progress", body = ApiError),
+    (status = 404, description = "Cargo does not exist", body = ApiError),
+  ),
+))]
+#[web::post("/cargoes/{name}/promote")]
+pub async fn promote_cargo(
+  state: web::types::State<SystemState>,
+  path: web::types::Path<(String, String)>,
+  qs: web::types::Query<GenericNspQuery>,
+) -> HttpResult<web::HttpResponse> {
+  let namespace = utils::key::resolve_nsp(&qs.namespace);
+  let key = utils::key::gen_key(&namespace, &path.1);
+  let cargo = utils::container::cargo::promote(&key, &state).await?;
+  Ok(web::HttpResponse::Ok().json(&cargo))
+}
@@ -0,0 +1,46 @@
+use ntex::web;
+
+use nanocl_error::http::{HttpError, HttpResult};
+use nanocl_stubs::system::EventPartial;
+
+use crate::models::SystemState;
+
+/// Maximum length of the action and the reason of an event
+const MAX_FIELD_LEN: usize = 128;
+
+/// Create an event reported by a controller running outside the daemon.
+/// The reporting node default to the current one when empty.
+#[cfg_attr(feature = "dev", utoipa::path(
+  post,
+  request_body = EventPartial,
+  tag = "Events",
+  path = "/events",
+  responses(
+    (status = 201, description = "Event created"),
+    (status = 400, description = "Invalid event", body = ApiError),
+  ),
+))]
+#[web::post("/events")]
+pub async fn create_event(
+  state: web::types::State<SystemState>,
+  payload: web::types::Json<EventPartial>,
+) -> HttpResult<web::HttpResponse> {
+  let mut event = payload.into_inner();
+  if event.reporting_controller.is_empty() {
+    return Err(HttpError::bad_request("ReportingController can't be empty"));
+  }
+  for (name, value) in [("Action", &event.action), ("Reason", &event.reason)] {
+    if value.is_empty() || value.len() > MAX_FIELD_LEN {
+      return Err(HttpError::bad_request(format!(
+        "{name} must have between 1 and {MAX_FIELD_LEN} characters"
+      )));
+    }
+  }
+  if event.reporting_node.is_empty() {
+    event
+      .reporting_node
+      .clone_from(&state.inner.config.hostname);
+  }
+  state.emit_event(event).await?;
+  Ok(web::HttpResponse::Created().finish())
+}
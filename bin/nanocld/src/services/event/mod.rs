@@ -1,11 +1,13 @@
 use ntex::web;
 
 mod count;
+mod create;
 mod inspect;
 mod list;
 mod watch;
 
 pub use count::*;
+pub use create::*;
 pub use inspect::*;
 pub use list::*;
 pub use watch::*;
@@ -15,6 +17,7 @@ pub fn ntex_config(config: &mut web::ServiceConfig) {
   config.service(watch_event);
   config.service(inspect_event);
   config.service(count_event);
+  config.service(create_event);
 }
 
 #[cfg(test)]
@@ -24,7 +27,8 @@ mod tests {
   use nanocl_stubs::{
     cargo_spec::CargoSpecPartial,
     system::{
-      Event, EventActorKind, EventCondition, EventKind, NativeEventAction,
+      Event, EventActorKind, EventCondition, EventKind, EventPartial,
+      NativeEventAction,
     },
   };
   use ntex::{http, rt};
@@ -48,6 +52,36 @@ mod tests {
     resp.json::<Event>().await.unwrap();
   }
 
+  #[ntex::test]
+  async fn create() {
+    let system = gen_default_test_system().await;
+    let client = system.client;
+    let mut event = EventPartial {
+      reporting_node: String::new(),
+      reporting_controller: "test.nanocl.io".to_owned(),
+      kind: EventKind::Warning,
+      action: "health".to_owned(),
+      reason: "test_event".to_owned(),
+      note: None,
+      actor: None,
+      related: None,
+      metadata: None,
+    };
+    let res = client
+      .send_post("/events", Some(&event), None::<String>)
+      .await;
+    test_status_code!(res.status(), http::StatusCode::CREATED, "create event");
+    event.reason = "a".repeat(129);
+    let res = client
+      .send_post("/events", Some(&event), None::<String>)
+      .await;
+    test_status_code!(
+      res.status(),
+      http::StatusCode::BAD_REQUEST,
+      "create event with a too long reason"
+    );
+  }
+
   #[ntex::test]
   async fn watch_events() {
     let system = gen_default_test_system().await;
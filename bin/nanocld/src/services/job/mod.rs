@@ -5,12 +5,14 @@ pub mod create;
 pub mod delete;
 pub mod inspect;
 pub mod list;
+pub mod runs;
 
 pub use count::*;
 pub use create::*;
 pub use delete::*;
 pub use inspect::*;
 pub use list::*;
+pub use runs::*;
 
 pub fn ntex_config(config: &mut web::ServiceConfig) {
   config.service(list_job);
@@ -18,6 +20,7 @@ pub fn ntex_config(config: &mut web::ServiceConfig) {
   config.service(delete_job);
   config.service(inspect_job);
   config.service(count_job);
+  config.service(list_job_runs);
 }
 
 #[cfg(test)]
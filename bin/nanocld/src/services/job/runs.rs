@@ -0,0 +1,30 @@
+use ntex::web;
+
+use nanocl_error::http::HttpResult;
+
+use crate::{
+  models::{JobDb, JobRunDb, SystemState},
+  repositories::generic::*,
+};
+
+/// List the recent scheduled runs of a job, the latest first
+#[cfg_attr(feature = "dev", utoipa::path(
+  get,
+  tag = "Jobs",
+  path = "/jobs/{name}/runs",
+  params(
+    ("name" = String, Path, description = "Name of the job"),
+  ),
+  responses(
+    (status = 200, description = "List of scheduled runs", body = [JobRun]),
+  ),
+))]
+#[web::get("/jobs/{name}/runs")]
+pub async fn list_job_runs(
+  state: web::types::State<SystemState>,
+  path: web::types::Path<(String, String)>,
+) -> HttpResult<web::HttpResponse> {
+  let job = JobDb::transform_read_by_pk(&path.1, &state.inner.pool).await?;
+  let runs = JobRunDb::read_by_job(&job.name, &state.inner.pool).await?;
+  Ok(web::HttpResponse::Ok().json(&runs))
+}
@@ -5,6 +5,7 @@ use nanocl_error::http::{HttpError, HttpResult};
 #[cfg(feature = "dev")]
 mod openapi;
 
+mod audit;
 mod cargo;
 mod event;
 mod exec;
@@ -15,10 +16,13 @@ mod node;
 mod process;
 mod resource;
 mod resource_kind;
+mod role;
 mod secret;
 mod system;
+mod token;
 mod vm;
 mod vm_image;
+mod volume;
 
 pub async fn unhandled() -> HttpResult<web::HttpResponse> {
   Err(HttpError::not_found("Route or method unhandled"))
@@ -50,6 +54,9 @@ pub fn ntex_config(config: &mut web::ServiceConfig) {
         nanocl_utils::ntex::middlewares::Versioning::new(crate::vars::VERSION)
           .finish(),
       )
+      .wrap(crate::middlewares::Auth)
+      .wrap(crate::middlewares::Audit)
+      .configure(audit::ntex_config)
       .configure(exec::ntex_config)
       .configure(node::ntex_config)
       .configure(namespace::ntex_config)
@@ -60,6 +67,9 @@ pub fn ntex_config(config: &mut web::ServiceConfig) {
       .configure(vm::ntex_config)
       .configure(metric::ntex_config)
       .configure(secret::ntex_config)
+      .configure(volume::ntex_config)
+      .configure(role::ntex_config)
+      .configure(token::ntex_config)
       .configure(process::ntex_config)
       .configure(job::ntex_config)
       .configure(event::ntex_config)
@@ -5,12 +5,14 @@ pub mod create;
 pub mod delete;
 pub mod inspect;
 pub mod list;
+pub mod put;
 
 pub use count::*;
 pub use create::*;
 pub use delete::*;
 pub use inspect::*;
 pub use list::*;
+pub use put::*;
 
 pub fn ntex_config(config: &mut web::ServiceConfig) {
   config.service(list_namespace);
@@ -18,6 +20,7 @@ pub fn ntex_config(config: &mut web::ServiceConfig) {
   config.service(inspect_namespace);
   config.service(delete_namespace);
   config.service(count_namespace);
+  config.service(put_namespace);
 }
 
 #[cfg(test)]
@@ -40,6 +43,8 @@ mod test_namespace {
     let new_namespace = NamespacePartial {
       name: String::from("controller-default"),
       metadata: None,
+      quota: None,
+      limit_range: None,
     };
     let res = client
       .send_post(ENDPOINT, Some(new_namespace), None::<String>)
@@ -0,0 +1,33 @@
+use ntex::web;
+
+use nanocl_error::http::HttpResult;
+use nanocl_stubs::namespace::NamespaceUpdate;
+
+use crate::{
+  models::{NamespaceDb, SystemState},
+  objects::generic::*,
+};
+
+/// Replace the quota and limit range of a namespace
+#[cfg_attr(feature = "dev", utoipa::path(
+  put,
+  request_body = NamespaceUpdate,
+  tag = "Namespaces",
+  path = "/namespaces/{name}",
+  params(
+    ("name" = String, Path, description = "Name of the namespace"),
+  ),
+  responses(
+    (status = 200, description = "The updated namespace", body = Namespace),
+    (status = 404, description = "Namespace is not existing", body = ApiError),
+  ),
+))]
+#[web::put("/namespaces/{name}")]
+pub async fn put_namespace(
+  state: web::types::State<SystemState>,
+  path: web::types::Path<(String, String)>,
+  payload: web::types::Json<NamespaceUpdate>,
+) -> HttpResult<web::HttpResponse> {
+  let item = NamespaceDb::put_obj_by_pk(&path.1, &payload, &state).await?;
+  Ok(web::HttpResponse::Ok().json(&item))
+}
@@ -0,0 +1,49 @@
+use ntex::web;
+
+use nanocl_error::http::HttpResult;
+
+use crate::{models::SystemState, utils};
+
+/// Cordon a node so it receives no new instances
+#[cfg_attr(feature = "dev", utoipa::path(
+  post,
+  tag = "Nodes",
+  path = "/nodes/{name}/cordon",
+  params(
+    ("name" = String, Path, description = "Name of the node"),
+  ),
+  responses(
+    (status = 200, description = "Node cordoned", body = Node),
+    (status = 404, description = "Node does not exist", body = ApiError),
+  ),
+))]
+#[web::post("/nodes/{name}/cordon")]
+pub async fn cordon_node(
+  state: web::types::State<SystemState>,
+  path: web::types::Path<(String, String)>,
+) -> HttpResult<web::HttpResponse> {
+  let node = utils::node::cordon(&path.1, true, &state).await?;
+  Ok(web::HttpResponse::Ok().json(&node))
+}
+
+/// Uncordon a node so it receives new instances again and stop its drain
+#[cfg_attr(feature = "dev", utoipa::path(
+  delete,
+  tag = "Nodes",
+  path = "/nodes/{name}/cordon",
+  params(
+    ("name" = String, Path, description = "Name of the node"),
+  ),
+  responses(
+    (status = 200, description = "Node uncordoned", body = Node),
+    (status = 404, description = "Node does not exist", body = ApiError),
+  ),
+))]
+#[web::delete("/nodes/{name}/cordon")]
+pub async fn uncordon_node(
+  state: web::types::State<SystemState>,
+  path: web::types::Path<(String, String)>,
+) -> HttpResult<web::HttpResponse> {
+  let node = utils::node::cordon(&path.1, false, &state).await?;
+  Ok(web::HttpResponse::Ok().json(&node))
+}
@@ -0,0 +1,30 @@
+use ntex::web;
+
+use nanocl_error::http::HttpResult;
+
+use crate::{models::SystemState, utils};
+
+/// Cordon a node and move its cargo instances to the other nodes
+/// within the disruption budget of each cargo, then wait for its running jobs.
+/// The drain runs in background and is reported as events of the node.
+#[cfg_attr(feature = "dev", utoipa::path(
+  post,
+  tag = "Nodes",
+  path = "/nodes/{name}/drain",
+  params(
+    ("name" = String, Path, description = "Name of the node"),
+  ),
+  responses(
+    (status = 202, description = "Node drain started", body = Node),
+    (status = 400, description = "Node is unreachable", body = ApiError),
+    (status = 404, description = "Node does not exist", body = ApiError),
+  ),
+))]
+#[web::post("/nodes/{name}/drain")]
+pub async fn drain_node(
+  state: web::types::State<SystemState>,
+  path: web::types::Path<(String, String)>,
+) -> HttpResult<web::HttpResponse> {
+  let node = utils::node::request_drain(&path.1, &state).await?;
+  Ok(web::HttpResponse::Accepted().json(&node))
+}
@@ -1,16 +1,23 @@
 pub use ntex::web;
 
+pub mod cordon;
 pub mod count;
+pub mod drain;
 pub mod list;
 pub mod ws;
 
+pub use cordon::*;
 pub use count::*;
+pub use drain::*;
 pub use list::*;
 pub use ws::*;
 
 pub fn ntex_config(config: &mut web::ServiceConfig) {
   config.service(list_node);
   config.service(count_node);
+  config.service(cordon_node);
+  config.service(uncordon_node);
+  config.service(drain_node);
   config.service(web::resource("/nodes/ws").route(web::get().to(node_ws)));
 }
 
@@ -11,12 +11,16 @@ use ntex::{
 };
 
 use crate::{
-  models::{SystemState, WsConState},
+  models::{SystemState, WsConState, NODE_HEADER},
   utils,
 };
 
 async fn node_ws_service(
-  (sink, state): (ws::WsSink, web::types::State<SystemState>),
+  (sink, state, peer): (
+    ws::WsSink,
+    web::types::State<SystemState>,
+    Option<String>,
+  ),
 ) -> Result<
   impl Service<ws::Frame, Response = Option<ws::Message>, Error = std::io::Error>,
   web::Error,
@@ -29,16 +33,24 @@ async fn node_ws_service(
   let _ = sink
     .send(ws::Message::Text(ByteString::from(message)))
     .await;
+  // record the heartbeats of the node that opened the connection
+  let mark_seen = move || {
+    if let Some(peer) = &peer {
+      utils::node::spawn_mark_seen(peer, &state);
+    }
+  };
   // handler service for incoming web sockets frames
   let service = fn_service(move |frame| {
     let item = match frame {
       ws::Frame::Ping(msg) => {
         con_state.borrow_mut().hb = Instant::now();
+        mark_seen();
         Some(ws::Message::Pong(msg))
       }
       ws::Frame::Pong(_) => {
         // update heartbeat time
         con_state.borrow_mut().hb = Instant::now();
+        mark_seen();
         None
       }
       ws::Frame::Close(reason) => Some(ws::Message::Close(reason)),
@@ -54,7 +66,8 @@ async fn node_ws_service(
   Ok(chain(service).and_then(on_shutdown))
 }
 
-/// Websocket endpoint for communication between nodes used internally
+/// Websocket endpoint for communication between nodes used internally.
+/// The nodes exchange heartbeats over it to detect the failing ones.
 #[cfg_attr(feature = "dev", utoipa::path(
   get,
   tag = "Nodes",
@@ -67,11 +80,16 @@ pub async fn node_ws(
   state: web::types::State<SystemState>,
   req: web::HttpRequest,
 ) -> Result<web::HttpResponse, web::Error> {
+  let peer = req
+    .headers()
+    .get(NODE_HEADER)
+    .and_then(|value| value.to_str().ok())
+    .map(|value| value.to_owned());
   web::ws::start(
     req,
-    // inject state to the node_ws_service
+    // inject state and the connected node to the node_ws_service
     map_config(fn_factory_with_config(node_ws_service), move |cfg| {
-      (cfg, state.clone())
+      (cfg, state.clone(), peer.clone())
     }),
   )
   .await
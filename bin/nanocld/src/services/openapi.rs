@@ -35,29 +35,44 @@ use bollard_next::service::{
   TlsInfo,
 };
 
+use nanocl_stubs::audit::AuditLog;
+use nanocl_stubs::auth::{
+  Role, RolePartial, RoleRule, RoleVerb, Token, TokenCreated, TokenPartial,
+};
 use nanocl_stubs::cargo::{
   Cargo, CargoInspect, CargoKillOptions, CargoSummary, CreateExecOptions,
 };
 use nanocl_stubs::cargo_spec::{
-  CargoSpec, CargoSpecPartial, CargoSpecUpdate, ReplicationMode,
-  ReplicationStatic,
+  AutoscaleMetric, AutoscaleTarget, CargoAutoscale, CargoBlueGreen,
+  CargoCanary, CargoDisruptionBudget, CargoRollingUpdate, CargoSpec,
+  CargoSpecPartial, CargoSpecUpdate, CargoUpdateStrategy, NodeAffinity,
+  ReplicationMode, ReplicationStatic,
 };
 use nanocl_stubs::config::DaemonConfig;
 use nanocl_stubs::dns::{DnsEntry, ResourceDnsRule};
 use nanocl_stubs::generic::{
   GenericClause, GenericCount, GenericFilter, GenericWhere, ImagePullPolicy,
 };
-use nanocl_stubs::job::{Job, JobInspect, JobPartial, JobSummary};
+use nanocl_stubs::job::{
+  Job, JobBackoff, JobBackoffPolicy, JobConcurrencyPolicy, JobInspect,
+  JobPartial, JobRun, JobRunStatus, JobStep, JobStepState, JobStepStatus,
+  JobSummary,
+};
 use nanocl_stubs::metric::{Metric, MetricPartial};
 use nanocl_stubs::namespace::{
-  Namespace, NamespaceInspect, NamespacePartial, NamespaceSummary,
+  ContainerLimits, Namespace, NamespaceInspect, NamespaceLimitRange,
+  NamespacePartial, NamespaceQuota, NamespaceSummary, NamespaceUpdate,
+  NamespaceUsage,
 };
-use nanocl_stubs::node::Node;
+use nanocl_stubs::node::{Node, NodeStatus};
 use nanocl_stubs::process::{Process, ProcessKind, ProcessStats};
 use nanocl_stubs::proxy::{
-  HttpTarget, LimitReq, LimitReqZone, LocationTarget, ProxyHttpLocation,
-  ProxyRule, ProxyRuleHttp, ProxyRuleStream, ProxySsl, ProxySslConfig,
-  ProxyStreamProtocol, ResourceProxyRule, StreamTarget, UnixTarget,
+  HttpTarget, LimitReq, LimitReqZone, LocationTarget, ProxyHsts,
+  ProxyHttpBasicAuth, ProxyHttpCors, ProxyHttpErrorPage, ProxyHttpLocation,
+  ProxyHttpRewrite, ProxyHttpTimeouts, ProxyRule, ProxyRuleHttp,
+  ProxyRuleStream, ProxySsl, ProxySslAcme, ProxySslAcmeConfig, ProxySslConfig,
+  ProxyStreamProtocol, ProxyTlsPolicy, ProxyTlsProtocol, ResourceProxyRule,
+  StreamTarget, UnixTarget, UpstreamHealthCheck, UpstreamLoadBalancing,
   UpstreamTarget, UriTarget, UrlRedirect,
 };
 use nanocl_stubs::resource::{
@@ -67,26 +82,33 @@ use nanocl_stubs::resource_kind::{
   ResourceKind, ResourceKindInspect, ResourceKindPartial, ResourceKindSpec,
   ResourceKindVersion,
 };
-use nanocl_stubs::secret::{Secret, SecretPartial, SecretUpdate};
+use nanocl_stubs::secret::{
+  Secret, SecretPartial, SecretRotateKey, SecretRotateKeyResult, SecretUpdate,
+};
 use nanocl_stubs::statefile::{
   Statefile, StatefileArg, StatefileArgKind, SubState, SubStateArg,
   SubStateDef, SubStateValue,
 };
 use nanocl_stubs::system::{
   BinaryInfo, Event, EventActor, EventActorKind, EventCondition, EventKind,
-  HostInfo, NativeEventAction, ObjPsStatus, ObjPsStatusKind, SslConfig,
+  EventPartial, HostInfo, NativeEventAction, ObjPsStatus, ObjPsStatusKind,
+  SslConfig, StoreSnapshot,
 };
 use nanocl_stubs::vm::{Vm, VmInspect, VmSummary};
 use nanocl_stubs::vm_image::{VmImage, VmImageResizePayload};
 use nanocl_stubs::vm_spec::{
   VmDisk, VmHostConfig, VmSpec, VmSpecPartial, VmSpecUpdate,
 };
+use nanocl_stubs::volume::{
+  Volume, VolumeInspect, VolumeKind, VolumeMount, VolumePartial, VolumeRestore,
+  VolumeSnapshot,
+};
 
 use crate::vars;
 
 use super::{
-  cargo, event, exec, job, metric, namespace, node, process, resource,
-  resource_kind, secret, system, vm, vm_image,
+  audit, cargo, event, exec, job, metric, namespace, node, process, resource,
+  resource_kind, role, secret, system, token, vm, vm_image, volume,
 };
 
 /// When returning a [HttpError](nanocl_error::http::HttpError)
@@ -231,17 +253,23 @@ impl Modify for VersionModifier {
     // Node
     node::list_node,
     node::count_node,
+    node::cordon_node,
+    node::uncordon_node,
+    node::drain_node,
     node::node_ws,
     // System
     system::get_info,
     system::get_version,
     system::get_ping,
+    system::create_store_snapshot,
+    system::list_store_snapshot,
     // Namespace
     namespace::list_namespace,
     namespace::inspect_namespace,
     namespace::create_namespace,
     namespace::delete_namespace,
     namespace::count_namespace,
+    namespace::put_namespace,
     // Secret
     secret::list_secret,
     secret::inspect_secret,
@@ -249,10 +277,25 @@ impl Modify for VersionModifier {
     secret::delete_secret,
     secret::patch_secret,
     secret::count_secret,
+    secret::reveal_secret,
+    secret::rotate_secret_key,
+    // Role
+    role::list_role,
+    role::create_role,
+    role::inspect_role,
+    role::delete_role,
+    // Token
+    token::list_token,
+    token::create_token,
+    token::delete_token,
+    // Audit
+    audit::list_audit,
+    audit::count_audit,
     // Job
     job::list_job,
     job::delete_job,
     job::inspect_job,
+    job::list_job_runs,
     job::create_job,
     job::count_job,
     // Cargo
@@ -264,6 +307,8 @@ impl Modify for VersionModifier {
     cargo::patch_cargo,
     cargo::list_cargo_history,
     cargo::revert_cargo,
+    cargo::promote_cargo,
+    cargo::abort_cargo,
     cargo::count_cargo,
     // Exec
     exec::create_exec_command,
@@ -276,6 +321,18 @@ impl Modify for VersionModifier {
     vm_image::resize_vm_image,
     vm_image::clone_vm_image,
     vm_image::snapshot_vm_image,
+    vm_image::export_vm_image,
+    // Volume
+    volume::list_volume,
+    volume::count_volume,
+    volume::create_volume,
+    volume::inspect_volume,
+    volume::delete_volume,
+    volume::snapshot_volume,
+    volume::list_volume_snapshot,
+    volume::restore_volume,
+    volume::export_volume_snapshot,
+    volume::import_volume_snapshot,
     // Vm
     vm::list_vm,
     vm::inspect_vm,
@@ -322,17 +379,32 @@ impl Modify for VersionModifier {
     event::watch_event,
     event::inspect_event,
     event::count_event,
+    event::create_event,
   ),
   components(schemas(
     // Node
     Node,
+    NodeStatus,
     // Secret
     Secret,
     SecretPartial,
     SecretUpdate,
+    SecretRotateKey,
+    SecretRotateKeyResult,
+    // Auth
+    Role,
+    RolePartial,
+    RoleRule,
+    RoleVerb,
+    Token,
+    TokenCreated,
+    TokenPartial,
+    // Audit
+    AuditLog,
     // System
     BinaryInfo,
     HostInfo,
+    StoreSnapshot,
     SystemInfo,
     Commit,
     Runtime,
@@ -368,6 +440,11 @@ impl Modify for VersionModifier {
     NamespacePartial,
     NamespaceInspect,
     NamespaceSummary,
+    NamespaceUpdate,
+    NamespaceQuota,
+    NamespaceLimitRange,
+    NamespaceUsage,
+    ContainerLimits,
     // Process
     Process,
     ProcessKind,
@@ -380,6 +457,14 @@ impl Modify for VersionModifier {
     JobPartial,
     JobInspect,
     JobSummary,
+    JobRun,
+    JobRunStatus,
+    JobConcurrencyPolicy,
+    JobBackoff,
+    JobBackoffPolicy,
+    JobStep,
+    JobStepState,
+    JobStepStatus,
     // Cargo
     Cargo,
     CreateExecOptions,
@@ -391,6 +476,15 @@ impl Modify for VersionModifier {
     CargoSpecPartial,
     CargoSpecUpdate,
     ReplicationStatic,
+    NodeAffinity,
+    CargoUpdateStrategy,
+    CargoRollingUpdate,
+    CargoBlueGreen,
+    CargoCanary,
+    CargoAutoscale,
+    CargoDisruptionBudget,
+    AutoscaleTarget,
+    AutoscaleMetric,
     PidsStats,
     NetworkStats,
     BlkioStats,
@@ -458,6 +552,14 @@ impl Modify for VersionModifier {
     // Vm Image
     VmImage,
     VmImageResizePayload,
+    // Volume
+    Volume,
+    VolumeInspect,
+    VolumeKind,
+    VolumeMount,
+    VolumePartial,
+    VolumeRestore,
+    VolumeSnapshot,
     // Vm
     Vm,
     VmSummary,
@@ -486,8 +588,18 @@ impl Modify for VersionModifier {
     ProxyRule,
     ProxyRuleHttp,
     ProxyHttpLocation,
+    ProxyHttpRewrite,
+    ProxyHttpBasicAuth,
+    ProxyHttpCors,
+    ProxyHttpTimeouts,
+    ProxyHttpErrorPage,
     ProxySsl,
     ProxySslConfig,
+    ProxySslAcme,
+    ProxySslAcmeConfig,
+    ProxyTlsPolicy,
+    ProxyTlsProtocol,
+    ProxyHsts,
     ProxyRuleStream,
     StreamTarget,
     ProxyStreamProtocol,
@@ -495,6 +607,8 @@ impl Modify for VersionModifier {
     HttpTarget,
     UrlRedirect,
     UpstreamTarget,
+    UpstreamLoadBalancing,
+    UpstreamHealthCheck,
     UnixTarget,
     UriTarget,
     LimitReq,
@@ -530,6 +644,7 @@ impl Modify for VersionModifier {
     EventActorKind,
     EventKind,
     EventCondition,
+    EventPartial,
     NativeEventAction,
   )),
   tags(
@@ -542,6 +657,10 @@ impl Modify for VersionModifier {
     (name = "Metrics", description = "Metrics management endpoints."),
     (name = "Processes", description = "Processes management endpoints."),
     (name = "Secrets", description = "Secrets management endpoints."),
+    (name = "Volumes", description = "Volumes management endpoints."),
+    (name = "Roles", description = "Roles management endpoints."),
+    (name = "Tokens", description = "Api tokens management endpoints."),
+    (name = "Audit", description = "Audit log of the mutating calls."),
     (name = "Jobs", description = "Jobs management endpoints."),
     (name = "Events", description = "Events management endpoints."),
   ),
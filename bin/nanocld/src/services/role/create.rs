@@ -0,0 +1,37 @@
+use ntex::web;
+
+use nanocl_error::http::{HttpError, HttpResult};
+use nanocl_stubs::auth::{Role, RolePartial};
+
+use crate::{
+  models::{RoleDb, SystemState},
+  repositories::generic::*,
+  utils,
+};
+
+/// Create a new role
+#[cfg_attr(feature = "dev", utoipa::path(
+  post,
+  request_body = RolePartial,
+  tag = "Roles",
+  path = "/roles",
+  responses(
+    (status = 201, description = "The role created", body = Role),
+    (status = 409, description = "Role already exist", body = ApiError),
+  ),
+))]
+#[web::post("/roles")]
+pub async fn create_role(
+  state: web::types::State<SystemState>,
+  payload: web::types::Json<RolePartial>,
+) -> HttpResult<web::HttpResponse> {
+  utils::key::validate_name(&payload.name)?;
+  if payload.rules.is_empty() {
+    return Err(HttpError::bad_request("a role must have at least one rule"));
+  }
+  let role: Role =
+    RoleDb::create_try_from(&payload.into_inner(), &state.inner.pool)
+      .await?
+      .try_into()?;
+  Ok(web::HttpResponse::Created().json(&role))
+}
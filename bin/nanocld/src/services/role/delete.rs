@@ -0,0 +1,39 @@
+use ntex::web;
+
+use nanocl_error::http::{HttpError, HttpResult};
+
+use crate::{
+  models::{RoleDb, SystemState},
+  repositories::generic::*,
+  utils,
+};
+
+/// Delete a role, tokens having it lose its rules
+#[cfg_attr(feature = "dev", utoipa::path(
+  delete,
+  tag = "Roles",
+  path = "/roles/{name}",
+  params(
+    ("name" = String, Path, description = "Name of the role")
+  ),
+  responses(
+    (status = 202, description = "Role have been deleted"),
+    (status = 400, description = "Built-in role can't be deleted", body = ApiError),
+    (status = 404, description = "Role don't exists", body = ApiError),
+  ),
+))]
+#[web::delete("/roles/{name}")]
+pub async fn delete_role(
+  state: web::types::State<SystemState>,
+  path: web::types::Path<(String, String)>,
+) -> HttpResult<web::HttpResponse> {
+  if path.1 == utils::auth::ADMIN_ROLE {
+    return Err(HttpError::bad_request(format!(
+      "role {} is built-in and can't be deleted",
+      path.1
+    )));
+  }
+  RoleDb::read_by_pk(&path.1, &state.inner.pool).await?;
+  RoleDb::del_by_pk(&path.1, &state.inner.pool).await?;
+  Ok(web::HttpResponse::Accepted().into())
+}
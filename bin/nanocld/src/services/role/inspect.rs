@@ -0,0 +1,30 @@
+use ntex::web;
+
+use nanocl_error::http::HttpResult;
+
+use crate::{
+  models::{RoleDb, SystemState},
+  repositories::generic::*,
+};
+
+/// Get detailed information about a role
+#[cfg_attr(feature = "dev", utoipa::path(
+  get,
+  tag = "Roles",
+  path = "/roles/{name}/inspect",
+  params(
+    ("name" = String, Path, description = "Name of the role")
+  ),
+  responses(
+    (status = 200, description = "Detailed information about a role", body = Role),
+    (status = 404, description = "Role don't exists", body = ApiError),
+  ),
+))]
+#[web::get("/roles/{name}/inspect")]
+pub async fn inspect_role(
+  state: web::types::State<SystemState>,
+  path: web::types::Path<(String, String)>,
+) -> HttpResult<web::HttpResponse> {
+  let role = RoleDb::transform_read_by_pk(&path.1, &state.inner.pool).await?;
+  Ok(web::HttpResponse::Ok().json(&role))
+}
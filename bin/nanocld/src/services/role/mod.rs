@@ -0,0 +1,18 @@
+pub use ntex::web;
+
+pub mod create;
+pub mod delete;
+pub mod inspect;
+pub mod list;
+
+pub use create::*;
+pub use delete::*;
+pub use inspect::*;
+pub use list::*;
+
+pub fn ntex_config(config: &mut web::ServiceConfig) {
+  config.service(list_role);
+  config.service(create_role);
+  config.service(inspect_role);
+  config.service(delete_role);
+}
@@ -36,6 +36,22 @@ pub async fn create_secret(
       serde_json::from_value::<Vec<String>>(payload.data.clone())
         .map_err(|e| HttpError::bad_request(e.to_string()))?;
     }
+    "nanocl.io/htpasswd" => {
+      let users = serde_json::from_value::<Vec<String>>(payload.data.clone())
+        .map_err(|e| HttpError::bad_request(e.to_string()))?;
+      if users.iter().any(|user| {
+        !user
+          .split_once(':')
+          .is_some_and(|(name, hash)| !name.is_empty() && !hash.is_empty())
+      }) {
+        return Err(HttpError::bad_request(
+          "nanocl.io/htpasswd entries must be formatted as user:hash",
+        ));
+      }
+    }
+    utils::container::secret::FILE_KIND => {
+      utils::container::secret::parse_files(&payload.data)?;
+    }
     "nanocl.io/container-registry" => {
       serde_json::from_value::<DockerCredentials>(payload.data.clone())
         .map_err(|e| HttpError::bad_request(e.to_string()))?;
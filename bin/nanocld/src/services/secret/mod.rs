@@ -6,6 +6,8 @@ pub mod delete;
 pub mod inspect;
 pub mod list;
 pub mod patch;
+pub mod reveal;
+pub mod rotate_key;
 
 pub use count::*;
 pub use create::*;
@@ -13,14 +15,18 @@ pub use delete::*;
 pub use inspect::*;
 pub use list::*;
 pub use patch::*;
+pub use reveal::*;
+pub use rotate_key::*;
 
 pub fn ntex_config(config: &mut web::ServiceConfig) {
+  config.service(rotate_secret_key);
   config.service(list_secret);
   config.service(create_secret);
   config.service(inspect_secret);
   config.service(delete_secret);
   config.service(count_secret);
   config.service(patch_secret);
+  config.service(reveal_secret);
 }
 
 #[cfg(test)]
@@ -80,6 +86,21 @@ mod test_secret {
       http::StatusCode::BAD_REQUEST,
       "create secret with no body"
     );
+    let htpasswd = SecretPartial {
+      name: String::from("test-htpasswd"),
+      kind: String::from("nanocl.io/htpasswd"),
+      immutable: false,
+      data: json!(["admin"]),
+      metadata: None,
+    };
+    let res = client
+      .send_post(ENDPOINT, Some(htpasswd), None::<String>)
+      .await;
+    test_status_code!(
+      res.status(),
+      http::StatusCode::BAD_REQUEST,
+      "create htpasswd secret without hash"
+    );
   }
 
   async fn test_inspect_by_id(client: &TestClient) {
@@ -87,6 +108,12 @@ mod test_secret {
       .send_get(&format!("{ENDPOINT}/test-secret/inspect"), None::<String>)
       .await;
     test_status_code!(res.status(), http::StatusCode::OK, "inspect secret");
+    let mut res = client
+      .send_get(&format!("{ENDPOINT}/test-secret/reveal"), None::<String>)
+      .await;
+    test_status_code!(res.status(), http::StatusCode::OK, "reveal secret");
+    let secret = res.json::<Secret>().await.unwrap();
+    assert_eq!(secret.data["Tls"]["cert"], "MY CERT");
   }
 
   async fn test_delete(client: &TestClient) {
@@ -0,0 +1,27 @@
+use ntex::web;
+
+use nanocl_error::http::HttpResult;
+
+use crate::models::{SecretDb, SystemState};
+
+/// Get a secret with its data decrypted
+#[cfg_attr(feature = "dev", utoipa::path(
+  get,
+  tag = "Secrets",
+  path = "/secrets/{key}/reveal",
+  params(
+    ("key" = String, Path, description = "Key of the secret")
+  ),
+  responses(
+    (status = 200, description = "Secret with its data decrypted", body = Secret),
+    (status = 404, description = "Secret does not exist", body = ApiError),
+  ),
+))]
+#[web::get("/secrets/{key}/reveal")]
+pub async fn reveal_secret(
+  state: web::types::State<SystemState>,
+  path: web::types::Path<(String, String)>,
+) -> HttpResult<web::HttpResponse> {
+  let secret = SecretDb::read_decrypted_by_pk(&path.1, &state).await?;
+  Ok(web::HttpResponse::Ok().json(&secret))
+}
@@ -0,0 +1,26 @@
+use ntex::web;
+
+use nanocl_error::http::HttpResult;
+use nanocl_stubs::secret::SecretRotateKey;
+
+use crate::{models::SystemState, utils};
+
+/// Rotate the master key used to encrypt the secrets
+#[cfg_attr(feature = "dev", utoipa::path(
+  post,
+  tag = "Secrets",
+  request_body = SecretRotateKey,
+  path = "/secrets/rotate-key",
+  responses(
+    (status = 200, description = "Secrets encrypted with the new key", body = SecretRotateKeyResult),
+    (status = 400, description = "No master key configured or more than one node", body = ApiError),
+  ),
+))]
+#[web::post("/secrets/rotate-key")]
+pub async fn rotate_secret_key(
+  state: web::types::State<SystemState>,
+  payload: web::types::Json<SecretRotateKey>,
+) -> HttpResult<web::HttpResponse> {
+  let res = utils::secret::rotate_key(payload.key.as_deref(), &state).await?;
+  Ok(web::HttpResponse::Ok().json(&res))
+}
@@ -2,16 +2,20 @@ use ntex::web;
 
 pub mod info;
 pub mod ping;
+pub mod snapshot;
 pub mod version;
 
 pub use info::*;
 pub use ping::*;
+pub use snapshot::*;
 pub use version::*;
 
 pub fn ntex_config(config: &mut web::ServiceConfig) {
   config.service(get_ping);
   config.service(get_version);
   config.service(get_info);
+  config.service(create_store_snapshot);
+  config.service(list_store_snapshot);
 }
 
 #[cfg(test)]
@@ -0,0 +1,42 @@
+use ntex::web;
+
+use nanocl_error::http::HttpResult;
+
+use crate::{models::SystemState, utils};
+
+/// Dump every table of the store into a new snapshot archive
+#[cfg_attr(feature = "dev", utoipa::path(
+  post,
+  tag = "System",
+  path = "/system/snapshot",
+  responses(
+    (status = 201, description = "The snapshot created", body = StoreSnapshot),
+  ),
+))]
+#[web::post("/system/snapshot")]
+pub async fn create_store_snapshot(
+  state: web::types::State<SystemState>,
+) -> HttpResult<web::HttpResponse> {
+  let snapshot =
+    utils::store::snapshot(&state.inner.pool, &state.inner.config.state_dir)
+      .await?;
+  Ok(web::HttpResponse::Created().json(&snapshot))
+}
+
+/// List the snapshots of the store from the most recent
+#[cfg_attr(feature = "dev", utoipa::path(
+  get,
+  tag = "System",
+  path = "/system/snapshot",
+  responses(
+    (status = 200, description = "List of store snapshots", body = [StoreSnapshot]),
+  ),
+))]
+#[web::get("/system/snapshot")]
+pub async fn list_store_snapshot(
+  state: web::types::State<SystemState>,
+) -> HttpResult<web::HttpResponse> {
+  let snapshots =
+    utils::store::list_snapshots(&state.inner.config.state_dir).await?;
+  Ok(web::HttpResponse::Ok().json(&snapshots))
+}
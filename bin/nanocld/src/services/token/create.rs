@@ -0,0 +1,28 @@
+use ntex::web;
+
+use nanocl_error::http::HttpResult;
+use nanocl_stubs::auth::TokenPartial;
+
+use crate::{models::SystemState, utils};
+
+/// Create a new token, its value is only returned in this response
+#[cfg_attr(feature = "dev", utoipa::path(
+  post,
+  request_body = TokenPartial,
+  tag = "Tokens",
+  path = "/tokens",
+  responses(
+    (status = 201, description = "The token created with its value", body = TokenCreated),
+    (status = 404, description = "Role don't exists", body = ApiError),
+    (status = 409, description = "Token already exist", body = ApiError),
+  ),
+))]
+#[web::post("/tokens")]
+pub async fn create_token(
+  state: web::types::State<SystemState>,
+  payload: web::types::Json<TokenPartial>,
+) -> HttpResult<web::HttpResponse> {
+  utils::key::validate_name(&payload.name)?;
+  let token = utils::auth::create_token(&payload, &state).await?;
+  Ok(web::HttpResponse::Created().json(&token))
+}
@@ -0,0 +1,31 @@
+use ntex::web;
+
+use nanocl_error::http::HttpResult;
+
+use crate::{
+  models::{SystemState, TokenDb},
+  repositories::generic::*,
+};
+
+/// Delete a token, requests using it are rejected right away
+#[cfg_attr(feature = "dev", utoipa::path(
+  delete,
+  tag = "Tokens",
+  path = "/tokens/{name}",
+  params(
+    ("name" = String, Path, description = "Name of the token")
+  ),
+  responses(
+    (status = 202, description = "Token have been deleted"),
+    (status = 404, description = "Token don't exists", body = ApiError),
+  ),
+))]
+#[web::delete("/tokens/{name}")]
+pub async fn delete_token(
+  state: web::types::State<SystemState>,
+  path: web::types::Path<(String, String)>,
+) -> HttpResult<web::HttpResponse> {
+  TokenDb::read_by_pk(&path.1, &state.inner.pool).await?;
+  TokenDb::del_by_pk(&path.1, &state.inner.pool).await?;
+  Ok(web::HttpResponse::Accepted().into())
+}
@@ -0,0 +1,32 @@
+use ntex::web;
+
+use nanocl_error::http::HttpResult;
+use nanocl_stubs::generic::GenericListQuery;
+
+use crate::{
+  models::{SystemState, TokenDb},
+  repositories::generic::*,
+  utils,
+};
+
+/// List tokens with optional filter, their values are never returned
+#[cfg_attr(feature = "dev", utoipa::path(
+  get,
+  tag = "Tokens",
+  path = "/tokens",
+  params(
+    ("filter" = Option<String>, Query, description = "Generic filter", example = "{ \"filter\": { \"where\": { \"key\": { \"eq\": \"ci\" } } } }"),
+  ),
+  responses(
+    (status = 200, description = "List of token", body = [Token]),
+  ),
+))]
+#[web::get("/tokens")]
+pub async fn list_token(
+  state: web::types::State<SystemState>,
+  qs: web::types::Query<GenericListQuery>,
+) -> HttpResult<web::HttpResponse> {
+  let filter = utils::query_string::parse_qs_filter(&qs)?;
+  let items = TokenDb::transform_read_by(&filter, &state.inner.pool).await?;
+  Ok(web::HttpResponse::Ok().json(&items))
+}
@@ -0,0 +1,15 @@
+pub use ntex::web;
+
+pub mod create;
+pub mod delete;
+pub mod list;
+
+pub use create::*;
+pub use delete::*;
+pub use list::*;
+
+pub fn ntex_config(config: &mut web::ServiceConfig) {
+  config.service(list_token);
+  config.service(create_token);
+  config.service(delete_token);
+}
@@ -0,0 +1,42 @@
+use ntex::web;
+
+use nanocl_error::http::{HttpError, HttpResult};
+
+use crate::{
+  models::{SystemState, VmImageDb},
+  repositories::generic::*,
+  utils,
+};
+
+/// Download the file of a base virtual machine image
+#[cfg_attr(feature = "dev", utoipa::path(
+  get,
+  tag = "VmImages",
+  path = "/vms/images/{name}/export",
+  params(
+    ("name" = String, Path, description = "The name of the vm image"),
+  ),
+  responses(
+    (status = 200, description = "Content of the vm image", content_type = "application/octet-stream"),
+    (status = 400, description = "The vm image is a snapshot", body = ApiError),
+  ),
+))]
+#[web::get("/vms/images/{name}/export")]
+pub async fn export_vm_image(
+  state: web::types::State<SystemState>,
+  path: web::types::Path<(String, String)>,
+) -> HttpResult<web::HttpResponse> {
+  let name = path.1.to_owned();
+  let item = VmImageDb::read_by_pk(&name, &state.inner.pool).await?;
+  if item.kind != "Base" {
+    return Err(HttpError::bad_request(format!(
+      "Vm image {name} is a snapshot only base images can be exported"
+    )));
+  }
+  let stream = utils::stream::file_stream(&item.path).await?;
+  Ok(
+    web::HttpResponse::Ok()
+      .content_type("application/octet-stream")
+      .streaming(stream),
+  )
+}
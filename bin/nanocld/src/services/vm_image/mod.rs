@@ -4,6 +4,7 @@ pub mod clone;
 pub mod count;
 pub mod create_snapshot;
 pub mod delete;
+pub mod export;
 pub mod import;
 pub mod inspect;
 pub mod list;
@@ -13,6 +14,7 @@ pub use clone::*;
 pub use count::*;
 pub use create_snapshot::*;
 pub use delete::*;
+pub use export::*;
 pub use import::*;
 pub use inspect::*;
 pub use list::*;
@@ -27,6 +29,7 @@ pub fn ntex_config(config: &mut web::ServiceConfig) {
   config.service(count_vm_image);
   config.service(resize_vm_image);
   config.service(inspect_vm_image);
+  config.service(export_vm_image);
 }
 
 #[cfg(test)]
@@ -0,0 +1,38 @@
+use ntex::web;
+
+use nanocl_error::http::HttpResult;
+use nanocl_stubs::generic::{GenericClause, GenericCount, GenericListQueryNsp};
+
+use crate::{
+  models::{SystemState, VolumeDb},
+  repositories::generic::*,
+  utils,
+};
+
+/// Count volumes of a namespace with optional filter
+#[cfg_attr(feature = "dev", utoipa::path(
+  get,
+  tag = "Volumes",
+  path = "/volumes/count",
+  params(
+    ("filter" = Option<String>, Query, description = "Generic filter", example = "{ \"filter\": { \"where\": { \"kind\": { \"eq\": \"Host\" } } } }"),
+    ("namespace" = Option<String>, Query, description = "Namespace where the volumes are"),
+  ),
+  responses(
+    (status = 200, description = "Count result", body = GenericCount),
+  ),
+))]
+#[web::get("/volumes/count")]
+pub async fn count_volume(
+  state: web::types::State<SystemState>,
+  qs: web::types::Query<GenericListQueryNsp>,
+) -> HttpResult<web::HttpResponse> {
+  let query = utils::query_string::parse_qs_nsp_filter(&qs)?;
+  let namespace = utils::key::resolve_nsp(&query.namespace);
+  let filter = query
+    .filter
+    .unwrap_or_default()
+    .r#where("namespace_name", GenericClause::Eq(namespace));
+  let count = VolumeDb::count_by(&filter, &state.inner.pool).await?;
+  Ok(web::HttpResponse::Ok().json(&GenericCount { count }))
+}
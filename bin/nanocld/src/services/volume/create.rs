@@ -0,0 +1,38 @@
+use ntex::web;
+
+use nanocl_error::http::HttpResult;
+use nanocl_stubs::{generic::GenericNspQuery, volume::VolumePartial};
+
+use crate::{
+  models::{SystemState, VolumeDb, VolumeObjCreateIn},
+  objects::generic::*,
+  utils,
+};
+
+/// Create a new volume in a namespace
+#[cfg_attr(feature = "dev", utoipa::path(
+  post,
+  tag = "Volumes",
+  path = "/volumes",
+  request_body = VolumePartial,
+  params(
+    ("namespace" = Option<String>, Query, description = "Namespace where the volume belongs"),
+  ),
+  responses(
+    (status = 201, description = "Volume created", body = Volume),
+    (status = 409, description = "Volume already exist", body = ApiError),
+  ),
+))]
+#[web::post("/volumes")]
+pub async fn create_volume(
+  state: web::types::State<SystemState>,
+  payload: web::types::Json<VolumePartial>,
+  qs: web::types::Query<GenericNspQuery>,
+) -> HttpResult<web::HttpResponse> {
+  let obj = VolumeObjCreateIn {
+    namespace: utils::key::resolve_nsp(&qs.namespace),
+    spec: payload.into_inner(),
+  };
+  let volume = VolumeDb::create_obj(&obj, &state).await?;
+  Ok(web::HttpResponse::Created().json(&volume))
+}
@@ -0,0 +1,37 @@
+use ntex::web;
+
+use nanocl_error::http::HttpResult;
+use nanocl_stubs::generic::GenericNspQuery;
+
+use crate::{
+  models::{SystemState, VolumeDb},
+  objects::generic::*,
+  utils,
+};
+
+/// Delete a volume and its data, a volume used by a cargo can't be deleted
+#[cfg_attr(feature = "dev", utoipa::path(
+  delete,
+  tag = "Volumes",
+  path = "/volumes/{name}",
+  params(
+    ("name" = String, Path, description = "Name of the volume"),
+    ("namespace" = Option<String>, Query, description = "Namespace where the volume belongs"),
+  ),
+  responses(
+    (status = 202, description = "Volume have been deleted"),
+    (status = 404, description = "Volume doesn't exist", body = ApiError),
+    (status = 409, description = "Volume is used by a cargo", body = ApiError),
+  ),
+))]
+#[web::delete("/volumes/{name}")]
+pub async fn delete_volume(
+  state: web::types::State<SystemState>,
+  path: web::types::Path<(String, String)>,
+  qs: web::types::Query<GenericNspQuery>,
+) -> HttpResult<web::HttpResponse> {
+  let namespace = utils::key::resolve_nsp(&qs.namespace);
+  let key = utils::key::gen_key(&namespace, &path.1);
+  VolumeDb::del_obj_by_pk(&key, &(), &state).await?;
+  Ok(web::HttpResponse::Accepted().into())
+}
@@ -0,0 +1,43 @@
+use ntex::web;
+
+use nanocl_error::http::HttpResult;
+use nanocl_stubs::generic::GenericNspQuery;
+
+use crate::{
+  models::{SystemState, VolumeDb},
+  repositories::generic::*,
+  utils,
+};
+
+/// Download the tarball of a snapshot of a volume
+#[cfg_attr(feature = "dev", utoipa::path(
+  get,
+  tag = "Volumes",
+  path = "/volumes/{name}/snapshots/{snapshot}/export",
+  params(
+    ("name" = String, Path, description = "Name of the volume"),
+    ("snapshot" = String, Path, description = "Name of the snapshot"),
+    ("namespace" = Option<String>, Query, description = "Namespace where the volume belongs"),
+  ),
+  responses(
+    (status = 200, description = "Tarball of the snapshot", content_type = "application/x-tar"),
+    (status = 404, description = "Volume or snapshot doesn't exist", body = ApiError),
+  ),
+))]
+#[web::get("/volumes/{name}/snapshots/{snapshot}/export")]
+pub async fn export_volume_snapshot(
+  state: web::types::State<SystemState>,
+  path: web::types::Path<(String, String, String)>,
+  qs: web::types::Query<GenericNspQuery>,
+) -> HttpResult<web::HttpResponse> {
+  let namespace = utils::key::resolve_nsp(&qs.namespace);
+  let key = utils::key::gen_key(&namespace, &path.1);
+  let volume = VolumeDb::transform_read_by_pk(&key, &state.inner.pool).await?;
+  let file = utils::volume::get_snapshot_path(&volume, &path.2, &state)?;
+  let stream = utils::stream::file_stream(&file).await?;
+  Ok(
+    web::HttpResponse::Ok()
+      .content_type("application/x-tar")
+      .streaming(stream),
+  )
+}
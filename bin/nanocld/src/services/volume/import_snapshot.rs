@@ -0,0 +1,40 @@
+use ntex::web;
+
+use nanocl_error::http::HttpResult;
+use nanocl_stubs::generic::GenericNspQuery;
+
+use crate::{
+  models::{SystemState, VolumeDb},
+  repositories::generic::*,
+  utils,
+};
+
+/// Upload a tarball as a new snapshot of a volume
+#[cfg_attr(feature = "dev", utoipa::path(
+  post,
+  tag = "Volumes",
+  request_body = String,
+  path = "/volumes/{name}/snapshots/import",
+  params(
+    ("name" = String, Path, description = "Name of the volume"),
+    ("namespace" = Option<String>, Query, description = "Namespace where the volume belongs"),
+  ),
+  responses(
+    (status = 201, description = "Snapshot imported", body = VolumeSnapshot),
+    (status = 404, description = "Volume doesn't exist", body = ApiError),
+  ),
+))]
+#[web::post("/volumes/{name}/snapshots/import")]
+pub async fn import_volume_snapshot(
+  state: web::types::State<SystemState>,
+  path: web::types::Path<(String, String)>,
+  qs: web::types::Query<GenericNspQuery>,
+  payload: web::types::Payload,
+) -> HttpResult<web::HttpResponse> {
+  let namespace = utils::key::resolve_nsp(&qs.namespace);
+  let key = utils::key::gen_key(&namespace, &path.1);
+  let volume = VolumeDb::transform_read_by_pk(&key, &state.inner.pool).await?;
+  let snapshot =
+    utils::volume::import_snapshot(&volume, payload, &state).await?;
+  Ok(web::HttpResponse::Created().json(&snapshot))
+}
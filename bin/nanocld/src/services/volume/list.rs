@@ -0,0 +1,39 @@
+use ntex::web;
+
+use nanocl_error::http::HttpResult;
+use nanocl_stubs::generic::{GenericClause, GenericListQueryNsp};
+
+use crate::{
+  models::{NamespaceDb, SystemState, VolumeDb},
+  repositories::generic::*,
+  utils,
+};
+
+/// List volumes of a namespace with optional filter
+#[cfg_attr(feature = "dev", utoipa::path(
+  get,
+  tag = "Volumes",
+  path = "/volumes",
+  params(
+    ("filter" = Option<String>, Query, description = "Generic filter", example = "{ \"filter\": { \"where\": { \"kind\": { \"eq\": \"Host\" } } } }"),
+    ("namespace" = Option<String>, Query, description = "Namespace where the volumes are default to global namespace"),
+  ),
+  responses(
+    (status = 200, description = "List of volumes", body = [Volume]),
+  ),
+))]
+#[web::get("/volumes")]
+pub async fn list_volume(
+  state: web::types::State<SystemState>,
+  qs: web::types::Query<GenericListQueryNsp>,
+) -> HttpResult<web::HttpResponse> {
+  let query = utils::query_string::parse_qs_nsp_filter(&qs)?;
+  let namespace = utils::key::resolve_nsp(&query.namespace);
+  NamespaceDb::read_by_pk(&namespace, &state.inner.pool).await?;
+  let filter = query
+    .filter
+    .unwrap_or_default()
+    .r#where("namespace_name", GenericClause::Eq(namespace));
+  let volumes = VolumeDb::transform_read_by(&filter, &state.inner.pool).await?;
+  Ok(web::HttpResponse::Ok().json(&volumes))
+}
@@ -0,0 +1,37 @@
+use ntex::web;
+
+use nanocl_error::http::HttpResult;
+use nanocl_stubs::generic::GenericNspQuery;
+
+use crate::{
+  models::{SystemState, VolumeDb},
+  repositories::generic::*,
+  utils,
+};
+
+/// List the snapshots of a volume from the most recent
+#[cfg_attr(feature = "dev", utoipa::path(
+  get,
+  tag = "Volumes",
+  path = "/volumes/{name}/snapshots",
+  params(
+    ("name" = String, Path, description = "Name of the volume"),
+    ("namespace" = Option<String>, Query, description = "Namespace where the volume belongs"),
+  ),
+  responses(
+    (status = 200, description = "List of snapshots", body = [VolumeSnapshot]),
+    (status = 404, description = "Volume doesn't exist", body = ApiError),
+  ),
+))]
+#[web::get("/volumes/{name}/snapshots")]
+pub async fn list_volume_snapshot(
+  state: web::types::State<SystemState>,
+  path: web::types::Path<(String, String)>,
+  qs: web::types::Query<GenericNspQuery>,
+) -> HttpResult<web::HttpResponse> {
+  let namespace = utils::key::resolve_nsp(&qs.namespace);
+  let key = utils::key::gen_key(&namespace, &path.1);
+  let volume = VolumeDb::transform_read_by_pk(&key, &state.inner.pool).await?;
+  let snapshots = utils::volume::list_snapshots(&volume, &state).await?;
+  Ok(web::HttpResponse::Ok().json(&snapshots))
+}
@@ -0,0 +1,92 @@
+pub use ntex::web;
+
+pub mod count;
+pub mod create;
+pub mod delete;
+pub mod export_snapshot;
+pub mod import_snapshot;
+pub mod inspect;
+pub mod list;
+pub mod list_snapshot;
+pub mod restore;
+pub mod snapshot;
+
+pub use count::*;
+pub use create::*;
+pub use delete::*;
+pub use export_snapshot::*;
+pub use import_snapshot::*;
+pub use inspect::*;
+pub use list::*;
+pub use list_snapshot::*;
+pub use restore::*;
+pub use snapshot::*;
+
+pub fn ntex_config(config: &mut web::ServiceConfig) {
+  config.service(count_volume);
+  config.service(list_volume);
+  config.service(create_volume);
+  config.service(inspect_volume);
+  config.service(delete_volume);
+  config.service(snapshot_volume);
+  config.service(list_volume_snapshot);
+  config.service(restore_volume);
+  config.service(export_volume_snapshot);
+  config.service(import_volume_snapshot);
+}
+
+#[cfg(test)]
+mod test_volume {
+  use ntex::http;
+
+  use nanocl_stubs::volume::{
+    Volume, VolumeInspect, VolumeKind, VolumePartial,
+  };
+
+  use crate::utils::tests::*;
+
+  const ENDPOINT: &str = "/volumes";
+
+  #[ntex::test]
+  async fn basic() {
+    let system = gen_default_test_system().await;
+    let client = system.client;
+    let new_volume = VolumePartial {
+      name: "test-volume".to_owned(),
+      kind: VolumeKind::Host,
+      metadata: None,
+    };
+    let mut res = client
+      .send_post(ENDPOINT, Some(&new_volume), None::<String>)
+      .await;
+    test_status_code!(res.status(), http::StatusCode::CREATED, "create volume");
+    let volume = res.json::<Volume>().await.unwrap();
+    assert_eq!(volume.key, "test-volume.global");
+    let res = client
+      .send_post(ENDPOINT, Some(&new_volume), None::<String>)
+      .await;
+    test_status_code!(
+      res.status(),
+      http::StatusCode::CONFLICT,
+      "create existing volume"
+    );
+    let mut res = client
+      .send_get(&format!("{ENDPOINT}/test-volume/inspect"), None::<String>)
+      .await;
+    test_status_code!(res.status(), http::StatusCode::OK, "inspect volume");
+    let inspect = res.json::<VolumeInspect>().await.unwrap();
+    assert!(inspect.used_by.is_empty());
+    let mut res = client.send_get(ENDPOINT, None::<String>).await;
+    test_status_code!(res.status(), http::StatusCode::OK, "list volumes");
+    let volumes = res.json::<Vec<Volume>>().await.unwrap();
+    assert!(volumes.iter().any(|v| v.key == volume.key));
+    let res = client
+      .send_delete(&format!("{ENDPOINT}/test-volume"), None::<String>)
+      .await;
+    test_status_code!(
+      res.status(),
+      http::StatusCode::ACCEPTED,
+      "delete volume"
+    );
+  }
+}
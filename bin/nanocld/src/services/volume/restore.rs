@@ -0,0 +1,39 @@
+use ntex::web;
+
+use nanocl_error::http::HttpResult;
+use nanocl_stubs::{generic::GenericNspQuery, volume::VolumeRestore};
+
+use crate::{
+  models::{SystemState, VolumeDb},
+  repositories::generic::*,
+  utils,
+};
+
+/// Replace the data of a volume with the content of one of its snapshots
+#[cfg_attr(feature = "dev", utoipa::path(
+  post,
+  tag = "Volumes",
+  path = "/volumes/{name}/restore",
+  request_body = VolumeRestore,
+  params(
+    ("name" = String, Path, description = "Name of the volume"),
+    ("namespace" = Option<String>, Query, description = "Namespace where the volume belongs"),
+  ),
+  responses(
+    (status = 202, description = "Volume restored"),
+    (status = 404, description = "Volume or snapshot doesn't exist", body = ApiError),
+  ),
+))]
+#[web::post("/volumes/{name}/restore")]
+pub async fn restore_volume(
+  state: web::types::State<SystemState>,
+  path: web::types::Path<(String, String)>,
+  payload: web::types::Json<VolumeRestore>,
+  qs: web::types::Query<GenericNspQuery>,
+) -> HttpResult<web::HttpResponse> {
+  let namespace = utils::key::resolve_nsp(&qs.namespace);
+  let key = utils::key::gen_key(&namespace, &path.1);
+  let volume = VolumeDb::transform_read_by_pk(&key, &state.inner.pool).await?;
+  utils::volume::restore(&volume, &payload.snapshot, &state).await?;
+  Ok(web::HttpResponse::Accepted().into())
+}
@@ -0,0 +1,27 @@
+use std::time::Duration;
+
+use ntex::{rt, time::interval};
+
+use crate::{models::SystemState, utils};
+
+/// Interval between two purges of the audit log
+const TICK: Duration = Duration::from_secs(60 * 60);
+
+/// Spawn a background loop removing the audit log entries
+/// older than the configured retention
+pub fn spawn(state: &SystemState) {
+  let retention = state.inner.config.audit_retention;
+  if retention == 0 {
+    return;
+  }
+  let state = state.clone();
+  rt::spawn(async move {
+    let ticker = interval(TICK);
+    loop {
+      ticker.tick().await;
+      if let Err(err) = utils::audit::purge(retention, &state).await {
+        log::warn!("audit::spawn: {err}");
+      }
+    }
+  });
+}
@@ -0,0 +1,477 @@
+use std::{
+  collections::HashMap,
+  time::{Duration, Instant},
+};
+
+use futures::StreamExt;
+use ntex::{rt, time::interval};
+
+use bollard_next::container::{Stats, StatsOptions};
+use nanocl_error::io::{IoError, IoResult};
+use nanocl_stubs::{
+  cargo::Cargo,
+  cargo_spec::{AutoscaleMetric, CargoAutoscale},
+  generic::{GenericClause, GenericFilter},
+  process::Process,
+  system::{EventKind, EventPartial, NativeEventAction, ObjPsStatusKind},
+};
+
+use crate::{
+  models::{CargoDb, CargoUpdateDb, MetricDb, ProcessDb, SystemState},
+  repositories::generic::*,
+  utils, vars,
+};
+
+/// Interval between two evaluations of the autoscaled cargoes
+const TICK: Duration = Duration::from_secs(15);
+/// Relative difference between the observed and the target value
+/// under which the number of replicas is kept
+const TOLERANCE: f64 = 0.1;
+/// Time without renewal after which another node evaluates a cargo
+const LEASE: Duration = Duration::from_secs(45);
+/// Kind of the metrics holding the cpu usage of the instances
+/// of a cargo on a node
+const CPU_METRIC: &str = "nanocl.io/cargo-cpu";
+
+/// State of an autoscaled cargo kept between two ticks
+#[derive(Debug)]
+struct CargoScaling {
+  /// Last time the cargo was scaled or first evaluated by the current node
+  last_scale: Instant,
+  /// Number of replicas the local instances were scaled to
+  applied: Option<Option<usize>>,
+}
+
+impl Default for CargoScaling {
+  fn default() -> Self {
+    Self {
+      last_scale: Instant::now(),
+      applied: None,
+    }
+  }
+}
+
+/// Average cpu usage of the instances of a cargo over the cluster
+/// from the samples recorded by every node,
+/// each sample weighted by its number of instances
+fn cpu_average(samples: &[MetricDb]) -> Option<f64> {
+  let (sum, instances) = samples
+    .iter()
+    .filter_map(|sample| {
+      let cpu = sample.data["Cpu"].as_f64()?;
+      let instances = sample.data["Instances"].as_f64()?;
+      Some((cpu * instances, instances))
+    })
+    .fold((0.0, 0.0), |acc, (cpu, instances)| {
+      (acc.0 + cpu, acc.1 + instances)
+    });
+  if instances <= 0.0 {
+    return None;
+  }
+  Some(sum / instances)
+}
+
+/// Instances of a cargo running their main container
+fn get_running(processes: Vec<Process>) -> Vec<Process> {
+  processes
+    .into_iter()
+    .filter(|process| {
+      !process.name.starts_with("init-")
+        && !process.name.starts_with("tmp-")
+        && process
+          .data
+          .state
+          .clone()
+          .unwrap_or_default()
+          .running
+          .unwrap_or_default()
+    })
+    .collect()
+}
+
+/// Compute the number of replicas wanted to reach the targets
+/// knowing the observed average value of each metric per instance.
+/// The highest number wanted by a target is used within the bounds.
+fn desired_replicas(
+  current: usize,
+  autoscale: &CargoAutoscale,
+  observed: &HashMap<AutoscaleMetric, f64>,
+) -> usize {
+  let desired = autoscale
+    .targets
+    .iter()
+    .filter(|target| target.average > 0)
+    .filter_map(|target| {
+      let value = observed.get(&target.metric)?;
+      let ratio = value / target.average as f64;
+      if (ratio - 1.0).abs() <= TOLERANCE {
+        return Some(current);
+      }
+      Some((current.max(1) as f64 * ratio).ceil() as usize)
+    })
+    .max()
+    .unwrap_or(current);
+  let max = autoscale.max_replicas.max(autoscale.min_replicas);
+  desired.clamp(autoscale.min_replicas, max)
+}
+
+/// Cpu usage in percent of a container from his stats
+fn cpu_usage(stats: &Stats) -> f64 {
+  let cpu_delta = stats.cpu_stats.cpu_usage.total_usage as f64
+    - stats.precpu_stats.cpu_usage.total_usage as f64;
+  let system_delta = stats.cpu_stats.system_cpu_usage.unwrap_or_default()
+    as f64
+    - stats.precpu_stats.system_cpu_usage.unwrap_or_default() as f64;
+  let cpus = stats.cpu_stats.online_cpus.unwrap_or(1) as f64;
+  if system_delta <= 0.0 || cpu_delta < 0.0 {
+    return 0.0;
+  }
+  cpu_delta / system_delta * cpus * 100.0
+}
+
+/// Average cpu usage in percent of the given instances
+async fn read_cpu_usage(
+  processes: &[Process],
+  state: &SystemState,
+) -> Option<f64> {
+  let mut usages = Vec::new();
+  for process in processes {
+    let opts = StatsOptions {
+      stream: false,
+      one_shot: false,
+    };
+    let stats = state
+      .inner
+      .docker_api
+      .stats(&process.key, Some(opts))
+      .next()
+      .await;
+    match stats {
+      Some(Ok(stats)) => usages.push(cpu_usage(&stats)),
+      Some(Err(err)) => {
+        log::warn!("autoscaler::read_cpu_usage: {}: {err}", process.name)
+      }
+      None => {}
+    }
+  }
+  if usages.is_empty() {
+    return None;
+  }
+  Some(usages.iter().sum::<f64>() / usages.len() as f64)
+}
+
+/// Average http requests per second received by each instance over the window
+async fn read_requests_per_second(
+  processes: &[Process],
+  window: Duration,
+  state: &SystemState,
+) -> IoResult<Option<f64>> {
+  let addresses = processes
+    .iter()
+    .filter_map(utils::network::get_process_address)
+    .collect::<Vec<_>>();
+  if addresses.is_empty() {
+    return Ok(None);
+  }
+  let since = chrono::Utc::now().naive_utc()
+    - chrono::Duration::seconds(window.as_secs() as i64);
+  let count =
+    MetricDb::count_http_requests(&addresses, since, &state.inner.pool).await?;
+  let rps = count as f64 / window.as_secs().max(1) as f64;
+  Ok(Some(rps / addresses.len() as f64))
+}
+
+/// Record the cpu usage of the local instances of a cargo
+/// for the node evaluating its autoscaler
+async fn record_cpu(
+  cargo: &Cargo,
+  window: Duration,
+  state: &SystemState,
+) -> IoResult<()> {
+  let key = &cargo.spec.cargo_key;
+  let processes = get_running(
+    utils::container::cargo::read_local_processes(key, state).await?,
+  );
+  let Some(cpu) = read_cpu_usage(&processes, state).await else {
+    return Ok(());
+  };
+  let now = chrono::Utc::now().naive_utc();
+  let metric = MetricDb {
+    key: uuid::Uuid::new_v4(),
+    created_at: now,
+    expires_at: now + chrono::Duration::from_std(window).unwrap_or_default(),
+    node_name: state.inner.config.hostname.clone(),
+    kind: CPU_METRIC.to_owned(),
+    data: serde_json::json!({
+      "CargoKey": key,
+      "Cpu": cpu,
+      "Instances": processes.len(),
+    }),
+    note: None,
+  };
+  MetricDb::create_from(metric, &state.inner.pool).await?;
+  Ok(())
+}
+
+/// Record the scale decision of the autoscaler as an event
+async fn emit_scale(
+  cargo: &Cargo,
+  current: usize,
+  desired: usize,
+  observed: &HashMap<AutoscaleMetric, f64>,
+  state: &SystemState,
+) {
+  let reason = if desired > current {
+    "scale_up"
+  } else {
+    "scale_down"
+  };
+  let metrics = observed
+    .iter()
+    .map(|(metric, value)| (format!("{metric:?}"), *value))
+    .collect::<HashMap<_, _>>();
+  let event = EventPartial {
+    reporting_controller: vars::AUTOSCALER_CONTROLLER_NAME.to_owned(),
+    reporting_node: state.inner.config.hostname.clone(),
+    kind: EventKind::Normal,
+    action: NativeEventAction::Update.to_string(),
+    related: None,
+    reason: reason.to_owned(),
+    note: Some(format!(
+      "Scaled {} from {current} to {desired} replicas",
+      cargo.spec.cargo_key
+    )),
+    metadata: Some(serde_json::json!({
+      "PreviousReplicas": current,
+      "Replicas": desired,
+      "Metrics": metrics,
+    })),
+    actor: Some(cargo.clone().into()),
+  };
+  if let Err(err) = state.emit_event(event).await {
+    log::warn!("autoscaler::emit_scale: {err}");
+  }
+}
+
+/// Evaluate an autoscaled cargo from the metrics of the whole cluster
+/// and store the number of replicas wanted when it changes
+async fn evaluate(
+  cargo: &Cargo,
+  autoscale: &CargoAutoscale,
+  scaling: &mut CargoScaling,
+  state: &SystemState,
+) -> IoResult<Option<usize>> {
+  let key = &cargo.spec.cargo_key;
+  let Some(spec_replicas) = utils::container::replication::get_replicas(
+    cargo.spec.replication.as_ref(),
+  ) else {
+    log::warn!("autoscaler::evaluate: {key} replication mode can't be scaled");
+    return Ok(None);
+  };
+  let current = cargo.replicas.unwrap_or(spec_replicas);
+  let window = Duration::from_secs(autoscale.window.unwrap_or(60));
+  let since = chrono::Utc::now().naive_utc()
+    - chrono::Duration::from_std(window).unwrap_or_default();
+  let mut observed = HashMap::new();
+  let metrics = autoscale
+    .targets
+    .iter()
+    .map(|target| target.metric.clone())
+    .collect::<Vec<_>>();
+  if metrics.contains(&AutoscaleMetric::Cpu) {
+    let samples = MetricDb::read_recent(CPU_METRIC, since, &state.inner.pool)
+      .await?
+      .into_iter()
+      .filter(|sample| sample.data["CargoKey"].as_str() == Some(key))
+      .collect::<Vec<_>>();
+    if let Some(cpu) = cpu_average(&samples) {
+      observed.insert(AutoscaleMetric::Cpu, cpu);
+    }
+  }
+  if metrics.contains(&AutoscaleMetric::RequestsPerSecond) {
+    let processes = get_running(
+      ProcessDb::read_by_kind_key(key, None, &state.inner.pool).await?,
+    );
+    if let Some(rps) =
+      read_requests_per_second(&processes, window, state).await?
+    {
+      observed.insert(AutoscaleMetric::RequestsPerSecond, rps);
+    }
+  }
+  let desired = desired_replicas(current, autoscale, &observed);
+  // Scaling up is limited by the quota of the namespace
+  let desired =
+    utils::quota::clamp_cargo_replicas(cargo, current, desired, state)
+      .await
+      .map_err(|err| IoError::interrupted("Quota", err.msg.as_str()))?;
+  if desired == current {
+    return Ok(None);
+  }
+  let cooldown = if desired > current {
+    autoscale.scale_up_cooldown.unwrap_or(60)
+  } else {
+    autoscale.scale_down_cooldown.unwrap_or(300)
+  };
+  // Replicas out of the bounds are fixed without waiting
+  let in_bounds =
+    current >= autoscale.min_replicas && current <= autoscale.max_replicas;
+  if in_bounds && scaling.last_scale.elapsed() < Duration::from_secs(cooldown) {
+    log::debug!("autoscaler::evaluate: {key} in cooldown");
+    return Ok(None);
+  }
+  log::info!("autoscaler::evaluate: scaling {key} from {current} to {desired}");
+  // The replicas are stored for every node to place the same instances
+  let new_item = CargoUpdateDb {
+    replicas: Some(Some(desired as i32)),
+    ..Default::default()
+  };
+  CargoDb::update_pk(key, new_item, &state.inner.pool).await?;
+  scaling.last_scale = Instant::now();
+  emit_scale(cargo, current, desired, &observed, state).await;
+  Ok(Some(desired))
+}
+
+/// Sample, evaluate and scale an autoscaled cargo.
+/// Every node records the cpu usage of its instances
+/// and scales them when the number of replicas changed,
+/// only the node holding the lease of the cargo evaluates it
+async fn tick(
+  cargo: &Cargo,
+  autoscale: &CargoAutoscale,
+  scaling: &mut CargoScaling,
+  state: &SystemState,
+) -> IoResult<()> {
+  let key = &cargo.spec.cargo_key;
+  let hostname = &state.inner.config.hostname;
+  let window = Duration::from_secs(autoscale.window.unwrap_or(60));
+  if autoscale
+    .targets
+    .iter()
+    .any(|target| target.metric == AutoscaleMetric::Cpu)
+  {
+    record_cpu(cargo, window, state).await?;
+  }
+  let mut cargo = cargo.clone();
+  if CargoDb::claim_autoscaler(key, hostname, LEASE, &state.inner.pool).await? {
+    if let Some(desired) = evaluate(&cargo, autoscale, scaling, state).await? {
+      cargo.replicas = Some(desired);
+    }
+  }
+  // The instances are scaled once the cargo is started or updated
+  if scaling.applied == Some(cargo.replicas)
+    || cargo.status.actual != ObjPsStatusKind::Start
+  {
+    return Ok(());
+  }
+  utils::container::cargo::scale(&cargo, state).await?;
+  scaling.applied = Some(cargo.replicas);
+  Ok(())
+}
+
+/// Evaluate every autoscaled cargoes
+async fn run(
+  scalings: &mut HashMap<String, CargoScaling>,
+  state: &SystemState,
+) -> IoResult<()> {
+  let filter = GenericFilter::new()
+    .r#where("data", GenericClause::HasKey("Autoscale".to_owned()));
+  let cargoes = CargoDb::transform_read_by(&filter, &state.inner.pool)
+    .await?
+    .into_iter()
+    .filter(|cargo| cargo.status.wanted == ObjPsStatusKind::Start)
+    .collect::<Vec<_>>();
+  let keys = cargoes
+    .iter()
+    .map(|cargo| cargo.spec.cargo_key.clone())
+    .collect::<Vec<_>>();
+  // Forget the cargoes not autoscaled anymore
+  scalings.retain(|key, _| keys.contains(key));
+  for cargo in &cargoes {
+    let Some(autoscale) = &cargo.spec.autoscale else {
+      continue;
+    };
+    let scaling = scalings.entry(cargo.spec.cargo_key.clone()).or_default();
+    if let Err(err) = tick(cargo, autoscale, scaling, state).await {
+      log::warn!("autoscaler::run: {}: {err}", cargo.spec.cargo_key);
+    }
+  }
+  Ok(())
+}
+
+/// Spawn a background thread that will periodically scale the cargoes
+/// with an autoscale spec based on their cpu usage and http requests
+/// averaged over a sliding window.
+pub fn spawn(state: &SystemState) {
+  let state = state.clone();
+  rt::Arbiter::new().exec_fn(move || {
+    rt::spawn(async move {
+      let mut scalings = HashMap::new();
+      let ticker = interval(TICK);
+      loop {
+        ticker.tick().await;
+        if let Err(err) = run(&mut scalings, &state).await {
+          log::warn!("autoscaler::spawn: {err}");
+        }
+      }
+    });
+  });
+}
+
+#[cfg(test)]
+mod tests {
+  use nanocl_stubs::cargo_spec::AutoscaleTarget;
+
+  use super::*;
+
+  fn autoscale(metric: AutoscaleMetric, average: u32) -> CargoAutoscale {
+    CargoAutoscale {
+      min_replicas: 1,
+      max_replicas: 5,
+      targets: vec![AutoscaleTarget { metric, average }],
+      ..Default::default()
+    }
+  }
+
+  #[test]
+  fn scale_to_reach_target() {
+    let autoscale = autoscale(AutoscaleMetric::Cpu, 50);
+    let observed = HashMap::from([(AutoscaleMetric::Cpu, 100.0)]);
+    assert_eq!(desired_replicas(2, &autoscale, &observed), 4);
+    let observed = HashMap::from([(AutoscaleMetric::Cpu, 10.0)]);
+    assert_eq!(desired_replicas(4, &autoscale, &observed), 1);
+    // Within the tolerance the replicas are kept
+    let observed = HashMap::from([(AutoscaleMetric::Cpu, 54.0)]);
+    assert_eq!(desired_replicas(3, &autoscale, &observed), 3);
+  }
+
+  #[test]
+  fn cluster_cpu_average() {
+    let sample = |node: &str, cpu: f64, instances: usize| MetricDb {
+      key: uuid::Uuid::new_v4(),
+      created_at: chrono::Utc::now().naive_utc(),
+      expires_at: chrono::Utc::now().naive_utc(),
+      node_name: node.to_owned(),
+      kind: CPU_METRIC.to_owned(),
+      data: serde_json::json!({
+        "CargoKey": "api.global",
+        "Cpu": cpu,
+        "Instances": instances,
+      }),
+      note: None,
+    };
+    assert_eq!(cpu_average(&[]), None);
+    let samples = [sample("node-a", 90.0, 1), sample("node-b", 30.0, 2)];
+    assert_eq!(cpu_average(&samples), Some(50.0));
+  }
+
+  #[test]
+  fn scale_within_bounds() {
+    let autoscale = autoscale(AutoscaleMetric::RequestsPerSecond, 10);
+    let observed =
+      HashMap::from([(AutoscaleMetric::RequestsPerSecond, 1000.0)]);
+    assert_eq!(desired_replicas(2, &autoscale, &observed), 5);
+    // Without metrics the replicas are only brought back in the bounds
+    assert_eq!(desired_replicas(8, &autoscale, &HashMap::new()), 5);
+    assert_eq!(desired_replicas(0, &autoscale, &HashMap::new()), 1);
+  }
+}
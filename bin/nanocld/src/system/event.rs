@@ -1,10 +1,10 @@
 use std::str::FromStr;
 
-use ntex::rt;
-
 use nanocl_error::io::IoResult;
 use nanocl_stubs::{
+  cargo::Cargo,
   generic::{GenericClause, GenericFilter},
+  process::ProcessKind,
   system::{
     Event, EventActor, EventActorKind, EventKind, NativeEventAction,
     ObjPsStatusKind,
@@ -13,13 +13,14 @@ use nanocl_stubs::{
 
 use crate::{
   models::{CargoDb, JobDb, ObjPsStatusDb, ProcessDb, SystemState, VmDb},
-  objects::generic::*,
   repositories::generic::*,
   tasks::generic::*,
   utils,
 };
 
-/// Remove a job after when finished and ttl is set
+/// Set the final status of a job when its last instance die
+/// and remove it after its ttl when set.
+/// Jobs started by the task runner are finished by the runner itself
 async fn job_ttl(actor: &EventActor, state: &SystemState) -> IoResult<()> {
   let attributes = actor.attributes.clone().unwrap_or_default();
   let job_id = match attributes.get("io.nanocl.j") {
@@ -35,6 +36,11 @@ async fn job_ttl(actor: &EventActor, state: &SystemState) -> IoResult<()> {
     }
     _ => {}
   }
+  let task_key = format!("{}@{}", EventActorKind::Job, job.name);
+  if state.inner.task_manager.get_task(&task_key).await.is_some() {
+    log::debug!("event::job_ttl: {job_id} is handled by its task");
+    return Ok(());
+  }
   let instances =
     ProcessDb::read_by_kind_key(&job.name, None, &state.inner.pool).await?;
   let (_, instance_failed, _, running) =
@@ -47,38 +53,9 @@ async fn job_ttl(actor: &EventActor, state: &SystemState) -> IoResult<()> {
     return Ok(());
   }
   log::debug!("instance_failed: {instance_failed}");
-  if instance_failed > 0 {
-    ObjPsStatusDb::update_actual_status(
-      &job.name,
-      &ObjPsStatusKind::Fail,
-      &state.inner.pool,
-    )
-    .await?;
-    state
-      .emit_normal_native_action_sync(&job, NativeEventAction::Fail)
-      .await;
-  } else {
-    ObjPsStatusDb::update_actual_status(
-      &job.name,
-      &ObjPsStatusKind::Finish,
-      &state.inner.pool,
-    )
-    .await?;
-    state
-      .emit_normal_native_action_sync(&job, NativeEventAction::Finish)
-      .await;
-  }
-  let ttl = match job.ttl {
-    None => return Ok(()),
-    Some(ttl) => ttl,
-  };
-  let state = state.clone();
-  rt::spawn(async move {
-    log::debug!("event::job_ttl: {} will be deleted in {ttl}s", job.name);
-    ntex::time::sleep(std::time::Duration::from_secs(ttl as u64)).await;
-    let _ = JobDb::del_obj_by_pk(&job.name, &(), &state).await;
-  });
-  Ok(())
+  let failure = (instance_failed > 0)
+    .then(|| format!("{instance_failed} instances failed"));
+  utils::container::job::finish(&job, failure, state).await
 }
 
 fn starting(
@@ -154,9 +131,10 @@ async fn update(
 ) -> Option<ObjTaskFuture> {
   match actor.kind {
     // If a secret is updated we check for the cargoes using it and fire an update for them
+    // and write again the files of the jobs mounting it
     EventActorKind::Secret => {
       log::debug!("handling update event for secret {key}");
-      let filter = GenericFilter::new().r#where(
+      let env_filter = GenericFilter::new().r#where(
         "data",
         GenericClause::Contains(serde_json::json!({
           "Secrets": [
@@ -164,9 +142,47 @@ async fn update(
           ]
         })),
       );
-      let cargoes = CargoDb::transform_read_by(&filter, &state.inner.pool)
+      let file_filter = GenericFilter::new().r#where(
+        "data",
+        GenericClause::Contains(serde_json::json!({
+          "SecretMounts": [
+            { "Secret": key }
+          ]
+        })),
+      );
+      // Jobs write their files when their containers are created,
+      // the files are written again for the ones already created
+      let jobs = JobDb::transform_read_by(&file_filter, &state.inner.pool)
         .await
-        .unwrap();
+        .unwrap_or_default();
+      for job in jobs.iter().filter(|job| {
+        utils::container::secret::is_mounted(&ProcessKind::Job, &job.name)
+      }) {
+        if let Err(err) = utils::container::secret::mount(
+          &ProcessKind::Job,
+          &job.name,
+          job.secret_mounts.as_deref().unwrap_or_default(),
+          state,
+        )
+        .await
+        {
+          log::warn!("update: job {} secret {key} {err}", job.name);
+        }
+      }
+      let mut cargoes = Vec::new();
+      for filter in [env_filter, file_filter] {
+        for cargo in CargoDb::transform_read_by(&filter, &state.inner.pool)
+          .await
+          .unwrap_or_default()
+        {
+          if !cargoes
+            .iter()
+            .any(|c: &Cargo| c.spec.cargo_key == cargo.spec.cargo_key)
+          {
+            cargoes.push(cargo);
+          }
+        }
+      }
       log::debug!("found {} cargoes using secret {key}", cargoes.len());
       for cargo in &cargoes {
         ObjPsStatusDb::update_actual_status(
@@ -1,4 +1,4 @@
-use std::{os::unix::prelude::PermissionsExt, path::Path, process::Command};
+use std::{os::unix::prelude::PermissionsExt, path::Path};
 
 use notify::{Config, RecommendedWatcher, RecursiveMode, Watcher};
 use ntex::rt;
@@ -81,30 +81,6 @@ fn set_uds_perm() {
   });
 }
 
-/// Create a new thread and spawn and manage a crond instance to run cron jobs
-fn spawn_crond() {
-  log::trace!("boot::spawn_crond: start thread");
-  rt::Arbiter::new().exec_fn(|| {
-    rt::spawn(async {
-      let task = ntex::web::block(move || {
-        match Command::new("crond").args(["-f"]).spawn() {
-          Ok(mut child) => {
-            child.wait()?;
-            Ok(())
-          }
-          Err(err) => Err(err),
-        }
-      })
-      .await;
-      if let Err(err) = task {
-        log::error!("boot::spawn_crond: {err}");
-      }
-      log::trace!("boot::spawn_crond: stop thread");
-      rt::Arbiter::current().stop();
-    });
-  });
-}
-
 /// Ensure that the state dir exists and is ready to use
 async fn ensure_state_dir(state_dir: &str) -> IoResult<()> {
   let vm_dir = format!("{state_dir}/vms/images");
@@ -117,16 +93,27 @@ async fn ensure_state_dir(state_dir: &str) -> IoResult<()> {
 /// Init function called before http server start.
 /// To boot and initialize our state and database.
 pub async fn init(conf: &DaemonConfig) -> IoResult<SystemState> {
-  spawn_crond();
   set_uds_perm();
   ensure_state_dir(&conf.state_dir).await?;
   let system_state = SystemState::new(conf).await?;
+  if let Some(path) = &conf.restore_from {
+    let snapshot =
+      utils::store::restore(path, &system_state.inner.pool).await?;
+    log::info!(
+      "boot::init: store restored from {} made at {}",
+      snapshot.path,
+      snapshot.created_at
+    );
+  }
   let system_ptr = system_state.clone();
   NodeDb::register(&system_ptr).await?;
   utils::system::register_namespace("global", &system_ptr).await?;
   utils::system::register_namespace("system", &system_ptr).await?;
+  utils::auth::register_admin_role(&system_ptr).await?;
+  utils::network_policy::register_kind(&system_ptr).await?;
   rt::spawn(async move {
     let fut = async move {
+      utils::network::sync_namespaces(&system_ptr).await?;
       utils::system::sync_processes(&system_ptr).await?;
       utils::system::sync_vm_images(&system_ptr).await?;
       Ok::<_, IoError>(())
@@ -138,6 +125,14 @@ pub async fn init(conf: &DaemonConfig) -> IoResult<SystemState> {
   });
   super::docker_event::analyze(&system_state);
   super::metric::spawn(&system_state);
+  super::autoscaler::spawn(&system_state);
+  super::job_scheduler::spawn(&system_state);
+  super::audit::spawn(&system_state);
+  super::network_policy::spawn(&system_state);
+  super::node_membership::spawn(&system_state);
+  super::store_snapshot::spawn(&system_state);
+  #[cfg(feature = "sqlite")]
+  super::store_ttl::spawn(&system_state);
   Ok(system_state)
 }
 
@@ -0,0 +1,296 @@
+use std::{collections::HashMap, time::Duration};
+
+use chrono::NaiveDateTime;
+use ntex::{rt, time::interval};
+
+use nanocl_error::io::IoResult;
+use nanocl_stubs::{
+  generic::{GenericClause, GenericFilter},
+  job::{Job, JobConcurrencyPolicy, JobRunStatus},
+  process::ProcessKind,
+  system::{EventActorKind, ObjPsStatusKind},
+};
+
+use crate::{
+  models::{JobDb, JobRunDb, NodeDb, SystemState},
+  repositories::generic::*,
+  utils::{self, cron::CronSchedule},
+};
+
+/// Interval between two checks of the scheduled jobs
+const TICK: Duration = Duration::from_secs(1);
+/// Number of runs kept in the history when the job doesn't set a limit
+const DEFAULT_HISTORY_LIMIT: usize = 10;
+/// Scheduled times older than this are not caught up
+/// when the daemon was down for a long time
+const MAX_CATCH_UP: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// State of a scheduled job kept between two ticks
+#[derive(Debug)]
+struct ScheduledJob {
+  /// Creation date of the job to detect when it's recreated
+  created_at: NaiveDateTime,
+  schedule: CronSchedule,
+  /// Last scheduled time already handled
+  last: NaiveDateTime,
+}
+
+/// Scheduled times due after `last` up to `now`
+#[derive(Debug, PartialEq)]
+struct DueRuns {
+  /// Earliest time due
+  first: NaiveDateTime,
+  /// Latest time due, the one to run
+  latest: NaiveDateTime,
+  /// Number of times due
+  count: usize,
+}
+
+fn due_runs(
+  schedule: &CronSchedule,
+  last: &NaiveDateTime,
+  now: &NaiveDateTime,
+) -> Option<DueRuns> {
+  let catch_up = chrono::Duration::from_std(MAX_CATCH_UP).unwrap_or_default();
+  let mut time = *last.max(&(*now - catch_up));
+  let mut due: Option<DueRuns> = None;
+  while let Some(next) = schedule.next_after(&time) {
+    if next > *now {
+      break;
+    }
+    due = Some(match due {
+      None => DueRuns {
+        first: next,
+        latest: next,
+        count: 1,
+      },
+      Some(due) => DueRuns {
+        latest: next,
+        count: due.count + 1,
+        ..due
+      },
+    });
+    time = next;
+  }
+  due
+}
+
+/// Start the job according to its concurrency policy
+/// and return the outcome to record
+async fn trigger(
+  job: &Job,
+  state: &SystemState,
+) -> IoResult<(JobRunStatus, Option<String>)> {
+  let running = matches!(
+    job.status.actual,
+    ObjPsStatusKind::Start | ObjPsStatusKind::Starting
+  );
+  let policy = job.concurrency_policy.clone().unwrap_or_default();
+  let status = match (running, policy) {
+    (true, JobConcurrencyPolicy::Forbid) => {
+      return Ok((
+        JobRunStatus::Skipped,
+        Some("previous run still running".to_owned()),
+      ));
+    }
+    (true, JobConcurrencyPolicy::Replace) => {
+      let task_key = format!("{}@{}", EventActorKind::Job, job.name);
+      state.inner.task_manager.remove_task(&task_key).await;
+      utils::container::process::stop_instances(
+        &job.name,
+        &ProcessKind::Job,
+        state,
+      )
+      .await?;
+      JobRunStatus::Replaced
+    }
+    _ => JobRunStatus::Started,
+  };
+  utils::container::generic::emit_starting(&job.name, &ProcessKind::Job, state)
+    .await?;
+  Ok((status, None))
+}
+
+/// Record a run and drop the oldest ones above the history limit
+async fn record(
+  job: &Job,
+  scheduled_at: NaiveDateTime,
+  status: JobRunStatus,
+  note: Option<String>,
+  state: &SystemState,
+) -> IoResult<()> {
+  let run = JobRunDb::new(&job.name, scheduled_at, &status, note);
+  JobRunDb::create_from(run, &state.inner.pool).await?;
+  JobRunDb::prune(
+    &job.name,
+    job.history_limit.unwrap_or(DEFAULT_HISTORY_LIMIT),
+    &state.inner.pool,
+  )
+  .await
+}
+
+/// Check a scheduled job and run it if it's due
+async fn check(
+  job: &Job,
+  scheduled: &mut ScheduledJob,
+  now: &NaiveDateTime,
+  state: &SystemState,
+) -> IoResult<()> {
+  let Some(due) = due_runs(&scheduled.schedule, &scheduled.last, now) else {
+    return Ok(());
+  };
+  scheduled.last = due.latest;
+  // Another node already handled this run
+  if !JobDb::claim_run(&job.name, due.latest, &state.inner.pool).await? {
+    return Ok(());
+  }
+  if due.count > 1 {
+    record(
+      job,
+      due.first,
+      JobRunStatus::Missed,
+      Some(format!("{} runs missed", due.count - 1)),
+      state,
+    )
+    .await?;
+  }
+  if let Some(deadline) = job.starting_deadline_seconds {
+    let late = (*now - due.latest).num_seconds();
+    if late > deadline as i64 {
+      return record(
+        job,
+        due.latest,
+        JobRunStatus::Missed,
+        Some(format!("starting deadline exceeded by {late}s")),
+        state,
+      )
+      .await;
+    }
+  }
+  let (status, note) = match trigger(job, state).await {
+    Ok(res) => res,
+    Err(err) => (JobRunStatus::Missed, Some(err.to_string())),
+  };
+  record(job, due.latest, status, note, state).await
+}
+
+/// Read the jobs with a schedule and run the ones that are due
+async fn run(
+  scheduled: &mut HashMap<String, ScheduledJob>,
+  state: &SystemState,
+) -> IoResult<()> {
+  // A cordoned node starts no new jobs, the due runs are caught up
+  // once it's uncordoned
+  let node =
+    NodeDb::read_by_pk(&state.inner.config.hostname, &state.inner.pool).await?;
+  if node.cordoned {
+    return Ok(());
+  }
+  let filter = GenericFilter::new()
+    .r#where("data", GenericClause::HasKey("Schedule".to_owned()));
+  let jobs = JobDb::transform_read_by(&filter, &state.inner.pool).await?;
+  scheduled.retain(|name, _| jobs.iter().any(|job| job.name == *name));
+  let now = chrono::Utc::now().naive_utc();
+  for job in &jobs {
+    let Some(expr) = &job.schedule else {
+      continue;
+    };
+    let entry = match scheduled.get_mut(&job.name) {
+      Some(entry) if entry.created_at == job.created_at => entry,
+      _ => {
+        let schedule = match CronSchedule::parse(expr) {
+          Ok(schedule) => schedule,
+          Err(err) => {
+            log::warn!("job_scheduler::run: {} {err}", job.name);
+            continue;
+          }
+        };
+        let last = JobRunDb::read_by_job(&job.name, &state.inner.pool)
+          .await?
+          .first()
+          .map(|run| run.scheduled_at)
+          .unwrap_or(job.created_at);
+        scheduled.insert(
+          job.name.clone(),
+          ScheduledJob {
+            created_at: job.created_at,
+            schedule,
+            last,
+          },
+        );
+        scheduled
+          .get_mut(&job.name)
+          .expect("scheduled job to be inserted")
+      }
+    };
+    if let Err(err) = check(job, entry, &now, state).await {
+      log::warn!("job_scheduler::run: {} {err}", job.name);
+    }
+  }
+  Ok(())
+}
+
+/// Spawn a background thread starting the jobs on their cron schedule
+pub fn spawn(state: &SystemState) {
+  let state = state.clone();
+  rt::Arbiter::new().exec_fn(move || {
+    rt::spawn(async move {
+      let mut scheduled = HashMap::new();
+      let ticker = interval(TICK);
+      loop {
+        ticker.tick().await;
+        if let Err(err) = run(&mut scheduled, &state).await {
+          log::warn!("job_scheduler::spawn: {err}");
+        }
+      }
+    });
+  });
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn date(s: &str) -> NaiveDateTime {
+    NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").unwrap()
+  }
+
+  #[test]
+  fn due_runs_between_ticks() {
+    let schedule = CronSchedule::parse("*/5 * * * *").unwrap();
+    let last = date("2024-01-01 10:00:00");
+    assert_eq!(
+      due_runs(&schedule, &last, &date("2024-01-01 10:04:59")),
+      None
+    );
+    assert_eq!(
+      due_runs(&schedule, &last, &date("2024-01-01 10:05:00")),
+      Some(DueRuns {
+        first: date("2024-01-01 10:05:00"),
+        latest: date("2024-01-01 10:05:00"),
+        count: 1,
+      })
+    );
+    assert_eq!(
+      due_runs(&schedule, &last, &date("2024-01-01 10:17:00")),
+      Some(DueRuns {
+        first: date("2024-01-01 10:05:00"),
+        latest: date("2024-01-01 10:15:00"),
+        count: 3,
+      })
+    );
+  }
+
+  #[test]
+  fn due_runs_catch_up_limit() {
+    let schedule = CronSchedule::parse("@hourly").unwrap();
+    let due = due_runs(
+      &schedule,
+      &date("2024-01-01 00:00:00"),
+      &date("2024-01-10 00:30:00"),
+    )
+    .unwrap();
+    assert_eq!(due.count, 24);
+    assert_eq!(due.latest, date("2024-01-10 00:00:00"));
+  }
+}
@@ -1,7 +1,15 @@
+mod audit;
+mod autoscaler;
 mod docker_event;
 mod event;
 mod init;
+mod job_scheduler;
 mod metric;
+mod network_policy;
+mod node_membership;
+mod store_snapshot;
+#[cfg(feature = "sqlite")]
+mod store_ttl;
 mod system_state;
 
 pub use event::exec_event;
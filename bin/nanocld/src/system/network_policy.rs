@@ -0,0 +1,79 @@
+use std::time::Duration;
+
+use ntex::{rt, time::interval};
+
+use nanocl_error::io::IoResult;
+use nanocl_stubs::{
+  generic::{GenericClause, GenericFilter},
+  network_policy::NETWORK_POLICY_KIND,
+};
+
+use crate::{
+  models::{ResourceDb, SystemState},
+  repositories::generic::*,
+  utils,
+};
+
+/// Interval between two reconciliations of the network policies
+const TICK: Duration = Duration::from_secs(10);
+
+/// Resolve the network policies and generate the rules to apply
+async fn gen_rules(state: &SystemState) -> IoResult<Option<String>> {
+  let filter = GenericFilter::new()
+    .r#where("kind", GenericClause::Eq(NETWORK_POLICY_KIND.to_owned()));
+  let resources =
+    ResourceDb::transform_read_by(&filter, &state.inner.pool).await?;
+  if resources.is_empty() {
+    return Ok(None);
+  }
+  let mut policies = Vec::new();
+  for resource in resources {
+    let key = resource.spec.resource_key;
+    let policy = match utils::network_policy::validate(&resource.spec.data) {
+      Ok(policy) => policy,
+      Err(err) => {
+        log::warn!("network_policy::gen_rules: {key} {err}");
+        continue;
+      }
+    };
+    match utils::network_policy::resolve(&policy, state).await {
+      Ok(policy) => policies.push(policy),
+      Err(err) => log::warn!("network_policy::gen_rules: {key} {err}"),
+    }
+  }
+  Ok(Some(utils::network_policy::gen_rules(&policies)))
+}
+
+/// Spawn a background loop reconciling the iptables rules
+/// with the `nanocl.io/network-policy` resources.
+/// Rules are only applied when they changed since the last tick
+/// and nothing is touched as long as no policy has ever been created.
+pub fn spawn(state: &SystemState) {
+  let state = state.clone();
+  rt::spawn(async move {
+    let ticker = interval(TICK);
+    let mut applied: Option<String> = None;
+    loop {
+      ticker.tick().await;
+      let rules = match gen_rules(&state).await {
+        Ok(Some(rules)) => rules,
+        Ok(None) if applied.is_none() => continue,
+        Ok(None) => utils::network_policy::gen_rules(&[]),
+        Err(err) => {
+          log::warn!("network_policy::spawn: {err}");
+          continue;
+        }
+      };
+      if applied.as_ref() == Some(&rules) {
+        continue;
+      }
+      match utils::network_policy::apply(&rules).await {
+        Ok(_) => {
+          log::info!("network_policy::spawn: rules applied");
+          applied = Some(rules);
+        }
+        Err(err) => log::warn!("network_policy::spawn: {err}"),
+      }
+    }
+  });
+}
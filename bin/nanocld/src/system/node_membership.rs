@@ -0,0 +1,261 @@
+use std::{
+  cell::{Cell, RefCell},
+  collections::{BTreeSet, HashSet},
+  rc::Rc,
+};
+
+use ntex::{rt, time::interval};
+
+use nanocl_error::io::IoResult;
+use nanocl_stubs::{
+  generic::{GenericClause, GenericFilter},
+  node::NodeStatus,
+  process::ProcessKind,
+  system::{EventKind, ObjPsStatusKind},
+};
+
+use crate::{
+  models::{CargoDb, NodeDb, ProcessDb, SystemState, HEARTBEAT_INTERVAL},
+  repositories::generic::*,
+  utils,
+};
+
+/// Names of the peers with an open heartbeat connection
+type Connected = Rc<RefCell<HashSet<String>>>;
+/// Whether the current node is being drained
+type Draining = Rc<Cell<bool>>;
+
+/// Open a heartbeat connection to a peer if there is none
+fn connect_peer(peer: &NodeDb, connected: &Connected, state: &SystemState) {
+  if !connected.borrow_mut().insert(peer.name.clone()) {
+    return;
+  }
+  let peer = peer.clone();
+  let connected = connected.clone();
+  let state = state.clone();
+  rt::spawn(async move {
+    if let Err(err) = utils::node::heartbeat_peer(&peer, &state).await {
+      log::debug!("node_membership::connect_peer: {err}");
+    }
+    connected.borrow_mut().remove(&peer.name);
+  });
+}
+
+/// Record the status change of a node as an event
+async fn emit_status(node: &NodeDb, status: &NodeStatus, state: &SystemState) {
+  let mut node = node.clone();
+  node.status = status.to_string();
+  let (kind, reason) = match status {
+    NodeStatus::Ready => (EventKind::Normal, "node_ready"),
+    NodeStatus::NotReady => (EventKind::Warning, "node_not_ready"),
+    NodeStatus::Unreachable => (EventKind::Warning, "node_unreachable"),
+  };
+  let note = format!(
+    "Node {} is {status} last seen at {}",
+    node.name, node.last_seen
+  );
+  utils::node::emit(&node, kind, reason, note, None, state).await;
+}
+
+/// Drain the current node in background when it's requested
+/// unless a drain is already running
+fn start_drain(draining: &Draining, state: &SystemState) {
+  if draining.replace(true) {
+    return;
+  }
+  let draining = draining.clone();
+  let state = state.clone();
+  rt::spawn(async move {
+    if let Err(err) = utils::node::drain(&state).await {
+      log::warn!("node_membership::start_drain: {err}");
+    }
+    draining.set(false);
+  });
+}
+
+/// Get the new status of a node from its last heartbeat if it changed.
+/// The current node is never unreachable to itself,
+/// otherwise it would forget its own instances.
+fn get_status_change(
+  node: &NodeDb,
+  hostname: &str,
+  now: &chrono::NaiveDateTime,
+) -> Option<(NodeStatus, NodeStatus)> {
+  let current = node.status.parse::<NodeStatus>().unwrap_or_default();
+  let status = utils::node::get_status(&node.last_seen, now);
+  if status == current
+    || (node.name == hostname && status == NodeStatus::Unreachable)
+  {
+    return None;
+  }
+  Some((current, status))
+}
+
+/// Update the status of the nodes from their last heartbeat.
+/// The cargo instances of a node becoming unreachable are forgotten
+/// to be recreated on the reachable nodes.
+async fn update_statuses(
+  nodes: &[NodeDb],
+  state: &SystemState,
+) -> IoResult<()> {
+  let hostname = &state.inner.config.hostname;
+  let now = chrono::Utc::now().naive_utc();
+  for node in nodes {
+    let Some((current, status)) = get_status_change(node, hostname, &now)
+    else {
+      continue;
+    };
+    let pool = &state.inner.pool;
+    if !NodeDb::update_status(&node.name, &current, &status, pool).await? {
+      continue;
+    }
+    log::info!(
+      "node_membership::update_statuses: {} {current} -> {status}",
+      node.name
+    );
+    if status == NodeStatus::Unreachable && node.name != *hostname {
+      let filter = GenericFilter::new()
+        .r#where("node_name", GenericClause::Eq(node.name.clone()))
+        .r#where("kind", GenericClause::Eq(ProcessKind::Cargo.to_string()));
+      ProcessDb::del_by(&filter, pool).await?;
+    }
+    emit_status(node, &status, state).await;
+  }
+  Ok(())
+}
+
+/// Place again the instances of the started cargoes
+/// after a node joined or left the reachable nodes
+async fn reschedule(state: &SystemState) -> IoResult<()> {
+  let cargoes =
+    CargoDb::transform_read_by(&GenericFilter::new(), &state.inner.pool)
+      .await?;
+  for cargo in cargoes
+    .iter()
+    .filter(|cargo| cargo.status.wanted == ObjPsStatusKind::Start)
+  {
+    if let Err(err) = utils::container::cargo::scale(cargo, state).await {
+      log::warn!(
+        "node_membership::reschedule: {}: {err}",
+        cargo.spec.cargo_key
+      );
+    }
+  }
+  Ok(())
+}
+
+/// Heartbeat the peers, update the status of the nodes,
+/// settle the deployments promoted or aborted, start the drain of the current node when requested
+/// and reschedule the cargoes when the schedulable nodes changed
+async fn run(
+  connected: &Connected,
+  draining: &Draining,
+  reachable: &mut Option<BTreeSet<String>>,
+  state: &SystemState,
+) -> IoResult<()> {
+  let hostname = &state.inner.config.hostname;
+  let nodes = NodeDb::read_by(&GenericFilter::new(), &state.inner.pool).await?;
+  let peers = nodes
+    .iter()
+    .filter(|node| node.name != *hostname)
+    .collect::<Vec<_>>();
+  // The peers may be gone so the node doesn't rely on them to be seen
+  NodeDb::mark_seen(hostname, &state.inner.pool).await?;
+  for peer in peers {
+    connect_peer(peer, connected, state);
+  }
+  update_statuses(&nodes, state).await?;
+  // Deployments promoted or aborted from another node
+  if let Err(err) = utils::container::cargo::settle_deployments(state).await {
+    log::warn!("node_membership::run: {err}");
+  }
+  let nodes = NodeDb::read_by(&GenericFilter::new(), &state.inner.pool).await?;
+  if nodes
+    .iter()
+    .any(|node| node.name == *hostname && node.draining)
+  {
+    start_drain(draining, state);
+  }
+  // Draining nodes are left out to move their instances to the others
+  let current = nodes
+    .into_iter()
+    .filter(|node| node.status != NodeStatus::Unreachable.to_string())
+    .filter(|node| !node.draining)
+    .map(|node| node.name)
+    .collect::<BTreeSet<_>>();
+  let Some(previous) = reachable.replace(current.clone()) else {
+    return Ok(());
+  };
+  if previous == current || !current.contains(hostname) {
+    return Ok(());
+  }
+  // The peers forgot our instances while we were unreachable
+  if !previous.contains(hostname) {
+    utils::system::sync_processes(state).await?;
+  }
+  reschedule(state).await
+}
+
+/// Spawn a background loop exchanging heartbeats with the other nodes
+/// over their websocket to detect the failing ones
+/// and reschedule their cargo instances according to their replication mode
+pub fn spawn(state: &SystemState) {
+  let state = state.clone();
+  rt::spawn(async move {
+    let connected = Connected::default();
+    let draining = Draining::default();
+    let mut reachable = None;
+    let ticker = interval(HEARTBEAT_INTERVAL);
+    loop {
+      ticker.tick().await;
+      if let Err(err) = run(&connected, &draining, &mut reachable, &state).await
+      {
+        log::warn!("node_membership::spawn: {err}");
+      }
+    }
+  });
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn node(name: &str, status: NodeStatus, secs_ago: i64) -> NodeDb {
+    let now = chrono::Utc::now().naive_utc();
+    NodeDb {
+      name: name.to_owned(),
+      created_at: now,
+      ip_address: "10.0.0.1/32".parse().unwrap(),
+      endpoint: "10.0.0.1".to_owned(),
+      version: "0.16.0".to_owned(),
+      metadata: None,
+      status: status.to_string(),
+      last_seen: now - chrono::Duration::seconds(secs_ago),
+      cordoned: false,
+      draining: false,
+      token_hash: None,
+    }
+  }
+
+  #[test]
+  fn two_nodes_one_dies() {
+    let now = chrono::Utc::now().naive_utc();
+    // The peer stopped sending heartbeats
+    let dead = node("node-b", NodeStatus::Ready, 60);
+    assert_eq!(
+      get_status_change(&dead, "node-a", &now),
+      Some((NodeStatus::Ready, NodeStatus::Unreachable))
+    );
+    // The survivor marks itself seen on every tick
+    let survivor = node("node-a", NodeStatus::Ready, 0);
+    assert_eq!(get_status_change(&survivor, "node-a", &now), None);
+    // Even with a stale row the survivor is never unreachable to itself
+    let stale = node("node-a", NodeStatus::NotReady, 60);
+    assert_eq!(get_status_change(&stale, "node-a", &now), None);
+    // But its peer still sees it as unreachable
+    assert_eq!(
+      get_status_change(&stale, "node-b", &now),
+      Some((NodeStatus::NotReady, NodeStatus::Unreachable))
+    );
+  }
+}
@@ -0,0 +1,50 @@
+use std::time::Duration;
+
+use ntex::{rt, time::sleep};
+
+use crate::{models::SystemState, utils, utils::cron::CronSchedule};
+
+/// Delay before checking the schedule again when it has no next time
+const IDLE: Duration = Duration::from_secs(60 * 60);
+
+/// Spawn a background loop snapshotting the store on the configured schedule
+/// and removing the snapshots beyond the retention
+pub fn spawn(state: &SystemState) {
+  let Some(expr) = &state.inner.config.snapshot_schedule else {
+    return;
+  };
+  let schedule = match CronSchedule::parse(expr) {
+    Ok(schedule) => schedule,
+    Err(err) => {
+      log::warn!("store_snapshot::spawn: {err}");
+      return;
+    }
+  };
+  let state = state.clone();
+  rt::spawn(async move {
+    loop {
+      let now = chrono::Utc::now().naive_utc();
+      let Some(next) = schedule.next_after(&now) else {
+        sleep(IDLE).await;
+        continue;
+      };
+      sleep((next - now).to_std().unwrap_or_default()).await;
+      let config = &state.inner.config;
+      match utils::store::snapshot(&state.inner.pool, &config.state_dir).await {
+        Ok(snapshot) => log::info!("store_snapshot: {}", snapshot.path),
+        Err(err) => log::warn!("store_snapshot: {err}"),
+      }
+      if config.snapshot_retention == 0 {
+        continue;
+      }
+      if let Err(err) = utils::store::purge_snapshots(
+        config.snapshot_retention,
+        &config.state_dir,
+      )
+      .await
+      {
+        log::warn!("store_snapshot: {err}");
+      }
+    }
+  });
+}
@@ -0,0 +1,23 @@
+use std::time::Duration;
+
+use ntex::{rt, time::interval};
+
+use crate::{models::SystemState, utils};
+
+/// Interval between two purges of the expired rows
+const TICK: Duration = Duration::from_secs(60 * 60);
+
+/// Spawn a background loop removing the expired metrics and events
+/// of the SQLite store
+pub fn spawn(state: &SystemState) {
+  let state = state.clone();
+  rt::spawn(async move {
+    let ticker = interval(TICK);
+    loop {
+      ticker.tick().await;
+      if let Err(err) = utils::store::purge_expired(&state.inner.pool).await {
+        log::warn!("store_ttl::spawn: {err}");
+      }
+    }
+  });
+}
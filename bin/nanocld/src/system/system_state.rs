@@ -1,4 +1,4 @@
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
 
 use futures::channel::mpsc;
 use futures_util::{SinkExt, StreamExt};
@@ -35,6 +35,8 @@ impl SystemState {
     )
     .map_err(|err| err.map_err_context(|| "Docker"))?;
     let pool = utils::store::init(conf).await?;
+    let master_keys = utils::secret::load_keys(conf)?;
+    let node_token = utils::auth::generate_token()?;
     let (sx, rx) = mpsc::unbounded();
     let system_state = SystemState {
       inner: Arc::new(SystemStateInner {
@@ -44,6 +46,8 @@ impl SystemState {
         event_emitter: sx,
         event_emitter_raw: RawEventEmitter::new(),
         task_manager: TaskManager::new(),
+        master_keys: Arc::new(RwLock::new(master_keys)),
+        node_token,
         arbiter: rt::Arbiter::new(),
       }),
     };
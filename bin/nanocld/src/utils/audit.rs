@@ -0,0 +1,133 @@
+use ntex::{http::Method, io::IoRef, tls::openssl::PeerCert};
+use openssl::nid::Nid;
+
+use nanocl_error::io::IoResult;
+use nanocl_stubs::generic::{GenericClause, GenericFilter};
+
+use crate::{
+  models::{AuditLogDb, SystemState},
+  repositories::generic::*,
+};
+
+/// Only the calls changing something are recorded
+pub fn is_audited(method: &Method) -> bool {
+  matches!(
+    *method,
+    Method::POST | Method::PUT | Method::PATCH | Method::DELETE
+  )
+}
+
+/// Common name of the client certificate when using mtls
+pub fn get_cert_identity(io: Option<&IoRef>) -> Option<String> {
+  let io = io?;
+  let cert = io.query::<PeerCert>();
+  let cert = cert.as_ref()?;
+  let name = cert
+    .0
+    .subject_name()
+    .entries_by_nid(Nid::COMMONNAME)
+    .next()?
+    .data()
+    .as_utf8()
+    .ok()?
+    .to_string();
+  Some(format!("cert:{name}"))
+}
+
+/// Find the key of the object targeted by a call from its path without
+/// the version, its namespace and the name in its json payload
+pub fn get_object_key(
+  path: &str,
+  namespace: Option<&str>,
+  payload: Option<&serde_json::Value>,
+) -> Option<String> {
+  let segments = path
+    .split('/')
+    .filter(|segment| !segment.is_empty())
+    .collect::<Vec<_>>();
+  let (name, namespaced) = match segments.as_slice() {
+    ["secrets", "rotate-key"]
+    | ["events", "watch"]
+    | ["system", "snapshot"] => (None, false),
+    ["vms", "images", name, ..] => (Some(name.to_string()), false),
+    ["resource", "kinds", domain, name, ..] => {
+      (Some(format!("{domain}/{name}")), false)
+    }
+    ["processes", kind, name, _] => {
+      (Some(name.to_string()), ["cargo", "vm"].contains(kind))
+    }
+    [kind, name, ..] => (
+      Some(name.to_string()),
+      ["cargoes", "vms", "volumes"].contains(kind),
+    ),
+    [kind] => {
+      let name = payload
+        .and_then(|payload| payload.get("Name"))
+        .and_then(|name| name.as_str())
+        .map(|name| name.to_owned());
+      (name, ["cargoes", "vms", "volumes"].contains(kind))
+    }
+    [] => (None, false),
+  };
+  let name = name?;
+  if !namespaced {
+    return Some(name);
+  }
+  Some(format!("{name}.{}", namespace.unwrap_or("global")))
+}
+
+/// Remove the entries older than the retention in days
+pub async fn purge(retention: u32, state: &SystemState) -> IoResult<()> {
+  let before = chrono::Utc::now().naive_utc()
+    - chrono::Duration::days(i64::from(retention));
+  let filter = GenericFilter::new().r#where(
+    "created_at",
+    GenericClause::Lt(before.format("%Y-%m-%d %H:%M:%S").to_string()),
+  );
+  AuditLogDb::del_by(&filter, &state.inner.pool).await
+}
+
+#[cfg(test)]
+mod tests {
+  use serde_json::json;
+
+  use super::*;
+
+  #[test]
+  fn object_keys() {
+    assert_eq!(
+      get_object_key("cargoes/api", Some("prod"), None),
+      Some("api.prod".to_owned())
+    );
+    assert_eq!(
+      get_object_key("cargoes", None, Some(&json!({ "Name": "api" }))),
+      Some("api.global".to_owned())
+    );
+    assert_eq!(
+      get_object_key("processes/job/backup/start", None, None),
+      Some("backup".to_owned())
+    );
+    assert_eq!(
+      get_object_key("resource/kinds/ncproxy.io/rule", None, None),
+      Some("ncproxy.io/rule".to_owned())
+    );
+    assert_eq!(
+      get_object_key("vms/images/ubuntu/resize", None, None),
+      Some("ubuntu".to_owned())
+    );
+    assert_eq!(
+      get_object_key("volumes/data/restore", Some("prod"), None),
+      Some("data.prod".to_owned())
+    );
+    assert_eq!(get_object_key("secrets/rotate-key", None, None), None);
+    assert_eq!(get_object_key("secrets", None, Some(&json!([]))), None);
+  }
+
+  #[test]
+  fn audited() {
+    assert!(is_audited(&Method::POST));
+    assert!(is_audited(&Method::DELETE));
+    assert!(!is_audited(&Method::GET));
+    assert!(!is_audited(&Method::HEAD));
+  }
+}
@@ -0,0 +1,463 @@
+use std::fmt::Write;
+
+use ntex::{http::Method, web::WebRequest};
+use openssl::{rand::rand_bytes, sha::sha256};
+
+use nanocl_error::{
+  http::{HttpError, HttpResult},
+  io::{FromIo, IoError, IoResult},
+};
+use nanocl_stubs::{
+  auth::{RolePartial, RoleRule, RoleVerb, Token, TokenCreated, TokenPartial},
+  generic::{GenericClause, GenericFilter},
+};
+
+use crate::{
+  models::{NodeDb, ProcessDb, RoleDb, SystemState, TokenDb, NODE_HEADER},
+  repositories::generic::*,
+};
+
+/// Prefix of the token values to recognize them easily
+const TOKEN_PREFIX: &str = "nanocl_";
+/// Name of the built-in role allowing everything
+pub const ADMIN_ROLE: &str = "admin";
+
+/// The token used to authenticate a request,
+/// stored in the request extensions once authorized
+#[derive(Clone, Debug)]
+pub struct Identity {
+  /// Name of the token
+  pub token: String,
+}
+
+/// What a request is trying to do
+#[derive(Debug, PartialEq, Eq)]
+pub struct Action {
+  pub verb: RoleVerb,
+  pub kind: String,
+  pub namespace: Option<String>,
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+  bytes.iter().fold(String::new(), |mut hex, byte| {
+    let _ = write!(hex, "{byte:02x}");
+    hex
+  })
+}
+
+/// Sha256 of some bytes encoded in hex
+pub fn hash_bytes(bytes: &[u8]) -> String {
+  to_hex(&sha256(bytes))
+}
+
+/// Hash of a token value as stored in the database
+pub fn hash_token(value: &str) -> String {
+  hash_bytes(value.as_bytes())
+}
+
+/// Generate a new random token value
+pub fn generate_token() -> IoResult<String> {
+  let mut bytes = [0; 32];
+  rand_bytes(&mut bytes).map_err(|err| {
+    IoError::other("Token", &format!("unable to generate token: {err}"))
+  })?;
+  Ok(format!("{TOKEN_PREFIX}{}", to_hex(&bytes)))
+}
+
+/// Create the built-in admin role allowing every verb on every kind
+pub async fn register_admin_role(state: &SystemState) -> IoResult<()> {
+  if RoleDb::read_by_pk(ADMIN_ROLE, &state.inner.pool)
+    .await
+    .is_ok()
+  {
+    return Ok(());
+  }
+  let role = RolePartial {
+    name: ADMIN_ROLE.to_owned(),
+    rules: vec![RoleRule {
+      verbs: vec![RoleVerb::All],
+      kinds: vec!["*".to_owned()],
+      namespaces: None,
+    }],
+  };
+  RoleDb::create_try_from(&role, &state.inner.pool).await?;
+  Ok(())
+}
+
+/// Create a token, the value is only returned here since we only store its hash
+pub async fn create_token(
+  partial: &TokenPartial,
+  state: &SystemState,
+) -> IoResult<TokenCreated> {
+  let filter = GenericFilter::new()
+    .r#where("key", GenericClause::In(partial.roles.clone()));
+  let roles = RoleDb::read_by(&filter, &state.inner.pool).await?;
+  if let Some(missing) = partial
+    .roles
+    .iter()
+    .find(|name| !roles.iter().any(|role| &role.key == *name))
+  {
+    return Err(IoError::not_found("Role", missing));
+  }
+  let value = generate_token()?;
+  let db = TokenDb {
+    key: partial.name.clone(),
+    created_at: chrono::Utc::now().naive_utc(),
+    expires_at: partial.expires_at,
+    hash: hash_token(&value),
+    roles: serde_json::to_value(&partial.roles)
+      .map_err(|err| err.map_err_context(|| "TokenRoles"))?,
+  };
+  let token: Token = TokenDb::create_from(db, &state.inner.pool)
+    .await?
+    .try_into()?;
+  Ok(TokenCreated { token, value })
+}
+
+/// Kinds of objects owning processes
+const PROCESS_KINDS: [&str; 3] = ["cargo", "vm", "job"];
+
+/// Map the first segment of a path to the kind of object it manage
+fn get_kind(segment: &str) -> &str {
+  match segment {
+    "cargoes" => "cargo",
+    "processes" | "exec" => "process",
+    "nodes" => "node",
+    "vms" => "vm",
+    "jobs" => "job",
+    "secrets" => "secret",
+    "volumes" => "volume",
+    "resources" | "resource" => "resource",
+    "namespaces" => "namespace",
+    "roles" => "role",
+    "tokens" => "token",
+    "audit" => "audit",
+    _ => "system",
+  }
+}
+
+/// Find the action of a request from its method, path without the version
+/// and query string
+pub fn get_action(method: &Method, path: &str, query: &str) -> Action {
+  let segments = path
+    .split('/')
+    .filter(|segment| !segment.is_empty())
+    .collect::<Vec<_>>();
+  let verb = match *method {
+    Method::GET | Method::HEAD => RoleVerb::Read,
+    Method::DELETE => RoleVerb::Delete,
+    _ => RoleVerb::Write,
+  };
+  // Watching events only read them
+  let verb = match segments.as_slice() {
+    ["events", "watch"] => RoleVerb::Read,
+    _ => verb,
+  };
+  let kind = match segments.as_slice() {
+    ["processes", kind, _, _, ..] if PROCESS_KINDS.contains(kind) => {
+      kind.to_string()
+    }
+    [segment, ..] => get_kind(segment).to_owned(),
+    [] => "system".to_owned(),
+  };
+  let namespace = url::form_urlencoded::parse(query.as_bytes())
+    .find(|(key, _)| key == "namespace")
+    .map(|(_, value)| value.to_string());
+  let namespace = match (kind.as_str(), segments.as_slice()) {
+    ("namespace", ["namespaces", name, ..]) if *name != "count" => {
+      Some(name.to_string())
+    }
+    ("cargo" | "vm" | "volume", _) => {
+      Some(namespace.unwrap_or_else(|| "global".to_owned()))
+    }
+    _ => None,
+  };
+  Action {
+    verb,
+    kind,
+    namespace,
+  }
+}
+
+/// A process targeted by a path without the object owning it
+#[derive(Debug, PartialEq, Eq)]
+pub enum ProcessRef {
+  /// Key or name of the process
+  Process(String),
+  /// Id of an exec command in the container of a process
+  Exec(String),
+}
+
+/// Find the process targeted by a path without the object owning it,
+/// the action is authorized on its owner once resolved
+pub fn get_process_ref(path: &str) -> Option<ProcessRef> {
+  let segments = path
+    .split('/')
+    .filter(|segment| !segment.is_empty())
+    .collect::<Vec<_>>();
+  match segments.as_slice() {
+    ["processes", pk, _] => Some(ProcessRef::Process(pk.to_string())),
+    ["exec", id, ..] => Some(ProcessRef::Exec(id.to_string())),
+    _ => None,
+  }
+}
+
+/// Get the kind and the namespace of the object owning a process
+async fn resolve_process_ref(
+  process_ref: &ProcessRef,
+  state: &SystemState,
+) -> IoResult<(String, Option<String>)> {
+  let pk = match process_ref {
+    ProcessRef::Process(pk) => pk.clone(),
+    ProcessRef::Exec(id) => state
+      .inner
+      .docker_api
+      .inspect_exec(id)
+      .await
+      .map_err(|err| err.map_err_context(|| "Exec"))?
+      .container_id
+      .ok_or_else(|| IoError::not_found("Exec", id))?,
+  };
+  let process = match ProcessDb::read_by_pk(&pk, &state.inner.pool).await {
+    Ok(process) => process,
+    Err(_) => {
+      let filter = GenericFilter::new().r#where("name", GenericClause::Eq(pk));
+      ProcessDb::read_one_by(&filter, &state.inner.pool).await?
+    }
+  };
+  let namespace = match process.kind.as_str() {
+    "cargo" | "vm" => process
+      .kind_key
+      .rsplit_once('.')
+      .map(|(_, namespace)| namespace.to_owned()),
+    _ => None,
+  };
+  Ok((process.kind, namespace))
+}
+
+/// Check if the rules allow an action
+pub fn is_allowed(rules: &[RoleRule], action: &Action) -> bool {
+  rules.iter().any(|rule| {
+    let verb = rule
+      .verbs
+      .iter()
+      .any(|verb| *verb == RoleVerb::All || *verb == action.verb);
+    let kind = rule
+      .kinds
+      .iter()
+      .any(|kind| kind == "*" || *kind == action.kind);
+    let namespace = match (&rule.namespaces, &action.namespace) {
+      (None, _) => true,
+      (Some(namespaces), Some(namespace)) => namespaces
+        .iter()
+        .any(|name| name == "*" || name == namespace),
+      (Some(_), None) => false,
+    };
+    verb && kind && namespace
+  })
+}
+
+/// Check the token sent by a node against the hash it registered
+async fn authorize_node(
+  name: &str,
+  value: &str,
+  state: &SystemState,
+) -> HttpResult<Identity> {
+  let node = NodeDb::read_by_pk(name, &state.inner.pool)
+    .await
+    .map_err(|_| HttpError::unauthorized(format!("unknown node {name}")))?;
+  if node.token_hash.as_deref() != Some(hash_token(value).as_str()) {
+    return Err(HttpError::unauthorized(format!(
+      "invalid token of node {name}"
+    )));
+  }
+  Ok(Identity {
+    token: format!("node/{name}"),
+  })
+}
+
+/// Authenticate and authorize a request against the tokens and roles.
+/// Return `None` when the request is trusted without a token
+pub async fn authorize<Err>(
+  req: &WebRequest<Err>,
+  state: &SystemState,
+) -> HttpResult<Option<Identity>> {
+  // Strip the version from the path
+  let path = req
+    .path()
+    .trim_start_matches('/')
+    .split_once('/')
+    .map(|(_, path)| path)
+    .unwrap_or_default();
+  if path == "_ping" {
+    return Ok(None);
+  }
+  let value = req
+    .headers()
+    .get("authorization")
+    .and_then(|value| value.to_str().ok())
+    .and_then(|value| value.strip_prefix("Bearer "))
+    .map(|value| value.trim().to_owned());
+  let Some(value) = value else {
+    // Requests over the unix socket are already protected by its permissions
+    if req.peer_addr().is_none() {
+      return Ok(None);
+    }
+    return Err(HttpError::unauthorized("missing token"));
+  };
+  // The nodes exchange their heartbeats with their own token
+  if path == "nodes/ws" {
+    if let Some(node) = req
+      .headers()
+      .get(NODE_HEADER)
+      .and_then(|value| value.to_str().ok())
+    {
+      return authorize_node(node, &value, state).await.map(Some);
+    }
+  }
+  let token = TokenDb::read_by_hash(&hash_token(&value), &state.inner.pool)
+    .await
+    .map_err(|_| HttpError::unauthorized("invalid token"))?;
+  if let Some(expires_at) = token.expires_at {
+    if expires_at <= chrono::Utc::now().naive_utc() {
+      return Err(HttpError::unauthorized("token expired"));
+    }
+  }
+  let token: Token = token.try_into()?;
+  let filter =
+    GenericFilter::new().r#where("key", GenericClause::In(token.roles));
+  let rules = RoleDb::transform_read_by(&filter, &state.inner.pool)
+    .await?
+    .into_iter()
+    .flat_map(|role| role.rules)
+    .collect::<Vec<_>>();
+  let mut action = get_action(req.method(), path, req.query_string());
+  if let Some(process_ref) = get_process_ref(path) {
+    let (kind, namespace) = resolve_process_ref(&process_ref, state)
+      .await
+      .map_err(|err| {
+        HttpError::forbidden(format!(
+          "token {} is not allowed to access {process_ref:?}: {err}",
+          token.name
+        ))
+      })?;
+    action.kind = kind;
+    action.namespace = namespace;
+  }
+  if !is_allowed(&rules, &action) {
+    let on = match &action.namespace {
+      Some(namespace) => format!("{} in namespace {namespace}", action.kind),
+      None => action.kind.clone(),
+    };
+    return Err(HttpError::forbidden(format!(
+      "token {} is not allowed to {} {on}",
+      token.name, action.verb
+    )));
+  }
+  Ok(Some(Identity { token: token.name }))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn action(verb: RoleVerb, kind: &str, namespace: Option<&str>) -> Action {
+    Action {
+      verb,
+      kind: kind.to_owned(),
+      namespace: namespace.map(|ns| ns.to_owned()),
+    }
+  }
+
+  #[test]
+  fn actions() {
+    assert_eq!(
+      get_action(&Method::GET, "cargoes", ""),
+      action(RoleVerb::Read, "cargo", Some("global"))
+    );
+    assert_eq!(
+      get_action(&Method::PUT, "cargoes/api", "namespace=prod"),
+      action(RoleVerb::Write, "cargo", Some("prod"))
+    );
+    assert_eq!(
+      get_action(&Method::POST, "processes/vm/db/start", "namespace=dev"),
+      action(RoleVerb::Write, "vm", Some("dev"))
+    );
+    assert_eq!(
+      get_action(&Method::DELETE, "namespaces/prod", ""),
+      action(RoleVerb::Delete, "namespace", Some("prod"))
+    );
+    assert_eq!(
+      get_action(&Method::POST, "volumes/data/snapshots", "namespace=prod"),
+      action(RoleVerb::Write, "volume", Some("prod"))
+    );
+    assert_eq!(
+      get_action(&Method::GET, "secrets/db/reveal", ""),
+      action(RoleVerb::Read, "secret", None)
+    );
+    assert_eq!(
+      get_action(&Method::POST, "events/watch", ""),
+      action(RoleVerb::Read, "system", None)
+    );
+    // Processes of every object aren't listed with the system kind
+    assert_eq!(
+      get_action(&Method::GET, "processes", ""),
+      action(RoleVerb::Read, "process", None)
+    );
+    assert_eq!(
+      get_action(&Method::GET, "processes/job/backup/logs", ""),
+      action(RoleVerb::Read, "job", None)
+    );
+    assert_eq!(
+      get_action(&Method::GET, "nodes", ""),
+      action(RoleVerb::Read, "node", None)
+    );
+  }
+
+  #[test]
+  fn process_refs() {
+    assert_eq!(
+      get_process_ref("processes/api-x1.global.c/logs"),
+      Some(ProcessRef::Process("api-x1.global.c".to_owned()))
+    );
+    assert_eq!(
+      get_process_ref("exec/1234/cargo/start"),
+      Some(ProcessRef::Exec("1234".to_owned()))
+    );
+    assert_eq!(get_process_ref("processes/cargo/api/logs"), None);
+    assert_eq!(get_process_ref("processes"), None);
+  }
+
+  #[test]
+  fn rules() {
+    let rules = vec![RoleRule {
+      verbs: vec![RoleVerb::Read, RoleVerb::Write],
+      kinds: vec!["cargo".to_owned()],
+      namespaces: Some(vec!["prod".to_owned()]),
+    }];
+    let read = action(RoleVerb::Read, "cargo", Some("prod"));
+    assert!(is_allowed(&rules, &read));
+    let delete = action(RoleVerb::Delete, "cargo", Some("prod"));
+    assert!(!is_allowed(&rules, &delete));
+    let other = action(RoleVerb::Read, "cargo", Some("dev"));
+    assert!(!is_allowed(&rules, &other));
+    let secret = action(RoleVerb::Read, "secret", None);
+    assert!(!is_allowed(&rules, &secret));
+    let admin = vec![RoleRule {
+      verbs: vec![RoleVerb::All],
+      kinds: vec!["*".to_owned()],
+      namespaces: None,
+    }];
+    assert!(is_allowed(&admin, &delete));
+    assert!(is_allowed(&admin, &secret));
+  }
+
+  #[test]
+  fn tokens() {
+    let value = generate_token().unwrap();
+    assert!(value.starts_with(TOKEN_PREFIX));
+    assert_eq!(value.len(), TOKEN_PREFIX.len() + 64);
+    assert_eq!(hash_token(&value), hash_token(&value));
+    assert_ne!(hash_token(&value), hash_token(&generate_token().unwrap()));
+  }
+}
@@ -1,9 +1,15 @@
+use std::{cmp::Ordering, ops::Range, time::Instant};
+
 use bollard_next::{
   container::{
-    Config, RemoveContainerOptions, RenameContainerOptions,
-    StartContainerOptions, StopContainerOptions, WaitContainerOptions,
+    Config, InspectContainerOptions, RemoveContainerOptions,
+    RenameContainerOptions, StartContainerOptions, StopContainerOptions,
+    WaitContainerOptions,
+  },
+  secret::{
+    ContainerStateStatusEnum, HealthStatusEnum, HostConfig, RestartPolicy,
+    RestartPolicyNameEnum,
   },
-  secret::{HostConfig, RestartPolicy, RestartPolicyNameEnum},
 };
 use futures::{stream::FuturesUnordered, StreamExt};
 use ntex::rt;
@@ -11,14 +17,18 @@ use ntex::rt;
 use nanocl_error::io::{FromIo, IoError, IoResult};
 use nanocl_stubs::{
   cargo::Cargo,
-  cargo_spec::ReplicationMode,
+  cargo_spec::{CargoRollingUpdate, CargoUpdateStrategy},
   generic::{GenericClause, GenericFilter},
+  namespace::get_network_name,
   process::{Process, ProcessKind},
-  system::{NativeEventAction, ObjPsStatusKind},
+  system::{EventKind, NativeEventAction, ObjPsStatusKind},
 };
 
 use crate::{
-  models::{CargoDb, ObjPsStatusDb, ProcessDb, SecretDb, SystemState},
+  models::{
+    CargoDb, CargoUpdateDb, ObjPsStatusDb, ProcessDb, SecretDb, SpecDb,
+    SystemState,
+  },
   repositories::generic::*,
   utils,
 };
@@ -82,7 +92,9 @@ async fn create_init_container(
   init_container.image = Some(image.clone());
   init_container.host_config = Some(HostConfig {
     network_mode: Some(
-      host_config.network_mode.unwrap_or("nanoclbr0".to_owned()),
+      host_config
+        .network_mode
+        .unwrap_or(get_network_name(&cargo.namespace_name)),
     ),
     ..host_config
   });
@@ -98,6 +110,7 @@ async fn create_init_container(
   labels.insert("io.nanocl.c".to_owned(), cargo.spec.cargo_key.to_owned());
   labels.insert("io.nanocl.n".to_owned(), cargo.namespace_name.to_owned());
   labels.insert("io.nanocl.init-c".to_owned(), "true".to_owned());
+  labels.insert("io.nanocl.s".to_owned(), cargo.spec.key.to_string());
   labels.insert(
     "com.docker.compose.project".into(),
     format!("nanocl_{}", cargo.namespace_name),
@@ -125,6 +138,17 @@ pub async fn create(
   cargo: &Cargo,
   number: usize,
   state: &SystemState,
+) -> IoResult<Vec<Process>> {
+  create_instances(cargo, 0..number, state).await
+}
+
+/// Execute the cargo spec to create the cargo containers
+/// with the given range of instance numbers
+///
+async fn create_instances(
+  cargo: &Cargo,
+  instances: Range<usize>,
+  state: &SystemState,
 ) -> IoResult<Vec<Process>> {
   let data = serde_json::to_string(&cargo)?;
   let new_data = super::generic::inject_data(&data, state).await?;
@@ -142,7 +166,7 @@ pub async fn create(
     let filter = GenericFilter::new()
       .r#where("key", GenericClause::In(secrets.clone()))
       .r#where("kind", GenericClause::Eq("nanocl.io/env".to_owned()));
-    let secrets = SecretDb::transform_read_by(&filter, &state.inner.pool)
+    let secrets = SecretDb::read_decrypted_by(&filter, state)
       .await?
       .into_iter()
       .map(|secret| {
@@ -153,11 +177,26 @@ pub async fn create(
     // Flatten the secrets to have envs in a single vector
     secret_envs = secrets.into_iter().flatten().collect();
   }
-  let instances = (0..number)
+  let secret_binds = super::secret::mount(
+    &ProcessKind::Cargo,
+    &cargo.spec.cargo_key,
+    cargo.spec.secret_mounts.as_deref().unwrap_or_default(),
+    state,
+  )
+  .await?;
+  let volume_binds = utils::volume::mount(
+    &cargo.namespace_name,
+    cargo.spec.volume_mounts.as_deref().unwrap_or_default(),
+    state,
+  )
+  .await?;
+  let instances = instances
     .collect::<Vec<usize>>()
     .into_iter()
     .map(move |current| {
       let secret_envs = secret_envs.clone();
+      let secret_binds = secret_binds.clone();
+      let volume_binds = volume_binds.clone();
       async move {
         let ordinal_index = if current > 0 {
           current.to_string()
@@ -179,6 +218,7 @@ pub async fn create(
         labels
           .insert("io.nanocl.n".to_owned(), cargo.namespace_name.to_owned());
         labels.insert("io.nanocl.not-init-c".to_owned(), "true".to_owned());
+        labels.insert("io.nanocl.s".to_owned(), cargo.spec.key.to_string());
         labels.insert(
           "com.docker.compose.project".to_owned(),
           format!("nanocl_{}", cargo.namespace_name),
@@ -208,6 +248,9 @@ pub async fn create(
         env.push(format!("NANOCL_CARGO_INSTANCE={}", current));
         // Merge the cargo spec with the container spec
         // And set his network mode to the cargo namespace
+        let mut binds = host_config.binds.clone().unwrap_or_default();
+        binds.extend(secret_binds);
+        binds.extend(volume_binds);
         let hostname = match &cargo.spec.container.hostname {
           None => format!("{}{}", ordinal_index, cargo.spec.name),
           Some(hostname) => format!("{}{}", ordinal_index, hostname),
@@ -221,7 +264,8 @@ pub async fn create(
           env: Some(env),
           host_config: Some(HostConfig {
             restart_policy,
-            network_mode: Some("nanoclbr0".to_owned()),
+            network_mode: Some(get_network_name(&cargo.namespace_name)),
+            binds: Some(binds),
             ..host_config
           }),
           ..container
@@ -244,6 +288,30 @@ pub async fn create(
   Ok(instances)
 }
 
+/// Get the number of instances of a cargo that must run on the current node
+/// based on his replication mode
+///
+async fn get_local_number(
+  cargo: &Cargo,
+  state: &SystemState,
+) -> IoResult<usize> {
+  let placement = super::replication::get_placement(cargo, state).await?;
+  let number = placement
+    .get(&state.inner.config.hostname)
+    .copied()
+    .unwrap_or_default();
+  for (node, number) in placement
+    .iter()
+    .filter(|(node, _)| **node != state.inner.config.hostname)
+  {
+    log::debug!(
+      "cargo {} is placed with {number} instance(s) on node {node}",
+      cargo.spec.cargo_key
+    );
+  }
+  Ok(number)
+}
+
 /// Start cargo instances
 ///
 pub async fn start(key: &str, state: &SystemState) -> IoResult<()> {
@@ -260,7 +328,10 @@ pub async fn start(key: &str, state: &SystemState) -> IoResult<()> {
   );
   let processes = ProcessDb::read_by_kind_key(
     &cargo.spec.cargo_key,
-    Some(filter),
+    Some(filter.r#where(
+      "node_name",
+      GenericClause::Eq(state.inner.config.hostname.clone()),
+    )),
     &state.inner.pool,
   )
   .await?;
@@ -268,6 +339,7 @@ pub async fn start(key: &str, state: &SystemState) -> IoResult<()> {
     "processes {:?}",
     processes.iter().map(|p| p.name.clone()).collect::<Vec<_>>()
   );
+  let number = get_local_number(&cargo, state).await?;
   let filter = GenericFilter::new().r#where(
     "data",
     GenericClause::Contains(serde_json::json!({
@@ -284,7 +356,9 @@ pub async fn start(key: &str, state: &SystemState) -> IoResult<()> {
     &state.inner.pool,
   )
   .await?;
-  if let Some(init_container) = &cargo.spec.init_container {
+  if let Some(init_container) =
+    cargo.spec.init_container.as_ref().filter(|_| number > 0)
+  {
     if init_process.is_empty() {
       let process =
         create_init_container(&cargo, init_container, state).await?;
@@ -293,15 +367,7 @@ pub async fn start(key: &str, state: &SystemState) -> IoResult<()> {
       start_init_container(&init_process[0], state).await?;
     }
   }
-  // TODO: FIND BEST NODES TO RUN WORKLOAD
-  // let nodes =
-  //   MetricDb::find_best_nodes(90.0, 90.0, 100, &state.inner.pool).await?;
-  // log::debug!("BEST NODES FOR CARGO {key}: {nodes:?}");
   if processes.is_empty() {
-    let number = match &cargo.spec.replication {
-      Some(ReplicationMode::Static(replication)) => replication.number,
-      _ => 1,
-    };
     create(&cargo, number, state).await?;
   }
   super::process::start_instances(
@@ -313,14 +379,12 @@ pub async fn start(key: &str, state: &SystemState) -> IoResult<()> {
   Ok(())
 }
 
-/// Function that update the cargo container by creating new instances before removing the old ones
-/// This way we can have zero downtime deployment
+/// Rename the instances of a cargo to flag them for deletion
 ///
-pub async fn update(key: &str, state: &SystemState) -> IoResult<()> {
-  let cargo = CargoDb::transform_read_by_pk(&key, &state.inner.pool).await?;
-  let processes =
-    ProcessDb::read_by_kind_key(key, None, &state.inner.pool).await?;
-  // rename old instances to flag them for deletion
+async fn flag_instances(
+  processes: &[Process],
+  state: &SystemState,
+) -> IoResult<()> {
   processes
     .iter()
     .map(|process| {
@@ -355,16 +419,302 @@ pub async fn update(key: &str, state: &SystemState) -> IoResult<()> {
     .await
     .into_iter()
     .collect::<IoResult<Vec<_>>>()?;
-  let number = match &cargo.spec.replication {
-    Some(ReplicationMode::Static(replication)) => replication.number,
-    _ => 1,
+  Ok(())
+}
+
+/// Rename back the instances of a cargo flagged for deletion
+///
+async fn restore_instances(
+  processes: &[Process],
+  state: &SystemState,
+) -> IoResult<()> {
+  processes
+    .iter()
+    .map(|process| {
+      let docker_api = state.inner.docker_api.clone();
+      async move {
+        docker_api
+          .rename_container(
+            &process.key,
+            RenameContainerOptions {
+              name: &process.name,
+            },
+          )
+          .await
+          .map_err(|err| err.map_err_context(|| "RenameContainer"))?;
+        Ok::<_, IoError>(())
+      }
+    })
+    .collect::<FuturesUnordered<_>>()
+    .collect::<Vec<_>>()
+    .await
+    .into_iter()
+    .collect::<IoResult<Vec<_>>>()?;
+  Ok(())
+}
+
+/// Start the given instances of a cargo
+///
+async fn start_processes(
+  processes: &[Process],
+  state: &SystemState,
+) -> IoResult<()> {
+  for process in processes {
+    state
+      .inner
+      .docker_api
+      .start_container(&process.key, None::<StartContainerOptions<String>>)
+      .await
+      .map_err(|err| err.map_err_context(|| "StartProcess"))?;
+  }
+  Ok(())
+}
+
+/// Wait for the given instances to be ready before the deadline.
+/// An instance is ready when running and healthy if it has a health check
+/// and `wait_healthy` is enabled.
+///
+async fn wait_ready(
+  processes: &[Process],
+  wait_healthy: bool,
+  deadline: Instant,
+  state: &SystemState,
+) -> IoResult<()> {
+  let mut pending = processes.iter().collect::<Vec<_>>();
+  while !pending.is_empty() {
+    let mut still_pending = Vec::new();
+    for process in pending {
+      let inspect = state
+        .inner
+        .docker_api
+        .inspect_container(&process.key, None::<InspectContainerOptions>)
+        .await
+        .map_err(|err| err.map_err_context(|| "WaitReady"))?;
+      let container_state = inspect.state.unwrap_or_default();
+      if matches!(
+        container_state.status,
+        Some(ContainerStateStatusEnum::EXITED | ContainerStateStatusEnum::DEAD)
+      ) {
+        return Err(IoError::interrupted(
+          "WaitReady",
+          &format!(
+            "instance {} exited with code {}",
+            process.name,
+            container_state.exit_code.unwrap_or_default()
+          ),
+        ));
+      }
+      let health = container_state
+        .health
+        .and_then(|health| health.status)
+        .filter(|_| wait_healthy);
+      let ready = match health {
+        Some(HealthStatusEnum::UNHEALTHY) => {
+          return Err(IoError::interrupted(
+            "WaitReady",
+            &format!("instance {} is unhealthy", process.name),
+          ));
+        }
+        Some(HealthStatusEnum::HEALTHY) => true,
+        Some(HealthStatusEnum::STARTING) => false,
+        _ => container_state.running.unwrap_or_default(),
+      };
+      if !ready {
+        still_pending.push(process);
+      }
+    }
+    pending = still_pending;
+    if pending.is_empty() {
+      break;
+    }
+    if Instant::now() >= deadline {
+      return Err(IoError::interrupted(
+        "WaitReady",
+        &format!(
+          "progress deadline exceeded waiting for {}",
+          pending
+            .iter()
+            .map(|p| p.name.clone())
+            .collect::<Vec<_>>()
+            .join(", ")
+        ),
+      ));
+    }
+    ntex::time::sleep(std::time::Duration::from_secs(1)).await;
+  }
+  Ok(())
+}
+
+/// Point the cargo back to the spec preceding his current one.
+/// Return the cargo with the previous spec or `None` if there is no history
+///
+async fn rollback_spec(
+  cargo: &Cargo,
+  state: &SystemState,
+) -> IoResult<Option<Cargo>> {
+  let histories =
+    SpecDb::read_by_kind_key(&cargo.spec.cargo_key, &state.inner.pool).await?;
+  let Some(previous) = histories
+    .into_iter()
+    .filter(|spec| {
+      spec.key != cargo.spec.key && spec.created_at <= cargo.spec.created_at
+    })
+    .max_by_key(|spec| spec.created_at)
+  else {
+    return Ok(None);
+  };
+  let previous = previous.try_to_cargo_spec()?;
+  let new_item = CargoUpdateDb {
+    name: Some(previous.name.clone()),
+    spec_key: Some(previous.key),
+    ..Default::default()
   };
+  CargoDb::update_pk(&cargo.spec.cargo_key, new_item, &state.inner.pool)
+    .await?;
+  Ok(Some(Cargo {
+    spec: previous,
+    ..cargo.clone()
+  }))
+}
+
+/// Update the cargo instances by batches.
+/// A batch create at most `max_surge` + `max_unavailable` new instances
+/// and remove the old ones only when the new ones are ready.
+/// On failure the new instances are removed
+/// and the cargo is rolled back to his previous spec.
+///
+async fn rolling_update(
+  cargo: &Cargo,
+  opts: &CargoRollingUpdate,
+  processes: Vec<Process>,
+  number: usize,
+  state: &SystemState,
+) -> IoResult<()> {
+  let max_surge = opts.max_surge.unwrap_or(1);
+  let max_unavailable = opts.max_unavailable.unwrap_or(0);
+  let batch_size = (max_surge + max_unavailable).max(1);
+  let wait_healthy = opts.wait_healthy.unwrap_or(true);
+  let deadline = Instant::now()
+    + std::time::Duration::from_secs(opts.progress_deadline.unwrap_or(600));
+  let (old_inits, mut old_instances): (Vec<_>, Vec<_>) =
+    processes.into_iter().partition(|process| {
+      process
+        .data
+        .config
+        .clone()
+        .unwrap_or_default()
+        .labels
+        .unwrap_or_default()
+        .contains_key("io.nanocl.init-c")
+    });
+  let old_total = old_instances.len();
+  flag_instances(&old_instances, state).await?;
+  flag_instances(&old_inits, state).await?;
+  let mut new_instances: Vec<Process> = Vec::new();
+  let res = async {
+    if let Some(init_container) =
+      cargo.spec.init_container.as_ref().filter(|_| number > 0)
+    {
+      let process = create_init_container(cargo, init_container, state).await?;
+      start_init_container(&process, state).await?;
+    }
+    while new_instances.len() < number {
+      let size = batch_size.min(number - new_instances.len());
+      // Make room for the new instances within the unavailable budget
+      let removable = max_unavailable.min(size).min(old_instances.len());
+      let removed = old_instances.drain(..removable).collect::<Vec<_>>();
+      super::process::delete_instances(
+        &removed.iter().map(|p| p.key.clone()).collect::<Vec<_>>(),
+        state,
+      )
+      .await?;
+      let start = new_instances.len();
+      let batch = create_instances(cargo, start..start + size, state).await?;
+      new_instances.extend(batch.clone());
+      start_processes(&batch, state).await?;
+      wait_ready(&batch, wait_healthy, deadline, state).await?;
+      let replaced = (size - removable).min(old_instances.len());
+      let removed = old_instances.drain(..replaced).collect::<Vec<_>>();
+      super::process::delete_instances(
+        &removed.iter().map(|p| p.key.clone()).collect::<Vec<_>>(),
+        state,
+      )
+      .await?;
+    }
+    Ok::<_, IoError>(())
+  }
+  .await;
+  match res {
+    Ok(_) => {
+      let removed = old_instances.iter().chain(old_inits.iter());
+      super::process::delete_instances(
+        &removed.map(|p| p.key.clone()).collect::<Vec<_>>(),
+        state,
+      )
+      .await?;
+      Ok(())
+    }
+    Err(err) => {
+      log::error!(
+        "cargo::rolling_update: {} failed: {err}",
+        cargo.spec.cargo_key
+      );
+      let _ = super::process::delete_instances(
+        &new_instances
+          .iter()
+          .map(|p| p.key.clone())
+          .collect::<Vec<_>>(),
+        state,
+      )
+      .await;
+      restore_instances(&old_inits, state).await?;
+      restore_instances(&old_instances, state).await?;
+      start_processes(&old_instances, state).await.ok();
+      let previous = rollback_spec(cargo, state).await?;
+      // Recreate the old instances removed during the update
+      let missing = old_total - old_instances.len();
+      if let (Some(previous), true) = (&previous, missing > 0) {
+        let start = old_instances.len();
+        let instances =
+          create_instances(previous, start..start + missing, state).await?;
+        start_processes(&instances, state).await?;
+      }
+      let actor = previous.unwrap_or(cargo.clone()).into();
+      state
+        .emit_action_sync(
+          &actor,
+          NativeEventAction::Fail,
+          EventKind::Error,
+          "rolling_update",
+          Some(format!("Rolled back to the previous spec: {err}")),
+          None,
+        )
+        .await;
+      Ok(())
+    }
+  }
+}
+
+/// Function that update the cargo container by creating new instances before removing the old ones
+/// This way we can have zero downtime deployment
+///
+async fn replace(
+  cargo: &Cargo,
+  processes: Vec<Process>,
+  number: usize,
+  state: &SystemState,
+) -> IoResult<()> {
+  let key = &cargo.spec.cargo_key;
+  // rename old instances to flag them for deletion
+  flag_instances(&processes, state).await?;
   // Create instance with the new spec
-  if let Some(init_container) = &cargo.spec.init_container {
-    let process = create_init_container(&cargo, init_container, state).await?;
+  if let Some(init_container) =
+    cargo.spec.init_container.as_ref().filter(|_| number > 0)
+  {
+    let process = create_init_container(cargo, init_container, state).await?;
     start_init_container(&process, state).await?;
   }
-  let new_instances = match create(&cargo, number, state).await {
+  let new_instances = match create(cargo, number, state).await {
     Err(err) => {
       log::error!(
         "Unable to create cargo instance {} : {err}",
@@ -382,38 +732,15 @@ pub async fn update(key: &str, state: &SystemState) -> IoResult<()> {
         "Unable to start cargo instance {} : {err}",
         cargo.spec.cargo_key
       );
-      let state_ptr_ptr = state.clone();
       let _ = super::process::delete_instances(
         &new_instances
           .iter()
           .map(|p| p.key.clone())
           .collect::<Vec<_>>(),
-        &state_ptr_ptr,
+        state,
       )
       .await;
-      let res = processes
-        .iter()
-        .map(|process| {
-          let docker_api = state_ptr_ptr.inner.docker_api.clone();
-          async move {
-            docker_api
-              .rename_container(
-                &process.key,
-                RenameContainerOptions {
-                  name: &process.name,
-                },
-              )
-              .await
-              .map_err(|err| err.map_err_context(|| "RenameContainer"))?;
-            Ok::<_, IoError>(())
-          }
-        })
-        .collect::<FuturesUnordered<_>>()
-        .collect::<Vec<_>>()
-        .await
-        .into_iter()
-        .collect::<IoResult<Vec<_>>>();
-      if let Err(err) = res {
+      if let Err(err) = restore_instances(&processes, state).await {
         log::error!("Unable to rename containers back: {err}");
       }
     }
@@ -431,6 +758,240 @@ pub async fn update(key: &str, state: &SystemState) -> IoResult<()> {
       });
     }
   }
+  Ok(())
+}
+
+/// Read the instances of a cargo running on the current node
+///
+pub async fn read_local_processes(
+  key: &str,
+  state: &SystemState,
+) -> IoResult<Vec<Process>> {
+  let filter = GenericFilter::new().r#where(
+    "node_name",
+    GenericClause::Eq(state.inner.config.hostname.clone()),
+  );
+  ProcessDb::read_by_kind_key(key, Some(filter), &state.inner.pool).await
+}
+
+/// Split the instances of a cargo between the ones created with his current spec
+/// and the ones created with a previous spec
+///
+fn split_by_spec(
+  cargo: &Cargo,
+  processes: Vec<Process>,
+) -> (Vec<Process>, Vec<Process>) {
+  processes
+    .into_iter()
+    .partition(|process| process.is_from_spec(&cargo.spec))
+}
+
+/// Create the new instances next to the previous ones for a blue/green
+/// or canary deployment. The previous instances are kept until the deployment
+/// is promoted or aborted. On failure the cargo is rolled back to his previous spec.
+///
+async fn deploy(
+  cargo: &Cargo,
+  number: usize,
+  wait_healthy: bool,
+  progress_deadline: u64,
+  state: &SystemState,
+) -> IoResult<()> {
+  let mut new_instances: Vec<Process> = Vec::new();
+  let deadline =
+    Instant::now() + std::time::Duration::from_secs(progress_deadline);
+  let res = async {
+    if let Some(init_container) =
+      cargo.spec.init_container.as_ref().filter(|_| number > 0)
+    {
+      let process = create_init_container(cargo, init_container, state).await?;
+      start_init_container(&process, state).await?;
+    }
+    new_instances = create_instances(cargo, 0..number, state).await?;
+    start_processes(&new_instances, state).await?;
+    wait_ready(&new_instances, wait_healthy, deadline, state).await
+  }
+  .await;
+  if let Err(err) = res {
+    log::error!("cargo::deploy: {} failed: {err}", cargo.spec.cargo_key);
+    let _ = super::process::delete_instances(
+      &new_instances
+        .iter()
+        .map(|p| p.key.clone())
+        .collect::<Vec<_>>(),
+      state,
+    )
+    .await;
+    let previous = rollback_spec(cargo, state).await?;
+    let actor = previous.unwrap_or(cargo.clone()).into();
+    state
+      .emit_action_sync(
+        &actor,
+        NativeEventAction::Fail,
+        EventKind::Error,
+        "deploy",
+        Some(format!("Rolled back to the previous spec: {err}")),
+        None,
+      )
+      .await;
+  }
+  Ok(())
+}
+
+/// Remove the instances of the current node not created with the spec
+/// of the cargo once its deployment is settled
+///
+async fn settle_local(cargo: &Cargo, state: &SystemState) -> IoResult<()> {
+  let processes = read_local_processes(&cargo.spec.cargo_key, state).await?;
+  let (_, previous) = split_by_spec(cargo, processes);
+  if previous.is_empty() {
+    return Ok(());
+  }
+  super::process::delete_instances(
+    &previous.iter().map(|p| p.key.clone()).collect::<Vec<_>>(),
+    state,
+  )
+  .await
+}
+
+/// Record the spec of a cargo as settled for every node
+/// and remove the instances of the other specs on the current node.
+/// The other nodes remove theirs on their next heartbeat.
+///
+async fn settle(cargo: &Cargo, state: &SystemState) -> IoResult<()> {
+  let new_item = CargoUpdateDb {
+    settled_spec_key: Some(Some(cargo.spec.key.to_string())),
+    ..Default::default()
+  };
+  CargoDb::update_pk(&cargo.spec.cargo_key, new_item, &state.inner.pool)
+    .await?;
+  settle_local(cargo, state).await
+}
+
+/// Remove the instances of the current node left by the deployments
+/// promoted or aborted from another node
+///
+pub async fn settle_deployments(state: &SystemState) -> IoResult<()> {
+  let cargoes = CargoDb::read_by(&GenericFilter::new(), &state.inner.pool)
+    .await?
+    .into_iter()
+    .filter(|(cargo, spec, _)| {
+      cargo.settled_spec_key.as_deref() == Some(&spec.key.to_string())
+    })
+    .map(CargoDb::transform)
+    .collect::<IoResult<Vec<_>>>()?;
+  for cargo in cargoes {
+    settle_local(&cargo, state).await?;
+  }
+  Ok(())
+}
+
+/// Ensure a deployment of a cargo is in progress on any node
+///
+async fn ensure_deploying(
+  cargo: &Cargo,
+  context: &str,
+  state: &SystemState,
+) -> IoResult<()> {
+  let key = &cargo.spec.cargo_key;
+  let processes =
+    ProcessDb::read_by_kind_key(key, None, &state.inner.pool).await?;
+  let (_, previous) = split_by_spec(cargo, processes);
+  if previous.is_empty() {
+    return Err(IoError::invalid_input(
+      context,
+      &format!("No deployment in progress for cargo {key}"),
+    ));
+  }
+  Ok(())
+}
+
+/// Promote the deployment of a cargo by removing his previous instances
+/// on every node so the new ones receive all the traffic
+///
+pub async fn promote(key: &str, state: &SystemState) -> IoResult<Cargo> {
+  let cargo = CargoDb::transform_read_by_pk(&key, &state.inner.pool).await?;
+  ensure_deploying(&cargo, "CargoPromote", state).await?;
+  settle(&cargo, state).await?;
+  state
+    .emit_normal_native_action_sync(&cargo, NativeEventAction::Update)
+    .await;
+  Ok(cargo)
+}
+
+/// Abort the deployment of a cargo by rolling back to his previous spec
+/// and removing his new instances on every node
+///
+pub async fn abort(key: &str, state: &SystemState) -> IoResult<Cargo> {
+  let cargo = CargoDb::transform_read_by_pk(&key, &state.inner.pool).await?;
+  ensure_deploying(&cargo, "CargoAbort", state).await?;
+  let cargo = rollback_spec(&cargo, state).await?.unwrap_or(cargo);
+  settle(&cargo, state).await?;
+  state
+    .emit_normal_native_action_sync(&cargo, NativeEventAction::Update)
+    .await;
+  Ok(cargo)
+}
+
+/// Create or remove instances of a cargo on the current node
+/// to match the number of replicas placed on it.
+/// Only the instances of the current spec are counted, the ones
+/// of a previous spec are left to the deployment in progress
+///
+pub async fn scale(cargo: &Cargo, state: &SystemState) -> IoResult<()> {
+  let number = get_local_number(cargo, state).await?;
+  let processes = read_local_processes(&cargo.spec.cargo_key, state)
+    .await?
+    .into_iter()
+    .filter(|process| !process.name.starts_with("init-"))
+    .collect::<Vec<_>>();
+  let (mut processes, _) = split_by_spec(cargo, processes);
+  processes.sort_by_key(|process| process.created_at);
+  let current = processes.len();
+  match current.cmp(&number) {
+    Ordering::Less => {
+      let instances = create_instances(cargo, current..number, state).await?;
+      start_processes(&instances, state).await?;
+    }
+    Ordering::Greater => {
+      super::process::delete_instances(
+        &processes[number..]
+          .iter()
+          .map(|p| p.key.clone())
+          .collect::<Vec<_>>(),
+        state,
+      )
+      .await?;
+    }
+    Ordering::Equal => {}
+  }
+  Ok(())
+}
+
+/// Update the cargo instances of the current node with his update strategy
+///
+pub async fn update(key: &str, state: &SystemState) -> IoResult<()> {
+  let cargo = CargoDb::transform_read_by_pk(&key, &state.inner.pool).await?;
+  let processes = read_local_processes(key, state).await?;
+  let number = get_local_number(&cargo, state).await?;
+  match &cargo.spec.update_strategy {
+    Some(CargoUpdateStrategy::RollingUpdate(opts)) => {
+      rolling_update(&cargo, opts, processes, number, state).await?;
+    }
+    Some(CargoUpdateStrategy::BlueGreen(opts)) => {
+      let wait_healthy = opts.wait_healthy.unwrap_or(true);
+      let deadline = opts.progress_deadline.unwrap_or(600);
+      deploy(&cargo, number, wait_healthy, deadline, state).await?;
+    }
+    Some(CargoUpdateStrategy::Canary(opts)) => {
+      let wait_healthy = opts.wait_healthy.unwrap_or(true);
+      let deadline = opts.progress_deadline.unwrap_or(600);
+      deploy(&cargo, number, wait_healthy, deadline, state).await?;
+    }
+    None | Some(CargoUpdateStrategy::Replace) => {
+      replace(&cargo, processes, number, state).await?;
+    }
+  }
   ObjPsStatusDb::update_actual_status(
     key,
     &ObjPsStatusKind::Start,
@@ -462,8 +1023,62 @@ pub async fn delete(key: &str, state: &SystemState) -> IoResult<()> {
   }
   let cargo = CargoDb::transform_read_by_pk(&key, &state.inner.pool).await?;
   CargoDb::clear_by_pk(key, &state.inner.pool).await?;
+  super::secret::unmount(&ProcessKind::Cargo, key, state);
   state
     .emit_normal_native_action_sync(&cargo, NativeEventAction::Destroy)
     .await;
   Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+  use std::collections::HashMap;
+
+  use bollard_next::service::{ContainerConfig, ContainerInspectResponse};
+
+  use super::*;
+
+  fn process(name: &str, spec_key: Option<&str>, secs_ago: i64) -> Process {
+    let now = chrono::Utc::now().naive_utc();
+    let labels = spec_key
+      .map(|key| HashMap::from([("io.nanocl.s".to_owned(), key.to_owned())]));
+    Process {
+      key: name.to_owned(),
+      created_at: now - chrono::Duration::seconds(secs_ago),
+      updated_at: now,
+      name: name.to_owned(),
+      kind: ProcessKind::Cargo,
+      node_name: "node-a".to_owned(),
+      kind_key: "api.global".to_owned(),
+      data: ContainerInspectResponse {
+        config: Some(ContainerConfig {
+          labels,
+          ..Default::default()
+        }),
+        ..Default::default()
+      },
+    }
+  }
+
+  #[test]
+  fn split_unlabelled_instances() {
+    let mut cargo = Cargo::default();
+    cargo.spec.key = uuid::Uuid::new_v4();
+    cargo.spec.created_at =
+      chrono::Utc::now().naive_utc() - chrono::Duration::seconds(60);
+    let spec_key = cargo.spec.key.to_string();
+    let processes = vec![
+      process("labelled", Some(&spec_key), 10),
+      process("upgraded", None, 30),
+      process("outdated", None, 120),
+      process("previous", Some("previous-spec"), 10),
+    ];
+    let (current, previous) = split_by_spec(&cargo, processes);
+    let names = |processes: Vec<Process>| {
+      processes.into_iter().map(|p| p.name).collect::<Vec<_>>()
+    };
+    // Instances created before the upgrade belong to the spec they run
+    assert_eq!(names(current), ["labelled", "upgraded"]);
+    assert_eq!(names(previous), ["outdated", "previous"]);
+  }
+}
@@ -37,6 +37,7 @@ pub async fn emit(
         kind_key,
         JobUpdateDb {
           updated_at: Some(chrono::Utc::now().naive_utc()),
+          ..Default::default()
         },
         &state.inner.pool,
       )
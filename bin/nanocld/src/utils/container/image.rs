@@ -10,7 +10,6 @@ use nanocl_stubs::{
 
 use crate::{
   models::{SecretDb, SystemState},
-  repositories::generic::*,
   vars,
 };
 
@@ -22,7 +21,7 @@ async fn get_credentials(
 ) -> IoResult<Option<DockerCredentials>> {
   Ok(match secret {
     Some(secret) => {
-      let secret = SecretDb::read_by_pk(&secret, &state.inner.pool).await?;
+      let secret = SecretDb::read_decrypted_by_pk(&secret, state).await?;
       serde_json::from_value::<DockerCredentials>(secret.data)
         .map(Some)
         .map_err(|err| err.map_err_context(|| "GetCredentials"))?
@@ -1,37 +1,80 @@
+use std::{
+  collections::HashMap,
+  time::{Duration, Instant},
+};
+
 use bollard_next::{
-  container::{Config, StartContainerOptions, WaitContainerOptions},
+  container::{
+    Config, StartContainerOptions, StopContainerOptions, WaitContainerOptions,
+  },
   secret::HostConfig,
 };
-use futures::StreamExt;
+use futures::{stream::FuturesUnordered, StreamExt};
+use ntex::rt;
+
 use nanocl_error::io::{IoError, IoResult};
 use nanocl_stubs::{
-  job::Job,
+  generic::{GenericClause, GenericFilter},
+  job::{
+    Job, JobBackoff, JobBackoffPolicy, JobStep, JobStepState, JobStepStatus,
+  },
+  namespace::get_network_name,
   process::{Process, ProcessKind},
-  system::{NativeEventAction, ObjPsStatusKind},
+  system::{EventKind, NativeEventAction, ObjPsStatusKind},
 };
 
 use crate::{
-  models::{JobDb, ObjPsStatusDb, ProcessDb, SystemState},
+  models::{JobDb, JobUpdateDb, ObjPsStatusDb, ProcessDb, SystemState},
+  objects::generic::*,
   repositories::generic::*,
   utils,
+  vars::UNSCOPED_NAMESPACE,
 };
 
-/// Create process (container) for a job
+/// Default delay in seconds before the first retry of a failed job
+const DEFAULT_BACKOFF_DELAY: u64 = 10;
+/// Default maximum delay in seconds between two attempts of a failed job
+const DEFAULT_BACKOFF_MAX_DELAY: u64 = 300;
+
+/// Reason why an attempt of a job failed
+enum AttemptError {
+  /// A container failed, the job can be retried
+  Failed(String),
+  /// The active deadline of the job is exceeded
+  DeadlineExceeded,
+  /// An internal error that is not retried
+  Internal(IoError),
+}
+
+impl From<IoError> for AttemptError {
+  fn from(err: IoError) -> Self {
+    AttemptError::Internal(err)
+  }
+}
+
+/// Create process (container) for a step of a job
 ///
 async fn create_instance(
   name: &str,
   index: usize,
-  container: &Config,
+  step: &JobStep,
+  secret_binds: &[String],
   state: &SystemState,
 ) -> IoResult<Process> {
-  let mut container = container.clone();
+  let mut container = step.container.clone();
   let mut labels = container.labels.unwrap_or_default();
   labels.insert("io.nanocl.j".to_owned(), name.to_owned());
+  labels.insert("io.nanocl.js".to_owned(), step.name.clone());
   container.labels = Some(labels);
   let host_config = container.host_config.unwrap_or_default();
+  let mut binds = host_config.binds.clone().unwrap_or_default();
+  binds.extend_from_slice(secret_binds);
   container.host_config = Some(HostConfig {
+    binds: Some(binds),
     network_mode: Some(
-      host_config.network_mode.unwrap_or("nanoclbr0".to_owned()),
+      host_config
+        .network_mode
+        .unwrap_or(get_network_name(UNSCOPED_NAMESPACE)),
     ),
     ..host_config
   });
@@ -47,6 +90,27 @@ async fn create_instance(
   .await
 }
 
+/// Steps of a job, when the job use containers they are converted
+/// to steps named by their index each depending on the previous one
+///
+pub fn get_steps(
+  steps: Option<&Vec<JobStep>>,
+  containers: &[Config],
+) -> Vec<JobStep> {
+  if let Some(steps) = steps {
+    return steps.clone();
+  }
+  containers
+    .iter()
+    .enumerate()
+    .map(|(index, container)| JobStep {
+      name: index.to_string(),
+      depends_on: index.checked_sub(1).map(|prev| vec![prev.to_string()]),
+      container: container.clone(),
+    })
+    .collect()
+}
+
 /// Create processes (container) for a job
 ///
 pub async fn create_instances(
@@ -54,22 +118,346 @@ pub async fn create_instances(
   state: &SystemState,
 ) -> IoResult<Vec<Process>> {
   let mut processes = Vec::new();
-  for (index, container) in job.containers.iter().enumerate() {
+  let steps = get_steps(job.steps.as_ref(), &job.containers);
+  let secret_binds = super::secret::mount(
+    &ProcessKind::Job,
+    &job.name,
+    job.secret_mounts.as_deref().unwrap_or_default(),
+    state,
+  )
+  .await?;
+  for (index, step) in steps.iter().enumerate() {
     super::image::download(
-      &container.image.clone().unwrap_or_default(),
+      &step.container.image.clone().unwrap_or_default(),
       job.image_pull_secret.clone(),
       job.image_pull_policy.clone().unwrap_or_default(),
       job,
       state,
     )
     .await?;
-    let process = create_instance(&job.name, index, container, state).await?;
+    let process =
+      create_instance(&job.name, index, step, &secret_binds, state).await?;
     processes.push(process);
   }
   Ok(processes)
 }
 
+/// Map the steps of a job to their process using the step label
+/// or the index in the name of the container for older instances
+fn get_step_processes(
+  job: &Job,
+  steps: &[JobStep],
+  processes: Vec<Process>,
+) -> HashMap<String, Process> {
+  processes
+    .into_iter()
+    .filter_map(|process| {
+      let label = process
+        .data
+        .config
+        .as_ref()
+        .and_then(|config| config.labels.as_ref())
+        .and_then(|labels| labels.get("io.nanocl.js").cloned());
+      let name = match label {
+        Some(name) => name,
+        None => {
+          let index = process
+            .name
+            .trim_start_matches('/')
+            .strip_prefix(&format!("{}-", job.name))?
+            .split('-')
+            .next()?
+            .parse::<usize>()
+            .ok()?;
+          steps.get(index)?.name.clone()
+        }
+      };
+      Some((name, process))
+    })
+    .collect()
+}
+
+/// Indexes of the pending steps with all their dependencies succeeded
+fn get_ready_steps(steps: &[JobStep], states: &[JobStepState]) -> Vec<usize> {
+  let status = steps
+    .iter()
+    .zip(states)
+    .map(|(step, state)| (step.name.as_str(), &state.status))
+    .collect::<HashMap<_, _>>();
+  steps
+    .iter()
+    .zip(states)
+    .enumerate()
+    .filter(|(_, (step, state))| {
+      state.status == JobStepStatus::Pending
+        && step.depends_on.iter().flatten().all(|dep| {
+          status.get(dep.as_str()) == Some(&&JobStepStatus::Succeeded)
+        })
+    })
+    .map(|(index, _)| index)
+    .collect()
+}
+
+/// Save the state of the steps of a job
+async fn save_steps(
+  key: &str,
+  states: &[JobStepState],
+  state: &SystemState,
+) -> IoResult<()> {
+  JobDb::update_pk(
+    key,
+    JobUpdateDb {
+      steps: Some(serde_json::to_value(states)?),
+      ..Default::default()
+    },
+    &state.inner.pool,
+  )
+  .await?;
+  Ok(())
+}
+
+/// Delay to wait before the next attempt after `failures` failed attempts
+fn backoff_delay(backoff: &JobBackoff, failures: usize) -> Duration {
+  let delay = backoff.delay.unwrap_or(DEFAULT_BACKOFF_DELAY);
+  let max_delay = backoff.max_delay.unwrap_or(DEFAULT_BACKOFF_MAX_DELAY);
+  let delay = match backoff.policy.clone().unwrap_or_default() {
+    JobBackoffPolicy::Fixed => delay,
+    JobBackoffPolicy::Exponential => {
+      let exp = failures.saturating_sub(1).min(u32::MAX as usize) as u32;
+      delay.saturating_mul(2_u64.saturating_pow(exp))
+    }
+  };
+  Duration::from_secs(delay.min(max_delay))
+}
+
+/// Start a job instance and wait for it to exit successfully
+/// within the container timeout and the active deadline of the job
+async fn run_instance(
+  process: &Process,
+  timeout: Option<Duration>,
+  deadline: Option<Instant>,
+  state: &SystemState,
+) -> Result<(), AttemptError> {
+  let _ = state
+    .inner
+    .docker_api
+    .start_container(&process.key, None::<StartContainerOptions<String>>)
+    .await;
+  let wait = async {
+    let mut stream = state.inner.docker_api.wait_container(
+      &process.key,
+      Some(WaitContainerOptions {
+        condition: "not-running",
+      }),
+    );
+    while let Some(res) = stream.next().await {
+      match res {
+        Ok(result) if result.status_code == 0 => break,
+        Ok(_) => continue,
+        Err(bollard_next::errors::Error::DockerContainerWaitError {
+          code,
+          ..
+        }) => {
+          return Err(AttemptError::Failed(format!(
+            "{} exited with code {code}",
+            process.name
+          )))
+        }
+        Err(err) => {
+          return Err(AttemptError::Internal(IoError::interrupted(
+            "JobCreate",
+            &format!("{err}"),
+          )))
+        }
+      }
+    }
+    Ok(())
+  };
+  let remaining =
+    deadline.map(|deadline| deadline.saturating_duration_since(Instant::now()));
+  let limit = match (timeout, remaining) {
+    (Some(timeout), Some(remaining)) => Some(timeout.min(remaining)),
+    (timeout, remaining) => timeout.or(remaining),
+  };
+  let Some(limit) = limit else {
+    return wait.await;
+  };
+  match ntex::time::timeout(limit, wait).await {
+    Ok(res) => res,
+    Err(_) => {
+      let _ = state
+        .inner
+        .docker_api
+        .stop_container(&process.key, None::<StopContainerOptions>)
+        .await;
+      if remaining.is_some_and(|remaining| remaining <= limit) {
+        return Err(AttemptError::DeadlineExceeded);
+      }
+      Err(AttemptError::Failed(format!(
+        "{} timed out after {}s",
+        process.name,
+        limit.as_secs()
+      )))
+    }
+  }
+}
+
+/// Run the pending steps of a job concurrently as soon as their dependencies succeed.
+/// After a failure no more step is started and the running ones are awaited
+async fn run_steps(
+  key: &str,
+  steps: &[JobStep],
+  processes: &HashMap<String, Process>,
+  states: &mut [JobStepState],
+  timeout: Option<Duration>,
+  deadline: Option<Instant>,
+  state: &SystemState,
+) -> Result<(), AttemptError> {
+  let mut running = FuturesUnordered::new();
+  let mut failure = None;
+  loop {
+    if failure.is_none() {
+      for index in get_ready_steps(steps, states) {
+        let name = &steps[index].name;
+        let process = processes.get(name).cloned().ok_or_else(|| {
+          IoError::not_found("JobStep", &format!("{name} has no instance"))
+        })?;
+        states[index] = JobStepState {
+          name: name.clone(),
+          status: JobStepStatus::Running,
+          started_at: Some(chrono::Utc::now().naive_utc()),
+          ..Default::default()
+        };
+        let state = state.clone();
+        running.push(async move {
+          let res = run_instance(&process, timeout, deadline, &state).await;
+          (index, res)
+        });
+      }
+      save_steps(key, states, state).await?;
+    }
+    let Some((index, res)) = running.next().await else {
+      break;
+    };
+    let step = &mut states[index];
+    step.finished_at = Some(chrono::Utc::now().naive_utc());
+    match res {
+      Ok(()) => step.status = JobStepStatus::Succeeded,
+      Err(err) => {
+        step.status = JobStepStatus::Failed;
+        step.note = Some(match &err {
+          AttemptError::Failed(reason) => reason.clone(),
+          AttemptError::DeadlineExceeded => {
+            "active deadline exceeded".to_owned()
+          }
+          AttemptError::Internal(err) => err.to_string(),
+        });
+        failure.get_or_insert(err);
+      }
+    }
+    save_steps(key, states, state).await?;
+  }
+  match failure {
+    Some(err) => Err(err),
+    None => Ok(()),
+  }
+}
+
+/// Start the jobs depending on a finished job
+/// once all their dependencies are finished
+async fn start_dependents(job: &Job, state: &SystemState) -> IoResult<()> {
+  let filter = GenericFilter::new().r#where(
+    "data",
+    GenericClause::Contains(serde_json::json!({ "DependsOn": [job.name] })),
+  );
+  let dependents = JobDb::transform_read_by(&filter, &state.inner.pool).await?;
+  for dependent in dependents {
+    let mut ready = true;
+    for dep in dependent.depends_on.iter().flatten() {
+      if *dep == job.name {
+        continue;
+      }
+      match JobDb::transform_read_by_pk(dep, &state.inner.pool).await {
+        Ok(dep) if dep.status.actual == ObjPsStatusKind::Finish => {}
+        _ => {
+          ready = false;
+          break;
+        }
+      }
+    }
+    if !ready {
+      continue;
+    }
+    log::debug!(
+      "job::start_dependents: {} starts {}",
+      job.name,
+      dependent.name
+    );
+    if let Err(err) =
+      super::generic::emit_starting(&dependent.name, &ProcessKind::Job, state)
+        .await
+    {
+      log::warn!("job::start_dependents: {} {err}", dependent.name);
+    }
+  }
+  Ok(())
+}
+
+/// Set the final status of a job run and remove the job after its ttl
+///
+pub async fn finish(
+  job: &Job,
+  failure: Option<String>,
+  state: &SystemState,
+) -> IoResult<()> {
+  match failure {
+    Some(reason) => {
+      ObjPsStatusDb::update_actual_status(
+        &job.name,
+        &ObjPsStatusKind::Fail,
+        &state.inner.pool,
+      )
+      .await?;
+      state
+        .emit_action_sync(
+          &job.clone().into(),
+          NativeEventAction::Fail,
+          EventKind::Normal,
+          "state_sync",
+          Some(reason),
+          None,
+        )
+        .await;
+    }
+    None => {
+      ObjPsStatusDb::update_actual_status(
+        &job.name,
+        &ObjPsStatusKind::Finish,
+        &state.inner.pool,
+      )
+      .await?;
+      state
+        .emit_normal_native_action_sync(job, NativeEventAction::Finish)
+        .await;
+      start_dependents(job, state).await?;
+    }
+  }
+  let Some(ttl) = job.ttl else {
+    return Ok(());
+  };
+  let job = job.clone();
+  let state = state.clone();
+  rt::spawn(async move {
+    log::debug!("job::finish: {} will be deleted in {ttl}s", job.name);
+    ntex::time::sleep(Duration::from_secs(ttl as u64)).await;
+    let _ = JobDb::del_obj_by_pk(&job.name, &(), &state).await;
+  });
+  Ok(())
+}
+
 /// Start job instances
+/// The steps run concurrently following their dependencies, failed steps are retried
+/// with a backoff until the backoff limit or the active deadline is reached
 ///
 pub async fn start(key: &str, state: &SystemState) -> IoResult<()> {
   let job = JobDb::transform_read_by_pk(&key, &state.inner.pool).await?;
@@ -78,6 +466,16 @@ pub async fn start(key: &str, state: &SystemState) -> IoResult<()> {
   if processes.is_empty() {
     processes = create_instances(&job, state).await?;
   }
+  let steps = get_steps(job.steps.as_ref(), &job.containers);
+  let processes = get_step_processes(&job, &steps, processes);
+  let mut states = steps
+    .iter()
+    .map(|step| JobStepState {
+      name: step.name.clone(),
+      ..Default::default()
+    })
+    .collect::<Vec<_>>();
+  save_steps(key, &states, state).await?;
   ObjPsStatusDb::update_actual_status(
     key,
     &ObjPsStatusKind::Start,
@@ -87,28 +485,103 @@ pub async fn start(key: &str, state: &SystemState) -> IoResult<()> {
   state
     .emit_normal_native_action_sync(&job, NativeEventAction::Start)
     .await;
-  for process in processes {
-    let _ = state
-      .inner
-      .docker_api
-      .start_container(&process.key, None::<StartContainerOptions<String>>)
-      .await;
-    // We currently run a sequential order so we wait for the container to finish to start the next one.
-    let mut stream = state.inner.docker_api.wait_container(
-      &process.key,
-      Some(WaitContainerOptions {
-        condition: "not-running",
-      }),
+  let deadline = job
+    .active_deadline_seconds
+    .map(|secs| Instant::now() + Duration::from_secs(secs));
+  let timeout = job.container_timeout_seconds.map(Duration::from_secs);
+  let backoff = job.backoff.clone().unwrap_or_default();
+  let backoff_limit = job.backoff_limit.unwrap_or_default();
+  let mut attempts = 0;
+  let mut internal = None;
+  let failure = loop {
+    attempts += 1;
+    JobDb::update_pk(
+      key,
+      JobUpdateDb {
+        attempts: Some(attempts as i32),
+        ..Default::default()
+      },
+      &state.inner.pool,
+    )
+    .await?;
+    // On retry only the failed steps run again
+    for step in states.iter_mut() {
+      if step.status == JobStepStatus::Failed {
+        step.status = JobStepStatus::Pending;
+      }
+    }
+    let res = run_steps(
+      key,
+      &steps,
+      &processes,
+      &mut states,
+      timeout,
+      deadline,
+      state,
+    )
+    .await;
+    let reason = match res {
+      Ok(()) => break None,
+      // The error is returned once the run is recorded as failed
+      Err(AttemptError::Internal(err)) => {
+        let reason = err.to_string();
+        internal = Some(err);
+        break Some(reason);
+      }
+      Err(AttemptError::DeadlineExceeded) => {
+        break Some("active deadline exceeded".to_owned())
+      }
+      Err(AttemptError::Failed(reason)) => reason,
+    };
+    JobDb::update_pk(
+      key,
+      JobUpdateDb {
+        last_failure: Some(Some(reason.clone())),
+        ..Default::default()
+      },
+      &state.inner.pool,
+    )
+    .await?;
+    if attempts > backoff_limit {
+      break Some(reason);
+    }
+    let delay = backoff_delay(&backoff, attempts);
+    if deadline.is_some_and(|deadline| Instant::now() + delay >= deadline) {
+      break Some(format!("{reason}, active deadline exceeded"));
+    }
+    state.emit_warning_native_action(
+      &job,
+      NativeEventAction::Start,
+      Some(format!(
+        "attempt {attempts} failed: {reason}, retrying in {}s",
+        delay.as_secs()
+      )),
     );
-    while let Some(stream) = stream.next().await {
-      let result = stream
-        .map_err(|err| IoError::interrupted("JobCreate", &format!("{err}")))?;
-      if result.status_code == 0 {
-        break;
+    ntex::time::sleep(delay).await;
+  };
+  if failure.is_some() {
+    for step in states.iter_mut() {
+      if step.status == JobStepStatus::Pending {
+        step.status = JobStepStatus::Skipped;
       }
     }
+    save_steps(key, &states, state).await?;
+  }
+  // A successful run clears the failure of the previous attempts
+  JobDb::update_pk(
+    key,
+    JobUpdateDb {
+      last_failure: Some(failure.clone()),
+      ..Default::default()
+    },
+    &state.inner.pool,
+  )
+  .await?;
+  finish(&job, failure, state).await?;
+  match internal {
+    Some(err) => Err(err),
+    None => Ok(()),
   }
-  Ok(())
 }
 
 /// Delete job instances and the job itself in the database
@@ -127,11 +600,69 @@ pub async fn delete(key: &str, state: &SystemState) -> IoResult<()> {
   .await?;
   log::debug!("JobDb::delete_by_pk({:?})", &job.name);
   JobDb::clear_by_pk(&job.name, &state.inner.pool).await?;
-  if job.schedule.is_some() {
-    utils::cron::remove_cron_rule(&job, state).await?;
-  }
+  super::secret::unmount(&ProcessKind::Job, &job.name, state);
   state
     .emit_normal_native_action_sync(&job, NativeEventAction::Destroy)
     .await;
   Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn backoff_delays() {
+    let backoff = JobBackoff::default();
+    assert_eq!(backoff_delay(&backoff, 1), Duration::from_secs(10));
+    assert_eq!(backoff_delay(&backoff, 3), Duration::from_secs(40));
+    assert_eq!(backoff_delay(&backoff, 100), Duration::from_secs(300));
+    let backoff = JobBackoff {
+      policy: Some(JobBackoffPolicy::Fixed),
+      delay: Some(5),
+      ..Default::default()
+    };
+    assert_eq!(backoff_delay(&backoff, 4), Duration::from_secs(5));
+  }
+
+  fn step(name: &str, depends_on: &[&str]) -> JobStep {
+    JobStep {
+      name: name.to_owned(),
+      depends_on: Some(depends_on.iter().map(|dep| dep.to_string()).collect()),
+      ..Default::default()
+    }
+  }
+
+  #[test]
+  fn containers_as_steps() {
+    let steps = get_steps(None, &[Config::default(), Config::default()]);
+    assert_eq!(steps[0].name, "0");
+    assert_eq!(steps[0].depends_on, None);
+    assert_eq!(steps[1].depends_on, Some(vec!["0".to_owned()]));
+  }
+
+  #[test]
+  fn ready_steps() {
+    let steps = vec![
+      step("build", &[]),
+      step("test", &["build"]),
+      step("lint", &["build"]),
+      step("deploy", &["test", "lint"]),
+    ];
+    let mut states = steps
+      .iter()
+      .map(|step| JobStepState {
+        name: step.name.clone(),
+        ..Default::default()
+      })
+      .collect::<Vec<_>>();
+    assert_eq!(get_ready_steps(&steps, &states), vec![0]);
+    states[0].status = JobStepStatus::Succeeded;
+    assert_eq!(get_ready_steps(&steps, &states), vec![1, 2]);
+    states[1].status = JobStepStatus::Succeeded;
+    states[2].status = JobStepStatus::Failed;
+    assert!(get_ready_steps(&steps, &states).is_empty());
+    states[2].status = JobStepStatus::Succeeded;
+    assert_eq!(get_ready_steps(&steps, &states), vec![3]);
+  }
+}
@@ -3,4 +3,6 @@ pub mod generic;
 pub mod image;
 pub mod job;
 pub mod process;
+pub mod replication;
+pub mod secret;
 pub mod vm;
@@ -0,0 +1,425 @@
+use nanocl_error::io::IoResult;
+use nanocl_stubs::{
+  cargo::Cargo,
+  cargo_spec::{ReplicationMode, ReplicationStatic},
+};
+
+use crate::{
+  models::SystemState,
+  utils::scheduler::{self, NodeLoad, Placement, Scheduler},
+};
+
+/// Keep only the names matching an allowed node
+fn existing_nodes(names: &[String], nodes: &[String]) -> Vec<String> {
+  let mut names = names
+    .iter()
+    .filter(|name| nodes.contains(name))
+    .cloned()
+    .collect::<Vec<_>>();
+  names.sort();
+  names.dedup();
+  names
+}
+
+/// Plan `number` instances on the best candidates chosen by the scheduler
+fn place(
+  scheduler: &Scheduler,
+  candidates: &[String],
+  number: usize,
+  placement: &mut Placement,
+) {
+  for _ in 0..number {
+    let Some(node) = scheduler.pick(candidates, placement) else {
+      return;
+    };
+    *placement.entry(node).or_default() += 1;
+  }
+}
+
+/// Compute the placement of the instances of a cargo for the given replication mode.
+/// The nodes are the ones allowed by the scheduler
+/// which also choose the best nodes when the mode let it free to choose.
+pub fn compute_placement(
+  mode: Option<&ReplicationMode>,
+  scheduler: &Scheduler,
+) -> Placement {
+  let mut nodes = scheduler.nodes();
+  nodes.sort();
+  nodes.dedup();
+  let mut placement = Placement::new();
+  match mode {
+    None | Some(ReplicationMode::Unique) => {
+      place(scheduler, &nodes, 1, &mut placement);
+    }
+    Some(ReplicationMode::Static(replication)) => {
+      place(scheduler, &nodes, replication.number, &mut placement);
+    }
+    Some(ReplicationMode::Auto | ReplicationMode::UniqueByNode) => {
+      for node in &nodes {
+        placement.insert(node.clone(), 1);
+      }
+    }
+    Some(ReplicationMode::StaticByNodes(replication)) => {
+      for node in &nodes {
+        placement.insert(node.clone(), replication.number);
+      }
+    }
+    Some(ReplicationMode::UniqueByNodeNames { names }) => {
+      for node in existing_nodes(names, &nodes) {
+        placement.insert(node, 1);
+      }
+    }
+    Some(ReplicationMode::StaticByNodeNames { names, number }) => {
+      let number = (*number).max(0) as usize;
+      for node in existing_nodes(names, &nodes) {
+        placement.insert(node, number);
+      }
+    }
+    Some(ReplicationMode::UniqueByNodeGroups { groups }) => {
+      for group in groups {
+        let members = scheduler.group_nodes(group);
+        // Prefer a node not already used by another group
+        let free = members
+          .iter()
+          .filter(|node| !placement.contains_key(*node))
+          .cloned()
+          .collect::<Vec<_>>();
+        let candidates = if free.is_empty() { members } else { free };
+        place(scheduler, &candidates, 1, &mut placement);
+      }
+    }
+    Some(ReplicationMode::StaticByNodeGroups { groups, number }) => {
+      let number = (*number).max(0) as usize;
+      for group in groups {
+        let members = scheduler.group_nodes(group);
+        place(scheduler, &members, number, &mut placement);
+      }
+    }
+  }
+  for (node, number) in placement.iter_mut() {
+    *number = scheduler.limit(node, *number);
+  }
+  placement.retain(|_, number| *number > 0);
+  placement
+}
+
+/// Number of instances of a replication mode in the whole cluster
+/// knowing its nodes with their groups
+pub fn count_instances(
+  mode: Option<&ReplicationMode>,
+  nodes: &[NodeLoad],
+) -> usize {
+  let mut names = nodes
+    .iter()
+    .map(|node| node.name.clone())
+    .collect::<Vec<_>>();
+  names.sort();
+  names.dedup();
+  let matching_groups = |groups: &[String]| {
+    groups
+      .iter()
+      .filter(|group| nodes.iter().any(|node| node.groups.contains(group)))
+      .count()
+  };
+  match mode {
+    None | Some(ReplicationMode::Unique) => 1,
+    Some(ReplicationMode::Static(replication)) => replication.number,
+    Some(ReplicationMode::Auto | ReplicationMode::UniqueByNode) => names.len(),
+    Some(ReplicationMode::StaticByNodes(replication)) => {
+      replication.number * names.len()
+    }
+    Some(ReplicationMode::UniqueByNodeNames { names: wanted }) => {
+      existing_nodes(wanted, &names).len()
+    }
+    Some(ReplicationMode::StaticByNodeNames {
+      names: wanted,
+      number,
+    }) => (*number).max(0) as usize * existing_nodes(wanted, &names).len(),
+    Some(ReplicationMode::UniqueByNodeGroups { groups }) => {
+      matching_groups(groups)
+    }
+    Some(ReplicationMode::StaticByNodeGroups { groups, number }) => {
+      (*number).max(0) as usize * matching_groups(groups)
+    }
+  }
+}
+
+/// Number of replicas of a replication mode if it can be scaled
+pub fn get_replicas(mode: Option<&ReplicationMode>) -> Option<usize> {
+  match mode {
+    None | Some(ReplicationMode::Unique) => Some(1),
+    Some(
+      ReplicationMode::Static(replication)
+      | ReplicationMode::StaticByNodes(replication),
+    ) => Some(replication.number),
+    Some(
+      ReplicationMode::StaticByNodeNames { number, .. }
+      | ReplicationMode::StaticByNodeGroups { number, .. },
+    ) => Some((*number).max(0) as usize),
+    _ => None,
+  }
+}
+
+/// Replace the number of replicas of a replication mode.
+/// The modes without a number of replicas are left untouched.
+pub fn with_replicas(
+  mode: Option<&ReplicationMode>,
+  replicas: usize,
+) -> Option<ReplicationMode> {
+  let mode = match mode {
+    None | Some(ReplicationMode::Unique) | Some(ReplicationMode::Static(_)) => {
+      ReplicationMode::Static(ReplicationStatic { number: replicas })
+    }
+    Some(ReplicationMode::StaticByNodes(_)) => {
+      ReplicationMode::StaticByNodes(ReplicationStatic { number: replicas })
+    }
+    Some(ReplicationMode::StaticByNodeNames { names, .. }) => {
+      ReplicationMode::StaticByNodeNames {
+        names: names.clone(),
+        number: replicas as i64,
+      }
+    }
+    Some(ReplicationMode::StaticByNodeGroups { groups, .. }) => {
+      ReplicationMode::StaticByNodeGroups {
+        groups: groups.clone(),
+        number: replicas as i64,
+      }
+    }
+    Some(mode) => mode.clone(),
+  };
+  Some(mode)
+}
+
+/// Replication mode of a cargo,
+/// the number of replicas chosen by the autoscaler override the spec
+pub fn get_replication(cargo: &Cargo) -> Option<ReplicationMode> {
+  match cargo.replicas {
+    Some(replicas) if cargo.spec.autoscale.is_some() => {
+      with_replicas(cargo.spec.replication.as_ref(), replicas)
+    }
+    _ => cargo.spec.replication.clone(),
+  }
+}
+
+/// Resolve the placement of the instances of a cargo across the cluster
+/// using the scheduler to score the nodes.
+pub async fn get_placement(
+  cargo: &Cargo,
+  state: &SystemState,
+) -> IoResult<Placement> {
+  let loads = scheduler::read_node_loads(state).await?;
+  let scheduler = Scheduler::new(
+    &cargo.spec.cargo_key,
+    &state.inner.config.hostname,
+    loads,
+    cargo.spec.node_affinity.as_ref(),
+    cargo.spec.node_anti_affinity.as_ref(),
+  );
+  let mode = get_replication(cargo);
+  let placement = compute_placement(mode.as_ref(), &scheduler);
+  log::debug!(
+    "replication::get_placement: {} {placement:?}",
+    cargo.spec.cargo_key
+  );
+  Ok(placement)
+}
+
+#[cfg(test)]
+mod tests {
+  use std::collections::HashMap;
+
+  use crate::utils::scheduler::NodeLoad;
+
+  use super::*;
+
+  fn scheduler(current: Option<&str>) -> Scheduler {
+    let loads = [
+      ("node-b", vec!["edge"]),
+      ("node-a", vec!["edge"]),
+      ("node-c", vec!["core"]),
+    ]
+    .into_iter()
+    .map(|(name, groups)| NodeLoad {
+      name: name.to_owned(),
+      groups: groups.into_iter().map(String::from).collect(),
+      processes: match current {
+        Some(node) if node == name => {
+          HashMap::from([("api.global".to_owned(), 1)])
+        }
+        _ => HashMap::new(),
+      },
+      ..Default::default()
+    })
+    .collect();
+    Scheduler::new("api.global", "node-b", loads, None, None)
+  }
+
+  #[test]
+  fn unique_prefers_current_then_local() {
+    let placement = compute_placement(
+      Some(&ReplicationMode::Unique),
+      &scheduler(Some("node-c")),
+    );
+    assert_eq!(placement, Placement::from([("node-c".to_owned(), 1)]));
+    let placement =
+      compute_placement(Some(&ReplicationMode::Unique), &scheduler(None));
+    assert_eq!(placement, Placement::from([("node-b".to_owned(), 1)]));
+  }
+
+  #[test]
+  fn by_nodes_and_names() {
+    let placement =
+      compute_placement(Some(&ReplicationMode::UniqueByNode), &scheduler(None));
+    assert_eq!(placement.len(), 3);
+    assert!(placement.values().all(|number| *number == 1));
+    let placement = compute_placement(
+      Some(&ReplicationMode::StaticByNodes(ReplicationStatic {
+        number: 2,
+      })),
+      &scheduler(None),
+    );
+    assert!(placement.values().all(|number| *number == 2));
+    let placement = compute_placement(
+      Some(&ReplicationMode::StaticByNodeNames {
+        names: vec!["node-a".to_owned(), "unknown".to_owned()],
+        number: 3,
+      }),
+      &scheduler(None),
+    );
+    assert_eq!(placement, Placement::from([("node-a".to_owned(), 3)]));
+  }
+
+  #[test]
+  fn by_node_groups() {
+    let placement = compute_placement(
+      Some(&ReplicationMode::UniqueByNodeGroups {
+        groups: vec!["edge".to_owned(), "core".to_owned()],
+      }),
+      &scheduler(None),
+    );
+    assert_eq!(
+      placement,
+      Placement::from([("node-b".to_owned(), 1), ("node-c".to_owned(), 1)])
+    );
+    let placement = compute_placement(
+      Some(&ReplicationMode::StaticByNodeGroups {
+        groups: vec!["edge".to_owned()],
+        number: 3,
+      }),
+      &scheduler(None),
+    );
+    assert_eq!(
+      placement,
+      Placement::from([("node-a".to_owned(), 1), ("node-b".to_owned(), 2)])
+    );
+  }
+
+  fn cordoned_scheduler(current: Option<&str>, cordoned: &[&str]) -> Scheduler {
+    let loads = ["node-a", "node-b", "node-c"]
+      .into_iter()
+      .map(|name| NodeLoad {
+        name: name.to_owned(),
+        cordoned: cordoned.contains(&name),
+        processes: match current {
+          Some(node) if node == name => {
+            HashMap::from([("api.global".to_owned(), 1)])
+          }
+          _ => HashMap::new(),
+        },
+        ..Default::default()
+      })
+      .collect();
+    Scheduler::new("api.global", "node-b", loads, None, None)
+  }
+
+  #[test]
+  fn cordoned_nodes_keep_their_instances() {
+    let placement = compute_placement(
+      Some(&ReplicationMode::Unique),
+      &cordoned_scheduler(None, &["node-b"]),
+    );
+    assert_eq!(placement, Placement::from([("node-a".to_owned(), 1)]));
+    let placement = compute_placement(
+      Some(&ReplicationMode::Unique),
+      &cordoned_scheduler(Some("node-c"), &["node-c"]),
+    );
+    assert_eq!(placement, Placement::from([("node-c".to_owned(), 1)]));
+    let placement = compute_placement(
+      Some(&ReplicationMode::StaticByNodes(ReplicationStatic {
+        number: 2,
+      })),
+      &cordoned_scheduler(Some("node-c"), &["node-a", "node-c"]),
+    );
+    assert_eq!(
+      placement,
+      Placement::from([("node-b".to_owned(), 2), ("node-c".to_owned(), 1)])
+    );
+  }
+
+  #[test]
+  fn instances_in_cluster() {
+    let nodes = [
+      ("node-a", vec!["edge"]),
+      ("node-b", vec!["edge"]),
+      ("node-c", vec!["core"]),
+    ]
+    .into_iter()
+    .map(|(name, groups)| NodeLoad {
+      name: name.to_owned(),
+      groups: groups.into_iter().map(String::from).collect(),
+      ..Default::default()
+    })
+    .collect::<Vec<_>>();
+    let count = |mode: ReplicationMode| count_instances(Some(&mode), &nodes);
+    assert_eq!(count_instances(None, &nodes), 1);
+    assert_eq!(count(ReplicationMode::UniqueByNode), 3);
+    assert_eq!(
+      count(ReplicationMode::StaticByNodes(ReplicationStatic {
+        number: 2
+      })),
+      6
+    );
+    assert_eq!(
+      count(ReplicationMode::StaticByNodeNames {
+        names: vec!["node-a".to_owned(), "node-z".to_owned()],
+        number: 4,
+      }),
+      4
+    );
+    assert_eq!(
+      count(ReplicationMode::UniqueByNodeGroups {
+        groups: vec!["edge".to_owned(), "core".to_owned(), "gpu".to_owned()],
+      }),
+      2
+    );
+    assert_eq!(
+      count(ReplicationMode::StaticByNodeGroups {
+        groups: vec!["edge".to_owned()],
+        number: 3,
+      }),
+      3
+    );
+  }
+
+  #[test]
+  fn replace_replicas() {
+    assert_eq!(get_replicas(None), Some(1));
+    assert_eq!(get_replicas(Some(&ReplicationMode::UniqueByNode)), None);
+    let mode = with_replicas(Some(&ReplicationMode::Unique), 3);
+    assert_eq!(get_replicas(mode.as_ref()), Some(3));
+    let mode = with_replicas(
+      Some(&ReplicationMode::StaticByNodeGroups {
+        groups: vec!["edge".to_owned()],
+        number: 1,
+      }),
+      2,
+    );
+    assert_eq!(
+      mode,
+      Some(ReplicationMode::StaticByNodeGroups {
+        groups: vec!["edge".to_owned()],
+        number: 2,
+      })
+    );
+  }
+}
@@ -0,0 +1,267 @@
+use std::{collections::HashMap, fs, os::unix::fs::PermissionsExt, path::Path};
+
+use nanocl_error::io::{FromIo, IoError, IoResult};
+use nanocl_stubs::{
+  generic::{GenericClause, GenericFilter},
+  process::ProcessKind,
+  secret::SecretMount,
+};
+
+use crate::models::{SecretDb, SystemState};
+
+/// Kind of the secrets that can be mounted as files
+pub const FILE_KIND: &str = "nanocl.io/file";
+/// Runtime directory where the decrypted files are written,
+/// outside of the state dir to never persist them or back them up
+const SECRET_DIR: &str = "/run/nanocl/secrets";
+/// Permissions of the mounted files when not set
+const DEFAULT_MODE: u32 = 0o400;
+
+/// Parse the files of a `nanocl.io/file` secret as a map of name to content
+pub fn parse_files(
+  data: &serde_json::Value,
+) -> IoResult<HashMap<String, String>> {
+  let files = serde_json::from_value::<HashMap<String, String>>(data.clone())
+    .map_err(|err| err.map_err_context(|| "SecretFile"))?;
+  if let Some(name) = files.keys().find(|name| {
+    name.is_empty() || name.contains('/') || *name == "." || *name == ".."
+  }) {
+    return Err(IoError::invalid_input(
+      "SecretFile",
+      &format!("invalid file name {name:?}"),
+    ));
+  }
+  Ok(files)
+}
+
+/// Parse the permissions of the files written in octal like `0440`
+pub fn parse_mode(mode: Option<&str>) -> IoResult<u32> {
+  let Some(mode) = mode else {
+    return Ok(DEFAULT_MODE);
+  };
+  match u32::from_str_radix(mode, 8) {
+    Ok(mode) if mode <= 0o777 => Ok(mode),
+    _ => Err(IoError::invalid_input(
+      "SecretMount",
+      &format!("invalid mode {mode} expected an octal like 0440"),
+    )),
+  }
+}
+
+/// Validate the secret mounts of a cargo or a job
+pub fn validate_mounts(mounts: &[SecretMount]) -> IoResult<()> {
+  for mount in mounts {
+    parse_mode(mount.mode.as_deref())?;
+    if !mount.target.starts_with('/') {
+      return Err(IoError::invalid_input(
+        "SecretMount",
+        &format!("target {} must be an absolute path", mount.target),
+      ));
+    }
+  }
+  Ok(())
+}
+
+/// Directory where the secret files of a cargo or a job are written
+fn get_dir(kind: &ProcessKind, key: &str) -> String {
+  format!("{SECRET_DIR}/{kind}/{key}")
+}
+
+/// Directory where the secret files were written in the state dir
+/// by the previous versions
+fn get_legacy_dir(
+  kind: &ProcessKind,
+  key: &str,
+  state: &SystemState,
+) -> String {
+  format!("{}/secrets/{kind}/{key}", state.inner.config.state_dir)
+}
+
+/// Create the directory of the secret files of a cargo or a job
+/// only reachable by the daemon
+fn create_dir(dir: &str) -> IoResult<()> {
+  fs::create_dir_all(dir).map_err(|err| err.map_err_context(|| dir))?;
+  let mut path = Path::new(dir);
+  while path.starts_with(SECRET_DIR) {
+    fs::set_permissions(path, fs::Permissions::from_mode(0o700))
+      .map_err(|err| err.map_err_context(|| path.display()))?;
+    let Some(parent) = path.parent() else {
+      break;
+    };
+    path = parent;
+  }
+  Ok(())
+}
+
+/// Remove a directory of secret files if it exists
+fn remove_dir(dir: &str) {
+  if Path::new(dir).exists() {
+    if let Err(err) = fs::remove_dir_all(dir) {
+      log::warn!("secret::remove_dir: {dir} {err}");
+    }
+  }
+}
+
+/// Write the files in the directory, existing files are overwritten in place
+/// so the containers already mounting them see the new content.
+/// The directory is the mount point in the containers so it stays traversable
+/// for their users, its parents keep it out of reach on the host.
+fn write_files(
+  dir: &str,
+  files: &HashMap<String, String>,
+  mode: u32,
+) -> IoResult<()> {
+  fs::create_dir_all(dir).map_err(|err| err.map_err_context(|| dir))?;
+  fs::set_permissions(dir, fs::Permissions::from_mode(0o755))
+    .map_err(|err| err.map_err_context(|| dir))?;
+  for entry in fs::read_dir(dir).map_err(|err| err.map_err_context(|| dir))? {
+    let path = entry?.path();
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    if !files.contains_key(name.as_ref()) {
+      fs::remove_file(&path)?;
+    }
+  }
+  for (name, content) in files {
+    let path = format!("{dir}/{name}");
+    if Path::new(&path).exists() {
+      fs::set_permissions(&path, fs::Permissions::from_mode(0o600))
+        .map_err(|err| err.map_err_context(|| &path))?;
+    }
+    fs::write(&path, content).map_err(|err| err.map_err_context(|| &path))?;
+    fs::set_permissions(&path, fs::Permissions::from_mode(mode))
+      .map_err(|err| err.map_err_context(|| &path))?;
+  }
+  Ok(())
+}
+
+/// Remove the directories of the mounts dropped from a spec
+fn remove_stale_mounts(base: &str, count: usize) -> IoResult<()> {
+  for entry in fs::read_dir(base).map_err(|err| err.map_err_context(|| base))? {
+    let path = entry?.path();
+    let index = path.file_name().unwrap_or_default().to_string_lossy();
+    if index.parse::<usize>().map_or(true, |index| index >= count) {
+      fs::remove_dir_all(&path)
+        .map_err(|err| err.map_err_context(|| path.display()))?;
+    }
+  }
+  Ok(())
+}
+
+/// Whether the secret files of a cargo or a job are written
+pub fn is_mounted(kind: &ProcessKind, key: &str) -> bool {
+  Path::new(&get_dir(kind, key)).exists()
+}
+
+/// Write the files of the secrets mounted by a cargo or a job
+/// and return the read-only binds to add to its containers
+pub async fn mount(
+  kind: &ProcessKind,
+  key: &str,
+  mounts: &[SecretMount],
+  state: &SystemState,
+) -> IoResult<Vec<String>> {
+  remove_dir(&get_legacy_dir(kind, key, state));
+  let base = get_dir(kind, key);
+  if mounts.is_empty() {
+    remove_dir(&base);
+    return Ok(Vec::new());
+  }
+  let names = mounts.iter().map(|m| m.secret.clone()).collect::<Vec<_>>();
+  let filter =
+    GenericFilter::new().r#where("key", GenericClause::In(names.clone()));
+  let secrets = SecretDb::read_decrypted_by(&filter, state)
+    .await?
+    .into_iter()
+    .map(|secret| (secret.name.clone(), secret))
+    .collect::<HashMap<_, _>>();
+  create_dir(&base)?;
+  remove_stale_mounts(&base, mounts.len())?;
+  let mut binds = Vec::new();
+  for (index, mount) in mounts.iter().enumerate() {
+    let secret = secrets
+      .get(&mount.secret)
+      .ok_or_else(|| IoError::not_found("Secret", &mount.secret))?;
+    if secret.kind != FILE_KIND {
+      return Err(IoError::invalid_input(
+        "SecretMount",
+        &format!("secret {} is not of kind {FILE_KIND}", secret.name),
+      ));
+    }
+    let files = parse_files(&secret.data)?;
+    let mode = parse_mode(mount.mode.as_deref())?;
+    let dir = format!("{base}/{index}");
+    write_files(&dir, &files, mode)?;
+    binds.push(format!("{dir}:{}:ro", mount.target));
+  }
+  Ok(binds)
+}
+
+/// Remove the secret files written for a cargo or a job
+pub fn unmount(kind: &ProcessKind, key: &str, state: &SystemState) {
+  remove_dir(&get_dir(kind, key));
+  remove_dir(&get_legacy_dir(kind, key, state));
+}
+
+#[cfg(test)]
+mod tests {
+  use serde_json::json;
+
+  use super::*;
+
+  #[test]
+  fn modes() {
+    assert_eq!(parse_mode(None).unwrap(), 0o400);
+    assert_eq!(parse_mode(Some("0440")).unwrap(), 0o440);
+    assert_eq!(parse_mode(Some("644")).unwrap(), 0o644);
+    assert!(parse_mode(Some("0999")).is_err());
+    assert!(parse_mode(Some("1777")).is_err());
+  }
+
+  #[test]
+  fn files() {
+    let files = parse_files(&json!({ "app.conf": "key=value" })).unwrap();
+    assert_eq!(files.get("app.conf").unwrap(), "key=value");
+    assert!(parse_files(&json!({ "../etc/passwd": "" })).is_err());
+    assert!(parse_files(&json!({ "..": "" })).is_err());
+    assert!(parse_files(&json!(["key=value"])).is_err());
+  }
+
+  #[test]
+  fn write_and_update() {
+    let dir = std::env::temp_dir()
+      .join(format!("nanocl-secret-{}", std::process::id()))
+      .to_string_lossy()
+      .to_string();
+    let files = HashMap::from([
+      ("a".to_owned(), "1".to_owned()),
+      ("b".to_owned(), "2".to_owned()),
+    ]);
+    write_files(&dir, &files, 0o440).unwrap();
+    let mode = fs::metadata(format!("{dir}/a"))
+      .unwrap()
+      .permissions()
+      .mode();
+    assert_eq!(mode & 0o777, 0o440);
+    let files = HashMap::from([("a".to_owned(), "3".to_owned())]);
+    write_files(&dir, &files, 0o400).unwrap();
+    assert_eq!(fs::read_to_string(format!("{dir}/a")).unwrap(), "3");
+    assert!(!Path::new(&format!("{dir}/b")).exists());
+    fs::remove_dir_all(&dir).unwrap();
+  }
+
+  #[test]
+  fn stale_mounts() {
+    let base = std::env::temp_dir()
+      .join(format!("nanocl-secret-mounts-{}", std::process::id()))
+      .to_string_lossy()
+      .to_string();
+    for index in 0..3 {
+      fs::create_dir_all(format!("{base}/{index}")).unwrap();
+    }
+    remove_stale_mounts(&base, 1).unwrap();
+    assert!(Path::new(&format!("{base}/0")).exists());
+    assert!(!Path::new(&format!("{base}/1")).exists());
+    assert!(!Path::new(&format!("{base}/2")).exists());
+    fs::remove_dir_all(&base).unwrap();
+  }
+}
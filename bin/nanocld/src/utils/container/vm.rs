@@ -5,6 +5,7 @@ use bollard_next::secret::{DeviceMapping, HostConfig};
 use nanocl_error::io::IoResult;
 use nanocl_stubs::{
   generic::ImagePullPolicy,
+  namespace::get_network_name,
   process::{Process, ProcessKind},
   system::NativeEventAction,
   vm::Vm,
@@ -114,7 +115,7 @@ pub async fn create_instance(
           .host_config
           .runtime_network
           .clone()
-          .unwrap_or("nanoclbr0".to_owned()),
+          .unwrap_or(get_network_name(&vm.namespace_name)),
       ),
       binds: Some(vec![format!("{img_path}:{img_path}")]),
       devices: Some(devices),
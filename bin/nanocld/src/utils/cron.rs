@@ -1,82 +1,227 @@
-use ntex::web;
-use tokio::{fs, io::AsyncWriteExt};
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, Timelike};
 
-use nanocl_error::io::{FromIo, IoError, IoResult};
-use nanocl_stubs::job::Job;
+use nanocl_error::io::{IoError, IoResult};
 
-use crate::{models::SystemState, vars};
+/// Number of years searched for the next occurrence of a schedule
+const MAX_YEARS: i32 = 5;
 
-/// Format the cron job command to start a job at a given time
-fn format_cron_job_command(job: &Job, state: &SystemState) -> String {
-  let host = state
-    .inner
-    .config
-    .hosts
-    .first()
-    .cloned()
-    .unwrap_or("unix:///run/nanocl/nanocl.sock".to_owned())
-    .replace("unix://", "");
-  format!(
-    "curl -X POST --unix {host} http://localhost/v{}/processes/job/{}/start",
-    vars::VERSION,
-    &job.name
-  )
+const MONTHS: [&str; 12] = [
+  "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov",
+  "dec",
+];
+
+const DAYS: [&str; 7] = ["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+
+/// A cron schedule parsed from a standard 5 fields expression
+/// `minute hour day-of-month month day-of-week` evaluated in UTC.
+/// Each field is stored as a bit set of the allowed values.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronSchedule {
+  minutes: u64,
+  hours: u64,
+  days_of_month: u64,
+  months: u64,
+  days_of_week: u64,
+  /// Day of month or day of week is restricted
+  /// in that case a day match if any of them match like crond does
+  day_or: bool,
 }
 
-/// Execute the crontab command to update the cron jobs
-async fn exec_crontab() -> IoResult<()> {
-  web::block(|| {
-    std::process::Command::new("crontab")
-      .arg("/tmp/crontab")
-      .output()
-      .map_err(|err| err.map_err_context(|| "Cron job"))?;
-    Ok::<_, IoError>(())
-  })
-  .await?;
-  Ok(())
+/// Parse a value of a field that can be a number or a name
+fn parse_value(
+  value: &str,
+  min: u32,
+  max: u32,
+  names: &[&str],
+) -> IoResult<u32> {
+  let lower = value.to_lowercase();
+  if let Some(index) = names.iter().position(|name| *name == lower) {
+    return Ok(index as u32 + min);
+  }
+  let value = value.parse::<u32>().map_err(|_| {
+    IoError::invalid_input("CronSchedule", &format!("invalid value {value}"))
+  })?;
+  if value < min || value > max {
+    return Err(IoError::invalid_input(
+      "CronSchedule",
+      &format!("value {value} out of range {min}-{max}"),
+    ));
+  }
+  Ok(value)
 }
 
-/// Add a cron rule to the crontab to start a job at a given time
-pub async fn add_cron_rule(
-  item: &Job,
-  schedule: &str,
-  state: &SystemState,
-) -> IoResult<()> {
-  let cmd = format_cron_job_command(item, state);
-  let cron_rule = format!("{} {cmd}", schedule);
-  log::debug!("Creating cron rule: {cron_rule}");
-  fs::copy("/var/spool/cron/crontabs/root", "/tmp/crontab")
-    .await
-    .map_err(|err| err.map_err_context(|| "Cron job"))?;
-  let mut file = fs::OpenOptions::new()
-    .write(true)
-    .append(true)
-    .open("/tmp/crontab")
-    .await
-    .map_err(|err| err.map_err_context(|| "Cron job"))?;
-  file
-    .write_all(format!("{cron_rule}\n").as_bytes())
-    .await
-    .map_err(|err| err.map_err_context(|| "Cron job"))?;
-  exec_crontab().await?;
-  Ok(())
+/// Parse a field made of comma separated `*`, `a`, `a-b` with an optional `/step`
+fn parse_field(
+  field: &str,
+  min: u32,
+  max: u32,
+  names: &[&str],
+) -> IoResult<u64> {
+  let mut bits = 0;
+  for part in field.split(',') {
+    let (range, step) = match part.split_once('/') {
+      Some((range, step)) => {
+        let step = step.parse::<u32>().ok().filter(|step| *step > 0).ok_or(
+          IoError::invalid_input(
+            "CronSchedule",
+            &format!("invalid step {step}"),
+          ),
+        )?;
+        (range, Some(step))
+      }
+      None => (part, None),
+    };
+    let (start, end) = match range {
+      "*" => (min, max),
+      range => match range.split_once('-') {
+        Some((start, end)) => (
+          parse_value(start, min, max, names)?,
+          parse_value(end, min, max, names)?,
+        ),
+        None => {
+          let start = parse_value(range, min, max, names)?;
+          // `a/n` means from a to the end every n
+          (start, if step.is_some() { max } else { start })
+        }
+      },
+    };
+    if start > end {
+      return Err(IoError::invalid_input(
+        "CronSchedule",
+        &format!("invalid range {range}"),
+      ));
+    }
+    for value in (start..=end).step_by(step.unwrap_or(1) as usize) {
+      bits |= 1 << value;
+    }
+  }
+  Ok(bits)
 }
 
-/// Remove a cron rule from the crontab for the given job
-pub async fn remove_cron_rule(item: &Job, state: &SystemState) -> IoResult<()> {
-  let mut content = fs::read_to_string("/var/spool/cron/crontabs/root")
-    .await
-    .map_err(|err| err.map_err_context(|| "Cron job"))?;
-  let cmd = format_cron_job_command(item, state);
-  log::debug!("Removing cron rule: {cmd}");
-  content = content
-    .lines()
-    .filter(|line| !line.contains(&cmd))
-    .collect::<Vec<_>>()
-    .join("\n");
-  fs::write("/tmp/crontab", format!("{content}\n"))
-    .await
-    .map_err(|err| err.map_err_context(|| "Cron job"))?;
-  exec_crontab().await?;
-  Ok(())
+impl CronSchedule {
+  /// Parse a cron expression with 5 fields or a macro like `@daily`
+  pub fn parse(expr: &str) -> IoResult<Self> {
+    let expr = match expr.trim() {
+      "@yearly" | "@annually" => "0 0 1 1 *",
+      "@monthly" => "0 0 1 * *",
+      "@weekly" => "0 0 * * 0",
+      "@daily" | "@midnight" => "0 0 * * *",
+      "@hourly" => "0 * * * *",
+      expr => expr,
+    };
+    let fields = expr.split_whitespace().collect::<Vec<_>>();
+    let [minute, hour, dom, month, dow] = fields[..] else {
+      return Err(IoError::invalid_input(
+        "CronSchedule",
+        &format!("expected 5 fields got {}", fields.len()),
+      ));
+    };
+    let mut days_of_week = parse_field(dow, 0, 7, &DAYS)?;
+    // 7 is an alias for sunday
+    if days_of_week & (1 << 7) != 0 {
+      days_of_week = (days_of_week | 1) & !(1 << 7);
+    }
+    Ok(Self {
+      minutes: parse_field(minute, 0, 59, &[])?,
+      hours: parse_field(hour, 0, 23, &[])?,
+      days_of_month: parse_field(dom, 1, 31, &[])?,
+      months: parse_field(month, 1, 12, &MONTHS)?,
+      days_of_week,
+      day_or: !dom.starts_with('*') && !dow.starts_with('*'),
+    })
+  }
+
+  fn match_day(&self, date: &NaiveDate) -> bool {
+    let dom = self.days_of_month & (1 << date.day()) != 0;
+    let dow =
+      self.days_of_week & (1 << date.weekday().num_days_from_sunday()) != 0;
+    if self.day_or {
+      dom || dow
+    } else {
+      dom && dow
+    }
+  }
+
+  /// Next time matching the schedule strictly after the given time
+  pub fn next_after(&self, after: &NaiveDateTime) -> Option<NaiveDateTime> {
+    let mut time =
+      after.with_second(0)?.with_nanosecond(0)? + Duration::try_minutes(1)?;
+    let limit = after.year() + MAX_YEARS;
+    while time.year() <= limit {
+      let date = time.date();
+      if self.months & (1 << time.month()) == 0 {
+        let (year, month) = if time.month() == 12 {
+          (time.year() + 1, 1)
+        } else {
+          (time.year(), time.month() + 1)
+        };
+        time = NaiveDate::from_ymd_opt(year, month, 1)?.and_hms_opt(0, 0, 0)?;
+        continue;
+      }
+      if !self.match_day(&date) {
+        time = date.succ_opt()?.and_hms_opt(0, 0, 0)?;
+        continue;
+      }
+      if self.hours & (1 << time.hour()) == 0 {
+        time = time.with_minute(0)? + Duration::try_hours(1)?;
+        continue;
+      }
+      if self.minutes & (1 << time.minute()) == 0 {
+        time += Duration::try_minutes(1)?;
+        continue;
+      }
+      return Some(time);
+    }
+    None
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn date(s: &str) -> NaiveDateTime {
+    NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap()
+  }
+
+  #[test]
+  fn parse_expressions() {
+    assert!(CronSchedule::parse("* * * * *").is_ok());
+    assert!(CronSchedule::parse("*/15 0-6,22 1 jan-mar mon-fri").is_ok());
+    assert!(CronSchedule::parse("@daily").is_ok());
+    assert!(CronSchedule::parse("* * * *").is_err());
+    assert!(CronSchedule::parse("60 * * * *").is_err());
+    assert!(CronSchedule::parse("*/0 * * * *").is_err());
+  }
+
+  #[test]
+  fn next_occurrences() {
+    let every_5 = CronSchedule::parse("*/5 * * * *").unwrap();
+    assert_eq!(
+      every_5.next_after(&date("2024-01-01 10:02")),
+      Some(date("2024-01-01 10:05"))
+    );
+    assert_eq!(
+      every_5.next_after(&date("2024-01-01 10:05")),
+      Some(date("2024-01-01 10:10"))
+    );
+    let monthly = CronSchedule::parse("@monthly").unwrap();
+    assert_eq!(
+      monthly.next_after(&date("2024-12-15 08:00")),
+      Some(date("2025-01-01 00:00"))
+    );
+    // 2024-01-06 is a saturday
+    let weekdays = CronSchedule::parse("30 9 * * 1-5").unwrap();
+    assert_eq!(
+      weekdays.next_after(&date("2024-01-06 12:00")),
+      Some(date("2024-01-08 09:30"))
+    );
+    let sunday = CronSchedule::parse("0 0 * * 7").unwrap();
+    assert_eq!(
+      sunday.next_after(&date("2024-01-06 12:00")),
+      Some(date("2024-01-07 00:00"))
+    );
+    let never = CronSchedule::parse("0 0 31 2 *").unwrap();
+    assert_eq!(never.next_after(&date("2024-01-01 00:00")), None);
+  }
 }
@@ -0,0 +1,99 @@
+use std::collections::HashMap;
+
+/// Visit state of a node during the depth first search
+#[derive(Clone, Copy, PartialEq)]
+enum Visit {
+  InProgress,
+  Done,
+}
+
+fn visit<'a>(
+  node: &'a str,
+  graph: &'a HashMap<String, Vec<String>>,
+  visits: &mut HashMap<&'a str, Visit>,
+  path: &mut Vec<&'a str>,
+) -> Option<Vec<String>> {
+  match visits.get(node) {
+    Some(Visit::Done) => return None,
+    Some(Visit::InProgress) => {
+      let start = path.iter().position(|n| *n == node).unwrap_or_default();
+      let mut cycle = path[start..]
+        .iter()
+        .map(|n| n.to_string())
+        .collect::<Vec<_>>();
+      cycle.push(node.to_owned());
+      return Some(cycle);
+    }
+    None => {}
+  }
+  visits.insert(node, Visit::InProgress);
+  path.push(node);
+  for dep in graph.get(node).into_iter().flatten() {
+    if let Some(cycle) = visit(dep, graph, visits, path) {
+      return Some(cycle);
+    }
+  }
+  path.pop();
+  visits.insert(node, Visit::Done);
+  None
+}
+
+/// Find a cycle in a graph given as a map of node to the nodes it depends on.
+/// Return the nodes forming the cycle, the first one being repeated at the end
+pub fn find_cycle(graph: &HashMap<String, Vec<String>>) -> Option<Vec<String>> {
+  let mut visits = HashMap::new();
+  let mut nodes = graph.keys().collect::<Vec<_>>();
+  // Sort to always report the same cycle
+  nodes.sort();
+  for node in nodes {
+    let mut path = Vec::new();
+    if let Some(cycle) = visit(node, graph, &mut visits, &mut path) {
+      return Some(cycle);
+    }
+  }
+  None
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn graph(edges: &[(&str, &[&str])]) -> HashMap<String, Vec<String>> {
+    edges
+      .iter()
+      .map(|(node, deps)| {
+        (
+          node.to_string(),
+          deps.iter().map(|dep| dep.to_string()).collect(),
+        )
+      })
+      .collect()
+  }
+
+  #[test]
+  fn acyclic() {
+    let graph = graph(&[
+      ("a", &[]),
+      ("b", &["a"]),
+      ("c", &["a"]),
+      ("d", &["b", "c", "external"]),
+    ]);
+    assert_eq!(find_cycle(&graph), None);
+  }
+
+  #[test]
+  fn cyclic() {
+    let cycle = graph(&[("a", &["c"]), ("b", &["a"]), ("c", &["b"])]);
+    assert_eq!(
+      find_cycle(&cycle),
+      Some(vec![
+        "a".to_owned(),
+        "c".to_owned(),
+        "b".to_owned(),
+        "a".to_owned()
+      ])
+    );
+    let self_loop = graph(&[("a", &["a"])]);
+    assert!(find_cycle(&self_loop).is_some());
+  }
+}
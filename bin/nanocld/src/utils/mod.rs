@@ -2,15 +2,25 @@ pub mod key;
 pub mod stream;
 pub mod ws;
 
+pub mod audit;
+pub mod auth;
 pub mod container;
 pub mod cron;
 pub mod ctrl_client;
+pub mod dag;
 pub mod exec;
+pub mod network;
+pub mod network_policy;
+pub mod node;
 pub mod query_string;
+pub mod quota;
+pub mod scheduler;
+pub mod secret;
 pub mod server;
 pub mod store;
 pub mod system;
 pub mod vm_image;
+pub mod volume;
 
 #[cfg(test)]
 pub mod tests {
@@ -0,0 +1,140 @@
+use std::collections::HashMap;
+
+use bollard_next::network::{CreateNetworkOptions, InspectNetworkOptions};
+
+use nanocl_error::io::{IoError, IoResult};
+use nanocl_stubs::{
+  generic::GenericFilter,
+  namespace::{get_network_name, SYSTEM_NETWORK},
+  process::Process,
+};
+
+use crate::{
+  models::{NamespaceDb, SystemState},
+  repositories::generic::*,
+};
+
+/// Create the bridge network of a namespace if it doesn't exist
+pub async fn create(namespace: &str, state: &SystemState) -> IoResult<()> {
+  let name = get_network_name(namespace);
+  if state
+    .inner
+    .docker_api
+    .inspect_network(&name, None::<InspectNetworkOptions<String>>)
+    .await
+    .is_ok()
+  {
+    return Ok(());
+  }
+  if name == SYSTEM_NETWORK {
+    return Err(IoError::not_found("Network", SYSTEM_NETWORK));
+  }
+  let labels = HashMap::from([("io.nanocl.n", namespace)]);
+  state
+    .inner
+    .docker_api
+    .create_network(CreateNetworkOptions {
+      name: name.as_str(),
+      check_duplicate: true,
+      driver: "bridge",
+      internal: false,
+      attachable: true,
+      ingress: false,
+      enable_ipv6: false,
+      labels,
+      ..Default::default()
+    })
+    .await
+    .map_err(|err| {
+      IoError::interrupted(
+        "Network",
+        &format!("Unable to create network {name}: {err}"),
+      )
+    })?;
+  log::info!("network::create: {name} for namespace {namespace}");
+  Ok(())
+}
+
+/// Remove the bridge network of a namespace,
+/// the bridge of the system namespace is never removed
+pub async fn remove(namespace: &str, state: &SystemState) -> IoResult<()> {
+  let name = get_network_name(namespace);
+  if name == SYSTEM_NETWORK {
+    return Ok(());
+  }
+  state
+    .inner
+    .docker_api
+    .remove_network(&name)
+    .await
+    .map_err(|err| {
+      IoError::interrupted(
+        "Network",
+        &format!("Unable to remove network {name}: {err}"),
+      )
+    })?;
+  Ok(())
+}
+
+/// Ensure every namespace have its bridge network
+pub async fn sync_namespaces(state: &SystemState) -> IoResult<()> {
+  let namespaces =
+    NamespaceDb::read_by(&GenericFilter::new(), &state.inner.pool).await?;
+  for namespace in namespaces {
+    if let Err(err) = create(&namespace.name, state).await {
+      log::warn!("network::sync_namespaces: {err}");
+    }
+  }
+  Ok(())
+}
+
+/// Subnets of the bridge network of a namespace
+pub async fn get_subnets(
+  namespace: &str,
+  state: &SystemState,
+) -> IoResult<Vec<String>> {
+  let name = get_network_name(namespace);
+  let network = state
+    .inner
+    .docker_api
+    .inspect_network(&name, None::<InspectNetworkOptions<String>>)
+    .await
+    .map_err(|err| {
+      IoError::interrupted(
+        "Network",
+        &format!("Unable to inspect network {name}: {err}"),
+      )
+    })?;
+  let subnets = network
+    .ipam
+    .unwrap_or_default()
+    .config
+    .unwrap_or_default()
+    .into_iter()
+    .filter_map(|config| config.subnet)
+    .collect();
+  Ok(subnets)
+}
+
+/// Address of a process in the bridge network of its namespace
+pub fn get_process_address(process: &Process) -> Option<String> {
+  let namespace = process
+    .data
+    .config
+    .as_ref()?
+    .labels
+    .as_ref()?
+    .get("io.nanocl.n")?;
+  let networks = process
+    .data
+    .network_settings
+    .clone()
+    .unwrap_or_default()
+    .networks
+    .unwrap_or_default();
+  networks
+    .get(&get_network_name(namespace))?
+    .ip_address
+    .clone()
+    .filter(|ip| !ip.is_empty())
+}
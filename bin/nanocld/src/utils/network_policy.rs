@@ -0,0 +1,408 @@
+use std::process::Stdio;
+
+use tokio::{io::AsyncWriteExt, process::Command};
+
+use nanocl_error::{
+  http::{HttpError, HttpResult},
+  io::{IoError, IoResult},
+};
+use nanocl_stubs::{
+  network_policy::{
+    NetworkPolicy, NetworkPolicyPeer, NetworkPolicyPort, NetworkPolicyRule,
+    NETWORK_POLICY_KIND,
+  },
+  resource_kind::{ResourceKindPartial, ResourceKindSpec},
+};
+
+use crate::{
+  models::{ProcessDb, ResourceKindDb, SpecDb, SystemState},
+  utils,
+};
+
+/// Version of the network policy resource kind
+const KIND_VERSION: &str = "v1";
+/// Chain jumped from `DOCKER-USER` evaluating the policies
+const POLICY_CHAIN: &str = "NANOCL-POLICY";
+/// Chain accepting the traffic allowed into the targets
+const INGRESS_CHAIN: &str = "NANOCL-INGRESS";
+/// Chain restricting the traffic leaving the targets
+const EGRESS_CHAIN: &str = "NANOCL-EGRESS";
+
+/// A rule of a policy with its peers resolved to addresses
+#[derive(Debug, Default, PartialEq)]
+pub struct ResolvedRule {
+  pub peers: Vec<String>,
+  pub ports: Vec<NetworkPolicyPort>,
+}
+
+/// A policy with its target and peers resolved to addresses
+#[derive(Debug, Default, PartialEq)]
+pub struct ResolvedPolicy {
+  pub targets: Vec<String>,
+  pub ingress: Option<Vec<ResolvedRule>>,
+  pub egress: Option<Vec<ResolvedRule>>,
+}
+
+/// JSON schema of the network policy resources
+fn get_schema() -> serde_json::Value {
+  let peer = serde_json::json!({
+    "type": "object",
+    "additionalProperties": false,
+    "properties": {
+      "Namespace": { "type": "string" },
+      "Cargo": { "type": "string" },
+      "Cidr": { "type": "string" }
+    }
+  });
+  let rule = serde_json::json!({
+    "type": "object",
+    "additionalProperties": false,
+    "required": ["Peers"],
+    "properties": {
+      "Peers": { "type": "array", "items": peer },
+      "Ports": {
+        "type": "array",
+        "items": {
+          "type": "object",
+          "additionalProperties": false,
+          "required": ["Port"],
+          "properties": {
+            "Protocol": { "type": "string", "enum": ["tcp", "udp"] },
+            "Port": { "type": "integer", "minimum": 1, "maximum": 65535 }
+          }
+        }
+      }
+    }
+  });
+  serde_json::json!({
+    "type": "object",
+    "additionalProperties": false,
+    "required": ["Namespace"],
+    "properties": {
+      "Namespace": { "type": "string" },
+      "Cargo": { "type": "string" },
+      "Ingress": { "type": "array", "items": rule },
+      "Egress": { "type": "array", "items": rule }
+    }
+  })
+}
+
+/// Register the `nanocl.io/network-policy` resource kind
+pub async fn register_kind(state: &SystemState) -> IoResult<()> {
+  if SpecDb::get_version(NETWORK_POLICY_KIND, KIND_VERSION, &state.inner.pool)
+    .await
+    .is_ok()
+  {
+    return Ok(());
+  }
+  let kind = ResourceKindPartial {
+    name: NETWORK_POLICY_KIND.to_owned(),
+    version: KIND_VERSION.to_owned(),
+    metadata: None,
+    data: ResourceKindSpec {
+      schema: Some(get_schema()),
+      url: None,
+    },
+  };
+  ResourceKindDb::create_from_spec(&kind, &state.inner.pool)
+    .await
+    .map_err(|err| IoError::interrupted("NetworkPolicy", &err.to_string()))?;
+  Ok(())
+}
+
+fn validate_peer(peer: &NetworkPolicyPeer) -> HttpResult<()> {
+  match (&peer.namespace, &peer.cargo, &peer.cidr) {
+    (Some(_), _, None) => Ok(()),
+    (None, None, Some(cidr)) => {
+      cidr.parse::<ipnet::IpNet>().map_err(|err| {
+        HttpError::bad_request(format!("Invalid cidr {cidr}: {err}"))
+      })?;
+      Ok(())
+    }
+    _ => Err(HttpError::bad_request(
+      "A peer must have a Namespace with an optional Cargo or a Cidr",
+    )),
+  }
+}
+
+/// Parse and validate the data of a network policy resource
+pub fn validate(data: &serde_json::Value) -> HttpResult<NetworkPolicy> {
+  let policy =
+    serde_json::from_value::<NetworkPolicy>(data.clone()).map_err(|err| {
+      HttpError::bad_request(format!("Invalid network policy: {err}"))
+    })?;
+  let rules = policy
+    .ingress
+    .iter()
+    .chain(policy.egress.iter())
+    .flatten()
+    .collect::<Vec<_>>();
+  for rule in rules {
+    for peer in &rule.peers {
+      validate_peer(peer)?;
+    }
+  }
+  Ok(policy)
+}
+
+/// Addresses of a namespace or of the instances of one of its cargoes
+async fn resolve_target(
+  namespace: &str,
+  cargo: Option<&str>,
+  state: &SystemState,
+) -> IoResult<Vec<String>> {
+  let Some(cargo) = cargo else {
+    return utils::network::get_subnets(namespace, state).await;
+  };
+  let key = utils::key::gen_key(namespace, cargo);
+  let processes =
+    ProcessDb::read_by_kind_key(&key, None, &state.inner.pool).await?;
+  let addresses = processes
+    .iter()
+    .filter_map(utils::network::get_process_address)
+    .map(|address| format!("{address}/32"))
+    .collect();
+  Ok(addresses)
+}
+
+async fn resolve_rules(
+  rules: Option<&Vec<NetworkPolicyRule>>,
+  state: &SystemState,
+) -> IoResult<Option<Vec<ResolvedRule>>> {
+  let Some(rules) = rules else {
+    return Ok(None);
+  };
+  let mut resolved = Vec::new();
+  for rule in rules {
+    let mut peers = Vec::new();
+    for peer in &rule.peers {
+      match (&peer.namespace, &peer.cidr) {
+        (Some(namespace), _) => {
+          let addresses =
+            resolve_target(namespace, peer.cargo.as_deref(), state).await;
+          match addresses {
+            Ok(addresses) => peers.extend(addresses),
+            Err(err) => log::warn!("network_policy::resolve_rules: {err}"),
+          }
+        }
+        (None, Some(cidr)) => peers.push(cidr.clone()),
+        (None, None) => {}
+      }
+    }
+    resolved.push(ResolvedRule {
+      peers,
+      ports: rule.ports.clone().unwrap_or_default(),
+    });
+  }
+  Ok(Some(resolved))
+}
+
+/// Resolve the target and the peers of a policy to addresses
+pub async fn resolve(
+  policy: &NetworkPolicy,
+  state: &SystemState,
+) -> IoResult<ResolvedPolicy> {
+  Ok(ResolvedPolicy {
+    targets: resolve_target(&policy.namespace, policy.cargo.as_deref(), state)
+      .await?,
+    ingress: resolve_rules(policy.ingress.as_ref(), state).await?,
+    egress: resolve_rules(policy.egress.as_ref(), state).await?,
+  })
+}
+
+/// Matches of the ports of a rule, every port when empty
+fn port_matches(ports: &[NetworkPolicyPort]) -> Vec<String> {
+  if ports.is_empty() {
+    return vec![String::new()];
+  }
+  ports
+    .iter()
+    .map(|port| {
+      let protocol = port.protocol.as_deref().unwrap_or("tcp");
+      format!(" -p {protocol} --dport {}", port.port)
+    })
+    .collect()
+}
+
+/// Generate the `iptables-restore` input for the policies.
+/// Allowed ingress is accepted to bypass the isolation between the
+/// namespace bridges, allowed egress continues to the ingress chain
+/// and the traffic not allowed for a target is dropped
+pub fn gen_rules(policies: &[ResolvedPolicy]) -> String {
+  let mut allow_ingress = Vec::new();
+  let mut deny_ingress = Vec::new();
+  let mut allow_egress = Vec::new();
+  let mut deny_egress = Vec::new();
+  for policy in policies {
+    for target in &policy.targets {
+      if let Some(rules) = &policy.ingress {
+        for rule in rules {
+          for peer in &rule.peers {
+            for ports in port_matches(&rule.ports) {
+              allow_ingress.push(format!(
+                "-A {INGRESS_CHAIN} -s {peer} -d {target}{ports} -j ACCEPT"
+              ));
+            }
+          }
+        }
+        deny_ingress.push(format!("-A {INGRESS_CHAIN} -d {target} -j DROP"));
+      }
+      if let Some(rules) = &policy.egress {
+        for rule in rules {
+          for peer in &rule.peers {
+            for ports in port_matches(&rule.ports) {
+              allow_egress.push(format!(
+                "-A {EGRESS_CHAIN} -s {target} -d {peer}{ports} -j RETURN"
+              ));
+            }
+          }
+        }
+        deny_egress.push(format!("-A {EGRESS_CHAIN} -s {target} -j DROP"));
+      }
+    }
+  }
+  let mut lines = vec![
+    "*filter".to_owned(),
+    format!(":{POLICY_CHAIN} - [0:0]"),
+    format!(":{INGRESS_CHAIN} - [0:0]"),
+    format!(":{EGRESS_CHAIN} - [0:0]"),
+    format!(
+      "-A {POLICY_CHAIN} -m conntrack --ctstate ESTABLISHED,RELATED -j ACCEPT"
+    ),
+    format!("-A {POLICY_CHAIN} -j {EGRESS_CHAIN}"),
+    format!("-A {POLICY_CHAIN} -j {INGRESS_CHAIN}"),
+    format!("-A {POLICY_CHAIN} -j RETURN"),
+  ];
+  lines.extend(allow_egress);
+  lines.extend(deny_egress);
+  lines.extend(allow_ingress);
+  lines.extend(deny_ingress);
+  lines.push("COMMIT".to_owned());
+  lines.join("\n") + "\n"
+}
+
+/// Run iptables in the network namespace of the host
+fn iptables(program: &str) -> Command {
+  let mut cmd = Command::new("nsenter");
+  cmd.args(["-t", "1", "-n", program]);
+  cmd
+}
+
+/// Replace the rules of the policy chains and hook them in `DOCKER-USER`
+pub async fn apply(rules: &str) -> IoResult<()> {
+  let mut child = iptables("iptables-restore")
+    .arg("--noflush")
+    .stdin(Stdio::piped())
+    .stdout(Stdio::null())
+    .stderr(Stdio::piped())
+    .spawn()
+    .map_err(|err| {
+      IoError::interrupted(
+        "NetworkPolicy",
+        &format!("Unable to run iptables-restore: {err}"),
+      )
+    })?;
+  if let Some(mut stdin) = child.stdin.take() {
+    stdin.write_all(rules.as_bytes()).await?;
+  }
+  let output = child.wait_with_output().await?;
+  if !output.status.success() {
+    return Err(IoError::interrupted(
+      "NetworkPolicy",
+      &format!(
+        "iptables-restore failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+      ),
+    ));
+  }
+  let hook = ["DOCKER-USER", "-j", POLICY_CHAIN];
+  let exists = iptables("iptables")
+    .arg("-C")
+    .args(hook)
+    .output()
+    .await?
+    .status
+    .success();
+  if exists {
+    return Ok(());
+  }
+  let output = iptables("iptables")
+    .args(["-I", "DOCKER-USER", "1", "-j", POLICY_CHAIN])
+    .output()
+    .await?;
+  if !output.status.success() {
+    return Err(IoError::interrupted(
+      "NetworkPolicy",
+      &format!(
+        "Unable to hook {POLICY_CHAIN} in DOCKER-USER: {}",
+        String::from_utf8_lossy(&output.stderr)
+      ),
+    ));
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn validation() {
+    let data = serde_json::json!({
+      "Namespace": "prod",
+      "Ingress": [{
+        "Peers": [{ "Namespace": "front", "Cargo": "web" }],
+        "Ports": [{ "Port": 5432 }]
+      }],
+      "Egress": [{ "Peers": [{ "Cidr": "10.0.0.0/8" }] }]
+    });
+    let policy = validate(&data).unwrap();
+    assert_eq!(policy.namespace, "prod");
+    let data = serde_json::json!({
+      "Namespace": "prod",
+      "Egress": [{ "Peers": [{ "Cidr": "10.0.0.0/33" }] }]
+    });
+    assert!(validate(&data).is_err());
+    let data = serde_json::json!({
+      "Namespace": "prod",
+      "Ingress": [{ "Peers": [{ "Cargo": "web" }] }]
+    });
+    assert!(validate(&data).is_err());
+    let data = serde_json::json!({
+      "Namespace": "prod",
+      "Ingress": [{
+        "Peers": [{ "Namespace": "front", "Cidr": "10.0.0.0/8" }]
+      }]
+    });
+    assert!(validate(&data).is_err());
+  }
+
+  #[test]
+  fn rules() {
+    let rules = gen_rules(&[]);
+    assert!(rules.starts_with("*filter\n:NANOCL-POLICY - [0:0]\n"));
+    assert!(rules.ends_with("-A NANOCL-POLICY -j RETURN\nCOMMIT\n"));
+    let policy = ResolvedPolicy {
+      targets: vec!["172.20.0.0/16".to_owned()],
+      ingress: Some(vec![ResolvedRule {
+        peers: vec!["172.21.0.0/16".to_owned()],
+        ports: vec![NetworkPolicyPort {
+          protocol: None,
+          port: 5432,
+        }],
+      }]),
+      egress: Some(vec![]),
+    };
+    let rules = gen_rules(&[policy]);
+    let lines = rules.lines().collect::<Vec<_>>();
+    assert_eq!(
+      lines[8..],
+      [
+        "-A NANOCL-EGRESS -s 172.20.0.0/16 -j DROP",
+        "-A NANOCL-INGRESS -s 172.21.0.0/16 -d 172.20.0.0/16 -p tcp --dport 5432 -j ACCEPT",
+        "-A NANOCL-INGRESS -d 172.20.0.0/16 -j DROP",
+        "COMMIT",
+      ]
+    );
+  }
+}
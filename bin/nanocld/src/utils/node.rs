@@ -0,0 +1,504 @@
+use std::{
+  cell::RefCell,
+  collections::BTreeSet,
+  rc::Rc,
+  time::{Duration, Instant},
+};
+
+use futures::future::ready;
+use ntex::{channel::oneshot, fn_service, rt, time::sleep, ws};
+
+use nanocl_error::io::{IoError, IoResult};
+use nanocl_stubs::{
+  cargo::Cargo,
+  generic::{GenericClause, GenericFilter},
+  node::{Node, NodeStatus},
+  process::{Process, ProcessKind},
+  system::{EventKind, EventPartial, NativeEventAction},
+};
+
+use crate::{
+  models::{
+    CargoDb, NodeDb, NodeUpdateDb, ProcessDb, SystemState, WsConState,
+    CLIENT_TIMEOUT, HEARTBEAT_INTERVAL, NODE_HEADER,
+  },
+  repositories::generic::*,
+  utils, vars,
+};
+
+/// Port of the daemon when the endpoint of a node doesn't tell it
+const DEFAULT_PORT: u16 = 8585;
+/// How long a node can miss its heartbeats before being unreachable
+/// and having its instances rescheduled on the other nodes
+pub const UNREACHABLE_TIMEOUT: Duration = Duration::from_secs(30);
+/// Instances of a cargo unavailable at the same time during a drain
+/// when the cargo has no disruption budget
+const DEFAULT_MAX_UNAVAILABLE: usize = 1;
+/// Delay between two checks of the instances while draining a node
+const DRAIN_INTERVAL: Duration = Duration::from_secs(2);
+/// Maximum duration of the drain of a node
+const DRAIN_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// Status of a node knowing when its last heartbeat was received
+pub fn get_status(
+  last_seen: &chrono::NaiveDateTime,
+  now: &chrono::NaiveDateTime,
+) -> NodeStatus {
+  let elapsed = (*now - *last_seen).to_std().unwrap_or_default();
+  if elapsed <= CLIENT_TIMEOUT {
+    NodeStatus::Ready
+  } else if elapsed <= UNREACHABLE_TIMEOUT {
+    NodeStatus::NotReady
+  } else {
+    NodeStatus::Unreachable
+  }
+}
+
+/// Url of the websocket of a node built from its endpoint and version
+pub fn get_ws_url(node: &NodeDb) -> String {
+  let endpoint = node.endpoint.trim_end_matches('/');
+  let (scheme, addr) = endpoint.split_once("://").unwrap_or(("http", endpoint));
+  let has_port = addr
+    .rsplit_once(':')
+    .is_some_and(|(_, port)| port.parse::<u16>().is_ok());
+  let addr = if has_port {
+    addr.to_owned()
+  } else {
+    format!("{addr}:{DEFAULT_PORT}")
+  };
+  format!("{scheme}://{addr}/v{}/nodes/ws", node.version)
+}
+
+/// Record in background a heartbeat received from a node
+pub fn spawn_mark_seen(name: &str, state: &SystemState) {
+  let name = name.to_owned();
+  let pool = state.inner.pool.clone();
+  rt::spawn(async move {
+    if let Err(err) = NodeDb::mark_seen(&name, &pool).await {
+      log::warn!("node::mark_seen: {name}: {err}");
+    }
+  });
+}
+
+/// Connect to the websocket of a peer and exchange heartbeats with it
+/// until the connection is lost or the peer stops answering
+pub async fn heartbeat_peer(
+  peer: &NodeDb,
+  state: &SystemState,
+) -> IoResult<()> {
+  let url = get_ws_url(peer);
+  let client = ws::WsClient::build(url.as_str())
+    .header(NODE_HEADER, state.inner.config.hostname.as_str())
+    .header(
+      "authorization",
+      format!("Bearer {}", state.inner.node_token).as_str(),
+    )
+    .timeout(HEARTBEAT_INTERVAL)
+    .finish()
+    .map_err(|err| {
+      IoError::invalid_input("NodeHeartbeat", &format!("{url}: {err}"))
+    })?;
+  // Only an upgraded connection tells the peer is alive
+  let con = client.connect().await.map_err(|err| {
+    IoError::interrupted("NodeHeartbeat", &format!("{url}: {err}"))
+  })?;
+  spawn_mark_seen(&peer.name, state);
+  let (tx, rx) = oneshot::channel();
+  let con_state = Rc::new(RefCell::new(WsConState::new()));
+  rt::spawn(utils::ws::heartbeat(con_state.clone(), con.sink(), rx));
+  let name = peer.name.clone();
+  let state = state.clone();
+  let res = con
+    .seal()
+    .start(fn_service(move |frame| {
+      let item = match frame {
+        ws::Frame::Ping(msg) => {
+          con_state.borrow_mut().hb = Instant::now();
+          spawn_mark_seen(&name, &state);
+          Some(ws::Message::Pong(msg))
+        }
+        ws::Frame::Pong(_) => {
+          con_state.borrow_mut().hb = Instant::now();
+          spawn_mark_seen(&name, &state);
+          None
+        }
+        ws::Frame::Close(reason) => Some(ws::Message::Close(reason)),
+        _ => None,
+      };
+      ready(Ok::<_, std::io::Error>(item))
+    }))
+    .await;
+  let _ = tx.send(());
+  res.map_err(|err| {
+    IoError::interrupted("NodeHeartbeat", &format!("{url}: {err}"))
+  })
+}
+
+/// Record an event about a node
+pub async fn emit(
+  node: &NodeDb,
+  kind: EventKind,
+  reason: &str,
+  note: String,
+  metadata: Option<serde_json::Value>,
+  state: &SystemState,
+) {
+  let node = match Node::try_from(node.clone()) {
+    Ok(node) => node,
+    Err(err) => {
+      log::warn!("node::emit: {err}");
+      return;
+    }
+  };
+  let event = EventPartial {
+    reporting_controller: vars::CONTROLLER_NAME.to_owned(),
+    reporting_node: state.inner.config.hostname.clone(),
+    kind,
+    action: NativeEventAction::Update.to_string(),
+    related: None,
+    reason: reason.to_owned(),
+    note: Some(note),
+    metadata,
+    actor: Some(node.into()),
+  };
+  if let Err(err) = state.emit_event(event).await {
+    log::warn!("node::emit: {err}");
+  }
+}
+
+/// Cordon or uncordon a node, a cordoned node receives no new instances.
+/// Uncordoning a node also stops its drain.
+pub async fn cordon(
+  name: &str,
+  cordoned: bool,
+  state: &SystemState,
+) -> IoResult<Node> {
+  NodeDb::read_by_pk(name, &state.inner.pool).await?;
+  let update = NodeUpdateDb {
+    cordoned: Some(cordoned),
+    draining: (!cordoned).then_some(false),
+    ..Default::default()
+  };
+  let node = NodeDb::update_pk(name, update, &state.inner.pool).await?;
+  let (reason, note) = if cordoned {
+    ("cordon", format!("Node {name} receives no new instances"))
+  } else {
+    ("uncordon", format!("Node {name} receives new instances"))
+  };
+  emit(&node, EventKind::Normal, reason, note, None, state).await;
+  node.try_into()
+}
+
+/// Cordon a node and mark it as draining.
+/// The node moves its cargo instances to the other nodes on its next heartbeat.
+pub async fn request_drain(name: &str, state: &SystemState) -> IoResult<Node> {
+  let node = NodeDb::read_by_pk(name, &state.inner.pool).await?;
+  if node.status == NodeStatus::Unreachable.to_string() {
+    return Err(IoError::invalid_input(
+      "NodeDrain",
+      &format!("Node {name} is unreachable its instances are already moved"),
+    ));
+  }
+  let update = NodeUpdateDb {
+    cordoned: Some(true),
+    draining: Some(true),
+    ..Default::default()
+  };
+  let node = NodeDb::update_pk(name, update, &state.inner.pool).await?;
+  emit(
+    &node,
+    EventKind::Normal,
+    "drain",
+    format!("Node {name} is cordoned and will be drained"),
+    None,
+    state,
+  )
+  .await;
+  node.try_into()
+}
+
+/// Stop a drain when the node was uncordoned meanwhile
+async fn ensure_draining(node: &NodeDb, state: &SystemState) -> IoResult<()> {
+  let node = NodeDb::read_by_pk(&node.name, &state.inner.pool).await?;
+  if !node.draining {
+    return Err(IoError::interrupted(
+      "NodeDrain",
+      &format!("Node {} was uncordoned", node.name),
+    ));
+  }
+  Ok(())
+}
+
+/// Whether a process is running
+fn is_running(process: &Process) -> bool {
+  process
+    .data
+    .state
+    .as_ref()
+    .and_then(|state| state.running)
+    .unwrap_or_default()
+}
+
+/// Number of local instances of a cargo that can be removed without
+/// having less than `wanted - max_unavailable` running instances.
+/// The local instances not running can always be removed.
+pub fn get_evictable(
+  wanted: usize,
+  max_unavailable: usize,
+  running_elsewhere: usize,
+  local: &[bool],
+) -> usize {
+  let running = local.iter().filter(|running| **running).count();
+  let stopped = local.len() - running;
+  let min_available = wanted.saturating_sub(max_unavailable);
+  let evictable = (running_elsewhere + running).saturating_sub(min_available);
+  (stopped + evictable).min(local.len())
+}
+
+/// Move the local instances of a cargo to the other nodes
+/// removing them by batches within the disruption budget of the cargo
+/// once their replacements are running
+async fn drain_cargo(
+  node: &NodeDb,
+  cargo: &Cargo,
+  deadline: Instant,
+  state: &SystemState,
+) -> IoResult<()> {
+  let key = &cargo.spec.cargo_key;
+  let max_unavailable = cargo
+    .spec
+    .disruption_budget
+    .as_ref()
+    .and_then(|budget| budget.max_unavailable)
+    .unwrap_or(DEFAULT_MAX_UNAVAILABLE);
+  loop {
+    ensure_draining(node, state).await?;
+    let mut local =
+      utils::container::cargo::read_local_processes(key, state).await?;
+    if local.is_empty() {
+      return Ok(());
+    }
+    // The instances not running are removed first
+    local.sort_by_key(is_running);
+    let wanted = utils::container::replication::get_placement(cargo, state)
+      .await?
+      .values()
+      .sum::<usize>();
+    let filter = GenericFilter::new()
+      .r#where("node_name", GenericClause::Ne(node.name.clone()));
+    let running_elsewhere =
+      ProcessDb::read_by_kind_key(key, Some(filter), &state.inner.pool)
+        .await?
+        .iter()
+        .filter(|process| !process.name.starts_with("init-"))
+        .filter(|process| is_running(process))
+        .count();
+    let count = get_evictable(
+      wanted,
+      max_unavailable,
+      running_elsewhere,
+      &local.iter().map(is_running).collect::<Vec<_>>(),
+    );
+    if count > 0 {
+      let keys = local[..count]
+        .iter()
+        .map(|process| process.key.clone())
+        .collect::<Vec<_>>();
+      utils::container::process::delete_instances(&keys, state).await?;
+      emit(
+        node,
+        EventKind::Normal,
+        "drain_evict",
+        format!("Removed {count} instance(s) of cargo {key}"),
+        Some(serde_json::json!({
+          "Cargo": key,
+          "Instances": keys,
+          "Wanted": wanted,
+          "RunningElsewhere": running_elsewhere,
+        })),
+        state,
+      )
+      .await;
+      continue;
+    }
+    if Instant::now() > deadline {
+      return Err(IoError::interrupted(
+        "NodeDrain",
+        &format!("Timeout waiting for the instances of cargo {key} to run on other nodes"),
+      ));
+    }
+    sleep(DRAIN_INTERVAL).await;
+  }
+}
+
+/// Wait for the jobs running on a node to finish
+async fn wait_jobs(
+  node: &NodeDb,
+  deadline: Instant,
+  state: &SystemState,
+) -> IoResult<()> {
+  let filter = GenericFilter::new()
+    .r#where("node_name", GenericClause::Eq(node.name.clone()))
+    .r#where("kind", GenericClause::Eq(ProcessKind::Job.to_string()));
+  let mut reported = false;
+  loop {
+    ensure_draining(node, state).await?;
+    let running = ProcessDb::transform_read_by(&filter, &state.inner.pool)
+      .await?
+      .iter()
+      .filter(|process| is_running(process))
+      .map(|process| process.kind_key.clone())
+      .collect::<BTreeSet<_>>();
+    if running.is_empty() {
+      return Ok(());
+    }
+    if !reported {
+      reported = true;
+      emit(
+        node,
+        EventKind::Normal,
+        "drain_wait_jobs",
+        format!("Waiting for {} job(s) to finish", running.len()),
+        Some(serde_json::json!({ "Jobs": running })),
+        state,
+      )
+      .await;
+    }
+    if Instant::now() > deadline {
+      return Err(IoError::interrupted(
+        "NodeDrain",
+        "Timeout waiting for the jobs to finish",
+      ));
+    }
+    sleep(DRAIN_INTERVAL).await;
+  }
+}
+
+/// Drain the current node by moving its cargo instances to the other nodes
+/// and waiting for its running jobs. The node stays cordoned once drained.
+pub async fn drain(state: &SystemState) -> IoResult<()> {
+  let pool = &state.inner.pool;
+  let node = NodeDb::read_by_pk(&state.inner.config.hostname, pool).await?;
+  let deadline = Instant::now() + DRAIN_TIMEOUT;
+  let filter = GenericFilter::new()
+    .r#where("node_name", GenericClause::Eq(node.name.clone()))
+    .r#where("kind", GenericClause::Eq(ProcessKind::Cargo.to_string()));
+  let keys = ProcessDb::read_by(&filter, pool)
+    .await?
+    .into_iter()
+    .map(|process| process.kind_key)
+    .collect::<BTreeSet<_>>();
+  emit(
+    &node,
+    EventKind::Normal,
+    "drain_start",
+    format!("Moving the instances of {} cargo(es)", keys.len()),
+    Some(serde_json::json!({ "Cargoes": keys })),
+    state,
+  )
+  .await;
+  let res = async {
+    for key in &keys {
+      let cargo = CargoDb::transform_read_by_pk(key, pool).await?;
+      drain_cargo(&node, &cargo, deadline, state).await?;
+    }
+    wait_jobs(&node, deadline, state).await
+  }
+  .await;
+  let update = NodeUpdateDb {
+    draining: Some(false),
+    ..Default::default()
+  };
+  let node = NodeDb::update_pk(&node.name, update, pool).await?;
+  match &res {
+    Ok(_) => {
+      emit(
+        &node,
+        EventKind::Normal,
+        "drain_done",
+        format!("Node {} is drained", node.name),
+        None,
+        state,
+      )
+      .await
+    }
+    Err(err) => {
+      emit(
+        &node,
+        EventKind::Error,
+        "drain_fail",
+        format!("Drain of node {} failed: {err}", node.name),
+        None,
+        state,
+      )
+      .await
+    }
+  }
+  res
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn node(endpoint: &str) -> NodeDb {
+    let now = chrono::Utc::now().naive_utc();
+    NodeDb {
+      name: "node-a".to_owned(),
+      created_at: now,
+      ip_address: "10.0.0.1/32".parse().unwrap(),
+      endpoint: endpoint.to_owned(),
+      version: "0.16.0".to_owned(),
+      metadata: None,
+      status: NodeStatus::Ready.to_string(),
+      last_seen: now,
+      cordoned: false,
+      draining: false,
+      token_hash: None,
+    }
+  }
+
+  #[test]
+  fn status_from_last_seen() {
+    let now = chrono::Utc::now().naive_utc();
+    let ago = |secs| now - chrono::Duration::seconds(secs);
+    assert_eq!(get_status(&now, &now), NodeStatus::Ready);
+    assert_eq!(get_status(&ago(10), &now), NodeStatus::Ready);
+    assert_eq!(get_status(&ago(11), &now), NodeStatus::NotReady);
+    assert_eq!(get_status(&ago(30), &now), NodeStatus::NotReady);
+    assert_eq!(get_status(&ago(31), &now), NodeStatus::Unreachable);
+    // A clock ahead of ours doesn't make the node unreachable
+    assert_eq!(get_status(&ago(-5), &now), NodeStatus::Ready);
+  }
+
+  #[test]
+  fn ws_url_from_endpoint() {
+    assert_eq!(
+      get_ws_url(&node("10.0.0.1")),
+      "http://10.0.0.1:8585/v0.16.0/nodes/ws"
+    );
+    assert_eq!(
+      get_ws_url(&node("10.0.0.1:9000")),
+      "http://10.0.0.1:9000/v0.16.0/nodes/ws"
+    );
+    assert_eq!(
+      get_ws_url(&node("https://node-a.internal/")),
+      "https://node-a.internal:8585/v0.16.0/nodes/ws"
+    );
+  }
+
+  #[test]
+  fn evictable_within_budget() {
+    // One instance can be unavailable before its replacement runs
+    assert_eq!(get_evictable(3, 1, 0, &[true, true, true]), 1);
+    // Each replacement running elsewhere frees one more instance
+    assert_eq!(get_evictable(3, 1, 1, &[true, true, true]), 2);
+    assert_eq!(get_evictable(3, 1, 3, &[true, true, true]), 3);
+    // Without budget an instance waits for its replacement
+    assert_eq!(get_evictable(2, 0, 0, &[true, true]), 0);
+    assert_eq!(get_evictable(2, 0, 1, &[true, true]), 1);
+    // The instances not running are always removed
+    assert_eq!(get_evictable(2, 0, 0, &[false, true]), 1);
+    assert_eq!(get_evictable(0, 1, 0, &[true, false]), 2);
+  }
+}
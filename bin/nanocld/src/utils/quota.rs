@@ -0,0 +1,522 @@
+use bollard_next::{container::Config, service::HostConfig};
+
+use nanocl_error::http::{HttpError, HttpResult};
+use nanocl_stubs::{
+  cargo::Cargo,
+  cargo_spec::{CargoSpecPartial, ReplicationMode},
+  generic::GenericFilter,
+  job::JobPartial,
+  namespace::{NamespaceLimitRange, NamespaceQuota, NamespaceUsage},
+  vm_spec::VmSpecPartial,
+};
+
+use crate::{
+  models::{CargoDb, JobDb, NamespaceDb, SystemState, VmDb},
+  repositories::generic::*,
+  utils::{
+    self,
+    scheduler::{self, NodeLoad},
+  },
+  vars::UNSCOPED_NAMESPACE,
+};
+
+/// Number of nano cpus in a cpu
+const NANO_CPUS: f64 = 1_000_000_000.0;
+/// Default cpu period used by docker when only a cpu quota is set
+const DEFAULT_CPU_PERIOD: i64 = 100_000;
+/// Default disk size of a virtual machine in GB
+const DEFAULT_VM_DISK_SIZE: u64 = 20;
+
+/// Number of cpus given to a container, 0 when unlimited
+pub fn get_cpus(host_config: &HostConfig) -> f64 {
+  if let Some(nano_cpus) = host_config.nano_cpus.filter(|cpus| *cpus > 0) {
+    return nano_cpus as f64 / NANO_CPUS;
+  }
+  let Some(quota) = host_config.cpu_quota.filter(|quota| *quota > 0) else {
+    return 0.0;
+  };
+  let period = host_config
+    .cpu_period
+    .filter(|period| *period > 0)
+    .unwrap_or(DEFAULT_CPU_PERIOD);
+  quota as f64 / period as f64
+}
+
+/// Inject the default limits in a container that doesn't set them
+/// and ensure it doesn't exceed the maximum limits.
+/// When there is no default the maximum is used instead
+pub fn apply_limit_range(
+  container: &mut Config,
+  limit_range: Option<&NamespaceLimitRange>,
+) -> HttpResult<()> {
+  let Some(limit_range) = limit_range else {
+    return Ok(());
+  };
+  let default = limit_range.default.clone().unwrap_or_default();
+  let max = limit_range.max.clone().unwrap_or_default();
+  let mut host_config = container.host_config.clone().unwrap_or_default();
+  if get_cpus(&host_config) == 0.0 {
+    if let Some(cpus) = default.cpus.or(max.cpus) {
+      host_config.nano_cpus = Some((cpus * NANO_CPUS) as i64);
+    }
+  }
+  if host_config.memory.unwrap_or_default() <= 0 {
+    if let Some(memory) = default.memory.or(max.memory) {
+      host_config.memory = Some(memory);
+    }
+  }
+  if let Some(max_cpus) = max.cpus {
+    let cpus = get_cpus(&host_config);
+    if cpus > max_cpus {
+      return Err(HttpError::bad_request(format!(
+        "Container cpus {cpus} exceed the maximum of {max_cpus}"
+      )));
+    }
+  }
+  if let Some(max_memory) = max.memory {
+    let memory = host_config.memory.unwrap_or_default();
+    if memory > max_memory {
+      return Err(HttpError::bad_request(format!(
+        "Container memory {memory} exceed the maximum of {max_memory}"
+      )));
+    }
+  }
+  container.host_config = Some(host_config);
+  Ok(())
+}
+
+/// Resources used by a container running `instances` times
+pub fn get_container_usage(
+  container: &Config,
+  instances: usize,
+) -> NamespaceUsage {
+  let host_config = container.host_config.clone().unwrap_or_default();
+  NamespaceUsage {
+    cpus: get_cpus(&host_config) * instances as f64,
+    memory: host_config.memory.unwrap_or_default().max(0) * instances as i64,
+    instances,
+    ..Default::default()
+  }
+}
+
+/// Resources used by a cargo with all the instances
+/// its replication mode runs on the given nodes
+pub fn get_cargo_usage(
+  container: &Config,
+  replication: Option<&ReplicationMode>,
+  nodes: &[NodeLoad],
+) -> NamespaceUsage {
+  let instances =
+    utils::container::replication::count_instances(replication, nodes);
+  get_container_usage(container, instances)
+}
+
+/// Resources used by a virtual machine
+pub fn get_vm_usage(spec: &VmSpecPartial) -> NamespaceUsage {
+  let host_config = spec.host_config.clone().unwrap_or_default();
+  NamespaceUsage {
+    cpus: host_config.cpu as f64,
+    memory: (host_config.memory * 1024 * 1024) as i64,
+    instances: 1,
+    vm_disk_size: spec.disk.size.unwrap_or(DEFAULT_VM_DISK_SIZE),
+  }
+}
+
+/// Resources used by the containers of a job
+pub fn get_job_usage(job: &JobPartial) -> NamespaceUsage {
+  utils::container::job::get_steps(job.steps.as_ref(), &job.containers)
+    .iter()
+    .fold(NamespaceUsage::default(), |usage, step| {
+      usage + get_container_usage(&step.container, 1)
+    })
+}
+
+/// Ensure the resources requested fit in the quota given the current usage.
+/// Only the resources requested are checked so a namespace already over
+/// its quota can still remove or shrink objects
+pub fn check_quota(
+  namespace: &str,
+  quota: &NamespaceQuota,
+  usage: &NamespaceUsage,
+  requested: &NamespaceUsage,
+) -> HttpResult<()> {
+  let total = usage.clone() + requested.clone();
+  let mut exceeded = Vec::new();
+  if let Some(cpus) = quota.cpus {
+    if requested.cpus > 0.0 && total.cpus > cpus {
+      exceeded.push(format!("cpus {} > {cpus}", total.cpus));
+    }
+  }
+  if let Some(memory) = quota.memory {
+    if requested.memory > 0 && total.memory > memory {
+      exceeded.push(format!("memory {} > {memory}", total.memory));
+    }
+  }
+  if let Some(instances) = quota.instances {
+    if requested.instances > 0 && total.instances > instances {
+      exceeded.push(format!("instances {} > {instances}", total.instances));
+    }
+  }
+  if let Some(size) = quota.vm_disk_size {
+    if requested.vm_disk_size > 0 && total.vm_disk_size > size {
+      exceeded.push(format!("vm disk size {} > {size}", total.vm_disk_size));
+    }
+  }
+  if exceeded.is_empty() {
+    return Ok(());
+  }
+  Err(HttpError::forbidden(format!(
+    "Namespace {namespace} quota exceeded: {}",
+    exceeded.join(", ")
+  )))
+}
+
+/// Resources currently used by a namespace.
+/// The object with the key `exclude` is not counted to check its update
+pub async fn get_usage(
+  namespace: &str,
+  exclude: Option<&str>,
+  state: &SystemState,
+) -> HttpResult<NamespaceUsage> {
+  let pool = &state.inner.pool;
+  let nodes = scheduler::read_node_loads(state).await?;
+  let mut usage = NamespaceUsage::default();
+  for cargo in CargoDb::read_by_namespace(namespace, pool).await? {
+    if exclude == Some(cargo.spec.cargo_key.as_str()) {
+      continue;
+    }
+    let replication = utils::container::replication::get_replication(&cargo);
+    usage = usage
+      + get_cargo_usage(&cargo.spec.container, replication.as_ref(), &nodes);
+  }
+  for vm in VmDb::read_by_namespace(namespace, pool).await? {
+    if exclude == Some(vm.spec.vm_key.as_str()) {
+      continue;
+    }
+    usage = usage + get_vm_usage(&vm.spec.into());
+  }
+  if namespace != UNSCOPED_NAMESPACE {
+    return Ok(usage);
+  }
+  for job in JobDb::transform_read_by(&GenericFilter::new(), pool).await? {
+    if exclude == Some(job.name.as_str()) {
+      continue;
+    }
+    usage = usage + get_job_usage(&job.into());
+  }
+  Ok(usage)
+}
+
+/// Ensure a namespace have enough quota left for the requested resources
+async fn check(
+  namespace: &NamespaceDb,
+  exclude: Option<&str>,
+  requested: &NamespaceUsage,
+  state: &SystemState,
+) -> HttpResult<()> {
+  let Some(quota) = namespace.get_quota()? else {
+    return Ok(());
+  };
+  let usage = get_usage(&namespace.name, exclude, state).await?;
+  check_quota(&namespace.name, &quota, &usage, requested)
+}
+
+/// Apply the limit range of the namespace to a cargo
+/// and ensure it fit in the namespace quota
+pub async fn prepare_cargo(
+  namespace: &str,
+  key: Option<&str>,
+  spec: &CargoSpecPartial,
+  state: &SystemState,
+) -> HttpResult<CargoSpecPartial> {
+  let namespace = NamespaceDb::read_by_pk(namespace, &state.inner.pool).await?;
+  let mut spec = spec.clone();
+  apply_limit_range(
+    &mut spec.container,
+    namespace.get_limit_range()?.as_ref(),
+  )?;
+  let nodes = scheduler::read_node_loads(state).await?;
+  // An autoscaled cargo keeps the replicas chosen by the autoscaler
+  let replicas = match key {
+    Some(key) if spec.autoscale.is_some() => {
+      CargoDb::transform_read_by_pk(key, &state.inner.pool)
+        .await?
+        .replicas
+    }
+    _ => None,
+  };
+  let replication = match replicas {
+    Some(replicas) => utils::container::replication::with_replicas(
+      spec.replication.as_ref(),
+      replicas,
+    ),
+    None => spec.replication.clone(),
+  };
+  let requested =
+    get_cargo_usage(&spec.container, replication.as_ref(), &nodes);
+  check(&namespace, key, &requested, state).await?;
+  Ok(spec)
+}
+
+/// Highest number of replicas up to `desired` fitting in the quota
+/// knowing the resources `requested` by a number of replicas,
+/// `current` is kept when none of them fit
+fn get_max_replicas(
+  namespace: &str,
+  quota: &NamespaceQuota,
+  usage: &NamespaceUsage,
+  current: usize,
+  desired: usize,
+  requested: impl Fn(usize) -> NamespaceUsage,
+) -> usize {
+  if desired <= current {
+    return desired;
+  }
+  (current + 1..=desired)
+    .rev()
+    .find(|replicas| {
+      check_quota(namespace, quota, usage, &requested(*replicas)).is_ok()
+    })
+    .unwrap_or(current)
+}
+
+/// Clamp the number of replicas wanted by the autoscaler for a cargo
+/// to the quota of its namespace
+pub async fn clamp_cargo_replicas(
+  cargo: &Cargo,
+  current: usize,
+  desired: usize,
+  state: &SystemState,
+) -> HttpResult<usize> {
+  if desired <= current {
+    return Ok(desired);
+  }
+  let namespace =
+    NamespaceDb::read_by_pk(&cargo.namespace_name, &state.inner.pool).await?;
+  let Some(quota) = namespace.get_quota()? else {
+    return Ok(desired);
+  };
+  let usage =
+    get_usage(&namespace.name, Some(&cargo.spec.cargo_key), state).await?;
+  let nodes = scheduler::read_node_loads(state).await?;
+  // The replicas are per node, name or group for some replication modes
+  let requested = |replicas| {
+    let replication = utils::container::replication::with_replicas(
+      cargo.spec.replication.as_ref(),
+      replicas,
+    );
+    get_cargo_usage(&cargo.spec.container, replication.as_ref(), &nodes)
+  };
+  Ok(get_max_replicas(
+    &namespace.name,
+    &quota,
+    &usage,
+    current,
+    desired,
+    requested,
+  ))
+}
+
+/// Ensure a virtual machine fit in the namespace quota
+pub async fn check_vm(
+  namespace: &str,
+  key: Option<&str>,
+  spec: &VmSpecPartial,
+  state: &SystemState,
+) -> HttpResult<()> {
+  let namespace = NamespaceDb::read_by_pk(namespace, &state.inner.pool).await?;
+  check(&namespace, key, &get_vm_usage(spec), state).await
+}
+
+/// Apply the limit range of the unscoped namespace to the containers of a job
+/// and ensure it fit in its quota
+pub async fn prepare_job(
+  job: &JobPartial,
+  state: &SystemState,
+) -> HttpResult<JobPartial> {
+  let namespace =
+    NamespaceDb::read_by_pk(UNSCOPED_NAMESPACE, &state.inner.pool).await?;
+  let limit_range = namespace.get_limit_range()?;
+  let mut job = job.clone();
+  for container in job.containers.iter_mut() {
+    apply_limit_range(container, limit_range.as_ref())?;
+  }
+  for step in job.steps.iter_mut().flatten() {
+    apply_limit_range(&mut step.container, limit_range.as_ref())?;
+  }
+  check(&namespace, Some(&job.name), &get_job_usage(&job), state).await?;
+  Ok(job)
+}
+
+#[cfg(test)]
+mod tests {
+  use nanocl_stubs::{
+    cargo_spec::ReplicationStatic, namespace::ContainerLimits,
+  };
+
+  use super::*;
+
+  fn container(nano_cpus: Option<i64>, memory: Option<i64>) -> Config {
+    Config {
+      host_config: Some(HostConfig {
+        nano_cpus,
+        memory,
+        ..Default::default()
+      }),
+      ..Default::default()
+    }
+  }
+
+  #[test]
+  fn cpus() {
+    assert_eq!(get_cpus(&HostConfig::default()), 0.0);
+    let host_config = HostConfig {
+      nano_cpus: Some(1_500_000_000),
+      ..Default::default()
+    };
+    assert_eq!(get_cpus(&host_config), 1.5);
+    let host_config = HostConfig {
+      cpu_quota: Some(50_000),
+      ..Default::default()
+    };
+    assert_eq!(get_cpus(&host_config), 0.5);
+  }
+
+  #[test]
+  fn limit_range() {
+    let limit_range = NamespaceLimitRange {
+      default: Some(ContainerLimits {
+        cpus: Some(0.5),
+        memory: None,
+      }),
+      max: Some(ContainerLimits {
+        cpus: Some(2.0),
+        memory: Some(1024),
+      }),
+    };
+    let mut config = Config::default();
+    apply_limit_range(&mut config, Some(&limit_range)).unwrap();
+    let host_config = config.host_config.unwrap();
+    assert_eq!(host_config.nano_cpus, Some(500_000_000));
+    assert_eq!(host_config.memory, Some(1024));
+    let mut config = container(Some(1_000_000_000), Some(512));
+    apply_limit_range(&mut config, Some(&limit_range)).unwrap();
+    let host_config = config.host_config.unwrap();
+    assert_eq!(host_config.nano_cpus, Some(1_000_000_000));
+    assert_eq!(host_config.memory, Some(512));
+    let mut config = container(Some(3_000_000_000), None);
+    assert!(apply_limit_range(&mut config, Some(&limit_range)).is_err());
+    let mut config = container(None, Some(2048));
+    assert!(apply_limit_range(&mut config, Some(&limit_range)).is_err());
+    let mut config = Config::default();
+    apply_limit_range(&mut config, None).unwrap();
+    assert!(config.host_config.is_none());
+  }
+
+  fn nodes() -> Vec<NodeLoad> {
+    ["node-a", "node-b"]
+      .iter()
+      .map(|name| NodeLoad {
+        name: name.to_string(),
+        ..Default::default()
+      })
+      .collect()
+  }
+
+  #[test]
+  fn usage() {
+    let config = container(Some(500_000_000), Some(256));
+    let replication = ReplicationMode::Static(ReplicationStatic { number: 3 });
+    let usage = get_cargo_usage(&config, Some(&replication), &nodes());
+    assert_eq!(usage.cpus, 1.5);
+    assert_eq!(usage.memory, 768);
+    assert_eq!(usage.instances, 3);
+    let usage =
+      get_cargo_usage(&config, Some(&ReplicationMode::Auto), &nodes());
+    assert_eq!(usage.instances, 2);
+    // The number is per node
+    let replication =
+      ReplicationMode::StaticByNodes(ReplicationStatic { number: 3 });
+    let usage = get_cargo_usage(&config, Some(&replication), &nodes());
+    assert_eq!(usage.instances, 6);
+  }
+
+  #[test]
+  fn quota() {
+    let quota = NamespaceQuota {
+      memory: Some(1024),
+      instances: Some(2),
+      ..Default::default()
+    };
+    let usage = NamespaceUsage {
+      memory: 512,
+      instances: 1,
+      ..Default::default()
+    };
+    let requested = NamespaceUsage {
+      memory: 512,
+      instances: 1,
+      ..Default::default()
+    };
+    check_quota("global", &quota, &usage, &requested).unwrap();
+    let requested = NamespaceUsage {
+      memory: 1024,
+      instances: 1,
+      ..Default::default()
+    };
+    let err = check_quota("global", &quota, &usage, &requested).unwrap_err();
+    assert_eq!(err.status, ntex::http::StatusCode::FORBIDDEN);
+    let over = NamespaceUsage {
+      instances: 5,
+      ..Default::default()
+    };
+    let requested = NamespaceUsage {
+      vm_disk_size: 1,
+      ..Default::default()
+    };
+    check_quota("global", &quota, &over, &requested).unwrap();
+  }
+
+  #[test]
+  fn max_replicas() {
+    let quota = NamespaceQuota {
+      memory: Some(1024),
+      instances: Some(4),
+      ..Default::default()
+    };
+    let usage = NamespaceUsage {
+      memory: 256,
+      instances: 1,
+      ..Default::default()
+    };
+    let config = container(None, Some(256));
+    let requested = |replicas| get_container_usage(&config, replicas);
+    assert_eq!(
+      get_max_replicas("global", &quota, &usage, 1, 5, requested),
+      3
+    );
+    assert_eq!(
+      get_max_replicas("global", &quota, &usage, 1, 2, requested),
+      2
+    );
+    assert_eq!(
+      get_max_replicas("global", &quota, &usage, 4, 2, requested),
+      2
+    );
+    // Each replica runs on the two nodes
+    let requested = |replicas| get_container_usage(&config, replicas * 2);
+    assert_eq!(
+      get_max_replicas("global", &quota, &usage, 1, 5, requested),
+      1
+    );
+    let usage = NamespaceUsage {
+      memory: 1024,
+      instances: 4,
+      ..Default::default()
+    };
+    assert_eq!(
+      get_max_replicas("global", &quota, &usage, 1, 5, requested),
+      1
+    );
+  }
+}
@@ -0,0 +1,319 @@
+use std::collections::{BTreeMap, HashMap};
+
+use nanocl_error::io::IoResult;
+use nanocl_stubs::{
+  cargo_spec::NodeAffinity,
+  generic::{GenericClause, GenericFilter},
+  node::NodeStatus,
+};
+
+use crate::{
+  models::{MetricDb, NodeDb, NodeGroupLinkDb, ProcessDb, SystemState},
+  repositories::generic::*,
+};
+
+/// Usage in percent assumed for a node that didn't report any metric yet
+const UNKNOWN_USAGE: f64 = 50.0;
+/// Weight of a process already running on a node
+const INSTANCE_WEIGHT: f64 = 5.0;
+/// Bonus for a node already running an instance of the scheduled cargo
+/// It avoid to move instances around when nothing changed
+const STICKY_BONUS: f64 = 20.0;
+/// Bonus for a node running a cargo listed in the affinity
+const AFFINITY_BONUS: f64 = 50.0;
+/// Penalty for each instance of a cargo listed in the anti affinity
+const ANTI_AFFINITY_PENALTY: f64 = 100.0;
+
+/// Number of instances planned on each node indexed by the node name
+pub type Placement = BTreeMap<String, usize>;
+
+/// Resources usage and workload of a node used to score it
+#[derive(Debug, Clone, Default)]
+pub struct NodeLoad {
+  /// Name of the node
+  pub name: String,
+  /// Groups of the node
+  pub groups: Vec<String>,
+  /// Latest average cpu usage in percent if reported
+  pub cpu_usage: Option<f64>,
+  /// Latest memory usage in percent if reported
+  pub memory_usage: Option<f64>,
+  /// Number of processes running on the node indexed by their kind key
+  pub processes: HashMap<String, usize>,
+  /// Whether the node is cordoned and receives no new instances
+  pub cordoned: bool,
+}
+
+/// Score the nodes to choose where to run the instances of a cargo.
+/// The lower the score the better the node.
+#[derive(Debug, Clone)]
+pub struct Scheduler {
+  /// Key of the scheduled cargo
+  key: String,
+  /// Name of the local node, used to break ties
+  local_node: String,
+  /// Nodes allowed by the affinity and anti affinity
+  nodes: Vec<NodeLoad>,
+  /// Cargoes to run alongside
+  preferred_cargoes: Vec<String>,
+  /// Cargoes to avoid
+  avoided_cargoes: Vec<String>,
+}
+
+impl Scheduler {
+  /// Create a new scheduler for the cargo `key`.
+  /// Nodes not matching the affinity nodes and node groups
+  /// or matching the anti affinity nodes and node groups are excluded.
+  pub fn new(
+    key: &str,
+    local_node: &str,
+    nodes: Vec<NodeLoad>,
+    affinity: Option<&NodeAffinity>,
+    anti_affinity: Option<&NodeAffinity>,
+  ) -> Self {
+    let affinity = affinity.cloned().unwrap_or_default();
+    let anti_affinity = anti_affinity.cloned().unwrap_or_default();
+    let nodes = nodes
+      .into_iter()
+      .filter(|node| {
+        if let Some(names) = &affinity.nodes {
+          if !names.contains(&node.name) {
+            return false;
+          }
+        }
+        if let Some(groups) = &affinity.node_groups {
+          if !node.groups.iter().any(|group| groups.contains(group)) {
+            return false;
+          }
+        }
+        if let Some(names) = &anti_affinity.nodes {
+          if names.contains(&node.name) {
+            return false;
+          }
+        }
+        if let Some(groups) = &anti_affinity.node_groups {
+          if node.groups.iter().any(|group| groups.contains(group)) {
+            return false;
+          }
+        }
+        true
+      })
+      .collect();
+    Self {
+      key: key.to_owned(),
+      local_node: local_node.to_owned(),
+      nodes,
+      preferred_cargoes: affinity.cargoes.unwrap_or_default(),
+      avoided_cargoes: anti_affinity.cargoes.unwrap_or_default(),
+    }
+  }
+
+  /// Names of the nodes allowed to run the cargo
+  pub fn nodes(&self) -> Vec<String> {
+    self.nodes.iter().map(|node| node.name.clone()).collect()
+  }
+
+  /// Names of the allowed nodes member of the given group
+  pub fn group_nodes(&self, group: &str) -> Vec<String> {
+    self
+      .nodes
+      .iter()
+      .filter(|node| node.groups.iter().any(|name| name == group))
+      .map(|node| node.name.clone())
+      .collect()
+  }
+
+  /// Number of instances of the scheduled cargo a node can run out of `wanted`.
+  /// A cordoned node keeps its current instances but receives no new ones.
+  fn capacity(&self, node: &NodeLoad, wanted: usize) -> usize {
+    if !node.cordoned {
+      return wanted;
+    }
+    let current = node.processes.get(&self.key).copied().unwrap_or_default();
+    wanted.min(current)
+  }
+
+  /// Limit the instances planned on a node to the ones it can run
+  pub fn limit(&self, name: &str, wanted: usize) -> usize {
+    self
+      .nodes
+      .iter()
+      .find(|node| node.name == name)
+      .map(|node| self.capacity(node, wanted))
+      .unwrap_or_default()
+  }
+
+  /// Compute the score of a node knowing `placed` instances
+  /// of the scheduled cargo are already planned on it
+  fn score(&self, node: &NodeLoad, placed: usize) -> f64 {
+    let cpu = node.cpu_usage.unwrap_or(UNKNOWN_USAGE);
+    let memory = node.memory_usage.unwrap_or(UNKNOWN_USAGE);
+    let current = node.processes.get(&self.key).copied().unwrap_or_default();
+    // Instances of the scheduled cargo are replaced by the placed ones
+    let others = node.processes.values().sum::<usize>() - current;
+    let mut score = cpu + memory + (others + placed) as f64 * INSTANCE_WEIGHT;
+    if current > 0 {
+      score -= STICKY_BONUS;
+    }
+    if self
+      .preferred_cargoes
+      .iter()
+      .any(|key| node.processes.get(key).copied().unwrap_or_default() > 0)
+    {
+      score -= AFFINITY_BONUS;
+    }
+    let avoided = self
+      .avoided_cargoes
+      .iter()
+      .map(|key| {
+        if *key == self.key {
+          placed
+        } else {
+          node.processes.get(key).copied().unwrap_or_default()
+        }
+      })
+      .sum::<usize>();
+    score + avoided as f64 * ANTI_AFFINITY_PENALTY
+  }
+
+  /// Pick the best node in the candidates for a new instance
+  /// knowing the instances already planned in `placed`
+  pub fn pick(
+    &self,
+    candidates: &[String],
+    placed: &Placement,
+  ) -> Option<String> {
+    self
+      .nodes
+      .iter()
+      .filter(|node| candidates.contains(&node.name))
+      .filter_map(|node| {
+        let placed = placed.get(&node.name).copied().unwrap_or_default();
+        if self.capacity(node, placed + 1) <= placed {
+          return None;
+        }
+        Some((node, self.score(node, placed)))
+      })
+      .min_by(|(a, a_score), (b, b_score)| {
+        a_score
+          .total_cmp(b_score)
+          .then_with(|| {
+            (b.name == self.local_node).cmp(&(a.name == self.local_node))
+          })
+          .then_with(|| a.name.cmp(&b.name))
+      })
+      .map(|(node, _)| node.name.clone())
+  }
+}
+
+/// Read the load of every reachable node of the cluster
+/// using the metrics, the node groups and the running processes.
+/// Unreachable and draining nodes are left out
+/// so their instances are placed elsewhere.
+pub async fn read_node_loads(state: &SystemState) -> IoResult<Vec<NodeLoad>> {
+  let filter = GenericFilter::new().r#where(
+    "status",
+    GenericClause::Ne(NodeStatus::Unreachable.to_string()),
+  );
+  let nodes = NodeDb::read_by(&filter, &state.inner.pool)
+    .await?
+    .into_iter()
+    .filter(|node| !node.draining)
+    .collect::<Vec<_>>();
+  let names = nodes
+    .iter()
+    .map(|node| node.name.clone())
+    .collect::<Vec<_>>();
+  let links = NodeGroupLinkDb::read_by_nodes(&names, &state.inner.pool).await?;
+  let usages = MetricDb::read_node_usages(&state.inner.pool).await?;
+  let processes = ProcessDb::count_by_node(&state.inner.pool).await?;
+  let loads = nodes
+    .into_iter()
+    .map(|node| {
+      let usage = usages.iter().find(|usage| usage.node_name == node.name);
+      NodeLoad {
+        groups: links
+          .iter()
+          .filter(|link| link.node_name == node.name)
+          .map(|link| link.node_group_name.clone())
+          .collect(),
+        cpu_usage: usage.map(|usage| usage.cpu_usage),
+        memory_usage: usage.map(|usage| usage.memory_usage),
+        processes: processes
+          .iter()
+          .filter(|(node_name, _, _)| *node_name == node.name)
+          .map(|(_, kind_key, count)| (kind_key.clone(), *count as usize))
+          .collect(),
+        cordoned: node.cordoned,
+        name: node.name,
+      }
+    })
+    .collect();
+  Ok(loads)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn load(name: &str, cpu: f64, memory: f64) -> NodeLoad {
+    NodeLoad {
+      name: name.to_owned(),
+      cpu_usage: Some(cpu),
+      memory_usage: Some(memory),
+      ..Default::default()
+    }
+  }
+
+  #[test]
+  fn pick_least_used_node() {
+    let nodes = vec![load("node-a", 80.0, 70.0), load("node-b", 10.0, 20.0)];
+    let scheduler = Scheduler::new("api.global", "node-a", nodes, None, None);
+    let node = scheduler.pick(&scheduler.nodes(), &Placement::new());
+    assert_eq!(node, Some("node-b".to_owned()));
+  }
+
+  #[test]
+  fn affinity_filter_nodes() {
+    let mut edge = load("node-b", 10.0, 10.0);
+    edge.groups = vec!["edge".to_owned()];
+    let nodes =
+      vec![load("node-a", 90.0, 90.0), edge, load("node-c", 0.0, 0.0)];
+    let affinity = NodeAffinity {
+      node_groups: Some(vec!["edge".to_owned()]),
+      ..Default::default()
+    };
+    let scheduler = Scheduler::new(
+      "db.global",
+      "node-a",
+      nodes.clone(),
+      Some(&affinity),
+      None,
+    );
+    assert_eq!(scheduler.nodes(), vec!["node-b".to_owned()]);
+    let anti_affinity = NodeAffinity {
+      nodes: Some(vec!["node-c".to_owned()]),
+      ..Default::default()
+    };
+    let scheduler =
+      Scheduler::new("db.global", "node-a", nodes, None, Some(&anti_affinity));
+    assert_eq!(
+      scheduler.nodes(),
+      vec!["node-a".to_owned(), "node-b".to_owned()]
+    );
+  }
+
+  #[test]
+  fn anti_affinity_spread_replicas() {
+    let nodes = vec![load("node-a", 10.0, 10.0), load("node-b", 40.0, 40.0)];
+    let anti_affinity = NodeAffinity {
+      cargoes: Some(vec!["api.global".to_owned()]),
+      ..Default::default()
+    };
+    let scheduler =
+      Scheduler::new("api.global", "node-a", nodes, None, Some(&anti_affinity));
+    let placed = Placement::from([("node-a".to_owned(), 1)]);
+    let node = scheduler.pick(&scheduler.nodes(), &placed);
+    assert_eq!(node, Some("node-b".to_owned()));
+  }
+}
@@ -0,0 +1,408 @@
+use std::{fs, io::Write, os::unix::fs::OpenOptionsExt, path::Path};
+
+use openssl::{
+  base64::{decode_block, encode_block},
+  rand::rand_bytes,
+  sha::sha256,
+  symm::{decrypt_aead, encrypt_aead, Cipher},
+};
+
+use nanocl_error::io::{FromIo, IoError, IoResult};
+use nanocl_stubs::{config::DaemonConfig, secret::SecretRotateKeyResult};
+
+use crate::{
+  models::{NodeDb, SecretDb, SecretUpdateDb, SystemState},
+  repositories::generic::*,
+  vars,
+};
+
+/// Length in bytes of the master key and of the data keys
+const KEY_LEN: usize = 32;
+/// Length in bytes of the AES-GCM nonce
+const NONCE_LEN: usize = 12;
+/// Length in bytes of the AES-GCM authentication tag
+const TAG_LEN: usize = 16;
+/// Key of the object wrapping the encrypted data of a secret
+const ENVELOPE: &str = "NanoclEnvelope";
+
+/// A master key used to wrap the data keys of the secrets
+#[derive(Clone)]
+pub struct MasterKey {
+  /// Short identifier of the key stored along the secrets
+  pub id: String,
+  bytes: [u8; KEY_LEN],
+}
+
+impl std::fmt::Debug for MasterKey {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("MasterKey").field("id", &self.id).finish()
+  }
+}
+
+impl MasterKey {
+  fn from_bytes(bytes: [u8; KEY_LEN]) -> Self {
+    let hash = sha256(&bytes);
+    let id = format!(
+      "{:02x}{:02x}{:02x}{:02x}",
+      hash[0], hash[1], hash[2], hash[3]
+    );
+    Self { id, bytes }
+  }
+
+  /// Parse a base64 encoded key of 32 bytes
+  pub fn parse(encoded: &str) -> IoResult<Self> {
+    let bytes = decode_block(encoded.trim())
+      .map_err(|err| IoError::invalid_data("MasterKey", &err.to_string()))?;
+    let bytes: [u8; KEY_LEN] = bytes.try_into().map_err(|_| {
+      IoError::invalid_data("MasterKey", &format!("expected {KEY_LEN} bytes"))
+    })?;
+    Ok(Self::from_bytes(bytes))
+  }
+
+  /// Generate a new random key
+  pub fn generate() -> IoResult<Self> {
+    let mut bytes = [0; KEY_LEN];
+    rand_bytes(&mut bytes)
+      .map_err(|err| IoError::other("MasterKey", &err.to_string()))?;
+    Ok(Self::from_bytes(bytes))
+  }
+
+  /// Base64 representation of the key
+  pub fn encode(&self) -> String {
+    encode_block(&self.bytes)
+  }
+}
+
+/// Write a key in a file only readable by its owner
+fn write_key_file(path: &str, key: &MasterKey) -> IoResult<()> {
+  let tmp = format!("{path}.tmp");
+  let mut file = fs::OpenOptions::new()
+    .write(true)
+    .create(true)
+    .truncate(true)
+    .mode(0o600)
+    .open(&tmp)
+    .map_err(|err| err.map_err_context(|| tmp.clone()))?;
+  file
+    .write_all(key.encode().as_bytes())
+    .map_err(|err| err.map_err_context(|| tmp.clone()))?;
+  fs::rename(&tmp, path).map_err(|err| err.map_err_context(|| path))?;
+  Ok(())
+}
+
+fn read_key_file(path: &str) -> IoResult<MasterKey> {
+  let content =
+    fs::read_to_string(path).map_err(|err| err.map_err_context(|| path))?;
+  MasterKey::parse(&content)
+}
+
+/// Read the base64 encoded master key from the environment or from the file.
+/// The file is created with a new key when it doesn't exist.
+/// Secrets are stored in plain text when no key is configured.
+pub fn read_master_key(file: Option<&str>) -> IoResult<Option<String>> {
+  if let Ok(key) = std::env::var(vars::MASTER_KEY_ENV) {
+    return Ok(Some(MasterKey::parse(&key)?.encode()));
+  }
+  let Some(file) = file else {
+    return Ok(None);
+  };
+  if !Path::new(file).exists() {
+    log::info!("secret::read_master_key: generating a new key in {file}");
+    write_key_file(file, &MasterKey::generate()?)?;
+  }
+  Ok(Some(read_key_file(file)?.encode()))
+}
+
+/// Load the keys used to decrypt the secrets, the current one first.
+/// The previous key is kept when a rotation was interrupted.
+pub fn load_keys(config: &DaemonConfig) -> IoResult<Vec<MasterKey>> {
+  let Some(key) = &config.master_key else {
+    return Ok(Vec::new());
+  };
+  let mut keys = vec![MasterKey::parse(key)?];
+  if let Some(file) = &config.master_key_file {
+    let old = format!("{file}.old");
+    if Path::new(&old).exists() {
+      keys.push(read_key_file(&old)?);
+    }
+  }
+  Ok(keys)
+}
+
+/// Current keys of the daemon
+pub fn get_keys(state: &SystemState) -> Vec<MasterKey> {
+  state
+    .inner
+    .master_keys
+    .read()
+    .map(|keys| keys.clone())
+    .unwrap_or_default()
+}
+
+fn seal(key: &[u8], aad: &[u8], data: &[u8]) -> IoResult<String> {
+  let mut nonce = [0; NONCE_LEN];
+  rand_bytes(&mut nonce)
+    .map_err(|err| IoError::other("Secret", &err.to_string()))?;
+  let mut tag = [0; TAG_LEN];
+  let encrypted = encrypt_aead(
+    Cipher::aes_256_gcm(),
+    key,
+    Some(&nonce),
+    aad,
+    data,
+    &mut tag,
+  )
+  .map_err(|err| IoError::other("Secret", &err.to_string()))?;
+  Ok(encode_block(&[&nonce[..], &encrypted, &tag].concat()))
+}
+
+fn open(key: &[u8], aad: &[u8], sealed: &str) -> IoResult<Vec<u8>> {
+  let sealed = decode_block(sealed)
+    .map_err(|err| IoError::invalid_data("Secret", &err.to_string()))?;
+  if sealed.len() < NONCE_LEN + TAG_LEN {
+    return Err(IoError::invalid_data("Secret", "sealed data too short"));
+  }
+  let (nonce, rest) = sealed.split_at(NONCE_LEN);
+  let (encrypted, tag) = rest.split_at(rest.len() - TAG_LEN);
+  decrypt_aead(Cipher::aes_256_gcm(), key, Some(nonce), aad, encrypted, tag)
+    .map_err(|_| IoError::invalid_data("Secret", "unable to decrypt"))
+}
+
+/// Fields of an encrypted secret data
+struct Envelope<'a> {
+  key_id: &'a str,
+  key: &'a str,
+  data: &'a str,
+}
+
+fn parse_envelope(data: &serde_json::Value) -> Option<Envelope> {
+  let envelope = data.get(ENVELOPE)?;
+  Some(Envelope {
+    key_id: envelope.get("KeyId")?.as_str()?,
+    key: envelope.get("Key")?.as_str()?,
+    data: envelope.get("Data")?.as_str()?,
+  })
+}
+
+/// Encrypt the data of a secret with a new data key wrapped by the master key
+pub fn encrypt(
+  name: &str,
+  data: &serde_json::Value,
+  key: &MasterKey,
+) -> IoResult<serde_json::Value> {
+  let mut data_key = [0; KEY_LEN];
+  rand_bytes(&mut data_key)
+    .map_err(|err| IoError::other("Secret", &err.to_string()))?;
+  let plain =
+    serde_json::to_vec(data).map_err(|err| err.map_err_context(|| "Secret"))?;
+  Ok(serde_json::json!({
+    ENVELOPE: {
+      "KeyId": key.id,
+      "Key": seal(&key.bytes, name.as_bytes(), &data_key)?,
+      "Data": seal(&data_key, name.as_bytes(), &plain)?,
+    }
+  }))
+}
+
+fn open_data_key(
+  name: &str,
+  envelope: &Envelope,
+  keys: &[MasterKey],
+) -> IoResult<Vec<u8>> {
+  let key = keys
+    .iter()
+    .find(|key| key.id == envelope.key_id)
+    .ok_or_else(|| {
+      IoError::invalid_data(
+        "Secret",
+        &format!("{name} is encrypted with unknown key {}", envelope.key_id),
+      )
+    })?;
+  open(&key.bytes, name.as_bytes(), envelope.key)
+}
+
+/// Decrypt the data of a secret, plain text data is returned as is
+pub fn decrypt(
+  name: &str,
+  data: &serde_json::Value,
+  keys: &[MasterKey],
+) -> IoResult<serde_json::Value> {
+  let Some(envelope) = parse_envelope(data) else {
+    return Ok(data.clone());
+  };
+  let data_key = open_data_key(name, &envelope, keys)?;
+  let plain = open(&data_key, name.as_bytes(), envelope.data)?;
+  let data = serde_json::from_slice(&plain)
+    .map_err(|err| err.map_err_context(|| "Secret"))?;
+  Ok(data)
+}
+
+/// Wrap the data key of a secret with a new master key,
+/// plain text data is encrypted
+pub fn rewrap(
+  name: &str,
+  data: &serde_json::Value,
+  keys: &[MasterKey],
+  new_key: &MasterKey,
+) -> IoResult<serde_json::Value> {
+  let Some(envelope) = parse_envelope(data) else {
+    return encrypt(name, data, new_key);
+  };
+  let data_key = open_data_key(name, &envelope, keys)?;
+  Ok(serde_json::json!({
+    ENVELOPE: {
+      "KeyId": new_key.id,
+      "Key": seal(&new_key.bytes, name.as_bytes(), &data_key)?,
+      "Data": envelope.data,
+    }
+  }))
+}
+
+/// Encrypt the data of a secret with the current master key if any
+pub fn seal_data(
+  name: &str,
+  data: &serde_json::Value,
+  state: &SystemState,
+) -> IoResult<serde_json::Value> {
+  match get_keys(state).first() {
+    Some(key) => encrypt(name, data, key),
+    None => Ok(data.clone()),
+  }
+}
+
+fn set_keys(keys: Vec<MasterKey>, state: &SystemState) -> IoResult<()> {
+  let mut master_keys = state
+    .inner
+    .master_keys
+    .write()
+    .map_err(|err| IoError::other("MasterKey", &err.to_string()))?;
+  *master_keys = keys;
+  Ok(())
+}
+
+/// Replace the master key and wrap the data keys of all secrets with it.
+/// When the key is read from a file a new key is generated if none is given,
+/// the previous key is kept in `<file>.old` until all secrets are re-wrapped.
+/// The key is local to each node so the rotation is refused in a cluster
+/// of more than one node, the other nodes couldn't decrypt the secrets.
+pub async fn rotate_key(
+  new_key: Option<&str>,
+  state: &SystemState,
+) -> IoResult<SecretRotateKeyResult> {
+  let keys = get_keys(state);
+  let Some(current) = keys.first().cloned() else {
+    return Err(IoError::invalid_input(
+      "MasterKey",
+      "no master key configured",
+    ));
+  };
+  let nodes = NodeDb::count_by(
+    &nanocl_stubs::generic::GenericFilter::new(),
+    &state.inner.pool,
+  )
+  .await?;
+  if nodes > 1 {
+    return Err(IoError::invalid_input(
+      "MasterKey",
+      "unable to rotate the key of a cluster with more than one node",
+    ));
+  }
+  let from_env = std::env::var(vars::MASTER_KEY_ENV).is_ok();
+  let file = state.inner.config.master_key_file.clone();
+  let new_key = match (new_key, from_env, &file) {
+    (Some(key), _, _) => MasterKey::parse(key)?,
+    (None, false, Some(_)) => MasterKey::generate()?,
+    _ => {
+      return Err(IoError::invalid_input(
+        "MasterKey",
+        "a new key is required when the key is set from the environment",
+      ))
+    }
+  };
+  if let (false, Some(file)) = (from_env, &file) {
+    write_key_file(&format!("{file}.old"), &current)?;
+    write_key_file(file, &new_key)?;
+  }
+  let mut keyring = vec![new_key.clone()];
+  keyring.extend(keys);
+  set_keys(keyring.clone(), state)?;
+  let secrets = SecretDb::read_by(
+    &nanocl_stubs::generic::GenericFilter::new(),
+    &state.inner.pool,
+  )
+  .await?;
+  let count = secrets.len();
+  for secret in secrets {
+    let data = rewrap(&secret.key, &secret.data, &keyring, &new_key)?;
+    let update = SecretUpdateDb {
+      data: Some(data),
+      ..Default::default()
+    };
+    SecretDb::update_pk(&secret.key, update, &state.inner.pool).await?;
+  }
+  if let (false, Some(file)) = (from_env, &file) {
+    let old = format!("{file}.old");
+    fs::remove_file(&old).map_err(|err| err.map_err_context(|| old))?;
+  }
+  set_keys(vec![new_key.clone()], state)?;
+  log::info!(
+    "secret::rotate_key: {count} secrets wrapped with {}",
+    new_key.id
+  );
+  Ok(SecretRotateKeyResult {
+    key_id: new_key.id,
+    count,
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use serde_json::json;
+
+  use super::*;
+
+  #[test]
+  fn round_trip() {
+    let key = MasterKey::generate().unwrap();
+    let data = json!(["USER=admin", "PASSWORD=secret"]);
+    let encrypted = encrypt("env", &data, &key).unwrap();
+    assert!(parse_envelope(&encrypted).is_some());
+    assert!(!encrypted.to_string().contains("PASSWORD"));
+    let keys = vec![key.clone()];
+    assert_eq!(decrypt("env", &encrypted, &keys).unwrap(), data);
+    // The name is authenticated so data can't be moved to another secret
+    assert!(decrypt("other", &encrypted, &keys).is_err());
+    assert!(
+      decrypt("env", &encrypted, &[MasterKey::generate().unwrap()]).is_err()
+    );
+    // Plain text data is returned as is
+    assert_eq!(decrypt("env", &data, &keys).unwrap(), data);
+  }
+
+  #[test]
+  fn rewrap_with_new_key() {
+    let old = MasterKey::generate().unwrap();
+    let new = MasterKey::generate().unwrap();
+    let data = json!({ "Username": "admin" });
+    let encrypted = encrypt("registry", &data, &old).unwrap();
+    let keys = vec![new.clone(), old.clone()];
+    let rewrapped = rewrap("registry", &encrypted, &keys, &new).unwrap();
+    assert_eq!(
+      decrypt("registry", &rewrapped, &[new.clone()]).unwrap(),
+      data
+    );
+    assert!(decrypt("registry", &rewrapped, &[old]).is_err());
+    let from_plain = rewrap("registry", &data, &keys, &new).unwrap();
+    assert_eq!(decrypt("registry", &from_plain, &[new]).unwrap(), data);
+  }
+
+  #[test]
+  fn parse_key() {
+    let key = MasterKey::generate().unwrap();
+    let parsed = MasterKey::parse(&format!("{}\n", key.encode())).unwrap();
+    assert_eq!(parsed.id, key.id);
+    assert_eq!(parsed.id.len(), 8);
+    assert!(MasterKey::parse("dG9vIHNob3J0").is_err());
+    assert!(!format!("{key:?}").contains(&key.encode()));
+  }
+}
@@ -1,20 +1,119 @@
-use std::{net::ToSocketAddrs, time::Duration};
+use std::{
+  collections::HashMap,
+  io::Read,
+  path::{Path, PathBuf},
+};
 
 use diesel::{
+  prelude::*,
   r2d2::{ConnectionManager, Pool as R2D2Pool},
-  PgConnection,
+  sql_types::Text,
 };
 use diesel_migrations::{
   embed_migrations, EmbeddedMigrations, MigrationHarness,
 };
-use ntex::{rt, time, web};
+use ntex::{rt, web};
+use serde::{Deserialize, Serialize};
+
+use nanocl_error::io::{FromIo, IoError, IoResult};
+use nanocl_stubs::{config::DaemonConfig, system::StoreSnapshot};
+
+use crate::models::{DBConn, DbConnection, Pool};
+
+#[cfg(not(feature = "sqlite"))]
+const MIGRATIONS: EmbeddedMigrations = embed_migrations!("./migrations");
+#[cfg(feature = "sqlite")]
+const MIGRATIONS: EmbeddedMigrations = embed_migrations!("./migrations_sqlite");
+
+/// Name of the store backend written in the snapshots,
+/// a snapshot can only be restored on the backend it was made on
+#[cfg(not(feature = "sqlite"))]
+const BACKEND: &str = "postgres";
+#[cfg(feature = "sqlite")]
+const BACKEND: &str = "sqlite";
+
+/// Version of the layout of the store snapshot archives
+const SNAPSHOT_VERSION: u32 = 1;
+
+/// Every table of the store, a table comes after the tables it references
+/// so they are restored in this order and emptied in the reverse one
+const TABLES: [&str; 21] = [
+  "object_process_statuses",
+  "namespaces",
+  "specs",
+  "nodes",
+  "node_groups",
+  "node_group_links",
+  "vm_images",
+  "resource_kinds",
+  "resources",
+  "secrets",
+  "cargoes",
+  "vms",
+  "jobs",
+  "job_runs",
+  "processes",
+  "volumes",
+  "metrics",
+  "events",
+  "roles",
+  "tokens",
+  "audit_logs",
+];
+
+/// Rows of a table aggregated in a json array
+#[derive(QueryableByName)]
+struct TableDump {
+  #[diesel(sql_type = Text)]
+  data: String,
+}
+
+/// Description of a store snapshot stored in `manifest.json`
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct SnapshotManifest {
+  /// Version of the layout of the archive
+  version: u32,
+  /// Backend of the dumped store
+  #[serde(default = "default_backend")]
+  backend: String,
+  /// Version of the last migration applied to the dumped store
+  migration: String,
+  /// When the snapshot was created
+  created_at: chrono::NaiveDateTime,
+}
+
+/// Snapshots made before the backend was saved come from postgres
+fn default_backend() -> String {
+  "postgres".to_owned()
+}
 
-use nanocl_error::io::{IoError, IoResult};
-use nanocl_stubs::config::DaemonConfig;
+/// Setup every new SQLite connection of the pool,
+/// enable the foreign keys, wait for the locks and register our json functions
+#[cfg(feature = "sqlite")]
+#[derive(Debug)]
+struct SqliteCustomizer;
 
-use crate::models::{DBConn, Pool};
+#[cfg(feature = "sqlite")]
+impl diesel::r2d2::CustomizeConnection<DbConnection, diesel::r2d2::Error>
+  for SqliteCustomizer
+{
+  fn on_acquire(
+    &self,
+    conn: &mut DbConnection,
+  ) -> Result<(), diesel::r2d2::Error> {
+    diesel::sql_query(
+      "PRAGMA foreign_keys = ON; PRAGMA busy_timeout = 5000; PRAGMA journal_mode = WAL;",
+    )
+    .execute(conn)
+    .and_then(|_| crate::models::sql_types::register_functions(conn))
+    .map_err(diesel::r2d2::Error::QueryError)?;
+    Ok(())
+  }
+}
 
 /// Create a pool connection to the store `cockroachdb`
+/// or to the SQLite database file with the `sqlite` feature
 pub async fn create_pool(store_addr: &str) -> IoResult<Pool> {
   let store_addr = store_addr.to_owned();
   // let store_addr = std::env::var("STORE_ADDR")
@@ -22,8 +121,11 @@ pub async fn create_pool(store_addr: &str) -> IoResult<Pool> {
   // let options = format!("/defaultdb?sslmode=verify-full&sslcert={state_dir}/store/certs/client.root.crt&sslkey={state_dir}/store/certs/client.root.key&sslrootcert={state_dir}/store/certs/ca.crt");
   // let db_url = format!("postgresql://root:root@{host}{options}");
   let pool = web::block(move || {
-    let manager = ConnectionManager::<PgConnection>::new(store_addr);
-    R2D2Pool::builder().build(manager)
+    let manager = ConnectionManager::<DbConnection>::new(store_addr);
+    let builder = R2D2Pool::builder();
+    #[cfg(feature = "sqlite")]
+    let builder = builder.connection_customizer(Box::new(SqliteCustomizer));
+    builder.build(manager)
   })
   .await
   .map_err(|err| {
@@ -48,6 +150,7 @@ pub fn get_pool_conn(pool: &Pool) -> IoResult<DBConn> {
 
 /// Wait for store to be ready to accept tcp connection.
 /// We loop until a tcp connection can be established to the store.
+#[cfg(not(feature = "sqlite"))]
 async fn wait(store_addr: &str) -> IoResult<()> {
   let url = url::Url::parse(store_addr).map_err(|err| {
     IoError::invalid_data(
@@ -64,8 +167,7 @@ async fn wait(store_addr: &str) -> IoResult<()> {
   let store_addr = format!("{host_addr}:{port}");
   log::debug!("store::wait: {store_addr}");
   // Open tcp connection to check if store is ready
-  let addr = store_addr
-    .to_socket_addrs()
+  let addr = std::net::ToSocketAddrs::to_socket_addrs(&store_addr)
     .map_err(|err| {
       IoError::invalid_data(
         "Wait store",
@@ -77,9 +179,9 @@ async fn wait(store_addr: &str) -> IoResult<()> {
   log::info!("store::wait: {addr}");
   while let Err(_err) = rt::tcp_connect(addr).await {
     log::warn!("store::wait: retry in 2s");
-    time::sleep(Duration::from_secs(2)).await;
+    ntex::time::sleep(std::time::Duration::from_secs(2)).await;
   }
-  time::sleep(Duration::from_secs(2)).await;
+  ntex::time::sleep(std::time::Duration::from_secs(2)).await;
   log::info!("store::wait: ready");
   Ok(())
 }
@@ -88,8 +190,8 @@ async fn wait(store_addr: &str) -> IoResult<()> {
 /// We use cockroachdb with a postgresql connector.
 /// We also run latest migration on our database to have the latest schema.
 /// It will return a connection Pool that will be use in our State.
+#[cfg(not(feature = "sqlite"))]
 pub async fn init(daemon_conf: &DaemonConfig) -> IoResult<Pool> {
-  const MIGRATIONS: EmbeddedMigrations = embed_migrations!("./migrations");
   let store_addr = match &daemon_conf.store_addr {
     None => {
       return Err(IoError::invalid_data(
@@ -102,11 +204,517 @@ pub async fn init(daemon_conf: &DaemonConfig) -> IoResult<Pool> {
   log::info!("store::init: {store_addr}");
   wait(store_addr).await?;
   let pool = create_pool(store_addr).await?;
-  let mut conn = get_pool_conn(&pool)?;
+  migrate(&pool)?;
+  Ok(pool)
+}
+
+/// Open the SQLite database of the store,
+/// the store address is the path of the database file
+/// and default to `{state_dir}/store/nanocld.db`.
+/// We also run latest migration on our database to have the latest schema.
+#[cfg(feature = "sqlite")]
+pub async fn init(daemon_conf: &DaemonConfig) -> IoResult<Pool> {
+  let store_addr = match &daemon_conf.store_addr {
+    Some(addr) => addr.clone(),
+    None => format!("{}/store/nanocld.db", daemon_conf.state_dir),
+  };
+  log::info!("store::init: {store_addr}");
+  if let Some(dir) = Path::new(&store_addr).parent() {
+    std::fs::create_dir_all(dir)
+      .map_err(|err| err.map_err_context(|| dir.display().to_string()))?;
+  }
+  let pool = create_pool(&store_addr).await?;
+  migrate(&pool)?;
+  Ok(pool)
+}
+
+/// Run the pending migrations of the store
+fn migrate(pool: &Pool) -> IoResult<()> {
+  let mut conn = get_pool_conn(pool)?;
   log::info!("store::init: migrations running");
   conn.run_pending_migrations(MIGRATIONS).map_err(|err| {
     IoError::interrupted("CockroachDB migration", &format!("{err}"))
   })?;
   log::info!("store::init: migrations success");
-  Ok(pool)
+  Ok(())
+}
+
+/// Version of the last migration applied to the store
+fn get_migration(conn: &mut DBConn) -> IoResult<String> {
+  let migrations = conn.applied_migrations().map_err(|err| {
+    IoError::interrupted("CockroachDB migration", &format!("{err}"))
+  })?;
+  Ok(
+    migrations
+      .into_iter()
+      .max()
+      .map(|version| version.to_string())
+      .unwrap_or_default(),
+  )
+}
+
+/// Directory where the snapshots of the store are written
+fn get_snapshot_dir(state_dir: &str) -> String {
+  format!("{state_dir}/store/snapshots")
+}
+
+/// Add a file to a snapshot archive from memory
+fn append_file<W>(
+  builder: &mut tar::Builder<W>,
+  path: &str,
+  data: &[u8],
+) -> IoResult<()>
+where
+  W: std::io::Write,
+{
+  let mut header = tar::Header::new_gnu();
+  header.set_size(data.len() as u64);
+  header.set_mode(0o600);
+  header.set_mtime(chrono::Utc::now().timestamp() as u64);
+  header.set_cksum();
+  builder
+    .append_data(&mut header, path, data)
+    .map_err(|err| err.map_err_context(|| path))?;
+  Ok(())
+}
+
+/// Read the manifest and the tables of a snapshot archive
+fn read_archive(
+  path: &Path,
+) -> IoResult<(SnapshotManifest, HashMap<String, String>)> {
+  let file = std::fs::File::open(path)
+    .map_err(|err| err.map_err_context(|| path.display().to_string()))?;
+  let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(file));
+  let mut manifest = None;
+  let mut tables = HashMap::new();
+  let entries = archive
+    .entries()
+    .map_err(|err| err.map_err_context(|| path.display().to_string()))?;
+  for entry in entries {
+    let mut entry = entry
+      .map_err(|err| err.map_err_context(|| path.display().to_string()))?;
+    let name = entry.path()?.to_string_lossy().to_string();
+    let mut data = String::new();
+    entry
+      .read_to_string(&mut data)
+      .map_err(|err| err.map_err_context(|| &name))?;
+    if name == "manifest.json" {
+      manifest = Some(serde_json::from_str::<SnapshotManifest>(&data)?);
+    } else if let Some(table) = name
+      .strip_prefix("tables/")
+      .and_then(|name| name.strip_suffix(".json"))
+    {
+      tables.insert(table.to_owned(), data);
+    }
+  }
+  let manifest = manifest.ok_or(IoError::invalid_data(
+    "Store snapshot",
+    &format!("{} has no manifest.json", path.display()),
+  ))?;
+  Ok((manifest, tables))
+}
+
+/// Describe a snapshot archive from its manifest
+fn read_snapshot(path: &Path) -> IoResult<StoreSnapshot> {
+  let metadata = std::fs::metadata(path)
+    .map_err(|err| err.map_err_context(|| path.display().to_string()))?;
+  let file = std::fs::File::open(path)
+    .map_err(|err| err.map_err_context(|| path.display().to_string()))?;
+  let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(file));
+  let mut entries = archive.entries()?;
+  // The manifest is always the first file of the archive
+  let mut entry = entries.next().ok_or(IoError::invalid_data(
+    "Store snapshot",
+    &format!("{} is empty", path.display()),
+  ))??;
+  let mut data = String::new();
+  entry.read_to_string(&mut data)?;
+  let manifest = serde_json::from_str::<SnapshotManifest>(&data)?;
+  let name = path
+    .file_name()
+    .unwrap_or_default()
+    .to_string_lossy()
+    .trim_end_matches(".tar.gz")
+    .to_owned();
+  Ok(StoreSnapshot {
+    name,
+    path: path.display().to_string(),
+    size: metadata.len(),
+    migration: manifest.migration,
+    created_at: manifest.created_at,
+  })
+}
+
+/// Dump the rows of a table in a json array
+#[cfg(not(feature = "sqlite"))]
+fn dump_table(
+  conn: &mut DbConnection,
+  table: &str,
+) -> diesel::QueryResult<String> {
+  let dump = diesel::sql_query(format!(
+    "SELECT COALESCE(json_agg(t), '[]')::TEXT AS data FROM {table} t"
+  ))
+  .get_result::<TableDump>(conn)?;
+  Ok(dump.data)
+}
+
+/// Insert the rows of a table dumped in a json array
+#[cfg(not(feature = "sqlite"))]
+fn load_table(
+  conn: &mut DbConnection,
+  table: &str,
+  data: &str,
+) -> diesel::QueryResult<()> {
+  diesel::sql_query(format!(
+    "INSERT INTO {table} SELECT * FROM json_populate_recordset(NULL::{table}, $1::JSON)"
+  ))
+  .bind::<Text, _>(data)
+  .execute(conn)?;
+  Ok(())
+}
+
+/// Names of the columns of a table
+#[cfg(feature = "sqlite")]
+fn get_columns(
+  conn: &mut DbConnection,
+  table: &str,
+) -> diesel::QueryResult<Vec<String>> {
+  let columns = diesel::sql_query(format!(
+    "SELECT name AS data FROM pragma_table_info('{table}')"
+  ))
+  .get_results::<TableDump>(conn)?;
+  Ok(columns.into_iter().map(|column| column.data).collect())
+}
+
+/// Dump the rows of a table in a json array,
+/// SQLite has no row to json function so we list the columns
+#[cfg(feature = "sqlite")]
+fn dump_table(
+  conn: &mut DbConnection,
+  table: &str,
+) -> diesel::QueryResult<String> {
+  let fields = get_columns(conn, table)?
+    .iter()
+    .map(|column| format!("'{column}', \"{column}\""))
+    .collect::<Vec<_>>()
+    .join(", ");
+  let dump = diesel::sql_query(format!(
+    "SELECT COALESCE(json_group_array(json_object({fields})), '[]') AS data FROM {table}"
+  ))
+  .get_result::<TableDump>(conn)?;
+  Ok(dump.data)
+}
+
+/// Insert the rows of a table dumped in a json array
+#[cfg(feature = "sqlite")]
+fn load_table(
+  conn: &mut DbConnection,
+  table: &str,
+  data: &str,
+) -> diesel::QueryResult<()> {
+  let columns = get_columns(conn, table)?;
+  let values = columns
+    .iter()
+    .map(|column| format!("json_extract(value, '$.\"{column}\"')"))
+    .collect::<Vec<_>>()
+    .join(", ");
+  let columns = columns
+    .iter()
+    .map(|column| format!("\"{column}\""))
+    .collect::<Vec<_>>()
+    .join(", ");
+  diesel::sql_query(format!(
+    "INSERT INTO {table} ({columns}) SELECT {values} FROM json_each(?)"
+  ))
+  .bind::<Text, _>(data)
+  .execute(conn)?;
+  Ok(())
+}
+
+/// Dump every table of the store in a single transaction
+/// into a new archive of the snapshot directory
+pub async fn snapshot(pool: &Pool, state_dir: &str) -> IoResult<StoreSnapshot> {
+  let pool = pool.clone();
+  let dir = get_snapshot_dir(state_dir);
+  rt::spawn_blocking(move || {
+    let mut conn = get_pool_conn(&pool)?;
+    let migration = get_migration(&mut conn)?;
+    let dump_tables = |conn: &mut DbConnection| {
+      TABLES
+        .iter()
+        .map(|table| Ok((*table, dump_table(conn, table)?)))
+        .collect::<Result<Vec<_>, diesel::result::Error>>()
+    };
+    #[cfg(not(feature = "sqlite"))]
+    let tables = conn.build_transaction().read_only().run(dump_tables);
+    #[cfg(feature = "sqlite")]
+    let tables = DbConnection::transaction(&mut conn, dump_tables);
+    let tables = tables.map_err(|err| {
+      IoError::interrupted("Store snapshot", &format!("{err}"))
+    })?;
+    std::fs::create_dir_all(&dir)
+      .map_err(|err| err.map_err_context(|| &dir))?;
+    let created_at = chrono::Utc::now().naive_utc();
+    let name = created_at.format("%Y%m%d%H%M%S%3f").to_string();
+    let path = PathBuf::from(format!("{dir}/{name}.tar.gz"));
+    let manifest = SnapshotManifest {
+      version: SNAPSHOT_VERSION,
+      backend: BACKEND.to_owned(),
+      migration,
+      created_at,
+    };
+    let file = std::fs::File::create(&path)
+      .map_err(|err| err.map_err_context(|| path.display().to_string()))?;
+    let encoder =
+      flate2::write::GzEncoder::new(file, flate2::Compression::default());
+    let mut builder = tar::Builder::new(encoder);
+    append_file(
+      &mut builder,
+      "manifest.json",
+      serde_json::to_string(&manifest)?.as_bytes(),
+    )?;
+    for (table, data) in &tables {
+      append_file(
+        &mut builder,
+        &format!("tables/{table}.json"),
+        data.as_bytes(),
+      )?;
+    }
+    builder
+      .into_inner()
+      .and_then(|encoder| encoder.finish())
+      .map_err(|err| err.map_err_context(|| path.display().to_string()))?;
+    read_snapshot(&path)
+  })
+  .await?
+}
+
+/// List the snapshots of the store from the most recent
+pub async fn list_snapshots(state_dir: &str) -> IoResult<Vec<StoreSnapshot>> {
+  let dir = get_snapshot_dir(state_dir);
+  rt::spawn_blocking(move || {
+    if !Path::new(&dir).exists() {
+      return Ok(Vec::new());
+    }
+    let entries =
+      std::fs::read_dir(&dir).map_err(|err| err.map_err_context(|| &dir))?;
+    let mut snapshots = Vec::new();
+    for entry in entries {
+      let path = entry?.path();
+      if !path.to_string_lossy().ends_with(".tar.gz") {
+        continue;
+      }
+      match read_snapshot(&path) {
+        Ok(snapshot) => snapshots.push(snapshot),
+        Err(err) => log::warn!("store::list_snapshots: {err}"),
+      }
+    }
+    snapshots.sort_by(|a, b| b.name.cmp(&a.name));
+    Ok(snapshots)
+  })
+  .await?
+}
+
+/// Remove the oldest snapshots of the store to only keep `retention` of them
+pub async fn purge_snapshots(retention: u32, state_dir: &str) -> IoResult<()> {
+  let snapshots = list_snapshots(state_dir).await?;
+  for snapshot in snapshots.into_iter().skip(retention as usize) {
+    tokio::fs::remove_file(&snapshot.path)
+      .await
+      .map_err(|err| err.map_err_context(|| &snapshot.path))?;
+  }
+  Ok(())
+}
+
+/// Remove the expired metrics and events,
+/// CockroachDB does it itself with the ttl of their tables
+#[cfg(feature = "sqlite")]
+pub async fn purge_expired(pool: &Pool) -> IoResult<()> {
+  use crate::schema::{events, metrics};
+
+  let pool = pool.clone();
+  rt::spawn_blocking(move || {
+    let mut conn = get_pool_conn(&pool)?;
+    let now = chrono::Utc::now().naive_utc();
+    diesel::delete(metrics::table.filter(metrics::expires_at.lt(now)))
+      .execute(&mut conn)
+      .and_then(|_| {
+        diesel::delete(events::table.filter(events::expires_at.lt(now)))
+          .execute(&mut conn)
+      })
+      .map_err(|err| IoError::interrupted("Store purge", &err.to_string()))?;
+    Ok::<_, IoError>(())
+  })
+  .await?
+}
+
+/// Replace the content of every table of the store with a snapshot archive
+/// in a single transaction.
+/// The snapshot must have been made with the same migrations as the store.
+pub async fn restore(path: &str, pool: &Pool) -> IoResult<StoreSnapshot> {
+  let path = PathBuf::from(path);
+  let pool = pool.clone();
+  rt::spawn_blocking(move || {
+    let snapshot = read_snapshot(&path)?;
+    let (manifest, mut tables) = read_archive(&path)?;
+    if manifest.version != SNAPSHOT_VERSION {
+      return Err(IoError::invalid_data(
+        "Store snapshot",
+        &format!("unsupported version {}", manifest.version),
+      ));
+    }
+    if manifest.backend != BACKEND {
+      return Err(IoError::invalid_data(
+        "Store snapshot",
+        &format!("made on {} but the store is on {BACKEND}", manifest.backend),
+      ));
+    }
+    let mut conn = get_pool_conn(&pool)?;
+    let migration = get_migration(&mut conn)?;
+    if manifest.migration != migration {
+      return Err(IoError::invalid_data(
+        "Store snapshot",
+        &format!(
+          "made at migration {} but the store is at migration {migration}",
+          manifest.migration
+        ),
+      ));
+    }
+    let tables = TABLES
+      .iter()
+      .map(|table| match tables.remove(*table) {
+        Some(data) => Ok((*table, data)),
+        None => Err(IoError::invalid_data(
+          "Store snapshot",
+          &format!("missing table {table}"),
+        )),
+      })
+      .collect::<IoResult<Vec<_>>>()?;
+    conn
+      .transaction(|conn| {
+        for table in TABLES.iter().rev() {
+          diesel::sql_query(format!("DELETE FROM {table}")).execute(conn)?;
+        }
+        for (table, data) in &tables {
+          load_table(conn, table, data)?;
+        }
+        Ok::<_, diesel::result::Error>(())
+      })
+      .map_err(|err| {
+        IoError::interrupted("Store restore", &format!("{err}"))
+      })?;
+    Ok(snapshot)
+  })
+  .await?
+}
+
+/// Store unit test
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// Test every postgres migration has its SQLite counterpart
+  #[test]
+  fn sqlite_migrations() {
+    let migrations = |dir: &str| {
+      let mut names = std::fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+        .collect::<Vec<_>>();
+      names.sort();
+      names
+    };
+    assert_eq!(
+      migrations("./migrations"),
+      migrations("./migrations_sqlite")
+    );
+  }
+
+  /// Test the generic repositories, the json filters
+  /// and the snapshots against an SQLite store
+  #[cfg(feature = "sqlite")]
+  #[ntex::test]
+  async fn sqlite_store() {
+    use nanocl_stubs::generic::{GenericClause, GenericFilter};
+
+    use crate::{models::SpecDb, repositories::generic::*};
+
+    let state_dir = std::env::temp_dir()
+      .join(format!("nanocld-store-{}", uuid::Uuid::new_v4()))
+      .display()
+      .to_string();
+    let conf = DaemonConfig {
+      state_dir: state_dir.clone(),
+      ..Default::default()
+    };
+    let pool = init(&conf).await.unwrap();
+    let spec = SpecDb {
+      key: uuid::Uuid::new_v4(),
+      created_at: chrono::Utc::now().naive_utc(),
+      kind_name: "Cargo".to_owned(),
+      kind_key: "test.global".to_owned(),
+      version: "v0.16".to_owned(),
+      data: serde_json::json!({ "Image": "nginx", "Ports": ["80"] }),
+      metadata: None,
+    };
+    SpecDb::create_from(spec.clone(), &pool).await.unwrap();
+    let found = SpecDb::read_by_pk(&spec.key, &pool).await.unwrap();
+    assert_eq!(found.data, spec.data);
+    assert_eq!(found.metadata, None);
+    let filter = GenericFilter::new().r#where(
+      "data",
+      GenericClause::Contains(serde_json::json!({ "Ports": ["80"] })),
+    );
+    assert_eq!(SpecDb::read_by(&filter, &pool).await.unwrap().len(), 1);
+    let filter = GenericFilter::new().r#where(
+      "data",
+      GenericClause::Contains(serde_json::json!({ "Image": "redis" })),
+    );
+    assert!(SpecDb::read_by(&filter, &pool).await.unwrap().is_empty());
+    let filter = GenericFilter::new()
+      .r#where("data", GenericClause::HasKey("Image".to_owned()))
+      .r#where("key", GenericClause::Eq(spec.key.to_string()));
+    assert_eq!(SpecDb::read_by(&filter, &pool).await.unwrap().len(), 1);
+    let snapshot = snapshot(&pool, &state_dir).await.unwrap();
+    let filter = GenericFilter::new()
+      .r#where("key", GenericClause::Eq(spec.key.to_string()));
+    SpecDb::del_by(&filter, &pool).await.unwrap();
+    assert!(SpecDb::read_by(&filter, &pool).await.unwrap().is_empty());
+    restore(&snapshot.path, &pool).await.unwrap();
+    let found = SpecDb::read_by_pk(&spec.key, &pool).await.unwrap();
+    assert_eq!(found.data, spec.data);
+    assert_eq!(found.created_at, spec.created_at);
+    let _ = std::fs::remove_dir_all(&state_dir);
+  }
+
+  /// Test every table of the schema is in the snapshots
+  /// after the tables it references
+  #[test]
+  fn snapshot_tables() {
+    let schema = include_str!("../schema.rs");
+    let mut lines = schema.lines();
+    while let Some(line) = lines.next() {
+      if line.starts_with("diesel::table!") {
+        let table = lines
+          .by_ref()
+          .map(str::trim)
+          .find(|line| !line.is_empty() && !line.starts_with("use "))
+          .unwrap()
+          .split_whitespace()
+          .next()
+          .unwrap();
+        assert!(TABLES.contains(&table), "{table} is not in TABLES");
+      }
+      if let Some(join) = line.strip_prefix("diesel::joinable!(") {
+        let mut names = join.split_whitespace();
+        let child = names.next().unwrap();
+        let parent = names.nth(1).unwrap();
+        let position = |table| TABLES.iter().position(|t| *t == table);
+        assert!(
+          position(parent) < position(child),
+          "{parent} must come before {child}"
+        );
+      }
+    }
+  }
 }
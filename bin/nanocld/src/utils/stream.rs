@@ -27,3 +27,23 @@ where
     Ok(Bytes::from(item + "\r\n"))
   })
 }
+
+/// Read a file as a stream of bytes to send it in a response
+pub async fn file_stream(
+  path: &str,
+) -> HttpResult<impl StreamExt<Item = HttpResult<Bytes>>> {
+  let file = tokio::fs::File::open(path).await.map_err(|err| {
+    HttpError::internal_server_error(format!("Unable to open {path}: {err}"))
+  })?;
+  let stream = tokio_util::codec::FramedRead::new(
+    file,
+    tokio_util::codec::BytesCodec::new(),
+  )
+  .map(|item| {
+    let item = item.map_err(|err| {
+      HttpError::internal_server_error(format!("Failed to read file: {err}"))
+    })?;
+    Ok(Bytes::copy_from_slice(&item))
+  });
+  Ok(stream)
+}
@@ -105,8 +105,10 @@ pub async fn register_namespace(
   let new_nsp = NamespacePartial {
     name: name.to_owned(),
     metadata: None,
+    quota: None,
+    limit_range: None,
   };
-  NamespaceDb::create_from(&new_nsp, &state.inner.pool).await?;
+  NamespaceDb::create_try_from(&new_nsp, &state.inner.pool).await?;
   Ok(())
 }
 
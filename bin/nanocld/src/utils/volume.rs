@@ -0,0 +1,460 @@
+use std::{collections::HashMap, path::Path};
+
+use futures_util::StreamExt;
+
+use bollard_next::{
+  container::{
+    Config, CreateContainerOptions, RemoveContainerOptions,
+    StartContainerOptions, WaitContainerOptions,
+  },
+  service::HostConfig,
+  volume::{CreateVolumeOptions, RemoveVolumeOptions},
+};
+use nanocl_error::io::{FromIo, IoError, IoResult};
+use nanocl_stubs::{
+  generic::{GenericClause, GenericFilter, ImagePullPolicy},
+  volume::{Volume, VolumeKind, VolumeMount, VolumeSnapshot},
+};
+
+use crate::{
+  models::{CargoDb, SystemState, VolumeDb},
+  repositories::generic::*,
+  utils, vars,
+};
+
+/// Validate the name of a volume
+/// By checking if it's only contain a-z, A-Z, 0-9, - and _
+pub fn validate_name(name: &str) -> IoResult<()> {
+  if name.is_empty()
+    || !name
+      .chars()
+      .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+  {
+    return Err(IoError::invalid_input(
+      "Volume",
+      &format!("name {name} can only contain a-z, A-Z, 0-9, - and _"),
+    ));
+  }
+  Ok(())
+}
+
+/// Name of the docker volume or path of the directory on the host
+pub fn get_source(key: &str, kind: &VolumeKind, state: &SystemState) -> String {
+  match kind {
+    VolumeKind::Docker => key.to_owned(),
+    VolumeKind::Host => {
+      format!("{}/volumes/data/{key}", state.inner.config.state_dir)
+    }
+  }
+}
+
+/// Directory where the snapshots of a volume are stored
+fn get_snapshot_dir(key: &str, state: &SystemState) -> String {
+  format!("{}/volumes/snapshots/{key}", state.inner.config.state_dir)
+}
+
+/// Create the docker volume or the directory backing a volume
+pub async fn create(volume: &VolumeDb, state: &SystemState) -> IoResult<()> {
+  let kind: VolumeKind = volume.kind.parse()?;
+  match kind {
+    VolumeKind::Docker => {
+      let labels = HashMap::from([
+        ("io.nanocl.v", volume.key.as_str()),
+        ("io.nanocl.n", volume.namespace_name.as_str()),
+      ]);
+      state
+        .inner
+        .docker_api
+        .create_volume(CreateVolumeOptions {
+          name: volume.source.as_str(),
+          driver: "local",
+          labels,
+          ..Default::default()
+        })
+        .await
+        .map_err(|err| err.map_err_context(|| "Volume"))?;
+    }
+    VolumeKind::Host => {
+      tokio::fs::create_dir_all(&volume.source)
+        .await
+        .map_err(|err| err.map_err_context(|| &volume.source))?;
+    }
+  }
+  Ok(())
+}
+
+/// Remove the data and the snapshots of a volume
+pub async fn remove(volume: &Volume, state: &SystemState) -> IoResult<()> {
+  match volume.kind {
+    VolumeKind::Docker => {
+      state
+        .inner
+        .docker_api
+        .remove_volume(&volume.source, None::<RemoveVolumeOptions>)
+        .await
+        .map_err(|err| err.map_err_context(|| "Volume"))?;
+    }
+    VolumeKind::Host => {
+      if Path::new(&volume.source).exists() {
+        tokio::fs::remove_dir_all(&volume.source)
+          .await
+          .map_err(|err| err.map_err_context(|| &volume.source))?;
+      }
+    }
+  }
+  let dir = get_snapshot_dir(&volume.key, state);
+  if Path::new(&dir).exists() {
+    tokio::fs::remove_dir_all(&dir)
+      .await
+      .map_err(|err| err.map_err_context(|| &dir))?;
+  }
+  Ok(())
+}
+
+/// Total size of the files in a directory
+fn get_dir_size(path: &Path) -> std::io::Result<u64> {
+  let mut size = 0;
+  for entry in std::fs::read_dir(path)? {
+    let entry = entry?;
+    let metadata = entry.metadata()?;
+    if metadata.is_dir() {
+      size += get_dir_size(&entry.path())?;
+    } else {
+      size += metadata.len();
+    }
+  }
+  Ok(size)
+}
+
+/// Size of the data of a volume in bytes when it can be computed
+pub async fn get_size(volume: &Volume, state: &SystemState) -> Option<u64> {
+  match volume.kind {
+    VolumeKind::Docker => {
+      let usage = state.inner.docker_api.df().await.ok()?;
+      let size = usage
+        .volumes
+        .unwrap_or_default()
+        .into_iter()
+        .find(|v| v.name == volume.source)?
+        .usage_data?
+        .size;
+      u64::try_from(size).ok()
+    }
+    VolumeKind::Host => {
+      let path = volume.source.clone();
+      ntex::rt::spawn_blocking(move || get_dir_size(Path::new(&path)))
+        .await
+        .ok()?
+        .ok()
+    }
+  }
+}
+
+/// Keys of the cargoes mounting a volume
+pub async fn get_used_by(
+  volume: &Volume,
+  state: &SystemState,
+) -> IoResult<Vec<String>> {
+  let filter = GenericFilter::new()
+    .r#where(
+      "namespace_name",
+      GenericClause::Eq(volume.namespace_name.clone()),
+    )
+    .r#where(
+      "data",
+      GenericClause::Contains(serde_json::json!({
+        "VolumeMounts": [
+          { "Volume": volume.name }
+        ]
+      })),
+    );
+  let cargoes = CargoDb::transform_read_by(&filter, &state.inner.pool).await?;
+  Ok(
+    cargoes
+      .into_iter()
+      .map(|cargo| cargo.spec.cargo_key)
+      .collect(),
+  )
+}
+
+/// Validate the volume mounts of a cargo,
+/// the volumes must exist in the namespace of the cargo
+pub async fn validate_mounts(
+  namespace: &str,
+  mounts: &[VolumeMount],
+  state: &SystemState,
+) -> IoResult<()> {
+  for mount in mounts {
+    if !mount.target.starts_with('/') {
+      return Err(IoError::invalid_input(
+        "VolumeMount",
+        &format!("target {} must be an absolute path", mount.target),
+      ));
+    }
+    let key = utils::key::gen_key(namespace, &mount.volume);
+    VolumeDb::read_by_pk(&key, &state.inner.pool).await?;
+  }
+  Ok(())
+}
+
+/// Binds of the volumes mounted by a cargo
+pub async fn mount(
+  namespace: &str,
+  mounts: &[VolumeMount],
+  state: &SystemState,
+) -> IoResult<Vec<String>> {
+  let mut binds = Vec::new();
+  for mount in mounts {
+    let key = utils::key::gen_key(namespace, &mount.volume);
+    let volume = VolumeDb::read_by_pk(&key, &state.inner.pool).await?;
+    let mut bind = format!("{}:{}", volume.source, mount.target);
+    if mount.read_only.unwrap_or(false) {
+      bind.push_str(":ro");
+    }
+    binds.push(bind);
+  }
+  Ok(binds)
+}
+
+/// Run a short-lived container with the volume mounted on `/data`
+/// and its snapshot directory on `/snapshots`
+async fn run_helper(
+  volume: &Volume,
+  script: &str,
+  state: &SystemState,
+) -> IoResult<()> {
+  utils::container::image::download(
+    vars::VOLUME_HELPER_IMAGE,
+    None,
+    ImagePullPolicy::IfNotPresent,
+    volume,
+    state,
+  )
+  .await?;
+  let dir = get_snapshot_dir(&volume.key, state);
+  let name = format!("{}-{}.v", volume.name, utils::key::generate_short_id(6));
+  let config = Config {
+    image: Some(vars::VOLUME_HELPER_IMAGE.to_owned()),
+    cmd: Some(vec!["sh".to_owned(), "-c".to_owned(), script.to_owned()]),
+    labels: Some(HashMap::from([(
+      "io.nanocl.v".to_owned(),
+      volume.key.clone(),
+    )])),
+    host_config: Some(HostConfig {
+      binds: Some(vec![
+        format!("{}:/data", volume.source),
+        format!("{dir}:/snapshots"),
+      ]),
+      network_mode: Some("none".to_owned()),
+      ..Default::default()
+    }),
+    ..Default::default()
+  };
+  let docker_api = &state.inner.docker_api;
+  docker_api
+    .create_container(
+      Some(CreateContainerOptions {
+        name: name.as_str(),
+        ..Default::default()
+      }),
+      config,
+    )
+    .await
+    .map_err(|err| err.map_err_context(|| "VolumeHelper"))?;
+  let res = async {
+    docker_api
+      .start_container(&name, None::<StartContainerOptions<String>>)
+      .await
+      .map_err(|err| err.map_err_context(|| "VolumeHelper"))?;
+    let mut stream = docker_api.wait_container(
+      &name,
+      Some(WaitContainerOptions {
+        condition: "not-running",
+      }),
+    );
+    while let Some(status) = stream.next().await {
+      match status {
+        Ok(status) if status.status_code == 0 => {}
+        Ok(status) => {
+          return Err(IoError::interrupted(
+            "VolumeHelper",
+            &format!("exited with code {}", status.status_code),
+          ));
+        }
+        Err(err) => {
+          return Err(IoError::interrupted("VolumeHelper", &err.to_string()));
+        }
+      }
+    }
+    Ok(())
+  }
+  .await;
+  let options = Some(RemoveContainerOptions {
+    force: true,
+    ..Default::default()
+  });
+  if let Err(err) = docker_api.remove_container(&name, options).await {
+    log::warn!("volume::run_helper: {name} {err}");
+  }
+  res
+}
+
+/// Read the snapshot file of a volume
+async fn read_snapshot(path: &Path) -> IoResult<VolumeSnapshot> {
+  let metadata = tokio::fs::metadata(path)
+    .await
+    .map_err(|err| err.map_err_context(|| path.display().to_string()))?;
+  let created_at = metadata
+    .modified()
+    .map(|time| chrono::DateTime::<chrono::Utc>::from(time).naive_utc())
+    .unwrap_or_default();
+  Ok(VolumeSnapshot {
+    name: path
+      .file_stem()
+      .unwrap_or_default()
+      .to_string_lossy()
+      .to_string(),
+    size: metadata.len(),
+    created_at,
+  })
+}
+
+/// Write the data of a volume in a new tarball
+pub async fn snapshot(
+  volume: &Volume,
+  state: &SystemState,
+) -> IoResult<VolumeSnapshot> {
+  let dir = get_snapshot_dir(&volume.key, state);
+  tokio::fs::create_dir_all(&dir)
+    .await
+    .map_err(|err| err.map_err_context(|| &dir))?;
+  let name = chrono::Utc::now().format("%Y%m%d%H%M%S%3f").to_string();
+  run_helper(
+    volume,
+    &format!("tar -cf /snapshots/{name}.tar -C /data ."),
+    state,
+  )
+  .await?;
+  read_snapshot(Path::new(&format!("{dir}/{name}.tar"))).await
+}
+
+/// List the snapshots of a volume from the most recent
+pub async fn list_snapshots(
+  volume: &Volume,
+  state: &SystemState,
+) -> IoResult<Vec<VolumeSnapshot>> {
+  let dir = get_snapshot_dir(&volume.key, state);
+  if !Path::new(&dir).exists() {
+    return Ok(Vec::new());
+  }
+  let mut entries = tokio::fs::read_dir(&dir)
+    .await
+    .map_err(|err| err.map_err_context(|| &dir))?;
+  let mut snapshots = Vec::new();
+  while let Some(entry) = entries.next_entry().await? {
+    let path = entry.path();
+    if path.extension().unwrap_or_default() != "tar" {
+      continue;
+    }
+    snapshots.push(read_snapshot(&path).await?);
+  }
+  snapshots.sort_by(|a, b| b.name.cmp(&a.name));
+  Ok(snapshots)
+}
+
+/// Get the path of the tarball of an existing snapshot of a volume
+pub fn get_snapshot_path(
+  volume: &Volume,
+  snapshot: &str,
+  state: &SystemState,
+) -> IoResult<String> {
+  if snapshot.is_empty() || !snapshot.chars().all(|c| c.is_ascii_digit()) {
+    return Err(IoError::invalid_input(
+      "VolumeSnapshot",
+      &format!("invalid name {snapshot}"),
+    ));
+  }
+  let path = format!("{}/{snapshot}.tar", get_snapshot_dir(&volume.key, state));
+  if !Path::new(&path).exists() {
+    return Err(IoError::not_found("VolumeSnapshot", snapshot));
+  }
+  Ok(path)
+}
+
+/// Store a tarball uploaded by a client as a new snapshot of a volume
+pub async fn import_snapshot<S, E>(
+  volume: &Volume,
+  mut stream: S,
+  state: &SystemState,
+) -> IoResult<VolumeSnapshot>
+where
+  S: futures_util::Stream<Item = Result<ntex::util::Bytes, E>> + Unpin,
+  E: std::fmt::Display,
+{
+  let dir = get_snapshot_dir(&volume.key, state);
+  tokio::fs::create_dir_all(&dir)
+    .await
+    .map_err(|err| err.map_err_context(|| &dir))?;
+  let name = chrono::Utc::now().format("%Y%m%d%H%M%S%3f").to_string();
+  let path = format!("{dir}/{name}.tar");
+  let mut file = tokio::fs::File::create(&path)
+    .await
+    .map_err(|err| err.map_err_context(|| &path))?;
+  while let Some(bytes) = stream.next().await {
+    let bytes = match bytes {
+      Ok(bytes) => bytes,
+      Err(err) => {
+        let _ = tokio::fs::remove_file(&path).await;
+        return Err(IoError::interrupted("VolumeSnapshot", &err.to_string()));
+      }
+    };
+    tokio::io::AsyncWriteExt::write_all(&mut file, &bytes)
+      .await
+      .map_err(|err| err.map_err_context(|| &path))?;
+  }
+  tokio::io::AsyncWriteExt::flush(&mut file)
+    .await
+    .map_err(|err| err.map_err_context(|| &path))?;
+  read_snapshot(Path::new(&path)).await
+}
+
+/// Replace the data of a volume with the content of one of its snapshots
+pub async fn restore(
+  volume: &Volume,
+  snapshot: &str,
+  state: &SystemState,
+) -> IoResult<()> {
+  get_snapshot_path(volume, snapshot, state)?;
+  run_helper(
+    volume,
+    &format!(
+      "find /data -mindepth 1 -delete && tar -xf /snapshots/{snapshot}.tar -C /data"
+    ),
+    state,
+  )
+  .await
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn names() {
+    assert!(validate_name("my-data_1").is_ok());
+    assert!(validate_name("").is_err());
+    assert!(validate_name("../data").is_err());
+    assert!(validate_name("data.global").is_err());
+  }
+
+  #[test]
+  fn dir_size() {
+    let dir = std::env::temp_dir()
+      .join(format!("nanocl-volume-{}", std::process::id()));
+    std::fs::create_dir_all(dir.join("sub")).unwrap();
+    std::fs::write(dir.join("a"), "1234").unwrap();
+    std::fs::write(dir.join("sub/b"), "56").unwrap();
+    assert_eq!(get_dir_size(&dir).unwrap(), 6);
+    std::fs::remove_dir_all(&dir).unwrap();
+  }
+}
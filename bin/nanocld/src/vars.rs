@@ -15,5 +15,13 @@ pub const COMMIT_ID: &str = env!("GIT_HASH");
 pub const CHANNEL: &str = env!("CHANNEL");
 /// Controller
 pub const CONTROLLER_NAME: &str = "nanocl.io/core";
+/// Controller reporting the decisions of the autoscaler
+pub const AUTOSCALER_CONTROLLER_NAME: &str = "nanocl.io/autoscaler";
 /// Default Virtual Machine runtime
 pub const VM_RUNTIME: &str = "ghcr.io/next-hat/nanocl-qemu:8.0.2.0";
+/// Environment variable containing the master key used to encrypt the secrets
+pub const MASTER_KEY_ENV: &str = "NANOCL_MASTER_KEY";
+/// Namespace of the objects not scoped by a namespace like jobs and secrets
+pub const UNSCOPED_NAMESPACE: &str = "global";
+/// Image of the short-lived containers reading and writing the data of volumes
+pub const VOLUME_HELPER_IMAGE: &str = "alpine:3.20";
@@ -8,6 +8,19 @@ pub struct Cli {
   /// Path to state directory
   #[clap(long)]
   pub state_dir: String,
+  /// Url of the ACME directory used by the rules requesting certificates
+  #[clap(
+    long,
+    default_value = "https://acme-v02.api.letsencrypt.org/directory"
+  )]
+  pub acme_directory_url: String,
+  /// Path to an extra CA certificate to trust when connecting
+  /// to the ACME directory like the one of a local Pebble instance
+  #[clap(long)]
+  pub acme_ca_cert: Option<String>,
+  /// Number of days before the expiration to renew the certificates
+  #[clap(long, default_value = "30")]
+  pub acme_renew_before_days: u32,
 }
 
 #[cfg(test)]
@@ -28,6 +41,25 @@ mod tests {
     let args = Cli::parse_from(["ncproxy", "--state-dir", "/test/state"]);
     assert_eq!(args.nginx_dir, "/etc/nginx");
     assert_eq!(args.state_dir, "/test/state");
+    assert_eq!(
+      args.acme_directory_url,
+      "https://acme-v02.api.letsencrypt.org/directory"
+    );
+    assert_eq!(args.acme_renew_before_days, 30);
+    let args = Cli::parse_from([
+      "ncproxy",
+      "--state-dir",
+      "/test/state",
+      "--acme-directory-url",
+      "https://localhost:14000/dir",
+      "--acme-ca-cert",
+      "/test/pebble.minica.pem",
+    ]);
+    assert_eq!(args.acme_directory_url, "https://localhost:14000/dir");
+    assert_eq!(
+      args.acme_ca_cert.as_deref(),
+      Some("/test/pebble.minica.pem")
+    );
     let _ = Cli::try_parse();
   }
 }
@@ -0,0 +1,80 @@
+use serde::{Deserialize, Serialize};
+
+/// Settings of ncproxy to request certificates with the ACME protocol
+#[derive(Clone, Debug)]
+pub struct AcmeOpts {
+  /// Url of the ACME directory when a rule doesn't set one
+  pub directory_url: String,
+  /// Path to an extra CA certificate trusted to connect to the directory
+  pub ca_cert: Option<String>,
+  /// Number of days before the expiration to renew a certificate
+  pub renew_before_days: u32,
+}
+
+/// Urls of the operations of an ACME server
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AcmeDirectory {
+  pub new_nonce: String,
+  pub new_account: String,
+  pub new_order: String,
+}
+
+/// Status of an ACME order, authorization or challenge
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AcmeStatus {
+  Pending,
+  Ready,
+  Processing,
+  Valid,
+  Invalid,
+  Deactivated,
+  Expired,
+  Revoked,
+}
+
+/// Identifier a certificate is requested for
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AcmeIdentifier {
+  #[serde(rename = "type")]
+  pub kind: String,
+  pub value: String,
+}
+
+/// Request of a certificate
+#[derive(Debug, Deserialize)]
+pub struct AcmeOrder {
+  pub status: AcmeStatus,
+  pub authorizations: Vec<String>,
+  pub finalize: String,
+  pub certificate: Option<String>,
+}
+
+/// Challenge proving the control of an identifier
+#[derive(Debug, Deserialize)]
+pub struct AcmeChallenge {
+  #[serde(rename = "type")]
+  pub kind: String,
+  pub url: String,
+  pub token: String,
+  pub status: AcmeStatus,
+  /// Reason of the failure of the challenge
+  pub error: Option<AcmeProblem>,
+}
+
+/// Authorization of the account for an identifier
+#[derive(Debug, Deserialize)]
+pub struct AcmeAuthorization {
+  pub identifier: AcmeIdentifier,
+  pub status: AcmeStatus,
+  pub challenges: Vec<AcmeChallenge>,
+}
+
+/// Error returned by an ACME server
+#[derive(Debug, Deserialize)]
+pub struct AcmeProblem {
+  #[serde(rename = "type")]
+  pub kind: String,
+  pub detail: Option<String>,
+}
@@ -0,0 +1,142 @@
+use std::{
+  collections::HashMap,
+  sync::{Arc, Mutex},
+  time::Duration,
+};
+
+use nanocld_client::stubs::proxy::UpstreamHealthCheck;
+
+/// Health check of an upstream with the defaults applied
+#[derive(Clone, Debug, PartialEq)]
+pub struct HealthCheckOpts {
+  /// Http path requested, a tcp connection is opened when not set
+  pub path: Option<String>,
+  pub interval: Duration,
+  pub timeout: Duration,
+  pub unhealthy_threshold: u32,
+  pub healthy_threshold: u32,
+}
+
+impl From<&UpstreamHealthCheck> for HealthCheckOpts {
+  fn from(check: &UpstreamHealthCheck) -> Self {
+    Self {
+      path: check.path.clone(),
+      interval: Duration::from_secs(check.interval.unwrap_or(5).max(1)),
+      timeout: Duration::from_secs(check.timeout.unwrap_or(2).max(1)),
+      unhealthy_threshold: check.unhealthy_threshold.unwrap_or(3).max(1),
+      healthy_threshold: check.healthy_threshold.unwrap_or(2).max(1),
+    }
+  }
+}
+
+/// Health of an address of an upstream from its last probes
+#[derive(Clone, Debug)]
+pub struct AddressHealth {
+  pub healthy: bool,
+  /// Number of probes in a row with the opposite result of `healthy`
+  pub count: u32,
+}
+
+impl Default for AddressHealth {
+  /// An address is healthy until enough probes fail
+  fn default() -> Self {
+    Self {
+      healthy: true,
+      count: 0,
+    }
+  }
+}
+
+impl AddressHealth {
+  /// Record the result of a probe and return true when the health changed
+  pub fn record(&mut self, success: bool, opts: &HealthCheckOpts) -> bool {
+    if success == self.healthy {
+      self.count = 0;
+      return false;
+    }
+    self.count += 1;
+    let threshold = if self.healthy {
+      opts.unhealthy_threshold
+    } else {
+      opts.healthy_threshold
+    };
+    if self.count < threshold {
+      return false;
+    }
+    self.healthy = success;
+    self.count = 0;
+    true
+  }
+}
+
+/// Health of the probed addresses by upstream key
+/// shared between the health subsystem and the rule generation
+#[derive(Clone, Default)]
+pub struct HealthStore(
+  Arc<Mutex<HashMap<String, HashMap<String, AddressHealth>>>>,
+);
+
+impl HealthStore {
+  /// Whether an address of an upstream didn't fail its probes
+  pub fn is_healthy(&self, upstream: &str, address: &str) -> bool {
+    let store = self.0.lock().unwrap_or_else(|err| err.into_inner());
+    store
+      .get(upstream)
+      .and_then(|addresses| addresses.get(address))
+      .map_or(true, |health| health.healthy)
+  }
+
+  /// Record the result of the probe of an address
+  /// and return true when its health changed
+  pub fn record(
+    &self,
+    upstream: &str,
+    address: &str,
+    success: bool,
+    opts: &HealthCheckOpts,
+  ) -> bool {
+    let mut store = self.0.lock().unwrap_or_else(|err| err.into_inner());
+    store
+      .entry(upstream.to_owned())
+      .or_default()
+      .entry(address.to_owned())
+      .or_default()
+      .record(success, opts)
+  }
+
+  /// Forget the addresses of an upstream that are not probed anymore
+  pub fn retain(&self, upstream: &str, addresses: &[String]) {
+    let mut store = self.0.lock().unwrap_or_else(|err| err.into_inner());
+    if let Some(health) = store.get_mut(upstream) {
+      health.retain(|address, _| addresses.contains(address));
+    }
+  }
+
+  /// Forget the upstreams that are not probed anymore
+  pub fn retain_upstreams(&self, upstreams: &[String]) {
+    let mut store = self.0.lock().unwrap_or_else(|err| err.into_inner());
+    store.retain(|upstream, _| upstreams.contains(upstream));
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn health_thresholds() {
+    let opts = HealthCheckOpts::from(&UpstreamHealthCheck::default());
+    let mut health = AddressHealth::default();
+    assert!(!health.record(false, &opts));
+    assert!(!health.record(false, &opts));
+    // A success in between resets the failures in a row
+    assert!(!health.record(true, &opts));
+    assert!(!health.record(false, &opts));
+    assert!(!health.record(false, &opts));
+    assert!(health.record(false, &opts));
+    assert!(!health.healthy);
+    assert!(!health.record(true, &opts));
+    assert!(health.record(true, &opts));
+    assert!(health.healthy);
+  }
+}
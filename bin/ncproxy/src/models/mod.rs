@@ -1,7 +1,11 @@
+mod acme;
+mod health;
 mod store;
 mod system;
 mod template;
 
+pub use acme::*;
+pub use health::*;
 pub use store::*;
 pub use system::*;
 pub use template::*;
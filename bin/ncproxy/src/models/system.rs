@@ -9,7 +9,7 @@ use nanocld_client::NanocldClient;
 
 use crate::utils;
 
-use super::Store;
+use super::{AcmeOpts, HealthStore, Store};
 
 /// Shared state of the program
 #[derive(Clone)]
@@ -18,6 +18,8 @@ pub struct SystemState {
   pub client: NanocldClient,
   pub event_emitter: EventEmitter,
   pub nginx_dir: String,
+  pub acme: AcmeOpts,
+  pub health: HealthStore,
 }
 
 pub type SystemStateRef = Arc<SystemState>;
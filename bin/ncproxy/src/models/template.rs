@@ -1,9 +1,11 @@
-use nanocld_client::stubs::proxy::{LimitReq, ProxySslConfig};
+use nanocld_client::stubs::proxy::{
+  LimitReq, ProxyHttpTimeouts, ProxySslConfig,
+};
 use serde::{Deserialize, Serialize};
 
 use nanocl_error::io::{IoError, IoResult};
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct LocationTemplate {
   pub path: String,
   pub upstream_key: String,
@@ -14,6 +16,74 @@ pub struct LocationTemplate {
   pub version: Option<f64>,
   pub headers: Option<Vec<String>>,
   pub ssl: Option<ProxySslConfig>,
+  pub rewrite: Option<RewriteTemplate>,
+  pub basic_auth: Option<BasicAuthTemplate>,
+  pub cors: Option<CorsTemplate>,
+  pub response_headers: Option<Vec<String>>,
+  pub max_body_size: Option<String>,
+  pub timeouts: Option<ProxyHttpTimeouts>,
+  pub error_pages: Option<Vec<ErrorPageTemplate>>,
+}
+
+/// Rewrite of the path of the requests of a location
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RewriteTemplate {
+  pub pattern: String,
+  pub replacement: String,
+}
+
+/// Server name of a stream port routed to an upstream without terminating tls
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SniRouteTemplate {
+  pub server_name: String,
+  pub upstream_key: String,
+}
+
+/// Basic authentication of a location with the file listing its users
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BasicAuthTemplate {
+  pub realm: String,
+  pub user_file: String,
+}
+
+/// Cors headers of a location
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CorsTemplate {
+  /// Regex matching the allowed origins, every origin is allowed without it
+  pub origins: Option<String>,
+  /// Allowed origin returned when every origin is allowed
+  pub any_origin: String,
+  pub methods: String,
+  pub headers: Option<String>,
+  pub expose_headers: Option<String>,
+  pub credentials: bool,
+  pub max_age: Option<u64>,
+}
+
+/// Page returned for some status codes of a location,
+/// `file` is set when the page is served by nginx from the state dir
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ErrorPageTemplate {
+  pub codes: String,
+  pub uri: String,
+  pub file: Option<String>,
+  pub content_type: String,
+}
+
+/// Protocols and ciphers accepted by a rule using ssl
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TlsTemplate {
+  pub protocols: String,
+  pub ciphers: String,
+  pub prefer_server_ciphers: bool,
+}
+
+/// A server of an upstream with his weight when traffic is split
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UpstreamServerTemplate {
+  pub address: String,
+  pub weight: Option<usize>,
+  pub backup: bool,
 }
 
 pub struct Template<'a> {
@@ -47,6 +117,10 @@ pub const STREAM_TEMPLATE: &Template = &Template {
   data: include_str!("templates/stream.conf"),
 };
 
+pub const SNI_TEMPLATE: &Template = &Template {
+  data: include_str!("templates/sni.conf"),
+};
+
 pub const HTTP_TEMPLATE: &Template = &Template {
   data: include_str!("templates/http.conf"),
 };
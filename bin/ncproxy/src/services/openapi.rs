@@ -1,9 +1,12 @@
 use utoipa::OpenApi;
 
 use nanocld_client::stubs::proxy::{
-  HttpTarget, LocationTarget, ProxyHttpLocation, ProxyRule, ProxyRuleHttp,
-  ProxyRuleStream, ProxySsl, ProxySslConfig, ProxyStreamProtocol,
-  ResourceProxyRule, StreamTarget, UnixTarget, UpstreamTarget, UriTarget,
+  HttpTarget, LocationTarget, ProxyHsts, ProxyHttpBasicAuth, ProxyHttpCors,
+  ProxyHttpErrorPage, ProxyHttpLocation, ProxyHttpRewrite, ProxyHttpTimeouts,
+  ProxyRule, ProxyRuleHttp, ProxyRuleStream, ProxySsl, ProxySslAcme,
+  ProxySslAcmeConfig, ProxySslConfig, ProxyStreamProtocol, ProxyTlsPolicy,
+  ProxyTlsProtocol, ResourceProxyRule, StreamTarget, UnixTarget,
+  UpstreamHealthCheck, UpstreamLoadBalancing, UpstreamTarget, UriTarget,
   UrlRedirect,
 };
 
@@ -44,12 +47,24 @@ impl utoipa::Modify for VersionModifier {
     ProxyRuleHttp,
     ProxyRuleStream,
     ProxyHttpLocation,
+    ProxyHttpRewrite,
+    ProxyHttpBasicAuth,
+    ProxyHttpCors,
+    ProxyHttpTimeouts,
+    ProxyHttpErrorPage,
     ProxySsl,
     ProxySslConfig,
+    ProxySslAcme,
+    ProxySslAcmeConfig,
+    ProxyTlsPolicy,
+    ProxyTlsProtocol,
+    ProxyHsts,
     ProxyStreamProtocol,
     StreamTarget,
     LocationTarget,
     UpstreamTarget,
+    UpstreamLoadBalancing,
+    UpstreamHealthCheck,
     HttpTarget,
     UriTarget,
     UrlRedirect,
@@ -0,0 +1,174 @@
+use std::{
+  collections::HashMap,
+  sync::Arc,
+  time::{Duration, Instant},
+};
+
+use ntex::{rt, time::interval};
+
+use nanocl_error::io::{FromIo, IoResult};
+use nanocld_client::stubs::{
+  generic::{GenericClause, GenericFilter},
+  proxy::{ProxyRule, ProxySsl, ProxySslAcmeConfig, ProxySslConfig},
+  resource::Resource,
+  secret::{SecretPartial, SecretUpdate},
+};
+
+use crate::{models::SystemStateRef, utils, vars};
+
+/// Interval between two checks of the domains requesting a certificate
+const TICK: Duration = Duration::from_secs(30);
+/// Delay before requesting again a certificate after a failure
+const RETRY_DELAY: Duration = Duration::from_secs(15 * 60);
+/// Maximum delay between two checks of a valid certificate
+const CHECK_DELAY: Duration = Duration::from_secs(12 * 60 * 60);
+
+/// Domain requesting a certificate with the rules using it
+struct AcmeDomain {
+  config: ProxySslAcmeConfig,
+  resources: Vec<Resource>,
+}
+
+/// Read the http rules requesting a certificate grouped by domain
+async fn read_domains(
+  state: &SystemStateRef,
+) -> IoResult<HashMap<String, AcmeDomain>> {
+  let filter = GenericFilter::new()
+    .r#where("kind", GenericClause::Eq(vars::RULE_KEY.to_owned()))
+    .r#where(
+      "data",
+      GenericClause::Contains(
+        serde_json::json!({ "Rules": [ { "Ssl": { "Acme": {} } } ] }),
+      ),
+    );
+  let resources =
+    state
+      .client
+      .list_resource(Some(&filter))
+      .await
+      .map_err(|err| {
+        err.map_err_context(|| "Unable to list resources from nanocl daemon")
+      })?;
+  let mut domains = HashMap::<String, AcmeDomain>::new();
+  for resource in resources {
+    let rule = utils::resource::serialize(&resource.spec.data)?;
+    for rule in &rule.rules {
+      let ProxyRule::Http(http) = rule else {
+        continue;
+      };
+      let (Some(domain), Some(ProxySsl::Acme(acme))) =
+        (&http.domain, &http.ssl)
+      else {
+        continue;
+      };
+      let domain =
+        domains.entry(domain.clone()).or_insert_with(|| AcmeDomain {
+          config: acme.acme.clone(),
+          resources: Vec::new(),
+        });
+      if !domain
+        .resources
+        .iter()
+        .any(|r| r.spec.resource_key == resource.spec.resource_key)
+      {
+        domain.resources.push(resource.clone());
+      }
+    }
+  }
+  Ok(domains)
+}
+
+/// Issue or renew the certificate of a domain when it's missing
+/// or about to expire and return the delay before its next check
+async fn check(
+  domain: &str,
+  acme: &AcmeDomain,
+  state: &SystemStateRef,
+) -> IoResult<Duration> {
+  let name = utils::acme::secret_name(domain);
+  let renew_before = state.acme.renew_before_days as i32;
+  let secret = state.client.reveal_secret(&name).await.ok();
+  let current = secret.as_ref().and_then(|secret| {
+    serde_json::from_value::<ProxySslConfig>(secret.data.clone()).ok()
+  });
+  if let Some(current) = current {
+    let remaining = utils::acme::get_remaining_days(&current.certificate)?;
+    if remaining > renew_before {
+      let delay = (remaining - renew_before) as u64 * 24 * 60 * 60;
+      return Ok(CHECK_DELAY.min(Duration::from_secs(delay)));
+    }
+    log::info!("acme::check: renewing the certificate of {domain}");
+  } else {
+    log::info!("acme::check: requesting a certificate for {domain}");
+  }
+  let ssl = utils::acme::issue(domain, &acme.config, state).await?;
+  let data = serde_json::to_value(&ssl)
+    .map_err(|err| err.map_err_context(|| "ProxySslConfig"))?;
+  match secret {
+    Some(_) => {
+      let update = SecretUpdate {
+        metadata: None,
+        data,
+      };
+      state.client.patch_secret(&name, &update).await?;
+    }
+    None => {
+      let secret = SecretPartial {
+        name,
+        kind: "nanocl.io/tls".to_owned(),
+        immutable: false,
+        metadata: Some(serde_json::json!({ "AcmeDomain": domain })),
+        data,
+      };
+      state.client.create_secret(&secret).await?;
+    }
+  }
+  log::info!("acme::check: certificate of {domain} issued");
+  utils::resource::update_rules(&acme.resources, state).await?;
+  state.event_emitter.emit_reload().await;
+  Ok(CHECK_DELAY)
+}
+
+/// Check the certificates of the domains due for a check
+async fn run(
+  next_checks: &mut HashMap<String, Instant>,
+  state: &SystemStateRef,
+) -> IoResult<()> {
+  let domains = read_domains(state).await?;
+  next_checks.retain(|domain, _| domains.contains_key(domain));
+  for (domain, acme) in &domains {
+    if next_checks
+      .get(domain)
+      .is_some_and(|next| *next > Instant::now())
+    {
+      continue;
+    }
+    let delay = match check(domain, acme, state).await {
+      Ok(delay) => delay,
+      Err(err) => {
+        log::warn!("acme::run: {domain} {err}");
+        RETRY_DELAY
+      }
+    };
+    next_checks.insert(domain.clone(), Instant::now() + delay);
+  }
+  Ok(())
+}
+
+/// Spawn a background thread requesting and renewing the certificates
+/// of the http rules using `Acme` ssl
+pub(crate) fn spawn(state: &SystemStateRef) {
+  let state = Arc::clone(state);
+  rt::Arbiter::new().exec_fn(move || {
+    ntex::rt::spawn(async move {
+      let mut next_checks = HashMap::new();
+      let ticker = interval(TICK);
+      loop {
+        ticker.tick().await;
+        if let Err(err) = run(&mut next_checks, &state).await {
+          log::warn!("acme::spawn: {err}");
+        }
+      }
+    });
+  });
+}
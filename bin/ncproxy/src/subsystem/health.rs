@@ -0,0 +1,255 @@
+use std::{
+  collections::HashMap,
+  sync::Arc,
+  time::{Duration, Instant},
+};
+
+use futures::{stream::FuturesUnordered, StreamExt};
+use ntex::{rt, time::interval};
+
+use nanocl_error::io::{FromIo, IoResult};
+use nanocld_client::stubs::{
+  generic::{GenericClause, GenericFilter},
+  proxy::{LocationTarget, ProxyRule, StreamTarget, UpstreamTarget},
+  resource::Resource,
+  system::{EventActor, EventActorKind, EventKind, EventPartial},
+};
+
+use crate::{
+  models::{HealthCheckOpts, SystemStateRef},
+  utils, vars,
+};
+
+/// Interval between two checks of the upstreams due for a probe
+const TICK: Duration = Duration::from_secs(1);
+/// Delay before reading again the rules to find the upstreams to probe
+const REFRESH_DELAY: Duration = Duration::from_secs(10);
+/// Name of ncproxy when reporting events to nanocld
+const CONTROLLER: &str = "ncproxy.io";
+
+/// Upstream target with a health check and the rules using it
+struct HealthTarget {
+  target: UpstreamTarget,
+  opts: HealthCheckOpts,
+  resources: Vec<Resource>,
+}
+
+/// Upstreams to probe with the state of their probes
+#[derive(Default)]
+struct HealthState {
+  targets: HashMap<String, HealthTarget>,
+  refreshed_at: Option<Instant>,
+  next_probes: HashMap<String, Instant>,
+  /// Key of the generated upstream of each target
+  upstreams: HashMap<String, String>,
+}
+
+/// Get the upstream targets of a rule
+fn get_upstream_targets(rule: &ProxyRule) -> Vec<&UpstreamTarget> {
+  match rule {
+    ProxyRule::Http(http) => http
+      .locations
+      .iter()
+      .filter_map(|location| match &location.target {
+        LocationTarget::Upstream(upstream) => Some(upstream),
+        _ => None,
+      })
+      .collect(),
+    ProxyRule::Stream(stream) => match &stream.target {
+      StreamTarget::Upstream(upstream) => vec![upstream],
+      _ => Vec::new(),
+    },
+  }
+}
+
+/// Read the upstream targets with a health check
+/// by their target key and port
+async fn read_targets(
+  state: &SystemStateRef,
+) -> IoResult<HashMap<String, HealthTarget>> {
+  let filter = GenericFilter::new()
+    .r#where("kind", GenericClause::Eq(vars::RULE_KEY.to_owned()));
+  let resources =
+    state
+      .client
+      .list_resource(Some(&filter))
+      .await
+      .map_err(|err| {
+        err.map_err_context(|| "Unable to list resources from nanocl daemon")
+      })?;
+  let mut targets = HashMap::<String, HealthTarget>::new();
+  for resource in resources {
+    let rule = utils::resource::serialize(&resource.spec.data)?;
+    for upstream in rule.rules.iter().flat_map(get_upstream_targets) {
+      let Some(health_check) = &upstream.health_check else {
+        continue;
+      };
+      let id = format!("{}:{}", upstream.key, upstream.port);
+      let target = targets.entry(id).or_insert_with(|| HealthTarget {
+        target: upstream.clone(),
+        opts: health_check.as_ref().into(),
+        resources: Vec::new(),
+      });
+      if !target
+        .resources
+        .iter()
+        .any(|r| r.spec.resource_key == resource.spec.resource_key)
+      {
+        target.resources.push(resource.clone());
+      }
+    }
+  }
+  Ok(targets)
+}
+
+/// Report the health change of an instance to nanocld
+async fn emit(
+  target: &UpstreamTarget,
+  address: &str,
+  healthy: bool,
+  note: String,
+  state: &SystemStateRef,
+) -> IoResult<()> {
+  let (name, namespace, kind) =
+    utils::rule::parse_upstream_target(&target.key)?;
+  let actor_kind = match kind.as_str() {
+    "v" => EventActorKind::Vm,
+    _ => EventActorKind::Cargo,
+  };
+  let (kind, reason) = if healthy {
+    (EventKind::Normal, "upstream_healthy")
+  } else {
+    (EventKind::Warning, "upstream_unhealthy")
+  };
+  let event = EventPartial {
+    reporting_node: String::new(),
+    reporting_controller: CONTROLLER.to_owned(),
+    kind,
+    action: "health".to_owned(),
+    reason: reason.to_owned(),
+    note: Some(note),
+    actor: Some(EventActor {
+      key: Some(format!("{name}.{namespace}")),
+      kind: actor_kind,
+      attributes: Some(serde_json::json!({
+        "Name": name,
+        "Namespace": namespace,
+      })),
+    }),
+    related: None,
+    metadata: Some(serde_json::json!({
+      "Address": address,
+      "Port": target.port,
+    })),
+  };
+  state.client.emit_event(&event).await?;
+  Ok(())
+}
+
+/// Probe the instances of an upstream and update the rules using it
+/// when the health of one of them changed.
+/// Return the key of the probed upstream.
+async fn probe_target(
+  target: &HealthTarget,
+  state: &SystemStateRef,
+) -> IoResult<String> {
+  let (key, addresses) =
+    utils::rule::get_upstream_addresses(&target.target, state).await?;
+  state.health.retain(&key, &addresses);
+  let port = target.target.port;
+  let results = addresses
+    .iter()
+    .map(|address| async move {
+      let res = utils::health::probe(address, port, &target.opts).await;
+      (address, res)
+    })
+    .collect::<FuturesUnordered<_>>()
+    .collect::<Vec<_>>()
+    .await;
+  let mut changed = false;
+  for (address, res) in results {
+    if !state
+      .health
+      .record(&key, address, res.is_ok(), &target.opts)
+    {
+      continue;
+    }
+    changed = true;
+    let note = match &res {
+      Ok(_) => {
+        format!(
+          "Instance {address}:{port} of {} is healthy",
+          target.target.key
+        )
+      }
+      Err(err) => format!(
+        "Instance {address}:{port} of {} is unhealthy: {err}",
+        target.target.key
+      ),
+    };
+    log::info!("health::probe_target: {note}");
+    if let Err(err) =
+      emit(&target.target, address, res.is_ok(), note, state).await
+    {
+      log::warn!("health::probe_target: {err}");
+    }
+  }
+  if changed {
+    utils::resource::update_rules(&target.resources, state).await?;
+    state.event_emitter.emit_reload().await;
+  }
+  Ok(key)
+}
+
+/// Probe the upstreams due for a health check
+async fn run(health: &mut HealthState, state: &SystemStateRef) -> IoResult<()> {
+  if health
+    .refreshed_at
+    .map_or(true, |at| at.elapsed() >= REFRESH_DELAY)
+  {
+    health.targets = read_targets(state).await?;
+    health.refreshed_at = Some(Instant::now());
+    let targets = &health.targets;
+    health.next_probes.retain(|id, _| targets.contains_key(id));
+    health.upstreams.retain(|id, _| targets.contains_key(id));
+    let upstreams = health.upstreams.values().cloned().collect::<Vec<_>>();
+    state.health.retain_upstreams(&upstreams);
+  }
+  for (id, target) in &health.targets {
+    if health
+      .next_probes
+      .get(id)
+      .is_some_and(|next| *next > Instant::now())
+    {
+      continue;
+    }
+    match probe_target(target, state).await {
+      Ok(key) => {
+        health.upstreams.insert(id.clone(), key);
+      }
+      Err(err) => log::warn!("health::run: {id} {err}"),
+    }
+    health
+      .next_probes
+      .insert(id.clone(), Instant::now() + target.opts.interval);
+  }
+  Ok(())
+}
+
+/// Spawn a background thread probing the instances of the upstreams
+/// with a health check to remove the unhealthy ones from the rules
+pub(crate) fn spawn(state: &SystemStateRef) {
+  let state = Arc::clone(state);
+  rt::Arbiter::new().exec_fn(move || {
+    ntex::rt::spawn(async move {
+      let mut health = HealthState::default();
+      let ticker = interval(TICK);
+      loop {
+        ticker.tick().await;
+        if let Err(err) = run(&mut health, &state).await {
+          log::warn!("health::spawn: {err}");
+        }
+      }
+    });
+  });
+}
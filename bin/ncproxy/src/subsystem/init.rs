@@ -6,10 +6,12 @@ use nanocld_client::NanocldClient;
 
 use crate::{
   cli::Cli,
-  models::{EventEmitter, Store, SystemState, SystemStateRef},
+  models::{
+    AcmeOpts, EventEmitter, HealthStore, Store, SystemState, SystemStateRef,
+  },
 };
 
-use super::{event, metric};
+use super::{acme, event, health, metric};
 
 pub async fn init(cli: &Cli) -> IoResult<SystemStateRef> {
   #[allow(unused)]
@@ -28,8 +30,16 @@ pub async fn init(cli: &Cli) -> IoResult<SystemStateRef> {
     event_emitter,
     store: Store::new(&cli.state_dir),
     nginx_dir: cli.nginx_dir.clone(),
+    acme: AcmeOpts {
+      directory_url: cli.acme_directory_url.clone(),
+      ca_cert: cli.acme_ca_cert.clone(),
+      renew_before_days: cli.acme_renew_before_days,
+    },
+    health: HealthStore::default(),
   });
   event::spawn(&state);
   metric::spawn(&state);
+  acme::spawn(&state);
+  health::spawn(&state);
   Ok(state)
 }
@@ -1,4 +1,6 @@
+mod acme;
 mod event;
+mod health;
 mod init;
 mod metric;
 
@@ -0,0 +1,608 @@
+use std::time::Duration;
+
+use ntex::{
+  http::client::{Client, ClientResponse, Connector},
+  time::Millis,
+  util::Bytes,
+};
+use openssl::{
+  asn1::Asn1Time,
+  bn::{BigNum, BigNumContext},
+  ec::{EcGroup, EcKey},
+  ecdsa::EcdsaSig,
+  error::ErrorStack,
+  hash::MessageDigest,
+  nid::Nid,
+  pkey::{PKey, Private},
+  sha::sha256,
+  ssl::{SslConnector, SslMethod},
+  stack::Stack,
+  x509::{
+    extension::SubjectAlternativeName, X509NameBuilder, X509ReqBuilder, X509,
+  },
+};
+
+use nanocl_error::io::{FromIo, IoError, IoResult};
+use nanocld_client::stubs::proxy::{ProxySslAcmeConfig, ProxySslConfig};
+
+use crate::models::{
+  AcmeAuthorization, AcmeDirectory, AcmeIdentifier, AcmeOrder, AcmeProblem,
+  AcmeStatus, SystemStateRef,
+};
+
+/// Location serving the key authorizations of the HTTP-01 challenges
+pub const CHALLENGE_PATH: &str = "/.well-known/acme-challenge/";
+/// Directory of the state directory holding the key authorizations
+pub const CHALLENGE_DIR: &str = "acme-challenges";
+/// Delay between two checks of a pending order or authorization
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+/// Maximum number of checks of a pending order or authorization
+const POLL_ATTEMPTS: usize = 60;
+/// Maximum size of a response of the ACME server
+const BODY_LIMIT: usize = 1024 * 1024;
+
+/// Name of the `nanocl.io/tls` secret holding the certificate of a domain
+pub fn secret_name(domain: &str) -> String {
+  format!("acme.{domain}")
+}
+
+/// Path of the directory serving the key authorizations
+pub fn challenge_dir(state_dir: &str) -> String {
+  format!("{state_dir}/{CHALLENGE_DIR}")
+}
+
+fn ssl_err(err: ErrorStack) -> IoError {
+  IoError::invalid_data("Acme", &err.to_string())
+}
+
+/// Encode in url safe base64 without padding as required by JWS
+fn b64url(data: &[u8]) -> String {
+  openssl::base64::encode_block(data)
+    .trim_end_matches('=')
+    .replace('+', "-")
+    .replace('/', "_")
+}
+
+/// Generate a P-256 private key
+fn gen_key() -> Result<EcKey<Private>, ErrorStack> {
+  let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1)?;
+  EcKey::generate(&group)
+}
+
+/// Generate the certificate signing request of a domain in DER
+fn gen_csr(domain: &str, key: &PKey<Private>) -> Result<Vec<u8>, ErrorStack> {
+  let mut name = X509NameBuilder::new()?;
+  name.append_entry_by_nid(Nid::COMMONNAME, domain)?;
+  let mut req = X509ReqBuilder::new()?;
+  req.set_subject_name(&name.build())?;
+  req.set_pubkey(key)?;
+  let san = SubjectAlternativeName::new()
+    .dns(domain)
+    .build(&req.x509v3_context(None))?;
+  let mut extensions = Stack::new()?;
+  extensions.push(san)?;
+  req.add_extensions(&extensions)?;
+  req.sign(key, MessageDigest::sha256())?;
+  req.build().to_der()
+}
+
+/// Number of days before a certificate in PEM expires
+pub fn get_remaining_days(pem: &str) -> IoResult<i32> {
+  let cert = X509::from_pem(pem.as_bytes()).map_err(ssl_err)?;
+  let now = Asn1Time::days_from_now(0).map_err(ssl_err)?;
+  let diff = now.diff(cert.not_after()).map_err(ssl_err)?;
+  Ok(diff.days)
+}
+
+/// Key of the ACME account signing the requests
+pub struct AcmeAccount {
+  key: EcKey<Private>,
+  /// Public key as a JSON Web Key
+  jwk: serde_json::Value,
+  /// Thumbprint of the public key used in the key authorizations
+  thumbprint: String,
+}
+
+impl AcmeAccount {
+  pub fn new(key: EcKey<Private>) -> IoResult<Self> {
+    let mut ctx = BigNumContext::new().map_err(ssl_err)?;
+    let mut x = BigNum::new().map_err(ssl_err)?;
+    let mut y = BigNum::new().map_err(ssl_err)?;
+    key
+      .public_key()
+      .affine_coordinates_gfp(key.group(), &mut x, &mut y, &mut ctx)
+      .map_err(ssl_err)?;
+    let x = b64url(&x.to_vec_padded(32).map_err(ssl_err)?);
+    let y = b64url(&y.to_vec_padded(32).map_err(ssl_err)?);
+    // Members in lexicographic order without spaces as required by RFC 7638
+    let jwk = format!(r#"{{"crv":"P-256","kty":"EC","x":"{x}","y":"{y}"}}"#);
+    let thumbprint = b64url(&sha256(jwk.as_bytes()));
+    let jwk = serde_json::from_str(&jwk)
+      .map_err(|err| err.map_err_context(|| "Jwk"))?;
+    Ok(Self {
+      key,
+      jwk,
+      thumbprint,
+    })
+  }
+
+  /// Read the key of the account or create it on first use
+  pub async fn load(path: &str) -> IoResult<Self> {
+    match tokio::fs::read(path).await {
+      Ok(pem) => {
+        let key = EcKey::private_key_from_pem(&pem).map_err(ssl_err)?;
+        Self::new(key)
+      }
+      Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+        let key = gen_key().map_err(ssl_err)?;
+        let pem = key.private_key_to_pem().map_err(ssl_err)?;
+        if let Some(dir) = std::path::Path::new(path).parent() {
+          tokio::fs::create_dir_all(dir).await?;
+        }
+        tokio::fs::write(path, pem)
+          .await
+          .map_err(|err| err.map_err_context(|| path))?;
+        Self::new(key)
+      }
+      Err(err) => Err(err.map_err_context(|| path).into()),
+    }
+  }
+
+  /// Content to serve for the token of an HTTP-01 challenge
+  pub fn key_authorization(&self, token: &str) -> String {
+    format!("{token}.{}", self.thumbprint)
+  }
+
+  /// Sign a payload already encoded into a flattened JWS
+  fn sign(
+    &self,
+    protected: &serde_json::Value,
+    payload: &str,
+  ) -> IoResult<serde_json::Value> {
+    let protected = b64url(protected.to_string().as_bytes());
+    let digest = sha256(format!("{protected}.{payload}").as_bytes());
+    let sig = EcdsaSig::sign(&digest, &self.key).map_err(ssl_err)?;
+    let mut signature = sig.r().to_vec_padded(32).map_err(ssl_err)?;
+    signature.extend(sig.s().to_vec_padded(32).map_err(ssl_err)?);
+    Ok(serde_json::json!({
+      "protected": protected,
+      "payload": payload,
+      "signature": b64url(&signature),
+    }))
+  }
+}
+
+/// Read a header of a response as a string
+fn get_header(res: &ClientResponse, name: &str) -> Option<String> {
+  res
+    .headers()
+    .get(name)
+    .and_then(|value| value.to_str().ok())
+    .map(|value| value.to_owned())
+}
+
+/// Read the body of a response, an error status returns the problem
+/// reported by the server
+async fn read_body(url: &str, res: &mut ClientResponse) -> IoResult<Bytes> {
+  let body = res
+    .body()
+    .limit(BODY_LIMIT)
+    .await
+    .map_err(|err| IoError::interrupted("Acme", &format!("{url}: {err}")))?;
+  if res.status().is_success() {
+    return Ok(body);
+  }
+  let problem =
+    serde_json::from_slice::<AcmeProblem>(&body).unwrap_or(AcmeProblem {
+      kind: res.status().to_string(),
+      detail: None,
+    });
+  Err(IoError::interrupted(
+    "Acme",
+    &format!(
+      "{url}: {} {}",
+      problem.kind,
+      problem.detail.unwrap_or_default()
+    ),
+  ))
+}
+
+fn parse_json<T>(url: &str, body: &[u8]) -> IoResult<T>
+where
+  T: serde::de::DeserializeOwned,
+{
+  serde_json::from_slice::<T>(body)
+    .map_err(|err| err.map_err_context(|| url).into())
+}
+
+/// Client of an ACME server following RFC 8555
+pub struct AcmeClient {
+  http: Client,
+  directory: AcmeDirectory,
+  account: AcmeAccount,
+  /// Url of the account once registered
+  kid: Option<String>,
+  /// Nonce returned by the last request
+  nonce: Option<String>,
+}
+
+impl AcmeClient {
+  /// Read the directory of an ACME server
+  /// trusting an extra CA certificate when given
+  pub async fn connect(
+    url: &str,
+    ca_cert: Option<&str>,
+    account: AcmeAccount,
+  ) -> IoResult<Self> {
+    let mut builder =
+      SslConnector::builder(SslMethod::tls()).map_err(ssl_err)?;
+    if let Some(path) = ca_cert {
+      let pem = tokio::fs::read(path)
+        .await
+        .map_err(|err| err.map_err_context(|| path))?;
+      let cert = X509::from_pem(&pem).map_err(ssl_err)?;
+      builder.cert_store_mut().add_cert(cert).map_err(ssl_err)?;
+    }
+    let http = Client::build()
+      .connector(Connector::default().openssl(builder.build()).finish())
+      .timeout(Millis::from_secs(30))
+      .finish();
+    let mut res =
+      http.get(url).send().await.map_err(|err| {
+        IoError::interrupted("Acme", &format!("{url}: {err}"))
+      })?;
+    let body = read_body(url, &mut res).await?;
+    Ok(Self {
+      http,
+      directory: parse_json(url, &body)?,
+      account,
+      kid: None,
+      nonce: None,
+    })
+  }
+
+  async fn new_nonce(&self) -> IoResult<String> {
+    let url = &self.directory.new_nonce;
+    let res =
+      self.http.head(url).send().await.map_err(|err| {
+        IoError::interrupted("Acme", &format!("{url}: {err}"))
+      })?;
+    get_header(&res, "replay-nonce").ok_or_else(|| {
+      IoError::invalid_data("Acme", &format!("{url}: missing nonce"))
+    })
+  }
+
+  /// Send a signed request, without payload it's a POST-as-GET.
+  /// A request rejected for its nonce is sent again once with a new one.
+  async fn send(
+    &mut self,
+    url: &str,
+    payload: Option<&serde_json::Value>,
+  ) -> IoResult<(Option<String>, Bytes)> {
+    let payload = match payload {
+      Some(payload) => b64url(payload.to_string().as_bytes()),
+      None => String::new(),
+    };
+    let mut retried = false;
+    loop {
+      let nonce = match self.nonce.take() {
+        Some(nonce) => nonce,
+        None => self.new_nonce().await?,
+      };
+      let mut protected = serde_json::json!({
+        "alg": "ES256",
+        "nonce": nonce,
+        "url": url,
+      });
+      match &self.kid {
+        Some(kid) => protected["kid"] = serde_json::json!(kid),
+        None => protected["jwk"] = self.account.jwk.clone(),
+      }
+      let body = self.account.sign(&protected, &payload)?;
+      let mut res = self
+        .http
+        .post(url)
+        .content_type("application/jose+json")
+        .send_body(body.to_string())
+        .await
+        .map_err(|err| {
+          IoError::interrupted("Acme", &format!("{url}: {err}"))
+        })?;
+      self.nonce = get_header(&res, "replay-nonce");
+      let location = get_header(&res, "location");
+      match read_body(url, &mut res).await {
+        Ok(body) => return Ok((location, body)),
+        Err(err) if !retried && err.to_string().contains(":badNonce") => {
+          retried = true;
+        }
+        Err(err) => return Err(err),
+      }
+    }
+  }
+
+  /// Register the account or find it when it already exists
+  pub async fn register(&mut self, email: Option<&str>) -> IoResult<()> {
+    let mut payload = serde_json::json!({ "termsOfServiceAgreed": true });
+    if let Some(email) = email {
+      payload["contact"] = serde_json::json!([format!("mailto:{email}")]);
+    }
+    let url = self.directory.new_account.clone();
+    let (location, _) = self.send(&url, Some(&payload)).await?;
+    self.kid = Some(location.ok_or_else(|| {
+      IoError::invalid_data("Acme", &format!("{url}: missing account url"))
+    })?);
+    Ok(())
+  }
+
+  /// Create an order for a domain and return its url
+  pub async fn new_order(
+    &mut self,
+    domain: &str,
+  ) -> IoResult<(String, AcmeOrder)> {
+    let identifier = AcmeIdentifier {
+      kind: "dns".to_owned(),
+      value: domain.to_owned(),
+    };
+    let payload = serde_json::json!({ "identifiers": [identifier] });
+    let url = self.directory.new_order.clone();
+    let (location, body) = self.send(&url, Some(&payload)).await?;
+    let location = location.ok_or_else(|| {
+      IoError::invalid_data("Acme", &format!("{url}: missing order url"))
+    })?;
+    Ok((location, parse_json(&url, &body)?))
+  }
+
+  async fn get<T>(&mut self, url: &str) -> IoResult<T>
+  where
+    T: serde::de::DeserializeOwned,
+  {
+    let (_, body) = self.send(url, None).await?;
+    parse_json(url, &body)
+  }
+
+  /// Wait for an authorization to be valid
+  async fn wait_authorization(&mut self, url: &str) -> IoResult<()> {
+    for _ in 0..POLL_ATTEMPTS {
+      let authorization = self.get::<AcmeAuthorization>(url).await?;
+      match authorization.status {
+        AcmeStatus::Valid => return Ok(()),
+        AcmeStatus::Pending => ntex::time::sleep(POLL_INTERVAL).await,
+        status => {
+          let error = authorization
+            .challenges
+            .iter()
+            .find_map(|challenge| challenge.error.as_ref())
+            .map(|problem| {
+              format!(
+                " {} {}",
+                problem.kind,
+                problem.detail.clone().unwrap_or_default()
+              )
+            })
+            .unwrap_or_default();
+          return Err(IoError::interrupted(
+            "Acme",
+            &format!(
+              "Authorization of {} is {status:?}{error}",
+              authorization.identifier.value
+            ),
+          ));
+        }
+      }
+    }
+    Err(IoError::interrupted(
+      "Acme",
+      &format!("{url}: authorization still pending"),
+    ))
+  }
+
+  /// Wait for an order to be valid and return it
+  async fn wait_order(&mut self, url: &str) -> IoResult<AcmeOrder> {
+    for _ in 0..POLL_ATTEMPTS {
+      let order = self.get::<AcmeOrder>(url).await?;
+      match order.status {
+        AcmeStatus::Valid => return Ok(order),
+        AcmeStatus::Pending | AcmeStatus::Ready | AcmeStatus::Processing => {
+          ntex::time::sleep(POLL_INTERVAL).await
+        }
+        status => {
+          return Err(IoError::interrupted(
+            "Acme",
+            &format!("{url}: order is {status:?}"),
+          ))
+        }
+      }
+    }
+    Err(IoError::interrupted(
+      "Acme",
+      &format!("{url}: order still processing"),
+    ))
+  }
+
+  /// Validate the authorizations of an order with HTTP-01 challenges,
+  /// their key authorizations are written in the challenge directory
+  /// and the paths of the files are pushed in `files`
+  async fn authorize(
+    &mut self,
+    order: &AcmeOrder,
+    dir: &str,
+    files: &mut Vec<String>,
+  ) -> IoResult<()> {
+    for url in &order.authorizations {
+      let authorization = self.get::<AcmeAuthorization>(url).await?;
+      if authorization.status == AcmeStatus::Valid {
+        continue;
+      }
+      let challenge = authorization
+        .challenges
+        .iter()
+        .find(|challenge| challenge.kind == "http-01")
+        .ok_or_else(|| {
+          IoError::invalid_data(
+            "Acme",
+            &format!(
+              "No http-01 challenge for {}",
+              authorization.identifier.value
+            ),
+          )
+        })?;
+      let path = format!("{dir}/{}", challenge.token);
+      let content = self.account.key_authorization(&challenge.token);
+      tokio::fs::write(&path, content)
+        .await
+        .map_err(|err| err.map_err_context(|| &path))?;
+      files.push(path);
+      if challenge.status == AcmeStatus::Pending {
+        let url = challenge.url.clone();
+        self.send(&url, Some(&serde_json::json!({}))).await?;
+      }
+      self.wait_authorization(url).await?;
+    }
+    Ok(())
+  }
+}
+
+/// Request a certificate for a domain with HTTP-01 challenges
+/// and return its chain and key in PEM
+pub async fn issue(
+  domain: &str,
+  config: &ProxySslAcmeConfig,
+  state: &SystemStateRef,
+) -> IoResult<ProxySslConfig> {
+  if domain.starts_with("*.") {
+    return Err(IoError::invalid_input(
+      "Acme",
+      &format!("{domain}: wildcard domains can't use http-01 challenges"),
+    ));
+  }
+  let directory_url = config
+    .directory_url
+    .as_deref()
+    .unwrap_or(&state.acme.directory_url);
+  let account =
+    AcmeAccount::load(&format!("{}/acme/account.key", state.store.dir)).await?;
+  let mut client =
+    AcmeClient::connect(directory_url, state.acme.ca_cert.as_deref(), account)
+      .await?;
+  client.register(config.email.as_deref()).await?;
+  let (order_url, order) = client.new_order(domain).await?;
+  let dir = challenge_dir(&state.store.dir);
+  tokio::fs::create_dir_all(&dir).await?;
+  let mut files = Vec::new();
+  let res = client.authorize(&order, &dir, &mut files).await;
+  for file in files {
+    let _ = tokio::fs::remove_file(file).await;
+  }
+  res?;
+  let key = PKey::from_ec_key(gen_key().map_err(ssl_err)?).map_err(ssl_err)?;
+  let csr = gen_csr(domain, &key).map_err(ssl_err)?;
+  client
+    .send(
+      &order.finalize,
+      Some(&serde_json::json!({ "csr": b64url(&csr) })),
+    )
+    .await?;
+  let order = client.wait_order(&order_url).await?;
+  let url = order.certificate.ok_or_else(|| {
+    IoError::invalid_data("Acme", &format!("{order_url}: missing certificate"))
+  })?;
+  let (_, certificate) = client.send(&url, None).await?;
+  let certificate_key = key.private_key_to_pem_pkcs8().map_err(ssl_err)?;
+  Ok(ProxySslConfig {
+    certificate: String::from_utf8_lossy(&certificate).into_owned(),
+    certificate_key: String::from_utf8_lossy(&certificate_key).into_owned(),
+    certificate_client: None,
+    verify_client: None,
+    dhparam: None,
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use openssl::x509::X509Req;
+
+  use super::*;
+
+  /// Decode the url safe base64 of the JWS
+  fn b64url_decode(data: &str) -> Vec<u8> {
+    let mut data = data.replace('-', "+").replace('_', "/");
+    while data.len() % 4 != 0 {
+      data.push('=');
+    }
+    openssl::base64::decode_block(&data).unwrap()
+  }
+
+  #[test]
+  fn key_authorization_from_thumbprint() {
+    let account = AcmeAccount::new(gen_key().unwrap()).unwrap();
+    // A sha256 digest is 43 characters in base64 without padding
+    assert_eq!(account.thumbprint.len(), 43);
+    assert!(!account.thumbprint.contains(['=', '+', '/']));
+    let expected = b64url(&sha256(
+      serde_json::to_string(&account.jwk).unwrap().as_bytes(),
+    ));
+    assert_eq!(account.thumbprint, expected);
+    assert_eq!(
+      account.key_authorization("token"),
+      format!("token.{}", account.thumbprint)
+    );
+  }
+
+  #[test]
+  fn signature_verifies_with_account_key() {
+    let account = AcmeAccount::new(gen_key().unwrap()).unwrap();
+    let protected =
+      serde_json::json!({ "alg": "ES256", "url": "https://acme" });
+    let jws = account.sign(&protected, "e30").unwrap();
+    let signature = b64url_decode(jws["signature"].as_str().unwrap());
+    assert_eq!(signature.len(), 64);
+    let sig = EcdsaSig::from_private_components(
+      BigNum::from_slice(&signature[..32]).unwrap(),
+      BigNum::from_slice(&signature[32..]).unwrap(),
+    )
+    .unwrap();
+    let signed = format!("{}.e30", jws["protected"].as_str().unwrap());
+    assert!(sig
+      .verify(&sha256(signed.as_bytes()), &account.key)
+      .unwrap());
+  }
+
+  #[test]
+  fn csr_for_domain() {
+    let key = PKey::from_ec_key(gen_key().unwrap()).unwrap();
+    let csr =
+      X509Req::from_der(&gen_csr("example.com", &key).unwrap()).unwrap();
+    assert!(csr.verify(&key).unwrap());
+    let cn = csr
+      .subject_name()
+      .entries_by_nid(Nid::COMMONNAME)
+      .next()
+      .unwrap();
+    assert_eq!(cn.data().as_slice(), b"example.com");
+  }
+
+  #[test]
+  fn remaining_days_of_certificate() {
+    let key = PKey::from_ec_key(gen_key().unwrap()).unwrap();
+    let mut name = X509NameBuilder::new().unwrap();
+    name
+      .append_entry_by_nid(Nid::COMMONNAME, "example.com")
+      .unwrap();
+    let name = name.build();
+    let mut builder = X509::builder().unwrap();
+    builder.set_subject_name(&name).unwrap();
+    builder.set_issuer_name(&name).unwrap();
+    builder.set_pubkey(&key).unwrap();
+    builder
+      .set_not_before(&Asn1Time::days_from_now(0).unwrap())
+      .unwrap();
+    builder
+      .set_not_after(&Asn1Time::days_from_now(10).unwrap())
+      .unwrap();
+    builder.sign(&key, MessageDigest::sha256()).unwrap();
+    let pem = builder.build().to_pem().unwrap();
+    let days = get_remaining_days(&String::from_utf8(pem).unwrap()).unwrap();
+    assert!((9..=10).contains(&days));
+    assert!(get_remaining_days("invalid").is_err());
+  }
+}
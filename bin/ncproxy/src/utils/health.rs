@@ -0,0 +1,85 @@
+use tokio::{
+  io::{AsyncReadExt, AsyncWriteExt},
+  net::TcpStream,
+};
+
+use nanocl_error::io::{IoError, IoResult};
+
+use crate::models::HealthCheckOpts;
+
+/// Maximum size of the response read to find the status of an http probe
+const MAX_RESPONSE_LEN: usize = 1024;
+
+/// Get the status code from the status line of an http response
+fn parse_status(response: &str) -> Option<u16> {
+  let line = response.lines().next()?;
+  let mut parts = line.split_whitespace();
+  if !parts.next()?.starts_with("HTTP/") {
+    return None;
+  }
+  parts.next()?.parse().ok()
+}
+
+/// Request a path and ensure the response status is a success or a redirect
+async fn probe_http(
+  stream: &mut TcpStream,
+  address: &str,
+  path: &str,
+) -> IoResult<()> {
+  let request =
+    format!("GET {path} HTTP/1.0\r\nHost: {address}\r\nUser-Agent: ncproxy\r\nConnection: close\r\n\r\n");
+  stream.write_all(request.as_bytes()).await?;
+  let mut response = Vec::new();
+  let mut buf = [0; MAX_RESPONSE_LEN];
+  while !response.contains(&b'\n') && response.len() < MAX_RESPONSE_LEN {
+    let len = stream.read(&mut buf).await?;
+    if len == 0 {
+      break;
+    }
+    response.extend_from_slice(&buf[..len]);
+  }
+  let response = String::from_utf8_lossy(&response);
+  match parse_status(&response) {
+    Some(status) if (200..400).contains(&status) => Ok(()),
+    Some(status) => Err(IoError::invalid_data(
+      "HealthCheck",
+      &format!("{path} responded with status {status}"),
+    )),
+    None => Err(IoError::invalid_data(
+      "HealthCheck",
+      &format!("{path} responded without status"),
+    )),
+  }
+}
+
+/// Probe an instance of an upstream by opening a tcp connection
+/// and requesting the http path of the health check when set
+pub async fn probe(
+  address: &str,
+  port: u16,
+  opts: &HealthCheckOpts,
+) -> IoResult<()> {
+  let probe = async {
+    let mut stream = TcpStream::connect((address, port)).await?;
+    if let Some(path) = &opts.path {
+      probe_http(&mut stream, address, path).await?;
+    }
+    Ok::<_, IoError>(())
+  };
+  ntex::time::timeout(opts.timeout, probe)
+    .await
+    .map_err(|_| IoError::other("HealthCheck", "Probe timed out"))?
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn status_line() {
+    assert_eq!(parse_status("HTTP/1.1 200 OK\r\n"), Some(200));
+    assert_eq!(parse_status("HTTP/1.0 503 Service Unavailable"), Some(503));
+    assert_eq!(parse_status("SSH-2.0-OpenSSH_9.6\r\n"), None);
+    assert_eq!(parse_status(""), None);
+  }
+}
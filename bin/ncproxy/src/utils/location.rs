@@ -0,0 +1,312 @@
+use nanocl_error::io::{FromIo, IoError, IoResult};
+
+use nanocld_client::stubs::proxy::{
+  ProxyHttpBasicAuth, ProxyHttpCors, ProxyHttpErrorPage, ProxyHttpLocation,
+  ProxyHttpRewrite,
+};
+
+use crate::models::{
+  BasicAuthTemplate, CorsTemplate, ErrorPageTemplate, LocationTemplate,
+  RewriteTemplate, SystemStateRef,
+};
+
+/// Kind of the secrets listing the users of a basic authentication
+pub const HTPASSWD_KIND: &str = "nanocl.io/htpasswd";
+/// Directory of the state dir with the error pages of each rule
+pub const ERROR_PAGE_DIR: &str = "error-pages";
+/// Path of the internal locations serving the error pages
+const ERROR_PAGE_PATH: &str = "/.ncproxy/errors";
+/// Methods allowed by a cors policy when not set
+const DEFAULT_CORS_METHODS: &str = "GET, POST, PUT, PATCH, DELETE, OPTIONS";
+
+/// Ensure a value can be written quoted in the nginx config
+fn ensure_quotable(context: &str, value: &str) -> IoResult<()> {
+  if value.is_empty() || value.contains(['"', '\\', '\n', '\r']) {
+    return Err(IoError::invalid_input(
+      context,
+      &format!("{value:?} can't be empty or contain quotes and new lines"),
+    ));
+  }
+  Ok(())
+}
+
+/// Escape the regex special characters of a string
+fn escape_regex(value: &str) -> String {
+  let mut escaped = String::with_capacity(value.len());
+  for c in value.chars() {
+    if "\\.+*?()|[]{}^$".contains(c) {
+      escaped.push('\\');
+    }
+    escaped.push(c);
+  }
+  escaped
+}
+
+/// Generate the rewrite of the requests of a location,
+/// stripping the prefix keeps the path of the target
+pub fn gen_rewrite(
+  path: &str,
+  rewrite: &ProxyHttpRewrite,
+  upstream_path: &str,
+) -> IoResult<RewriteTemplate> {
+  let strip_prefix = rewrite.strip_prefix.unwrap_or_default();
+  match (strip_prefix, &rewrite.pattern, &rewrite.replacement) {
+    (true, None, None) => {
+      if !path.starts_with('/') {
+        return Err(IoError::invalid_input(
+          "Rewrite",
+          "StripPrefix requires a location path starting with /",
+        ));
+      }
+      let prefix = escape_regex(path.trim_end_matches('/'));
+      let upstream_path = upstream_path.trim_end_matches('/');
+      Ok(RewriteTemplate {
+        pattern: format!("^{prefix}/?(.*)$"),
+        replacement: format!("{upstream_path}/$1"),
+      })
+    }
+    (false, Some(pattern), Some(replacement)) => {
+      ensure_quotable("Rewrite", pattern)?;
+      ensure_quotable("Rewrite", replacement)?;
+      Ok(RewriteTemplate {
+        pattern: pattern.clone(),
+        replacement: replacement.clone(),
+      })
+    }
+    _ => Err(IoError::invalid_input(
+      "Rewrite",
+      "expected either StripPrefix or a Pattern with a Replacement",
+    )),
+  }
+}
+
+/// Write the users of a `nanocl.io/htpasswd` secret in a file read by nginx
+async fn gen_basic_auth(
+  auth: &ProxyHttpBasicAuth,
+  state: &SystemStateRef,
+) -> IoResult<BasicAuthTemplate> {
+  let realm = auth.realm.clone().unwrap_or("Restricted".to_owned());
+  ensure_quotable("BasicAuth", &realm)?;
+  let secret = state.client.reveal_secret(&auth.secret).await?;
+  if secret.kind != HTPASSWD_KIND {
+    return Err(IoError::invalid_input(
+      "BasicAuth",
+      &format!("secret {} is not a {HTPASSWD_KIND}", secret.name),
+    ));
+  }
+  let users = serde_json::from_value::<Vec<String>>(secret.data)
+    .map_err(|err| err.map_err_context(|| "Unable to deserialize htpasswd"))?;
+  let user_file =
+    format!("{}/secrets/{}.htpasswd", state.store.dir, secret.name);
+  tokio::fs::write(&user_file, users.join("\n") + "\n").await?;
+  Ok(BasicAuthTemplate { realm, user_file })
+}
+
+/// Generate the headers of a cors policy.
+/// The allowed origin is reflected when the credentials are allowed
+/// since browsers refuse `*` with credentials.
+pub fn gen_cors(cors: &ProxyHttpCors) -> IoResult<CorsTemplate> {
+  let join = |values: &Option<Vec<String>>| -> IoResult<Option<String>> {
+    let Some(values) = values else {
+      return Ok(None);
+    };
+    for value in values {
+      ensure_quotable("Cors", value)?;
+    }
+    Ok(Some(values.join(", ")))
+  };
+  if cors.allow_origins.is_empty() {
+    return Err(IoError::invalid_input(
+      "Cors",
+      "AllowOrigins can't be empty",
+    ));
+  }
+  let credentials = cors.allow_credentials.unwrap_or_default();
+  let origins = match cors.allow_origins.iter().any(|origin| origin == "*") {
+    true => None,
+    false => {
+      for origin in &cors.allow_origins {
+        ensure_quotable("Cors", origin)?;
+      }
+      let origins = cors
+        .allow_origins
+        .iter()
+        .map(|origin| escape_regex(origin))
+        .collect::<Vec<_>>();
+      Some(format!("^({})$", origins.join("|")))
+    }
+  };
+  Ok(CorsTemplate {
+    origins,
+    any_origin: if credentials { "$http_origin" } else { "*" }.to_owned(),
+    methods: join(&cors.allow_methods)?
+      .unwrap_or(DEFAULT_CORS_METHODS.to_owned()),
+    headers: join(&cors.allow_headers)?,
+    expose_headers: join(&cors.expose_headers)?,
+    credentials,
+    max_age: cors.max_age,
+  })
+}
+
+/// Generate the error pages of a location,
+/// their body is written in the state dir to be served by nginx
+async fn gen_error_pages(
+  name: &str,
+  index: usize,
+  pages: &[ProxyHttpErrorPage],
+  state: &SystemStateRef,
+) -> IoResult<Vec<ErrorPageTemplate>> {
+  let dir = format!("{}/{ERROR_PAGE_DIR}/{name}", state.store.dir);
+  let mut templates = Vec::new();
+  for (i, page) in pages.iter().enumerate() {
+    if page.codes.is_empty()
+      || page.codes.iter().any(|code| !(300..=599).contains(code))
+    {
+      return Err(IoError::invalid_input(
+        "ErrorPage",
+        "Codes must be a list of status between 300 and 599",
+      ));
+    }
+    let codes = page
+      .codes
+      .iter()
+      .map(|code| code.to_string())
+      .collect::<Vec<_>>()
+      .join(" ");
+    let content_type =
+      page.content_type.clone().unwrap_or("text/html".to_owned());
+    ensure_quotable("ErrorPage", &content_type)?;
+    let (uri, file) = match (&page.uri, &page.body) {
+      (Some(uri), None) => {
+        ensure_quotable("ErrorPage", uri)?;
+        (uri.clone(), None)
+      }
+      (None, Some(body)) => {
+        tokio::fs::create_dir_all(&dir).await?;
+        let file = format!("{dir}/{index}-{i}");
+        tokio::fs::write(&file, body).await?;
+        (format!("{ERROR_PAGE_PATH}/{name}/{index}-{i}"), Some(file))
+      }
+      _ => {
+        return Err(IoError::invalid_input(
+          "ErrorPage",
+          "expected either an Uri or a Body",
+        ))
+      }
+    };
+    templates.push(ErrorPageTemplate {
+      codes,
+      uri,
+      file,
+      content_type,
+    });
+  }
+  Ok(templates)
+}
+
+/// Generate the options of a location shared by every kind of target,
+/// the target and the rewrite of the path are set by the caller
+pub async fn gen_location(
+  name: &str,
+  index: usize,
+  location: &ProxyHttpLocation,
+  state: &SystemStateRef,
+) -> IoResult<LocationTemplate> {
+  let basic_auth = match &location.basic_auth {
+    Some(auth) => Some(gen_basic_auth(auth, state).await?),
+    None => None,
+  };
+  let error_pages = match &location.error_pages {
+    Some(pages) if !pages.is_empty() => {
+      Some(gen_error_pages(name, index, pages, state).await?)
+    }
+    _ => None,
+  };
+  for header in location.response_headers.iter().flatten() {
+    if header.contains([';', '\n', '{', '}']) {
+      return Err(IoError::invalid_input(
+        "ResponseHeaders",
+        &format!("invalid header {header:?}"),
+      ));
+    }
+  }
+  if let Some(size) = &location.max_body_size {
+    let digits = size.trim_end_matches(['k', 'K', 'm', 'M', 'g', 'G']);
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+      return Err(IoError::invalid_input(
+        "MaxBodySize",
+        &format!("invalid size {size:?} expected a number like 10m"),
+      ));
+    }
+  }
+  Ok(LocationTemplate {
+    path: location.path.clone(),
+    limit_req: location.limit_req.clone(),
+    version: location.version,
+    allowed_ips: location.allowed_ips.clone(),
+    headers: location.headers.clone(),
+    basic_auth,
+    cors: location.cors.as_ref().map(gen_cors).transpose()?,
+    response_headers: location.response_headers.clone(),
+    max_body_size: location.max_body_size.clone(),
+    timeouts: location.timeouts.clone(),
+    error_pages,
+    ..Default::default()
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn rewrite() {
+    let strip = ProxyHttpRewrite {
+      strip_prefix: Some(true),
+      ..Default::default()
+    };
+    let rewrite = gen_rewrite("/api.v1/", &strip, "/").unwrap();
+    assert_eq!(rewrite.pattern, "^/api\\.v1/?(.*)$");
+    assert_eq!(rewrite.replacement, "/$1");
+    let rewrite = gen_rewrite("/api", &strip, "/app/").unwrap();
+    assert_eq!(rewrite.replacement, "/app/$1");
+    assert!(gen_rewrite("~ ^/api", &strip, "/").is_err());
+    let regex = ProxyHttpRewrite {
+      pattern: Some("^/old/(.*)$".to_owned()),
+      replacement: Some("/new/$1".to_owned()),
+      ..Default::default()
+    };
+    assert!(gen_rewrite("/", &regex, "/").is_ok());
+    let both = ProxyHttpRewrite {
+      strip_prefix: Some(true),
+      ..regex
+    };
+    assert!(gen_rewrite("/", &both, "/").is_err());
+  }
+
+  #[test]
+  fn cors() {
+    let cors = ProxyHttpCors {
+      allow_origins: vec![
+        "https://example.com".to_owned(),
+        "http://localhost:3000".to_owned(),
+      ],
+      allow_credentials: Some(true),
+      ..Default::default()
+    };
+    let template = gen_cors(&cors).unwrap();
+    assert_eq!(
+      template.origins.as_deref(),
+      Some("^(https://example\\.com|http://localhost:3000)$")
+    );
+    assert_eq!(template.methods, DEFAULT_CORS_METHODS);
+    let any = ProxyHttpCors {
+      allow_origins: vec!["*".to_owned()],
+      ..Default::default()
+    };
+    let template = gen_cors(&any).unwrap();
+    assert_eq!(template.origins, None);
+    assert_eq!(template.any_origin, "*");
+    assert!(gen_cors(&ProxyHttpCors::default()).is_err());
+  }
+}
@@ -1,7 +1,11 @@
+pub mod acme;
+pub mod health;
+pub mod location;
 pub mod nginx;
 pub mod resource;
 pub mod rule;
 pub mod server;
+pub mod sni;
 
 #[cfg(test)]
 pub(crate) mod tests {
@@ -87,6 +91,9 @@ pub(crate) mod tests {
     let options = crate::cli::Cli {
       state_dir: format!("{home}/.nanocl_dev/state/proxy"),
       nginx_dir: "/etc/nginx".to_owned(),
+      acme_directory_url: "https://localhost:14000/dir".to_owned(),
+      acme_ca_cert: None,
+      acme_renew_before_days: 30,
     };
     let system_state = crate::subsystem::init(&options).await.unwrap();
     // Create test server
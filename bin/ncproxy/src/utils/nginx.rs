@@ -1,4 +1,4 @@
-use std::{fs, sync::Arc};
+use std::{collections::HashSet, fs, sync::Arc};
 
 use futures::StreamExt;
 use ntex::web;
@@ -7,7 +7,9 @@ use nanocl_error::io::{IoError, IoResult};
 
 use nanocld_client::{
   bollard_next::exec::{CreateExecOptions, StartExecOptions},
-  stubs::proxy::{LocationTarget, ProxyRule, ResourceProxyRule},
+  stubs::proxy::{
+    LocationTarget, ProxyRule, ProxySsl, ProxyStreamProtocol, ResourceProxyRule,
+  },
   NanocldClient,
 };
 
@@ -31,6 +33,9 @@ pub async fn ensure_conf(state: &SystemStateRef) -> IoResult<()> {
       "streams-enabled",
       "log",
       "secrets",
+      "acme",
+      super::acme::CHALLENGE_DIR,
+      super::location::ERROR_PAGE_DIR,
     ]
     .iter()
     .map(|name| {
@@ -95,9 +100,13 @@ pub async fn reload(client: &NanocldClient) -> IoResult<()> {
   Ok(())
 }
 
-pub async fn add_rule(
+/// Write the configs of the rules of a resource,
+/// the tcp stream rules on the ports routed by server name are skipped
+/// since they are written with the other rules of their port
+async fn gen_rule_conf(
   name: &str,
   rule: &ResourceProxyRule,
+  sni_ports: &HashSet<u16>,
   state: &SystemStateRef,
 ) -> IoResult<()> {
   let mut stream_conf = String::new();
@@ -105,6 +114,11 @@ pub async fn add_rule(
   for rule in &rule.rules {
     match rule {
       ProxyRule::Stream(stream_rule) => {
+        if stream_rule.protocol == ProxyStreamProtocol::Tcp
+          && sni_ports.contains(&stream_rule.port)
+        {
+          continue;
+        }
         let listen = super::rule::get_network_addr(
           &stream_rule.network,
           stream_rule.port,
@@ -137,10 +151,12 @@ pub async fn add_rule(
           log::warn!("Not ssl found for {name} {ssl:#?}");
           continue;
         }
+        let tls = super::rule::gen_tls_policy(stream_rule.tls.as_ref())?;
         let data = STREAM_TEMPLATE.compile(&liquid::object!({
           "listen": listen,
           "upstream_key": upstream_key,
           "ssl": ssl,
+          "tls": tls,
         }))?;
         stream_conf += &data;
       }
@@ -159,7 +175,13 @@ pub async fn add_rule(
         )
         .await?;
         let ssl = match &http_rule.ssl {
-          Some(ssl) => match super::rule::gen_ssl_config(ssl, state).await {
+          Some(ssl) => match super::rule::gen_http_ssl_config(
+            ssl,
+            http_rule.domain.as_deref(),
+            state,
+          )
+          .await
+          {
             Err(err) => {
               log::warn!("Not ssl found for {name} {ssl:#?} {err}");
               None
@@ -168,8 +190,39 @@ pub async fn add_rule(
           },
           None => None,
         };
-        for location in &http_rule.locations {
-          match &location.target {
+        // The HTTP-01 challenges are served on port 80 of the domain
+        let acme = matches!(http_rule.ssl, Some(ProxySsl::Acme(_)));
+        if acme && http_rule.domain.is_none() {
+          log::warn!("Acme ssl of {name} requires a domain");
+          continue;
+        }
+        // Companion server on port 80 for the challenges and the redirect
+        let https_redirect = http_rule.ssl.is_some()
+          && http_rule.https_redirect.unwrap_or_default();
+        let http_listen = match acme || https_redirect {
+          true => Some(
+            super::rule::get_network_addr(
+              &http_rule.network,
+              80,
+              &state.client,
+            )
+            .await?,
+          ),
+          false => None,
+        };
+        let https_port = match http_rule.port.unwrap_or(443) {
+          443 => String::new(),
+          port => format!(":{port}"),
+        };
+        let tls = super::rule::gen_tls_policy(http_rule.tls.as_ref())?;
+        let hsts = match ssl {
+          Some(_) => super::rule::gen_hsts(http_rule.hsts.as_ref()),
+          None => None,
+        };
+        for (index, location) in http_rule.locations.iter().enumerate() {
+          let base =
+            super::location::gen_location(name, index, location, state).await?;
+          let mut template = match &location.target {
             LocationTarget::Upstream(upstream) => {
               let upstream_key = match super::rule::gen_upstream(
                 upstream,
@@ -196,18 +249,12 @@ pub async fn add_rule(
                 }
                 None => None,
               };
-              let location = LocationTemplate {
-                path: location.path.clone(),
-                limit_req: location.limit_req.clone(),
+              LocationTemplate {
                 upstream_key: format!("http://{upstream_key}"),
-                redirect: None,
                 upstream_path: upstream.path.clone().unwrap_or("/".to_owned()),
-                version: location.version,
-                allowed_ips: location.allowed_ips.clone(),
-                headers: location.headers.clone(),
                 ssl,
-              };
-              locations.push(location);
+                ..base
+              }
             }
             LocationTarget::Unix(unix) => {
               let upstream_key = super::rule::gen_unix_target_key(
@@ -216,34 +263,27 @@ pub async fn add_rule(
                 state,
               )
               .await?;
-              let location = LocationTemplate {
-                path: location.path.clone(),
+              LocationTemplate {
                 upstream_key: format!("http://{upstream_key}"),
-                redirect: None,
-                limit_req: location.limit_req.clone(),
-                upstream_path: "/".to_owned(),
-                version: location.version,
-                allowed_ips: location.allowed_ips.clone(),
-                headers: location.headers.clone(),
-                ssl: None,
-              };
-              locations.push(location);
-            }
-            LocationTarget::Http(http) => {
-              let location = LocationTemplate {
-                path: location.path.clone(),
-                upstream_key: http.url.clone(),
-                limit_req: location.limit_req.clone(),
-                version: location.version,
                 upstream_path: "/".to_owned(),
-                allowed_ips: location.allowed_ips.clone(),
-                headers: location.headers.clone(),
-                redirect: http.redirect.clone().map(|r| format!("{r}")),
-                ssl: None,
-              };
-              locations.push(location);
+                ..base
+              }
             }
+            LocationTarget::Http(http) => LocationTemplate {
+              upstream_key: http.url.clone(),
+              upstream_path: "/".to_owned(),
+              redirect: http.redirect.clone().map(|r| format!("{r}")),
+              ..base
+            },
+          };
+          if let Some(rewrite) = &location.rewrite {
+            template.rewrite = Some(super::location::gen_rewrite(
+              &location.path,
+              rewrite,
+              &template.upstream_path,
+            )?);
           }
+          locations.push(template);
         }
         let data = HTTP_TEMPLATE.compile(&liquid::object!({
           "key": name,
@@ -254,12 +294,26 @@ pub async fn add_rule(
           "locations": locations,
           "ssl": ssl,
           "hide_upstream": http_rule.ssl.is_some() && ssl.is_none(),
+          "tls": tls,
+          "hsts": hsts,
+          "http_listen": http_listen,
+          "https_redirect": https_redirect && ssl.is_some(),
+          "https_port": https_port,
+          "acme": acme,
+          "acme_challenge_path": super::acme::CHALLENGE_PATH,
+          "acme_challenge_dir": super::acme::challenge_dir(&state.store.dir),
+          "acme_pending": acme && ssl.is_none(),
         }))?;
         http_conf += &data;
       }
     }
   }
-  if !stream_conf.is_empty() {
+  if stream_conf.is_empty() {
+    state
+      .store
+      .delete_conf_file(name, &NginxRuleKind::Stream)
+      .await;
+  } else {
     state
       .store
       .write_conf_file(name, &stream_conf, &NginxRuleKind::Stream)
@@ -271,6 +325,29 @@ pub async fn add_rule(
       .write_conf_file(name, &http_conf, &NginxRuleKind::Site)
       .await?;
   }
+  Ok(())
+}
+
+/// Write the configs of the other resources sharing a port with a rule
+/// that changed of mode between a single target and server names
+async fn gen_neighbours_conf(
+  sync: &super::sni::SniSync,
+  state: &SystemStateRef,
+) -> IoResult<()> {
+  for (name, rule) in &sync.neighbours {
+    gen_rule_conf(name, rule, &sync.ports, state).await?;
+  }
+  Ok(())
+}
+
+pub async fn add_rule(
+  name: &str,
+  rule: &ResourceProxyRule,
+  state: &SystemStateRef,
+) -> IoResult<()> {
+  let sync = super::sni::sync_ports(name, Some(rule), state).await?;
+  gen_rule_conf(name, rule, &sync.ports, state).await?;
+  gen_neighbours_conf(&sync, state).await?;
   if let Err(err) = self::test(&state.client).await {
     let _ = del_rule(name, state).await;
     return Err(err);
@@ -279,6 +356,12 @@ pub async fn add_rule(
 }
 
 pub async fn del_rule(name: &str, state: &SystemStateRef) {
+  let _ = tokio::fs::remove_dir_all(format!(
+    "{}/{}/{name}",
+    state.store.dir,
+    super::location::ERROR_PAGE_DIR
+  ))
+  .await;
   let _ = state
     .store
     .delete_conf_file(name, &NginxRuleKind::Site)
@@ -287,4 +370,11 @@ pub async fn del_rule(name: &str, state: &SystemStateRef) {
     .store
     .delete_conf_file(name, &NginxRuleKind::Stream)
     .await;
+  let res = match super::sni::sync_ports(name, None, state).await {
+    Ok(sync) => gen_neighbours_conf(&sync, state).await,
+    Err(err) => Err(err),
+  };
+  if let Err(err) = res {
+    log::warn!("nginx::del_rule: {name} {err}");
+  }
 }
@@ -1,18 +1,25 @@
+use std::collections::HashMap;
+
 use nanocl_error::io::{FromIo, IoError, IoResult};
 
 use nanocld_client::{
   stubs::{
+    cargo::CargoInspect,
+    cargo_spec::CargoUpdateStrategy,
     generic::NetworkKind,
+    namespace::get_network_name,
     process::Process,
     proxy::{
-      ProxySsl, ProxySslConfig, StreamTarget, UnixTarget, UpstreamTarget,
+      ProxyHsts, ProxySsl, ProxySslConfig, ProxyTlsPolicy, ProxyTlsProtocol,
+      StreamTarget, UnixTarget, UpstreamLoadBalancing, UpstreamTarget,
     },
   },
   NanocldClient,
 };
 
 use crate::models::{
-  NginxRuleKind, SystemStateRef, UNIX_UPSTREAM_TEMPLATE, UPSTREAM_TEMPLATE,
+  NginxRuleKind, SystemStateRef, TlsTemplate, UpstreamServerTemplate,
+  UNIX_UPSTREAM_TEMPLATE, UPSTREAM_TEMPLATE,
 };
 
 /// Get public address of host
@@ -41,7 +48,9 @@ async fn get_bridge_addr(client: &NanocldClient) -> IoResult<String> {
   Ok(network.gateway.clone().unwrap_or_default())
 }
 
-fn parse_upstream_target(key: &str) -> IoResult<(String, String, String)> {
+pub(crate) fn parse_upstream_target(
+  key: &str,
+) -> IoResult<(String, String, String)> {
   let info = key.split('.').collect::<Vec<&str>>();
   if info.len() < 3 {
     return Err(IoError::invalid_data(
@@ -55,35 +64,34 @@ fn parse_upstream_target(key: &str) -> IoResult<(String, String, String)> {
   Ok((name, namespace, kind))
 }
 
+/// Get the address of a process in the given network
+fn get_address(process: &Process, network: &str) -> Option<String> {
+  log::debug!("get_address from: {}", process.name);
+  if process.name.starts_with("tmp-") {
+    return None;
+  }
+  let networks = process
+    .data
+    .network_settings
+    .clone()
+    .unwrap_or_default()
+    .networks
+    .unwrap_or_default();
+  let ip_address = networks.get(network)?.ip_address.clone()?;
+  if ip_address.is_empty() {
+    return None;
+  }
+  Some(ip_address)
+}
+
 pub async fn get_addresses(
   processes: &[Process],
   network: &str,
 ) -> IoResult<Vec<String>> {
-  let mut addresses = vec![];
-  for process in processes {
-    log::debug!("get_addresses from: {}", process.name);
-    if process.name.starts_with("tmp-") {
-      continue;
-    }
-    let networks = process
-      .data
-      .network_settings
-      .clone()
-      .unwrap_or_default()
-      .networks
-      .unwrap_or_default();
-    let network = networks.get(network);
-    let Some(network) = network else {
-      continue;
-    };
-    let Some(ip_address) = network.ip_address.clone() else {
-      continue;
-    };
-    if ip_address.is_empty() {
-      continue;
-    }
-    addresses.push(ip_address);
-  }
+  let addresses = processes
+    .iter()
+    .filter_map(|process| get_address(process, network))
+    .collect::<Vec<_>>();
   if addresses.is_empty() {
     return Err(IoError::invalid_data(
       "Process",
@@ -93,6 +101,208 @@ pub async fn get_addresses(
   Ok(addresses)
 }
 
+fn gcd(a: usize, b: usize) -> usize {
+  if b == 0 {
+    a
+  } else {
+    gcd(b, a % b)
+  }
+}
+
+/// Split the traffic between the `current` and the `previous` servers
+/// sending `weight` percent of it to the current ones.
+/// The servers without traffic are kept as backup.
+fn weigh_servers(
+  current: &[String],
+  previous: &[String],
+  weight: u8,
+) -> Vec<UpstreamServerTemplate> {
+  let weight = weight.min(100) as usize;
+  if previous.is_empty() || current.is_empty() {
+    return current
+      .iter()
+      .chain(previous.iter())
+      .map(|address| UpstreamServerTemplate {
+        address: address.clone(),
+        ..Default::default()
+      })
+      .collect();
+  }
+  // Weight of a server so the sum of a group match his percentage
+  let current_weight = weight * previous.len();
+  let previous_weight = (100 - weight) * current.len();
+  let divisor = gcd(current_weight, previous_weight).max(1);
+  let server = |address: &String, weight: usize| UpstreamServerTemplate {
+    address: address.clone(),
+    weight: (weight > 0).then_some(weight / divisor),
+    backup: weight == 0,
+  };
+  current
+    .iter()
+    .map(|address| server(address, current_weight))
+    .chain(
+      previous
+        .iter()
+        .map(|address| server(address, previous_weight)),
+    )
+    .collect()
+}
+
+/// Get the upstream servers of a cargo.
+/// During a blue/green or canary deployment the traffic is split
+/// between the instances of the current spec and the previous ones.
+fn get_cargo_servers(
+  cargo: &CargoInspect,
+  network: &str,
+) -> IoResult<Vec<UpstreamServerTemplate>> {
+  let (current, previous): (Vec<_>, Vec<_>) = cargo
+    .instances
+    .iter()
+    .partition(|process| process.is_from_spec(&cargo.spec));
+  let current = current
+    .into_iter()
+    .filter_map(|process| get_address(process, network))
+    .collect::<Vec<_>>();
+  let previous = previous
+    .into_iter()
+    .filter_map(|process| get_address(process, network))
+    .collect::<Vec<_>>();
+  if current.is_empty() && previous.is_empty() {
+    return Err(IoError::invalid_data(
+      "Process",
+      &format!("No address found for {network} are processes running ?"),
+    ));
+  }
+  let weight = match &cargo.spec.update_strategy {
+    Some(CargoUpdateStrategy::BlueGreen(_)) => 0,
+    Some(CargoUpdateStrategy::Canary(canary)) => canary.weight.unwrap_or(10),
+    _ => 100,
+  };
+  Ok(weigh_servers(&current, &previous, weight))
+}
+
+/// Get the name of the node running each address of the processes
+fn get_nodes(processes: &[Process], network: &str) -> HashMap<String, String> {
+  processes
+    .iter()
+    .filter_map(|process| {
+      let address = get_address(process, network)?;
+      Some((address, process.node_name.clone()))
+    })
+    .collect()
+}
+
+/// Get the nginx directive of the load balancing policy of an upstream,
+/// stream upstreams have no `ip_hash` but an equivalent `hash`
+fn get_method(
+  load_balancing: Option<&UpstreamLoadBalancing>,
+  kind: &NginxRuleKind,
+) -> Option<&'static str> {
+  match (load_balancing?, kind) {
+    (UpstreamLoadBalancing::LeastConn, _) => Some("least_conn"),
+    (UpstreamLoadBalancing::IpHash, NginxRuleKind::Site) => Some("ip_hash"),
+    (UpstreamLoadBalancing::IpHash, NginxRuleKind::Stream) => {
+      Some("hash $remote_addr consistent")
+    }
+    _ => None,
+  }
+}
+
+/// Apply the load balancing policy to the servers of an upstream
+/// and drop the unhealthy ones.
+/// When none of them is healthy they are all kept to let nginx retry them.
+fn balance_servers(
+  servers: Vec<UpstreamServerTemplate>,
+  nodes: &HashMap<String, String>,
+  load_balancing: Option<&UpstreamLoadBalancing>,
+  is_healthy: impl Fn(&str) -> bool,
+) -> Vec<UpstreamServerTemplate> {
+  let mut servers = match load_balancing {
+    // Backup servers can't be used with the hash methods
+    Some(UpstreamLoadBalancing::IpHash) => {
+      let (servers, backups): (Vec<_>, Vec<_>) =
+        servers.into_iter().partition(|server| !server.backup);
+      if servers.is_empty() {
+        backups
+          .into_iter()
+          .map(|server| UpstreamServerTemplate {
+            backup: false,
+            ..server
+          })
+          .collect()
+      } else {
+        servers
+      }
+    }
+    Some(UpstreamLoadBalancing::Weighted(weights)) => servers
+      .into_iter()
+      .map(|server| {
+        let node_weight = nodes
+          .get(&server.address)
+          .and_then(|node| weights.get(node))
+          .copied()
+          .unwrap_or(1);
+        if server.backup || node_weight == 0 {
+          return UpstreamServerTemplate {
+            weight: None,
+            backup: true,
+            ..server
+          };
+        }
+        let weight = server.weight.unwrap_or(1) * node_weight;
+        UpstreamServerTemplate {
+          weight: (weight > 1).then_some(weight),
+          ..server
+        }
+      })
+      .collect(),
+    _ => servers,
+  };
+  if servers.iter().any(|server| is_healthy(&server.address)) {
+    servers.retain(|server| is_healthy(&server.address));
+  }
+  // An upstream only made of backup servers is invalid
+  if servers.iter().all(|server| server.backup) {
+    for server in servers.iter_mut() {
+      server.backup = false;
+    }
+  }
+  servers
+}
+
+/// Inspect the cargo or the vm of an upstream target
+/// and return the key of its upstream with the addresses of its instances
+pub(crate) async fn get_upstream_addresses(
+  target: &UpstreamTarget,
+  state: &SystemStateRef,
+) -> IoResult<(String, Vec<String>)> {
+  let (name, namespace, kind) = parse_upstream_target(&target.key)?;
+  let (key, instances, namespace) = match kind.as_str() {
+    "c" => {
+      let cargo = state.client.inspect_cargo(&name, Some(&namespace)).await?;
+      let key = format!("{}-{}-cargo", cargo.spec.cargo_key, target.port);
+      (key, cargo.instances, cargo.namespace_name)
+    }
+    "v" => {
+      let vm = state.client.inspect_vm(&name, Some(&namespace)).await?;
+      let key = format!("{}-{}-vm", vm.spec.vm_key, target.port);
+      (key, vm.instances, vm.namespace_name)
+    }
+    _ => {
+      return Err(IoError::invalid_data(
+        "UpstreamTarget",
+        &format!("Unknown Kind {kind}"),
+      ))
+    }
+  };
+  let network = get_network_name(&namespace);
+  let addresses = instances
+    .iter()
+    .filter_map(|process| get_address(process, &network))
+    .collect();
+  Ok((key, addresses))
+}
+
 pub async fn get_network_addr(
   network: &NetworkKind,
   port: u16,
@@ -120,7 +330,7 @@ pub async fn gen_ssl_config(
   match ssl {
     ProxySsl::Config(ssl_config) => Ok(ssl_config.clone()),
     ProxySsl::Secret(secret) => {
-      let secret = state.client.inspect_secret(secret).await?;
+      let secret = state.client.reveal_secret(secret).await?;
       let mut ssl_config =
         serde_json::from_value::<ProxySslConfig>(secret.data).map_err(
           |err| err.map_err_context(|| "Unable to deserialize ProxySslConfig"),
@@ -144,9 +354,92 @@ pub async fn gen_ssl_config(
       ssl_config.certificate_key = key_path;
       Ok(ssl_config)
     }
+    ProxySsl::Acme(_) => Err(IoError::invalid_input(
+      "ProxySsl",
+      "Acme is only available for http rules with a domain",
+    )),
+  }
+}
+
+/// Generate the protocols and ciphers accepted by a rule using ssl,
+/// only TLSv1.2 and TLSv1.3 are accepted by default
+pub fn gen_tls_policy(tls: Option<&ProxyTlsPolicy>) -> IoResult<TlsTemplate> {
+  let tls = tls.cloned().unwrap_or_default();
+  let protocols = tls
+    .protocols
+    .unwrap_or(vec![ProxyTlsProtocol::TlsV1_2, ProxyTlsProtocol::TlsV1_3]);
+  if protocols.is_empty() {
+    return Err(IoError::invalid_input(
+      "TlsPolicy",
+      "Protocols can't be empty",
+    ));
+  }
+  let ciphers = tls.ciphers.unwrap_or("HIGH:!aNULL:!MD5".to_owned());
+  if ciphers.is_empty()
+    || !ciphers
+      .chars()
+      .all(|c| c.is_ascii_alphanumeric() || ":!+-_@=.".contains(c))
+  {
+    return Err(IoError::invalid_input(
+      "TlsPolicy",
+      &format!("invalid ciphers {ciphers:?}"),
+    ));
+  }
+  Ok(TlsTemplate {
+    protocols: protocols
+      .iter()
+      .map(|protocol| protocol.to_string())
+      .collect::<Vec<_>>()
+      .join(" "),
+    ciphers,
+    prefer_server_ciphers: tls.prefer_server_ciphers.unwrap_or_default(),
+  })
+}
+
+/// Generate the value of the Strict-Transport-Security header,
+/// the subdomains and the preload list are opt-in
+pub fn gen_hsts(hsts: Option<&ProxyHsts>) -> Option<String> {
+  let hsts = hsts.cloned().unwrap_or_default();
+  if !hsts.enabled.unwrap_or(true) {
+    return None;
+  }
+  let mut value = format!("max-age={}", hsts.max_age.unwrap_or(31536000));
+  if hsts.include_sub_domains.unwrap_or_default() {
+    value.push_str("; includeSubDomains");
+  }
+  if hsts.preload.unwrap_or_default() {
+    value.push_str("; preload");
+  }
+  Some(value)
+}
+
+/// Generate the ssl config of an http rule,
+/// a certificate requested with ACME is read from its secret once issued
+pub async fn gen_http_ssl_config(
+  ssl: &ProxySsl,
+  domain: Option<&str>,
+  state: &SystemStateRef,
+) -> IoResult<ProxySslConfig> {
+  match (ssl, domain) {
+    (ProxySsl::Acme(_), Some(domain)) => {
+      let secret = ProxySsl::Secret(super::acme::secret_name(domain));
+      gen_ssl_config(&secret, state).await
+    }
+    _ => gen_ssl_config(ssl, state).await,
   }
 }
 
+/// Whether an address of an upstream passed its health checks,
+/// it's always the case without health check
+fn is_healthy(
+  target: &UpstreamTarget,
+  key: &str,
+  address: &str,
+  state: &SystemStateRef,
+) -> bool {
+  target.health_check.is_none() || state.health.is_healthy(key, address)
+}
+
 pub async fn gen_upstream(
   target: &UpstreamTarget,
   kind: &NginxRuleKind,
@@ -166,12 +459,19 @@ pub async fn gen_upstream(
             format!("Unable to inspect cargo {target_name}")
           })
         })?;
-      let addresses = get_addresses(&cargo.instances, "nanoclbr0").await?;
+      let network = get_network_name(&cargo.namespace_name);
       let key = format!("{}-{}-cargo", cargo.spec.cargo_key, port);
+      let servers = balance_servers(
+        get_cargo_servers(&cargo, &network)?,
+        &get_nodes(&cargo.instances, &network),
+        target.load_balancing.as_deref(),
+        |address| is_healthy(target, &key, address, state),
+      );
       let data = UPSTREAM_TEMPLATE.compile(&liquid::object!({
         "key": key,
         "port": port,
-        "addresses": addresses,
+        "method": get_method(target.load_balancing.as_deref(), kind),
+        "servers": servers,
       }))?;
       (key, data)
     }
@@ -183,12 +483,27 @@ pub async fn gen_upstream(
         .map_err(|err| {
           err.map_err_context(|| format!("Unable to inspect vm {target_name}"))
         })?;
-      let addresses = get_addresses(&vm.instances, "nanoclbr0").await?;
+      let network = get_network_name(&vm.namespace_name);
       let key = format!("{}-{}-vm", vm.spec.vm_key, port);
+      let servers = get_addresses(&vm.instances, &network)
+        .await?
+        .into_iter()
+        .map(|address| UpstreamServerTemplate {
+          address,
+          ..Default::default()
+        })
+        .collect::<Vec<_>>();
+      let servers = balance_servers(
+        servers,
+        &get_nodes(&vm.instances, &network),
+        target.load_balancing.as_deref(),
+        |address| is_healthy(target, &key, address, state),
+      );
       let data = UPSTREAM_TEMPLATE.compile(&liquid::object!({
         "key": key,
         "port": port,
-        "addresses": addresses,
+        "method": get_method(target.load_balancing.as_deref(), kind),
+        "servers": servers,
       }))?;
       (key, data)
     }
@@ -236,3 +551,112 @@ pub async fn gen_stream_upstream_key(
     }
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn addresses(addresses: &[&str]) -> Vec<String> {
+    addresses
+      .iter()
+      .map(|address| address.to_string())
+      .collect()
+  }
+
+  #[test]
+  fn weigh_canary_servers() {
+    let servers = weigh_servers(
+      &addresses(&["10.0.0.3"]),
+      &addresses(&["10.0.0.1", "10.0.0.2"]),
+      10,
+    );
+    let weights = servers.iter().map(|s| s.weight).collect::<Vec<_>>();
+    // 10% for the single new instance, 45% for each previous one
+    assert_eq!(weights, vec![Some(2), Some(9), Some(9)]);
+    assert!(servers.iter().all(|s| !s.backup));
+  }
+
+  #[test]
+  fn weigh_blue_green_servers() {
+    let servers =
+      weigh_servers(&addresses(&["10.0.0.2"]), &addresses(&["10.0.0.1"]), 0);
+    assert!(servers[0].backup);
+    assert_eq!(servers[1].weight, Some(1));
+    let servers = weigh_servers(&addresses(&["10.0.0.2"]), &[], 0);
+    assert_eq!(
+      servers[0],
+      UpstreamServerTemplate {
+        address: "10.0.0.2".to_owned(),
+        ..Default::default()
+      }
+    );
+  }
+
+  #[test]
+  fn balance_weighted_unhealthy_servers() {
+    let servers =
+      weigh_servers(&addresses(&["10.0.0.3"]), &addresses(&["10.0.0.1"]), 0);
+    let nodes = HashMap::from([
+      ("10.0.0.1".to_owned(), "node1".to_owned()),
+      ("10.0.0.3".to_owned(), "node2".to_owned()),
+    ]);
+    // Hash methods can't have backup servers
+    let hashed = balance_servers(
+      servers.clone(),
+      &nodes,
+      Some(&UpstreamLoadBalancing::IpHash),
+      |_| true,
+    );
+    assert_eq!(hashed.len(), 1);
+    assert_eq!(hashed[0].address, "10.0.0.1");
+    let weights = HashMap::from([("node1".to_owned(), 3)]);
+    let weighted = balance_servers(
+      servers.clone(),
+      &nodes,
+      Some(&UpstreamLoadBalancing::Weighted(weights)),
+      |_| true,
+    );
+    assert_eq!(weighted[1].weight, Some(3));
+    assert!(weighted[0].backup);
+    // The unhealthy server is dropped and the backup one takes the traffic
+    let healthy =
+      balance_servers(servers.clone(), &nodes, None, |addr| addr != "10.0.0.1");
+    assert_eq!(healthy.len(), 1);
+    assert!(!healthy[0].backup);
+    // Every server is kept when none of them is healthy
+    let unhealthy = balance_servers(servers, &nodes, None, |_| false);
+    assert_eq!(unhealthy.len(), 2);
+    assert_eq!(
+      get_method(Some(&UpstreamLoadBalancing::IpHash), &NginxRuleKind::Stream),
+      Some("hash $remote_addr consistent")
+    );
+  }
+
+  #[test]
+  fn tls_policy_and_hsts() {
+    let tls = gen_tls_policy(None).unwrap();
+    assert_eq!(tls.protocols, "TLSv1.2 TLSv1.3");
+    assert_eq!(tls.ciphers, "HIGH:!aNULL:!MD5");
+    let invalid = ProxyTlsPolicy {
+      ciphers: Some("HIGH; include /etc/passwd".to_owned()),
+      ..Default::default()
+    };
+    assert!(gen_tls_policy(Some(&invalid)).is_err());
+    assert_eq!(gen_hsts(None).as_deref(), Some("max-age=31536000"));
+    let hsts = ProxyHsts {
+      max_age: Some(600),
+      include_sub_domains: Some(true),
+      preload: Some(true),
+      ..Default::default()
+    };
+    assert_eq!(
+      gen_hsts(Some(&hsts)).as_deref(),
+      Some("max-age=600; includeSubDomains; preload")
+    );
+    let disabled = ProxyHsts {
+      enabled: Some(false),
+      ..Default::default()
+    };
+    assert_eq!(gen_hsts(Some(&disabled)), None);
+  }
+}
@@ -0,0 +1,384 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use nanocl_error::io::{FromIo, IoError, IoResult};
+
+use nanocld_client::stubs::{
+  generic::{GenericClause, GenericFilter, NetworkKind},
+  proxy::{ProxyRule, ProxyRuleStream, ProxyStreamProtocol, ResourceProxyRule},
+};
+
+use crate::{
+  models::{NginxRuleKind, SniRouteTemplate, SystemStateRef, SNI_TEMPLATE},
+  vars,
+};
+
+/// Prefix of the stream configs of the ports routed by server name
+const CONF_PREFIX: &str = "ncproxy-sni-";
+
+/// Tcp stream rules sharing a port routed by the tls server name
+#[derive(Debug)]
+pub struct SniPort<'a> {
+  pub port: u16,
+  pub network: &'a NetworkKind,
+  /// Lowercase server names with the rule they are routed to
+  pub routes: Vec<(String, &'a ProxyRuleStream)>,
+  /// Rule receiving the connections without a matching server name
+  pub default: Option<&'a ProxyRuleStream>,
+}
+
+/// Result of the update of the ports routed by server name
+#[derive(Debug, Default)]
+pub struct SniSync {
+  /// Ports routed by server name
+  pub ports: HashSet<u16>,
+  /// Other resources with stream rules on the ports of the updated rule,
+  /// they must be generated again when a port changed of mode
+  pub neighbours: Vec<(String, ResourceProxyRule)>,
+}
+
+/// Ensure a server name is a hostname or a wildcard like `*.example.com`
+fn validate_server_name(server_name: &str) -> IoResult<()> {
+  let hostname = server_name.strip_prefix("*.").unwrap_or(server_name);
+  if hostname.is_empty()
+    || hostname.starts_with('.')
+    || hostname.ends_with('.')
+    || !hostname
+      .chars()
+      .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '.')
+  {
+    return Err(IoError::invalid_input(
+      "Sni",
+      &format!("invalid server name {server_name:?}"),
+    ));
+  }
+  Ok(())
+}
+
+/// Group the tcp stream rules of the resources by port
+/// and ensure the rules of the ports routed by server name don't conflict
+pub fn gen_sni_ports<'a>(
+  resources: &[(&'a str, &'a ResourceProxyRule)],
+) -> IoResult<Vec<SniPort<'a>>> {
+  let mut ports = BTreeMap::<u16, Vec<(&str, &ProxyRuleStream)>>::new();
+  for (name, resource) in resources {
+    for rule in &resource.rules {
+      let ProxyRule::Stream(stream) = rule else {
+        continue;
+      };
+      if let Some(server_names) = &stream.sni {
+        if stream.protocol != ProxyStreamProtocol::Tcp {
+          return Err(IoError::invalid_input(
+            "Sni",
+            &format!("{name} port {} requires the Tcp protocol", stream.port),
+          ));
+        }
+        if stream.ssl.is_some() {
+          return Err(IoError::invalid_input(
+            "Sni",
+            &format!(
+              "{name} port {} can't terminate ssl, tls is passed to the target",
+              stream.port
+            ),
+          ));
+        }
+        if server_names.is_empty() {
+          return Err(IoError::invalid_input(
+            "Sni",
+            &format!("{name} port {} has no server names", stream.port),
+          ));
+        }
+        for server_name in server_names {
+          validate_server_name(server_name)?;
+        }
+      }
+      if stream.protocol == ProxyStreamProtocol::Tcp {
+        ports.entry(stream.port).or_default().push((name, stream));
+      }
+    }
+  }
+  let mut sni_ports = Vec::new();
+  for (port, rules) in ports {
+    if rules.iter().all(|(_, rule)| rule.sni.is_none()) {
+      continue;
+    }
+    let network = &rules[0].1.network;
+    let mut routes = Vec::new();
+    let mut owners = HashMap::<String, &str>::new();
+    let mut default: Option<(&str, &ProxyRuleStream)> = None;
+    for (name, rule) in rules {
+      if rule.network != *network {
+        return Err(IoError::invalid_input(
+          "Sni",
+          &format!(
+            "rules on port {port} must use the same network, {name} uses {}",
+            rule.network
+          ),
+        ));
+      }
+      let Some(server_names) = &rule.sni else {
+        if rule.ssl.is_some() {
+          return Err(IoError::invalid_input(
+            "Sni",
+            &format!(
+              "{name} can't terminate ssl on port {port} routed by server name"
+            ),
+          ));
+        }
+        if let Some((other, _)) = default {
+          return Err(IoError::invalid_input(
+            "Sni",
+            &format!("{other} and {name} both listen on port {port} without server names"),
+          ));
+        }
+        default = Some((name, rule));
+        continue;
+      };
+      for server_name in server_names {
+        let server_name = server_name.to_lowercase();
+        if let Some(other) = owners.insert(server_name.clone(), name) {
+          return Err(IoError::invalid_input(
+            "Sni",
+            &format!("server name {server_name} on port {port} is used by {other} and {name}"),
+          ));
+        }
+        routes.push((server_name, rule));
+      }
+    }
+    sni_ports.push(SniPort {
+      port,
+      network,
+      routes,
+      default: default.map(|(_, rule)| rule),
+    });
+  }
+  Ok(sni_ports)
+}
+
+/// Get the ports of the stream rules of a resource
+fn get_stream_ports(resource: &ResourceProxyRule) -> HashSet<u16> {
+  resource
+    .rules
+    .iter()
+    .filter_map(|rule| match rule {
+      ProxyRule::Stream(stream) => Some(stream.port),
+      _ => None,
+    })
+    .collect()
+}
+
+/// Generate the upstream of a rule routed by server name,
+/// the rule is ignored when its target can't be resolved
+async fn gen_upstream_key(
+  rule: &ProxyRuleStream,
+  state: &SystemStateRef,
+) -> Option<String> {
+  match super::rule::gen_stream_upstream_key(&rule.target, state).await {
+    Err(err) => {
+      log::warn!("sni::gen_upstream_key: {err} {:#?}", rule.target);
+      None
+    }
+    Ok(upstream_key) => Some(upstream_key),
+  }
+}
+
+/// Write the config of a port routed by server name
+async fn write_port(
+  sni_port: &SniPort<'_>,
+  state: &SystemStateRef,
+) -> IoResult<()> {
+  let listen = super::rule::get_network_addr(
+    sni_port.network,
+    sni_port.port,
+    &state.client,
+  )
+  .await?;
+  let mut routes = Vec::new();
+  for (server_name, rule) in &sni_port.routes {
+    if let Some(upstream_key) = gen_upstream_key(rule, state).await {
+      routes.push(SniRouteTemplate {
+        server_name: server_name.clone(),
+        upstream_key,
+      });
+    }
+  }
+  let default = match sni_port.default {
+    Some(rule) => gen_upstream_key(rule, state).await,
+    None => None,
+  };
+  let data = SNI_TEMPLATE.compile(&liquid::object!({
+    "variable": format!("$sni_{}", sni_port.port),
+    "listen": listen,
+    "routes": routes,
+    "default": default,
+  }))?;
+  state
+    .store
+    .write_conf_file(
+      &format!("{CONF_PREFIX}{}", sni_port.port),
+      &data,
+      &NginxRuleKind::Stream,
+    )
+    .await
+}
+
+/// Delete the configs of the ports not routed by server name anymore
+async fn delete_stale_ports(
+  ports: &HashSet<u16>,
+  state: &SystemStateRef,
+) -> IoResult<()> {
+  let dir = format!("{}/streams-available", state.store.dir);
+  let mut entries = tokio::fs::read_dir(&dir).await.map_err(|err| {
+    err.map_err_context(|| format!("Unable to read directory {dir}"))
+  })?;
+  while let Some(entry) = entries.next_entry().await? {
+    let file_name = entry.file_name();
+    let Some(port) = file_name
+      .to_str()
+      .and_then(|file_name| file_name.strip_prefix(CONF_PREFIX))
+      .and_then(|file_name| file_name.strip_suffix(".conf"))
+      .and_then(|port| port.parse::<u16>().ok())
+    else {
+      continue;
+    };
+    if !ports.contains(&port) {
+      state
+        .store
+        .delete_conf_file(
+          &format!("{CONF_PREFIX}{port}"),
+          &NginxRuleKind::Stream,
+        )
+        .await;
+    }
+  }
+  Ok(())
+}
+
+/// Update the configs of the ports routed by server name
+/// with the rule of a resource replaced by `incoming` or removed when `None`
+pub async fn sync_ports(
+  name: &str,
+  incoming: Option<&ResourceProxyRule>,
+  state: &SystemStateRef,
+) -> IoResult<SniSync> {
+  let filter = GenericFilter::new()
+    .r#where("kind", GenericClause::Eq(vars::RULE_KEY.to_owned()));
+  let resources =
+    state
+      .client
+      .list_resource(Some(&filter))
+      .await
+      .map_err(|err| {
+        err.map_err_context(|| "Unable to list resources from nanocl daemon")
+      })?;
+  let mut affected_ports = incoming.map(get_stream_ports).unwrap_or_default();
+  let mut others = Vec::new();
+  for resource in resources {
+    let rule = super::resource::serialize(&resource.spec.data)?;
+    if resource.spec.resource_key == name {
+      affected_ports.extend(get_stream_ports(&rule));
+      continue;
+    }
+    others.push((resource.spec.resource_key, rule));
+  }
+  let mut rules = others
+    .iter()
+    .map(|(name, rule)| (name.as_str(), rule))
+    .collect::<Vec<_>>();
+  if let Some(incoming) = incoming {
+    rules.push((name, incoming));
+  }
+  let sni_ports = gen_sni_ports(&rules)?;
+  for sni_port in &sni_ports {
+    write_port(sni_port, state).await?;
+  }
+  let ports = sni_ports
+    .iter()
+    .map(|sni_port| sni_port.port)
+    .collect::<HashSet<_>>();
+  delete_stale_ports(&ports, state).await?;
+  let neighbours = others
+    .into_iter()
+    .filter(|(_, rule)| !get_stream_ports(rule).is_disjoint(&affected_ports))
+    .collect();
+  Ok(SniSync { ports, neighbours })
+}
+
+#[cfg(test)]
+mod tests {
+  use nanocld_client::stubs::proxy::{ProxySsl, StreamTarget, UpstreamTarget};
+
+  use super::*;
+
+  fn stream_rule(port: u16, sni: Option<&[&str]>) -> ProxyRule {
+    ProxyRule::Stream(ProxyRuleStream {
+      network: NetworkKind::All,
+      protocol: ProxyStreamProtocol::Tcp,
+      port,
+      ssl: None,
+      tls: None,
+      sni: sni.map(|names| names.iter().map(|name| name.to_string()).collect()),
+      target: StreamTarget::Upstream(UpstreamTarget {
+        key: "app.global.c".to_owned(),
+        port: 443,
+        path: None,
+        disable_logging: None,
+        ssl: None,
+        load_balancing: None,
+        health_check: None,
+      }),
+    })
+  }
+
+  fn resource(rules: Vec<ProxyRule>) -> ResourceProxyRule {
+    ResourceProxyRule { rules }
+  }
+
+  #[test]
+  fn sni_ports() {
+    let api = resource(vec![stream_rule(443, Some(&["api.example.com"]))]);
+    let web = resource(vec![
+      stream_rule(443, Some(&["*.example.com", "example.com"])),
+      stream_rule(5432, None),
+    ]);
+    let fallback = resource(vec![stream_rule(443, None)]);
+    let ports =
+      gen_sni_ports(&[("api", &api), ("web", &web), ("fallback", &fallback)])
+        .unwrap();
+    assert_eq!(ports.len(), 1);
+    assert_eq!(ports[0].port, 443);
+    assert_eq!(ports[0].routes.len(), 3);
+    assert!(ports[0].default.is_some());
+    // A server name is routed to a single rule
+    let conflict = resource(vec![stream_rule(443, Some(&["API.example.com"]))]);
+    let err = gen_sni_ports(&[("api", &api), ("conflict", &conflict)])
+      .unwrap_err()
+      .to_string();
+    assert!(err.contains("api") && err.contains("conflict"));
+    // A single rule without server names receives the other connections
+    assert!(gen_sni_ports(&[
+      ("api", &api),
+      ("fallback", &fallback),
+      ("web", &web)
+    ])
+    .is_ok());
+    assert!(gen_sni_ports(&[
+      ("api", &api),
+      ("fallback", &fallback),
+      ("other", &fallback)
+    ])
+    .is_err());
+    // Ports without server names are left to the rules
+    assert!(
+      gen_sni_ports(&[("fallback", &fallback), ("other", &fallback)])
+        .unwrap()
+        .is_empty()
+    );
+    let invalid = resource(vec![stream_rule(443, Some(&["api example.com"]))]);
+    assert!(gen_sni_ports(&[("invalid", &invalid)]).is_err());
+    let mut ssl = stream_rule(443, Some(&["api.example.com"]));
+    if let ProxyRule::Stream(stream) = &mut ssl {
+      stream.ssl = Some(ProxySsl::Secret("cert".to_owned()));
+    }
+    assert!(gen_sni_ports(&[("ssl", &resource(vec![ssl]))]).is_err());
+  }
+}
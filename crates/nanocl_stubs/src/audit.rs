@@ -0,0 +1,40 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// An entry of the audit log recorded for every mutating api call
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "PascalCase"))]
+pub struct AuditLog {
+  /// Key of the entry
+  pub key: uuid::Uuid,
+  /// When the call have been made
+  pub created_at: chrono::NaiveDateTime,
+  /// Who made the call: `token:<name>`, `cert:<common name>`, `unix` or `anonymous`
+  pub identity: String,
+  /// Address of the caller or `unix` for the unix socket
+  pub source: String,
+  /// Http method of the call
+  pub method: String,
+  /// Path of the call without the version
+  pub route: String,
+  /// Key of the object targeted by the call if any
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub object_key: Option<String>,
+  /// Http status code of the response
+  pub status: u16,
+  /// Sha256 of the json payload if any
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub payload_hash: Option<String>,
+  /// Whether the json payload was too large to be hashed
+  #[cfg_attr(feature = "serde", serde(default))]
+  pub payload_truncated: bool,
+}
@@ -0,0 +1,160 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Action allowed by a role rule
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "PascalCase"))]
+pub enum RoleVerb {
+  /// List, inspect, read logs and watch (GET)
+  Read,
+  /// Create, update, start, stop and exec (POST, PUT, PATCH)
+  Write,
+  /// Delete (DELETE)
+  Delete,
+  /// Every verb
+  All,
+}
+
+impl std::fmt::Display for RoleVerb {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Self::Read => write!(f, "Read"),
+      Self::Write => write!(f, "Write"),
+      Self::Delete => write!(f, "Delete"),
+      Self::All => write!(f, "All"),
+    }
+  }
+}
+
+impl std::str::FromStr for RoleVerb {
+  type Err = std::io::Error;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s.to_lowercase().as_str() {
+      "read" => Ok(Self::Read),
+      "write" => Ok(Self::Write),
+      "delete" => Ok(Self::Delete),
+      "all" | "*" => Ok(Self::All),
+      _ => Err(std::io::Error::new(
+        std::io::ErrorKind::InvalidInput,
+        format!("invalid verb {s} expected Read, Write, Delete or All"),
+      )),
+    }
+  }
+}
+
+/// A rule granting verbs on kinds of objects
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+  feature = "serde",
+  serde(deny_unknown_fields, rename_all = "PascalCase")
+)]
+pub struct RoleRule {
+  /// Verbs allowed by the rule
+  pub verbs: Vec<RoleVerb>,
+  /// Kinds of objects the rule apply to:
+  /// cargo, vm, job, secret, resource, namespace, role, token, audit, system or `*`
+  pub kinds: Vec<String>,
+  /// Namespaces the rule apply to, every namespace when not set.
+  /// Objects not scoped by a namespace like jobs or secrets
+  /// are only allowed by rules without namespaces
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub namespaces: Option<Vec<String>>,
+}
+
+/// Payload used to create a role
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+  feature = "serde",
+  serde(deny_unknown_fields, rename_all = "PascalCase")
+)]
+pub struct RolePartial {
+  /// Name of the role
+  pub name: String,
+  /// Rules of the role
+  pub rules: Vec<RoleRule>,
+}
+
+/// A role is a set of rules that can be given to a token
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "PascalCase"))]
+pub struct Role {
+  /// Name of the role
+  pub name: String,
+  /// When the role have been created
+  pub created_at: chrono::NaiveDateTime,
+  /// Rules of the role
+  pub rules: Vec<RoleRule>,
+}
+
+/// Payload used to create a token
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+  feature = "serde",
+  serde(deny_unknown_fields, rename_all = "PascalCase")
+)]
+pub struct TokenPartial {
+  /// Name of the token
+  pub name: String,
+  /// Roles given to the token
+  pub roles: Vec<String>,
+  /// When the token expire, never when not set
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub expires_at: Option<chrono::NaiveDateTime>,
+}
+
+/// A token used to authenticate on the api with the `Authorization` header
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "PascalCase"))]
+pub struct Token {
+  /// Name of the token
+  pub name: String,
+  /// When the token have been created
+  pub created_at: chrono::NaiveDateTime,
+  /// When the token expire
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub expires_at: Option<chrono::NaiveDateTime>,
+  /// Roles given to the token
+  pub roles: Vec<String>,
+}
+
+/// A token just created with its value, the value is only returned once
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "PascalCase"))]
+pub struct TokenCreated {
+  /// The token
+  #[cfg_attr(feature = "serde", serde(flatten))]
+  pub token: Token,
+  /// Value to send as `Authorization: Bearer <value>`
+  pub value: String,
+}
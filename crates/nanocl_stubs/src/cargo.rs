@@ -35,6 +35,12 @@ pub struct Cargo {
   pub created_at: chrono::NaiveDateTime,
   /// Status of the cargo
   pub status: ObjPsStatus,
+  /// Number of replicas chosen by the autoscaler
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub replicas: Option<usize>,
   /// Specification of the cargo
   pub spec: CargoSpec,
 }
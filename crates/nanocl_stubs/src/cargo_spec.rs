@@ -5,7 +5,9 @@ pub use bollard_next::container::Config;
 pub use bollard_next::models::HealthConfig;
 pub use bollard_next::models::HostConfig;
 
-use crate::generic::ImagePullPolicy;
+use crate::{
+  generic::ImagePullPolicy, secret::SecretMount, volume::VolumeMount,
+};
 
 /// Auto is used to automatically define that the number of replicas in the cluster
 /// Number is used to manually set the number of replicas
@@ -31,7 +33,8 @@ pub enum ReplicationMode {
   UniqueByNodeGroups { groups: Vec<String> },
   /// UniqueByNodeNames is used to ensure one replica is running on each node name
   UniqueByNodeNames { names: Vec<String> },
-  /// Number is used to manually set the number of replicas in one node
+  /// Number is used to manually set the number of replicas in the cluster
+  /// The scheduler will choose the best nodes to run them
   Static(ReplicationStatic),
   /// NumberByNodes is used to manually set the number of replicas in each node
   StaticByNodes(ReplicationStatic),
@@ -53,6 +56,251 @@ pub struct ReplicationStatic {
   pub number: usize,
 }
 
+/// Node constraints used by the scheduler to choose where cargo instances run.
+/// Used as an affinity it restrict the instances to the given nodes and node groups
+/// and prefer the nodes already running the given cargoes.
+/// Used as an anti affinity it exclude the given nodes and node groups
+/// and avoid the nodes already running the given cargoes.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+  feature = "serde",
+  serde(deny_unknown_fields, rename_all = "PascalCase")
+)]
+pub struct NodeAffinity {
+  /// Names of the nodes
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub nodes: Option<Vec<String>>,
+  /// Names of the node groups
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub node_groups: Option<Vec<String>>,
+  /// Keys of the cargoes eg: `my-cargo.global`
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub cargoes: Option<Vec<String>>,
+}
+
+/// Options of the rolling update of a cargo
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+  feature = "serde",
+  serde(deny_unknown_fields, rename_all = "PascalCase")
+)]
+pub struct CargoRollingUpdate {
+  /// Maximum number of instances created above the wanted number during the update
+  /// Default to 1
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub max_surge: Option<usize>,
+  /// Maximum number of instances that can be unavailable during the update
+  /// Default to 0
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub max_unavailable: Option<usize>,
+  /// Wait for the new instances to be healthy before removing the old ones
+  /// Instances without health check are ready once running. Default to true
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub wait_healthy: Option<bool>,
+  /// Time in seconds allowed for the new instances to be ready
+  /// before the update is rolled back to the previous spec. Default to 600
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub progress_deadline: Option<u64>,
+}
+
+/// Options of the blue/green deployment of a cargo
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+  feature = "serde",
+  serde(deny_unknown_fields, rename_all = "PascalCase")
+)]
+pub struct CargoBlueGreen {
+  /// Wait for the new instances to be healthy before the deployment is ready
+  /// Instances without health check are ready once running. Default to true
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub wait_healthy: Option<bool>,
+  /// Time in seconds allowed for the new instances to be ready
+  /// before the deployment is rolled back to the previous spec. Default to 600
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub progress_deadline: Option<u64>,
+}
+
+/// Options of the canary deployment of a cargo
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+  feature = "serde",
+  serde(deny_unknown_fields, rename_all = "PascalCase")
+)]
+pub struct CargoCanary {
+  /// Percentage of the traffic sent to the new instances until promoted
+  /// Default to 10
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub weight: Option<u8>,
+  /// Wait for the new instances to be healthy before the deployment is ready
+  /// Instances without health check are ready once running. Default to true
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub wait_healthy: Option<bool>,
+  /// Time in seconds allowed for the new instances to be ready
+  /// before the deployment is rolled back to the previous spec. Default to 600
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub progress_deadline: Option<u64>,
+}
+
+/// Strategy used to update the instances of a cargo when his spec change
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+  feature = "serde",
+  serde(deny_unknown_fields, tag = "Mode", rename_all = "PascalCase")
+)]
+pub enum CargoUpdateStrategy {
+  /// Create all the new instances then remove the old ones (default)
+  Replace,
+  /// Replace the instances by batches gated by their health status
+  /// And roll back to the previous spec on failure
+  RollingUpdate(CargoRollingUpdate),
+  /// Keep the previous instances serving the traffic next to the new ones
+  /// until the deployment is promoted or aborted
+  BlueGreen(CargoBlueGreen),
+  /// Keep the previous instances next to the new ones
+  /// and send a part of the traffic to the new ones
+  /// until the deployment is promoted or aborted
+  Canary(CargoCanary),
+}
+
+/// Metric watched by the autoscaler
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "PascalCase"))]
+pub enum AutoscaleMetric {
+  /// Cpu usage in percent of an instance
+  Cpu,
+  /// Http requests per second received by an instance through the proxy
+  RequestsPerSecond,
+}
+
+/// Value of a metric the autoscaler try to reach for each instance
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+  feature = "serde",
+  serde(deny_unknown_fields, rename_all = "PascalCase")
+)]
+pub struct AutoscaleTarget {
+  /// The watched metric
+  pub metric: AutoscaleMetric,
+  /// Wanted average value of the metric per instance
+  pub average: u32,
+}
+
+/// Horizontal autoscaling of the replicas of a cargo
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+  feature = "serde",
+  serde(deny_unknown_fields, rename_all = "PascalCase")
+)]
+pub struct CargoAutoscale {
+  /// Minimum number of replicas
+  pub min_replicas: usize,
+  /// Maximum number of replicas
+  pub max_replicas: usize,
+  /// Targets to reach, the highest number of replicas wanted is used
+  pub targets: Vec<AutoscaleTarget>,
+  /// Duration in seconds of the sliding window used to average the metrics
+  /// Default to 60
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub window: Option<u64>,
+  /// Minimum duration in seconds between a scale and a scale up
+  /// Default to 60
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub scale_up_cooldown: Option<u64>,
+  /// Minimum duration in seconds between a scale and a scale down
+  /// Default to 300
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub scale_down_cooldown: Option<u64>,
+}
+
+/// Budget of the instances of a cargo disrupted at the same time
+/// when the node running them is drained
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+  feature = "serde",
+  serde(deny_unknown_fields, rename_all = "PascalCase")
+)]
+pub struct CargoDisruptionBudget {
+  /// Maximum number of instances below the wanted number
+  /// while they are recreated on the other nodes. Default to 1
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub max_unavailable: Option<usize>,
+}
+
 /// A cargo spec partial is used to create a Cargo
 #[derive(Debug, Default, Clone, PartialEq)]
 #[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
@@ -84,6 +332,18 @@ pub struct CargoSpecPartial {
     serde(skip_serializing_if = "Option::is_none")
   )]
   pub secrets: Option<Vec<String>>,
+  /// List of `nanocl.io/file` secrets to mount as files
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub secret_mounts: Option<Vec<SecretMount>>,
+  /// List of volumes of the namespace to mount
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub volume_mounts: Option<Vec<VolumeMount>>,
   /// Secret to use when pulling the image
   #[cfg_attr(
     feature = "serde",
@@ -104,6 +364,36 @@ pub struct CargoSpecPartial {
     serde(skip_serializing_if = "Option::is_none")
   )]
   pub replication: Option<ReplicationMode>,
+  /// Nodes where the instances should run
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub node_affinity: Option<NodeAffinity>,
+  /// Nodes where the instances should not run
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub node_anti_affinity: Option<NodeAffinity>,
+  /// Strategy used to update the instances
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub update_strategy: Option<CargoUpdateStrategy>,
+  /// Autoscaling of the number of replicas
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub autoscale: Option<CargoAutoscale>,
+  /// Instances that can be unavailable while a node is drained
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub disruption_budget: Option<CargoDisruptionBudget>,
 }
 
 /// Payload used to patch a cargo
@@ -143,6 +433,18 @@ pub struct CargoSpecUpdate {
     serde(skip_serializing_if = "Option::is_none")
   )]
   pub secrets: Option<Vec<String>>,
+  /// List of `nanocl.io/file` secrets to mount as files
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub secret_mounts: Option<Vec<SecretMount>>,
+  /// List of volumes of the namespace to mount
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub volume_mounts: Option<Vec<VolumeMount>>,
   /// Secret to use when pulling the image
   #[cfg_attr(
     feature = "serde",
@@ -167,6 +469,36 @@ pub struct CargoSpecUpdate {
     serde(skip_serializing_if = "Option::is_none")
   )]
   pub replication: Option<ReplicationMode>,
+  /// Nodes where the instances should run
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub node_affinity: Option<NodeAffinity>,
+  /// Nodes where the instances should not run
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub node_anti_affinity: Option<NodeAffinity>,
+  /// Strategy used to update the instances
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub update_strategy: Option<CargoUpdateStrategy>,
+  /// Autoscaling of the number of replicas
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub autoscale: Option<CargoAutoscale>,
+  /// Instances that can be unavailable while a node is drained
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub disruption_budget: Option<CargoDisruptionBudget>,
 }
 
 impl From<CargoSpecPartial> for CargoSpecUpdate {
@@ -178,8 +510,15 @@ impl From<CargoSpecPartial> for CargoSpecUpdate {
       replication: spec.replication,
       metadata: spec.metadata,
       secrets: spec.secrets,
+      secret_mounts: spec.secret_mounts,
+      volume_mounts: spec.volume_mounts,
       image_pull_secret: spec.image_pull_secret,
       image_pull_policy: spec.image_pull_policy,
+      node_affinity: spec.node_affinity,
+      node_anti_affinity: spec.node_anti_affinity,
+      update_strategy: spec.update_strategy,
+      autoscale: spec.autoscale,
+      disruption_budget: spec.disruption_budget,
     }
   }
 }
@@ -222,6 +561,18 @@ pub struct CargoSpec {
     serde(skip_serializing_if = "Option::is_none")
   )]
   pub secrets: Option<Vec<String>>,
+  /// List of `nanocl.io/file` secrets to mount as files
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub secret_mounts: Option<Vec<SecretMount>>,
+  /// List of volumes of the namespace to mount
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub volume_mounts: Option<Vec<VolumeMount>>,
   /// Secret to use when pulling the image
   #[cfg_attr(
     feature = "serde",
@@ -242,6 +593,36 @@ pub struct CargoSpec {
     serde(skip_serializing_if = "Option::is_none")
   )]
   pub replication: Option<ReplicationMode>,
+  /// Nodes where the instances should run
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub node_affinity: Option<NodeAffinity>,
+  /// Nodes where the instances should not run
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub node_anti_affinity: Option<NodeAffinity>,
+  /// Strategy used to update the instances
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub update_strategy: Option<CargoUpdateStrategy>,
+  /// Autoscaling of the number of replicas
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub autoscale: Option<CargoAutoscale>,
+  /// Instances that can be unavailable while a node is drained
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub disruption_budget: Option<CargoDisruptionBudget>,
 }
 
 impl From<CargoSpec> for CargoSpecPartial {
@@ -253,8 +634,15 @@ impl From<CargoSpec> for CargoSpecPartial {
       container: spec.container,
       metadata: spec.metadata,
       secrets: spec.secrets,
+      secret_mounts: spec.secret_mounts,
+      volume_mounts: spec.volume_mounts,
       image_pull_secret: spec.image_pull_secret,
       image_pull_policy: spec.image_pull_policy,
+      node_affinity: spec.node_affinity,
+      node_anti_affinity: spec.node_anti_affinity,
+      update_strategy: spec.update_strategy,
+      autoscale: spec.autoscale,
+      disruption_budget: spec.disruption_budget,
     }
   }
 }
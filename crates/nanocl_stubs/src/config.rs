@@ -33,6 +33,25 @@ pub struct DaemonConfig {
   pub gid: u32,
   /// Optional ssl configuration
   pub ssl: Option<SslConfig>,
+  /// File containing the master key used to encrypt the secrets
+  pub master_key_file: Option<String>,
+  /// Master key used to encrypt the secrets encoded in base64
+  /// Loaded from the master key file or the `NANOCL_MASTER_KEY` env variable
+  #[cfg_attr(feature = "serde", serde(skip))]
+  pub master_key: Option<String>,
+  /// Require a token with the right roles for requests over tcp
+  #[cfg_attr(feature = "serde", serde(default))]
+  pub auth: bool,
+  /// Number of days the audit log entries are kept, forever when 0
+  #[cfg_attr(feature = "serde", serde(default = "default_audit_retention"))]
+  pub audit_retention: u32,
+  /// Cron expression to snapshot the store, never when not set
+  pub snapshot_schedule: Option<String>,
+  /// Number of scheduled store snapshots kept, all when 0
+  #[cfg_attr(feature = "serde", serde(default = "default_snapshot_retention"))]
+  pub snapshot_retention: u32,
+  /// Store snapshot restored at startup before reconciling the containers
+  pub restore_from: Option<String>,
 }
 
 /// Configuration File of the daemon
@@ -52,6 +71,16 @@ pub struct DaemonConfigFile {
   pub gateway: Option<String>,
   /// Hostname to use for the node automatically detected if not set
   pub hostname: Option<String>,
+  /// File containing the master key used to encrypt the secrets
+  pub master_key_file: Option<String>,
+  /// Require a token with the right roles for requests over tcp
+  pub auth: Option<bool>,
+  /// Number of days the audit log entries are kept, forever when 0
+  pub audit_retention: Option<u32>,
+  /// Cron expression to snapshot the store, never when not set
+  pub snapshot_schedule: Option<String>,
+  /// Number of scheduled store snapshots kept, all when 0
+  pub snapshot_retention: Option<u32>,
 }
 
 impl Default for DaemonConfig {
@@ -68,10 +97,25 @@ impl Default for DaemonConfig {
       nodes: Vec::default(),
       advertise_addr: String::default(),
       ssl: None,
+      master_key_file: None,
+      master_key: None,
+      auth: false,
+      audit_retention: default_audit_retention(),
+      snapshot_schedule: None,
+      snapshot_retention: default_snapshot_retention(),
+      restore_from: None,
     }
   }
 }
 
+fn default_audit_retention() -> u32 {
+  90
+}
+
+fn default_snapshot_retention() -> u32 {
+  7
+}
+
 fn default_host() -> String {
   "/var/run/docker.sock".to_owned()
 }
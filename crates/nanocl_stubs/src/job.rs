@@ -4,8 +4,212 @@ use bollard_next::container::Config;
 
 use crate::generic::ImagePullPolicy;
 use crate::process::Process;
+use crate::secret::SecretMount;
 use crate::system::{EventActor, EventActorKind, ObjPsStatus};
 
+/// Policy applied when a scheduled run is due while the job is still running
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "PascalCase"))]
+pub enum JobConcurrencyPolicy {
+  /// Start the job anyway
+  #[default]
+  Allow,
+  /// Skip the run
+  Forbid,
+  /// Stop the running job and start it again
+  Replace,
+}
+
+/// Outcome of a scheduled run of a job
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "PascalCase"))]
+pub enum JobRunStatus {
+  /// The job have been started
+  Started,
+  /// The job was still running and have been started again
+  Replaced,
+  /// The job was still running and the run have been skipped
+  Skipped,
+  /// The run couldn't start before his starting deadline
+  Missed,
+}
+
+impl std::fmt::Display for JobRunStatus {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      JobRunStatus::Started => write!(f, "started"),
+      JobRunStatus::Replaced => write!(f, "replaced"),
+      JobRunStatus::Skipped => write!(f, "skipped"),
+      JobRunStatus::Missed => write!(f, "missed"),
+    }
+  }
+}
+
+impl std::str::FromStr for JobRunStatus {
+  type Err = std::io::Error;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "started" => Ok(JobRunStatus::Started),
+      "replaced" => Ok(JobRunStatus::Replaced),
+      "skipped" => Ok(JobRunStatus::Skipped),
+      "missed" => Ok(JobRunStatus::Missed),
+      _ => Err(std::io::Error::new(
+        std::io::ErrorKind::InvalidInput,
+        format!("Invalid job run status {s}"),
+      )),
+    }
+  }
+}
+
+/// A scheduled run of a job
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "PascalCase"))]
+pub struct JobRun {
+  /// Unique identifier of the run
+  pub key: uuid::Uuid,
+  /// When the run have been recorded
+  pub created_at: chrono::NaiveDateTime,
+  /// Name of the job
+  pub job_key: String,
+  /// When the run was scheduled
+  pub scheduled_at: chrono::NaiveDateTime,
+  /// Outcome of the run
+  pub status: JobRunStatus,
+  /// Details about the outcome
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub note: Option<String>,
+}
+
+/// Growth of the delay between two attempts of a failed job
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "PascalCase"))]
+pub enum JobBackoffPolicy {
+  /// Wait the same delay before each attempt
+  Fixed,
+  /// Double the delay after each attempt
+  #[default]
+  Exponential,
+}
+
+/// Delay applied before retrying a failed job
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+  feature = "serde",
+  serde(deny_unknown_fields, rename_all = "PascalCase")
+)]
+pub struct JobBackoff {
+  /// How the delay grows between attempts. Default to Exponential
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub policy: Option<JobBackoffPolicy>,
+  /// Delay in seconds before the first retry. Default to 10
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub delay: Option<u64>,
+  /// Maximum delay in seconds between two attempts. Default to 300
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub max_delay: Option<u64>,
+}
+
+/// A named container of a job that can depend on other steps
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+  feature = "serde",
+  serde(deny_unknown_fields, rename_all = "PascalCase")
+)]
+pub struct JobStep {
+  /// Name of the step unique in the job
+  pub name: String,
+  /// Steps that must succeed before this one starts
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub depends_on: Option<Vec<String>>,
+  /// Container to run
+  pub container: Config,
+}
+
+/// Status of a step during the last run of a job
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "PascalCase"))]
+pub enum JobStepStatus {
+  /// Waiting for its dependencies
+  #[default]
+  Pending,
+  /// The container is running
+  Running,
+  /// The container exited successfully
+  Succeeded,
+  /// The container failed
+  Failed,
+  /// The step didn't run because the job failed
+  Skipped,
+}
+
+/// State of a step during the last run of a job
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "PascalCase"))]
+pub struct JobStepState {
+  /// Name of the step
+  pub name: String,
+  /// Status of the step
+  pub status: JobStepStatus,
+  /// When the step started
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub started_at: Option<chrono::NaiveDateTime>,
+  /// When the step ended
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub finished_at: Option<chrono::NaiveDateTime>,
+  /// Reason of the failure
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub note: Option<String>,
+}
+
 /// Job partial is used to create a new job
 #[derive(Debug, Default, Clone, PartialEq)]
 #[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
@@ -24,6 +228,12 @@ pub struct JobPartial {
   )]
   /// Secrets to load as environment variables
   pub secrets: Option<Vec<String>>,
+  /// List of `nanocl.io/file` secrets to mount as files
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub secret_mounts: Option<Vec<SecretMount>>,
   /// Metadata (user defined)
   #[cfg_attr(
     feature = "serde",
@@ -37,6 +247,52 @@ pub struct JobPartial {
     serde(skip_serializing_if = "Option::is_none")
   )]
   pub schedule: Option<String>,
+  /// What to do when a scheduled run is due while the job is still running
+  /// Default to Allow
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub concurrency_policy: Option<JobConcurrencyPolicy>,
+  /// Seconds after the scheduled time a run can still start
+  /// Runs starting later are recorded as missed
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub starting_deadline_seconds: Option<u64>,
+  /// Number of scheduled runs kept in the history. Default to 10
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub history_limit: Option<usize>,
+  /// Number of retries of a failed job before it's marked as failed
+  /// Default to 0
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub backoff_limit: Option<usize>,
+  /// Delay between two attempts of a failed job
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub backoff: Option<JobBackoff>,
+  /// Maximum duration in seconds of a run including its retries
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub active_deadline_seconds: Option<u64>,
+  /// Maximum duration in seconds of each container
+  /// A container running longer is stopped and the attempt fails
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub container_timeout_seconds: Option<u64>,
   /// Remove the job after (x) seconds after execution
   #[cfg_attr(
     feature = "serde",
@@ -54,7 +310,22 @@ pub struct JobPartial {
     serde(skip_serializing_if = "Option::is_none")
   )]
   pub image_pull_policy: Option<ImagePullPolicy>,
-  /// List of container to run
+  /// Jobs that must finish before this one is started
+  /// The job starts once all of them are finished
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub depends_on: Option<Vec<String>>,
+  /// Named steps to run concurrently according to their dependencies
+  /// Cannot be used with containers
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub steps: Option<Vec<JobStep>>,
+  /// List of container to run in sequence
+  #[cfg_attr(feature = "serde", serde(default))]
   pub containers: Vec<Config>,
 }
 
@@ -64,9 +335,19 @@ impl From<Job> for JobPartial {
     JobPartial {
       name: job.name,
       secrets: job.secrets,
+      secret_mounts: job.secret_mounts,
       metadata: job.metadata,
       schedule: job.schedule,
+      concurrency_policy: job.concurrency_policy,
+      starting_deadline_seconds: job.starting_deadline_seconds,
+      history_limit: job.history_limit,
+      backoff_limit: job.backoff_limit,
+      backoff: job.backoff,
+      active_deadline_seconds: job.active_deadline_seconds,
+      container_timeout_seconds: job.container_timeout_seconds,
       ttl: job.ttl,
+      depends_on: job.depends_on,
+      steps: job.steps,
       containers: job.containers,
       image_pull_secret: job.image_pull_secret,
       image_pull_policy: job.image_pull_policy,
@@ -95,6 +376,12 @@ pub struct Job {
     serde(skip_serializing_if = "Option::is_none")
   )]
   pub secrets: Option<Vec<String>>,
+  /// List of `nanocl.io/file` secrets to mount as files
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub secret_mounts: Option<Vec<SecretMount>>,
   /// Metadata (user defined)
   #[cfg_attr(
     feature = "serde",
@@ -108,6 +395,52 @@ pub struct Job {
     serde(skip_serializing_if = "Option::is_none")
   )]
   pub schedule: Option<String>,
+  /// What to do when a scheduled run is due while the job is still running
+  /// Default to Allow
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub concurrency_policy: Option<JobConcurrencyPolicy>,
+  /// Seconds after the scheduled time a run can still start
+  /// Runs starting later are recorded as missed
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub starting_deadline_seconds: Option<u64>,
+  /// Number of scheduled runs kept in the history. Default to 10
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub history_limit: Option<usize>,
+  /// Number of retries of a failed job before it's marked as failed
+  /// Default to 0
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub backoff_limit: Option<usize>,
+  /// Delay between two attempts of a failed job
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub backoff: Option<JobBackoff>,
+  /// Maximum duration in seconds of a run including its retries
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub active_deadline_seconds: Option<u64>,
+  /// Maximum duration in seconds of each container
+  /// A container running longer is stopped and the attempt fails
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub container_timeout_seconds: Option<u64>,
   /// Remove the job after (x) seconds after execution
   #[cfg_attr(
     feature = "serde",
@@ -126,6 +459,18 @@ pub struct Job {
     serde(skip_serializing_if = "Option::is_none")
   )]
   pub image_pull_policy: Option<ImagePullPolicy>,
+  /// Jobs that must finish before this one is started
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub depends_on: Option<Vec<String>>,
+  /// Named steps to run concurrently according to their dependencies
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub steps: Option<Vec<JobStep>>,
   /// Containers to run
   pub containers: Vec<Config>,
 }
@@ -183,6 +528,16 @@ pub struct JobInspect {
   pub spec: Job,
   /// List of instances
   pub instances: Vec<Process>,
+  /// Number of attempts of the last run
+  pub attempts: usize,
+  /// Reason of the last failed attempt
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub last_failure: Option<String>,
+  /// State of the steps during the last run
+  pub steps: Vec<JobStepState>,
 }
 
 /// Convert a job inspect into a job partial
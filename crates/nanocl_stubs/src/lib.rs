@@ -1,6 +1,8 @@
 pub mod generic;
 pub mod system;
 
+pub mod audit;
+pub mod auth;
 pub mod cargo;
 pub mod cargo_spec;
 pub mod config;
@@ -8,6 +10,7 @@ pub mod dns;
 pub mod job;
 pub mod metric;
 pub mod namespace;
+pub mod network_policy;
 pub mod node;
 pub mod process;
 pub mod proxy;
@@ -18,3 +21,4 @@ pub mod statefile;
 pub mod vm;
 pub mod vm_image;
 pub mod vm_spec;
+pub mod volume;
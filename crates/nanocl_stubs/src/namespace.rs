@@ -6,6 +6,133 @@ use crate::{
   system::{EventActor, EventActorKind},
 };
 
+/// Name of the bridge network of the system namespace created at install
+pub const SYSTEM_NETWORK: &str = "nanoclbr0";
+
+/// Name of the bridge network isolating the objects of a namespace
+pub fn get_network_name(namespace: &str) -> String {
+  match namespace {
+    "system" => SYSTEM_NETWORK.to_owned(),
+    _ => format!("nanocl.{namespace}"),
+  }
+}
+
+/// Caps on the total resources used by the objects of a namespace.
+/// Jobs are not scoped by a namespace, they are accounted to the `global` namespace.
+/// Secrets are shared by the whole cluster and are not capped by a quota
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+  feature = "serde",
+  serde(deny_unknown_fields, rename_all = "PascalCase")
+)]
+pub struct NamespaceQuota {
+  /// Total number of cpus, `0.5` is half a cpu
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub cpus: Option<f64>,
+  /// Total memory in bytes
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub memory: Option<i64>,
+  /// Total number of cargo instances, virtual machines and job containers
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub instances: Option<usize>,
+  /// Total size of the virtual machine disks in GB
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub vm_disk_size: Option<u64>,
+}
+
+/// Resources of a single container
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+  feature = "serde",
+  serde(deny_unknown_fields, rename_all = "PascalCase")
+)]
+pub struct ContainerLimits {
+  /// Number of cpus, `0.5` is half a cpu
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub cpus: Option<f64>,
+  /// Memory in bytes
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub memory: Option<i64>,
+}
+
+/// Default and maximum resources of each container of a namespace
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+  feature = "serde",
+  serde(deny_unknown_fields, rename_all = "PascalCase")
+)]
+pub struct NamespaceLimitRange {
+  /// Limits given to the containers that don't set them
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub default: Option<ContainerLimits>,
+  /// Limits a container cannot exceed
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub max: Option<ContainerLimits>,
+}
+
+/// Resources used by the objects of a namespace
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "PascalCase"))]
+pub struct NamespaceUsage {
+  /// Number of cpus
+  pub cpus: f64,
+  /// Memory in bytes
+  pub memory: i64,
+  /// Number of cargo instances, virtual machines and job containers
+  pub instances: usize,
+  /// Size of the virtual machine disks in GB
+  pub vm_disk_size: u64,
+}
+
+impl std::ops::Add for NamespaceUsage {
+  type Output = Self;
+
+  fn add(self, other: Self) -> Self {
+    Self {
+      cpus: self.cpus + other.cpus,
+      memory: self.memory + other.memory,
+      instances: self.instances + other.instances,
+      vm_disk_size: self.vm_disk_size + other.vm_disk_size,
+    }
+  }
+}
+
 /// Namespace is a identifier for a set of cargoes
 /// It is used to group cargoes together
 #[derive(Clone, Debug)]
@@ -24,6 +151,18 @@ pub struct Namespace {
     serde(skip_serializing_if = "Option::is_none")
   )]
   pub metadata: Option<serde_json::Value>,
+  /// Caps on the total resources used by the namespace
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub quota: Option<NamespaceQuota>,
+  /// Default and maximum resources of each container of the namespace
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub limit_range: Option<NamespaceLimitRange>,
 }
 
 /// A Namespace partial is a payload used to create a new namespace
@@ -44,6 +183,42 @@ pub struct NamespacePartial {
     serde(skip_serializing_if = "Option::is_none")
   )]
   pub metadata: Option<serde_json::Value>,
+  /// Caps on the total resources used by the namespace
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub quota: Option<NamespaceQuota>,
+  /// Default and maximum resources of each container of the namespace
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub limit_range: Option<NamespaceLimitRange>,
+}
+
+/// Payload used to update the quota and limit range of a namespace
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+  feature = "serde",
+  serde(deny_unknown_fields, rename_all = "PascalCase")
+)]
+pub struct NamespaceUpdate {
+  /// Caps on the total resources used by the namespace
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub quota: Option<NamespaceQuota>,
+  /// Default and maximum resources of each container of the namespace
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub limit_range: Option<NamespaceLimitRange>,
 }
 
 /// A Namespace Summary is a summary of a namespace
@@ -79,6 +254,20 @@ pub struct NamespaceInspect {
   pub name: String,
   /// Number of cargoes
   pub cargoes: Vec<CargoInspect>,
+  /// Caps on the total resources used by the namespace
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub quota: Option<NamespaceQuota>,
+  /// Default and maximum resources of each container of the namespace
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub limit_range: Option<NamespaceLimitRange>,
+  /// Resources currently used by the namespace
+  pub usage: NamespaceUsage,
 }
 
 /// Convert a Namespace into an EventActor
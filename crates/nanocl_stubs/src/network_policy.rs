@@ -0,0 +1,111 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Resource kind of the network policies
+pub const NETWORK_POLICY_KIND: &str = "nanocl.io/network-policy";
+
+/// A peer of a network policy rule, exactly one of namespace or cidr is set
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+  feature = "serde",
+  serde(deny_unknown_fields, rename_all = "PascalCase")
+)]
+pub struct NetworkPolicyPeer {
+  /// Namespace of the peer
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub namespace: Option<String>,
+  /// Cargo of the namespace, every object of the namespace when not set
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub cargo: Option<String>,
+  /// Range of ip addresses in CIDR notation, eg: `10.0.0.0/8`
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub cidr: Option<String>,
+}
+
+/// A port of a network policy rule
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+  feature = "serde",
+  serde(deny_unknown_fields, rename_all = "PascalCase")
+)]
+pub struct NetworkPolicyPort {
+  /// Protocol of the port `tcp` or `udp` (default: tcp)
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub protocol: Option<String>,
+  /// Port number
+  pub port: u16,
+}
+
+/// Traffic allowed with some peers
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+  feature = "serde",
+  serde(deny_unknown_fields, rename_all = "PascalCase")
+)]
+pub struct NetworkPolicyRule {
+  /// Peers the traffic is allowed with
+  pub peers: Vec<NetworkPolicyPeer>,
+  /// Ports the traffic is allowed on, every port when not set
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub ports: Option<Vec<NetworkPolicyPort>>,
+}
+
+/// Data of a `nanocl.io/network-policy` resource.
+/// Namespaces are isolated from each other, ingress rules allow traffic
+/// into the target from other namespaces or CIDRs and deny the rest,
+/// egress rules restrict the traffic leaving the target
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+  feature = "serde",
+  serde(deny_unknown_fields, rename_all = "PascalCase")
+)]
+pub struct NetworkPolicy {
+  /// Namespace the policy apply to
+  pub namespace: String,
+  /// Cargo of the namespace the policy apply to,
+  /// every object of the namespace when not set
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub cargo: Option<String>,
+  /// Traffic allowed into the target, when set any other traffic is denied
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub ingress: Option<Vec<NetworkPolicyRule>>,
+  /// Traffic allowed out of the target, when set any other traffic is denied
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub egress: Option<Vec<NetworkPolicyRule>>,
+}
@@ -1,6 +1,50 @@
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
+use crate::system::{EventActor, EventActorKind};
+
+/// Status of a node known from its heartbeats
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum NodeStatus {
+  /// The node answered its last heartbeats
+  #[default]
+  Ready,
+  /// The node missed its last heartbeats
+  NotReady,
+  /// The node missed its heartbeats for too long,
+  /// its instances are rescheduled on the other nodes
+  Unreachable,
+}
+
+impl std::str::FromStr for NodeStatus {
+  type Err = std::io::Error;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "Ready" => Ok(Self::Ready),
+      "NotReady" => Ok(Self::NotReady),
+      "Unreachable" => Ok(Self::Unreachable),
+      _ => Err(std::io::Error::new(
+        std::io::ErrorKind::InvalidInput,
+        format!("Invalid node status {s}"),
+      )),
+    }
+  }
+}
+
+impl std::fmt::Display for NodeStatus {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Self::Ready => write!(f, "Ready"),
+      Self::NotReady => write!(f, "NotReady"),
+      Self::Unreachable => write!(f, "Unreachable"),
+    }
+  }
+}
+
 #[derive(Clone, Debug)]
 #[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
 #[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
@@ -21,4 +65,29 @@ pub struct Node {
   /// User defined metadata
   #[serde(skip_serializing_if = "Option::is_none")]
   pub metadata: Option<serde_json::Value>,
+  /// Status of the node
+  pub status: NodeStatus,
+  /// When the last heartbeat of the node was received
+  pub last_seen: chrono::NaiveDateTime,
+  /// Whether the node receives no new instances
+  pub cordoned: bool,
+  /// Whether the instances of the node are moved to the other nodes
+  pub draining: bool,
+}
+
+/// Convert a Node into an EventActor
+impl From<Node> for EventActor {
+  fn from(node: Node) -> Self {
+    Self {
+      key: Some(node.name),
+      kind: EventActorKind::Node,
+      attributes: Some(serde_json::json!({
+        "Endpoint": node.endpoint,
+        "Version": node.version,
+        "Status": node.status.to_string(),
+        "Cordoned": node.cordoned,
+        "Draining": node.draining,
+      })),
+    }
+  }
 }
@@ -10,6 +10,8 @@ use bollard_next::{
   },
 };
 
+use crate::cargo_spec::CargoSpec;
+
 /// Kind of process (Vm, Job, Cargo)
 #[derive(Clone, PartialEq, Debug)]
 #[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
@@ -118,6 +120,24 @@ pub struct Process {
   pub data: ContainerInspectResponse,
 }
 
+impl Process {
+  /// Whether the instance of a cargo was created with the given spec.
+  /// Instances created before their spec was labelled
+  /// belong to the spec when they were created after it.
+  pub fn is_from_spec(&self, spec: &CargoSpec) -> bool {
+    let label = self
+      .data
+      .config
+      .as_ref()
+      .and_then(|config| config.labels.as_ref())
+      .and_then(|labels| labels.get("io.nanocl.s"));
+    match label {
+      Some(spec_key) => *spec_key == spec.key.to_string(),
+      None => self.created_at >= spec.created_at,
+    }
+  }
+}
+
 /// Kind of Output
 #[derive(Debug)]
 #[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
@@ -57,6 +57,140 @@ pub struct ProxySslConfig {
 pub enum ProxySsl {
   Config(ProxySslConfig),
   Secret(String),
+  /// Certificate of the domain of an http rule issued by an ACME server
+  Acme(ProxySslAcme),
+}
+
+/// Settings of the ACME account requesting the certificates
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+  feature = "serde",
+  serde(deny_unknown_fields, rename_all = "PascalCase")
+)]
+pub struct ProxySslAcmeConfig {
+  /// Contact email of the account notified before the certificates expire
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub email: Option<String>,
+  /// Url of the ACME directory default to the one given to ncproxy
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub directory_url: Option<String>,
+}
+
+/// Request the certificate of the domain of an http rule with HTTP-01
+/// challenges, it's stored in a `nanocl.io/tls` secret and renewed before
+/// it expires
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+  feature = "serde",
+  serde(deny_unknown_fields, rename_all = "PascalCase")
+)]
+pub struct ProxySslAcme {
+  pub acme: ProxySslAcmeConfig,
+}
+
+/// Version of the TLS protocol
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ProxyTlsProtocol {
+  #[cfg_attr(feature = "serde", serde(rename = "TLSv1"))]
+  TlsV1,
+  #[cfg_attr(feature = "serde", serde(rename = "TLSv1.1"))]
+  TlsV1_1,
+  #[cfg_attr(feature = "serde", serde(rename = "TLSv1.2"))]
+  TlsV1_2,
+  #[cfg_attr(feature = "serde", serde(rename = "TLSv1.3"))]
+  TlsV1_3,
+}
+
+impl std::fmt::Display for ProxyTlsProtocol {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Self::TlsV1 => write!(f, "TLSv1"),
+      Self::TlsV1_1 => write!(f, "TLSv1.1"),
+      Self::TlsV1_2 => write!(f, "TLSv1.2"),
+      Self::TlsV1_3 => write!(f, "TLSv1.3"),
+    }
+  }
+}
+
+/// Protocols and ciphers accepted by a rule using ssl
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+  feature = "serde",
+  serde(deny_unknown_fields, rename_all = "PascalCase")
+)]
+pub struct ProxyTlsPolicy {
+  /// Accepted protocols default to `TLSv1.2` and `TLSv1.3`
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub protocols: Option<Vec<ProxyTlsProtocol>>,
+  /// Accepted ciphers in the openssl format default to `HIGH:!aNULL:!MD5`
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub ciphers: Option<String>,
+  /// Prefer the ciphers of the server over the ones of the client
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub prefer_server_ciphers: Option<bool>,
+}
+
+/// Strict-Transport-Security header telling browsers to only use https
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+  feature = "serde",
+  serde(deny_unknown_fields, rename_all = "PascalCase")
+)]
+pub struct ProxyHsts {
+  /// Send the header default to true
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub enabled: Option<bool>,
+  /// Seconds the browsers remember to use https default to 31536000
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub max_age: Option<u64>,
+  /// Apply the policy to the subdomains
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub include_sub_domains: Option<bool>,
+  /// Allow the domain to be added to the preload list of the browsers
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub preload: Option<bool>,
 }
 
 /// Config for targeting a cargo or a vm
@@ -91,6 +225,80 @@ pub struct UpstreamTarget {
     serde(skip_serializing_if = "Option::is_none")
   )]
   pub ssl: Option<ProxySsl>,
+  /// Policy used to balance the traffic between the instances
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub load_balancing: Option<Box<UpstreamLoadBalancing>>,
+  /// Probe the instances to stop sending traffic to the unhealthy ones
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub health_check: Option<Box<UpstreamHealthCheck>>,
+}
+
+/// Policy used to balance the traffic between the instances of an upstream
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "PascalCase"))]
+pub enum UpstreamLoadBalancing {
+  /// Send the requests to each instance in turn
+  #[default]
+  RoundRobin,
+  /// Send the requests to the instance with the least active connections
+  LeastConn,
+  /// Always send the requests of a client address to the same instance
+  IpHash,
+  /// Weight of the instances by the name of the node running them,
+  /// the instances on a node not listed have a weight of 1
+  Weighted(std::collections::HashMap<String, usize>),
+}
+
+/// Active health check of the instances of an upstream
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+  feature = "serde",
+  serde(deny_unknown_fields, rename_all = "PascalCase")
+)]
+pub struct UpstreamHealthCheck {
+  /// Http path requested expecting a 2xx or 3xx status,
+  /// only a tcp connection is opened when not set
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub path: Option<String>,
+  /// Seconds between two probes default to 5
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub interval: Option<u64>,
+  /// Seconds before a probe fails default to 2
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub timeout: Option<u64>,
+  /// Failed probes in a row marking an instance unhealthy default to 3
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub unhealthy_threshold: Option<u32>,
+  /// Successful probes in a row marking an instance healthy again default to 2
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub healthy_threshold: Option<u32>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -241,6 +449,19 @@ pub struct ProxyRuleStream {
     serde(skip_serializing_if = "Option::is_none")
   )]
   pub ssl: Option<ProxySsl>,
+  /// Protocols and ciphers accepted when using ssl
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub tls: Option<ProxyTlsPolicy>,
+  /// Server names routed to the target without terminating tls,
+  /// several rules with server names can share the same port
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub sni: Option<Vec<String>>,
   /// The target
   pub target: StreamTarget,
 }
@@ -275,6 +496,164 @@ pub struct LimitReq {
   pub delay: Option<usize>,
 }
 
+/// Rewrite of the path of the requests of a location
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+  feature = "serde",
+  serde(deny_unknown_fields, rename_all = "PascalCase")
+)]
+pub struct ProxyHttpRewrite {
+  /// Remove the path of the location from the requests
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub strip_prefix: Option<bool>,
+  /// Regex matching the path of the requests to rewrite
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub pattern: Option<String>,
+  /// New path of the requests matching the pattern,
+  /// the captured groups are available as `$1`, `$2`...
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub replacement: Option<String>,
+}
+
+/// Http basic authentication of a location
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+  feature = "serde",
+  serde(deny_unknown_fields, rename_all = "PascalCase")
+)]
+pub struct ProxyHttpBasicAuth {
+  /// Name of the `nanocl.io/htpasswd` secret with the `user:hash` entries
+  pub secret: String,
+  /// Realm shown by the browsers default to `Restricted`
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub realm: Option<String>,
+}
+
+/// Cross-origin resource sharing policy of a location
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+  feature = "serde",
+  serde(deny_unknown_fields, rename_all = "PascalCase")
+)]
+pub struct ProxyHttpCors {
+  /// Allowed origins like `https://example.com` or `*` for any
+  pub allow_origins: Vec<String>,
+  /// Allowed methods default to `GET, POST, PUT, PATCH, DELETE, OPTIONS`
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub allow_methods: Option<Vec<String>>,
+  /// Allowed request headers
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub allow_headers: Option<Vec<String>>,
+  /// Response headers readable by the browsers
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub expose_headers: Option<Vec<String>>,
+  /// Allow the requests with credentials
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub allow_credentials: Option<bool>,
+  /// Seconds the result of a preflight request can be cached
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub max_age: Option<u64>,
+}
+
+/// Timeouts in seconds of the connection to the target of a location
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+  feature = "serde",
+  serde(deny_unknown_fields, rename_all = "PascalCase")
+)]
+pub struct ProxyHttpTimeouts {
+  /// Timeout to connect to the target
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub connect: Option<u64>,
+  /// Timeout between two writes of the request
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub send: Option<u64>,
+  /// Timeout between two reads of the response
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub read: Option<u64>,
+}
+
+/// Page returned for some status codes of a location,
+/// either a `Uri` to redirect to or a `Body` served by the proxy
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+  feature = "serde",
+  serde(deny_unknown_fields, rename_all = "PascalCase")
+)]
+pub struct ProxyHttpErrorPage {
+  /// Status codes returning this page
+  pub codes: Vec<u16>,
+  /// Uri or url of the page
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub uri: Option<String>,
+  /// Content of the page
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub body: Option<String>,
+  /// Content type of the body default to `text/html`
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub content_type: Option<String>,
+}
+
 /// Defines a proxy rule location
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
@@ -313,6 +692,48 @@ pub struct ProxyHttpLocation {
     serde(skip_serializing_if = "Option::is_none")
   )]
   pub version: Option<f64>,
+  /// Rewrite the path of the requests before forwarding them
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub rewrite: Option<ProxyHttpRewrite>,
+  /// Require the users of a `nanocl.io/htpasswd` secret
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub basic_auth: Option<ProxyHttpBasicAuth>,
+  /// Cross-origin resource sharing policy
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub cors: Option<ProxyHttpCors>,
+  /// Extras header to add to the responses like `X-Frame-Options DENY`
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub response_headers: Option<Vec<String>>,
+  /// Maximum size of the request body like `10m`, 0 disable the check
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub max_body_size: Option<String>,
+  /// Timeouts of the connection to the target
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub timeouts: Option<ProxyHttpTimeouts>,
+  /// Pages returned instead of the error responses
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub error_pages: Option<Vec<ProxyHttpErrorPage>>,
 }
 
 /// Defines a proxy rule http config
@@ -349,6 +770,24 @@ pub struct ProxyRuleHttp {
     serde(skip_serializing_if = "Option::is_none")
   )]
   pub ssl: Option<ProxySsl>,
+  /// Protocols and ciphers accepted when using ssl
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub tls: Option<ProxyTlsPolicy>,
+  /// Redirect the http requests on port 80 to https when using ssl
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub https_redirect: Option<bool>,
+  /// Strict-Transport-Security header sent when using ssl
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub hsts: Option<ProxyHsts>,
   /// Path to extra config file to include
   #[cfg_attr(
     feature = "serde",
@@ -122,3 +122,59 @@ impl From<SecretPartial> for SecretUpdate {
     }
   }
 }
+
+/// Options to rotate the master key used to encrypt the secrets
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+  feature = "serde",
+  serde(deny_unknown_fields, rename_all = "PascalCase")
+)]
+pub struct SecretRotateKey {
+  /// The new master key encoded in base64 (32 bytes)
+  /// Generated when not set and the current key is loaded from a file
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub key: Option<String>,
+}
+
+/// Result of the rotation of the master key
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "PascalCase"))]
+pub struct SecretRotateKeyResult {
+  /// Identifier of the new master key
+  pub key_id: String,
+  /// Number of secrets encrypted with the new key
+  pub count: usize,
+}
+
+/// Mount the files of a `nanocl.io/file` secret inside a container.
+/// The data of the secret is a map of file name to file content,
+/// each file is created read-only in the target directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+  feature = "serde",
+  serde(deny_unknown_fields, rename_all = "PascalCase")
+)]
+pub struct SecretMount {
+  /// Name of the secret
+  pub secret: String,
+  /// Directory where the files are mounted inside the container
+  pub target: String,
+  /// Permissions of the files in octal notation. Default to `0400`
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub mode: Option<String>,
+}
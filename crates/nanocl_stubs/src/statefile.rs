@@ -3,7 +3,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
   cargo_spec::CargoSpecPartial, job::JobPartial, resource::ResourcePartial,
-  secret::SecretPartial, vm_spec::VmSpecPartial,
+  secret::SecretPartial, vm_spec::VmSpecPartial, volume::VolumePartial,
 };
 
 /// Statefile argument definition to pass to the Statefile
@@ -174,6 +174,12 @@ pub struct Statefile {
     serde(skip_serializing_if = "Option::is_none")
   )]
   pub resources: Option<Vec<ResourcePartial>>,
+  /// List of volumes to create in the namespace
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub volumes: Option<Vec<VolumePartial>>,
   /// List of cargoes to create and run
   #[cfg_attr(
     feature = "serde",
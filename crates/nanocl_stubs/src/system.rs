@@ -156,6 +156,25 @@ pub struct BinaryInfo {
   pub commit_id: String,
 }
 
+/// A consistent dump of every table of the store
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "PascalCase"))]
+pub struct StoreSnapshot {
+  /// Name of the snapshot
+  pub name: String,
+  /// Path of the archive on the node running the daemon
+  pub path: String,
+  /// Size of the archive in bytes
+  pub size: u64,
+  /// Version of the last migration applied to the dumped store
+  pub migration: String,
+  /// The creation date
+  pub created_at: chrono::NaiveDateTime,
+}
+
 /// Kind is the type of event related to the actor kind
 #[derive(Clone, Debug, PartialEq)]
 #[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
@@ -170,6 +189,8 @@ pub enum EventActorKind {
   Secret,
   Process,
   ContainerImage,
+  Volume,
+  Node,
 }
 
 impl std::fmt::Display for EventActorKind {
@@ -183,6 +204,8 @@ impl std::fmt::Display for EventActorKind {
       EventActorKind::Secret => write!(f, "Secret"),
       EventActorKind::Process => write!(f, "Process"),
       EventActorKind::ContainerImage => write!(f, "ContainerImage"),
+      EventActorKind::Volume => write!(f, "Volume"),
+      EventActorKind::Node => write!(f, "Node"),
     }
   }
 }
@@ -316,7 +339,9 @@ pub struct EventActor {
   pub attributes: Option<serde_json::Value>,
 }
 
+/// Partial event to create a new one
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[cfg_attr(feature = "serde", serde(rename_all = "PascalCase"))]
 pub struct EventPartial {
@@ -355,6 +380,7 @@ pub struct EventPartial {
   )]
   pub related: Option<EventActor>,
   /// Standard metadata.
+  #[cfg_attr(feature = "utoipa", schema(value_type = HashMap<String, Any>))]
   #[cfg_attr(
     feature = "serde",
     serde(skip_serializing_if = "Option::is_none")
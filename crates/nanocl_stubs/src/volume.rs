@@ -0,0 +1,193 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::system::{EventActor, EventActorKind};
+
+/// Storage backing a volume
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "PascalCase"))]
+pub enum VolumeKind {
+  /// A docker named volume
+  #[default]
+  Docker,
+  /// A directory on the host under the state directory
+  Host,
+}
+
+impl std::fmt::Display for VolumeKind {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      VolumeKind::Docker => write!(f, "Docker"),
+      VolumeKind::Host => write!(f, "Host"),
+    }
+  }
+}
+
+impl std::str::FromStr for VolumeKind {
+  type Err = std::io::Error;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "Docker" | "docker" => Ok(VolumeKind::Docker),
+      "Host" | "host" => Ok(VolumeKind::Host),
+      _ => Err(std::io::Error::new(
+        std::io::ErrorKind::InvalidInput,
+        format!("Invalid VolumeKind {s}"),
+      )),
+    }
+  }
+}
+
+/// A partial volume object. This is used to create a volume.
+/// A volume hold the persistent data of the cargoes of a namespace.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+  feature = "serde",
+  serde(deny_unknown_fields, rename_all = "PascalCase")
+)]
+pub struct VolumePartial {
+  /// The name of the volume
+  pub name: String,
+  /// The storage backing the volume
+  #[cfg_attr(feature = "serde", serde(default))]
+  pub kind: VolumeKind,
+  /// The metadata of the volume (user defined)
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  #[cfg_attr(feature = "utoipa", schema(value_type = HashMap<String, Any>))]
+  pub metadata: Option<serde_json::Value>,
+}
+
+/// A volume hold the persistent data of the cargoes of a namespace.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "test", derive(Default))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "PascalCase"))]
+pub struct Volume {
+  /// The key of the volume `{name}.{namespace}`
+  pub key: String,
+  /// The name of the volume
+  pub name: String,
+  /// The namespace of the volume
+  pub namespace_name: String,
+  /// The storage backing the volume
+  pub kind: VolumeKind,
+  /// Name of the docker volume or path of the directory on the host
+  pub source: String,
+  /// The creation date
+  pub created_at: chrono::NaiveDateTime,
+  /// The metadata of the volume (user defined)
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  #[cfg_attr(feature = "utoipa", schema(value_type = HashMap<String, Any>))]
+  pub metadata: Option<serde_json::Value>,
+}
+
+impl From<Volume> for VolumePartial {
+  fn from(volume: Volume) -> Self {
+    Self {
+      name: volume.name,
+      kind: volume.kind,
+      metadata: volume.metadata,
+    }
+  }
+}
+
+/// Convert a Volume into an EventActor
+impl From<Volume> for EventActor {
+  fn from(volume: Volume) -> Self {
+    Self {
+      key: Some(volume.key),
+      kind: EventActorKind::Volume,
+      attributes: Some(serde_json::json!({
+        "Name": volume.name,
+        "Namespace": volume.namespace_name,
+        "Kind": volume.kind,
+        "Metadata": volume.metadata,
+      })),
+    }
+  }
+}
+
+/// Detailed information about a volume
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "PascalCase"))]
+pub struct VolumeInspect {
+  /// The volume
+  #[cfg_attr(feature = "serde", serde(flatten))]
+  pub volume: Volume,
+  /// Size of the data in bytes when it can be computed
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub size: Option<u64>,
+  /// Keys of the cargoes mounting the volume
+  pub used_by: Vec<String>,
+}
+
+/// Mount a volume of the namespace inside the containers of a cargo
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+  feature = "serde",
+  serde(deny_unknown_fields, rename_all = "PascalCase")
+)]
+pub struct VolumeMount {
+  /// Name of the volume
+  pub volume: String,
+  /// Path where the volume is mounted inside the container
+  pub target: String,
+  /// Mount the volume read-only
+  #[cfg_attr(
+    feature = "serde",
+    serde(skip_serializing_if = "Option::is_none")
+  )]
+  pub read_only: Option<bool>,
+}
+
+/// A tarball of the data of a volume
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "PascalCase"))]
+pub struct VolumeSnapshot {
+  /// Name of the snapshot
+  pub name: String,
+  /// Size of the tarball in bytes
+  pub size: u64,
+  /// The creation date
+  pub created_at: chrono::NaiveDateTime,
+}
+
+/// Options to restore a volume from one of its snapshots
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+  feature = "serde",
+  serde(deny_unknown_fields, rename_all = "PascalCase")
+)]
+pub struct VolumeRestore {
+  /// Name of the snapshot to restore
+  pub snapshot: String,
+}
@@ -0,0 +1,42 @@
+use nanocl_error::http_client::HttpClientResult;
+
+use nanocl_stubs::audit::AuditLog;
+use nanocl_stubs::generic::{GenericCount, GenericFilter};
+
+use super::http_client::NanocldClient;
+
+impl NanocldClient {
+  /// ## Default path for the audit log
+  const AUDIT_PATH: &'static str = "/audit";
+
+  /// List the audit log entries, the most recent first
+  ///
+  /// ## Example
+  ///
+  /// ```no_run,ignore
+  /// use nanocld_client::NanocldClient;
+  ///
+  /// let client = NanocldClient::connect_to("http://localhost:8585", None);
+  /// let entries = client.list_audit(None).await?;
+  /// ```
+  pub async fn list_audit(
+    &self,
+    query: Option<&GenericFilter>,
+  ) -> HttpClientResult<Vec<AuditLog>> {
+    let query = Self::convert_query(query)?;
+    let res = self.send_get(Self::AUDIT_PATH, Some(&query)).await?;
+    Self::res_json(res).await
+  }
+
+  /// Count the audit log entries
+  pub async fn count_audit(
+    &self,
+    query: Option<&GenericFilter>,
+  ) -> HttpClientResult<GenericCount> {
+    let query = Self::convert_query(query)?;
+    let res = self
+      .send_get(&format!("{}/count", Self::AUDIT_PATH), Some(&query))
+      .await?;
+    Self::res_json(res).await
+  }
+}
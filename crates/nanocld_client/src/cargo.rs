@@ -221,6 +221,56 @@ impl NanocldClient {
     Self::res_json(res).await
   }
 
+  /// Promote the deployment of a cargo by removing his previous instances
+  ///
+  /// ## Example
+  ///
+  /// ```no_run,ignore
+  /// use nanocld_client::NanocldClient;
+  ///
+  /// let client = NanocldClient::connect_to("http://localhost:8585", None);
+  /// let cargo = client.promote_cargo("my-cargo", None).await.unwrap();
+  /// ```
+  pub async fn promote_cargo(
+    &self,
+    name: &str,
+    namespace: Option<&str>,
+  ) -> HttpClientResult<Cargo> {
+    let res = self
+      .send_post(
+        &format!("{}/{name}/promote", Self::CARGO_PATH),
+        None::<String>,
+        Some(GenericNspQuery::new(namespace)),
+      )
+      .await?;
+    Self::res_json(res).await
+  }
+
+  /// Abort the deployment of a cargo and roll back to his previous spec
+  ///
+  /// ## Example
+  ///
+  /// ```no_run,ignore
+  /// use nanocld_client::NanocldClient;
+  ///
+  /// let client = NanocldClient::connect_to("http://localhost:8585", None);
+  /// let cargo = client.abort_cargo("my-cargo", None).await.unwrap();
+  /// ```
+  pub async fn abort_cargo(
+    &self,
+    name: &str,
+    namespace: Option<&str>,
+  ) -> HttpClientResult<Cargo> {
+    let res = self
+      .send_post(
+        &format!("{}/{name}/abort", Self::CARGO_PATH),
+        None::<String>,
+        Some(GenericNspQuery::new(namespace)),
+      )
+      .await?;
+    Self::res_json(res).await
+  }
+
   /// List all the instances of a cargo by it's name and namespace
   ///
   /// ## Example
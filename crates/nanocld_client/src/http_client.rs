@@ -26,6 +26,8 @@ pub struct ConnectOpts {
   pub version: Option<String>,
   /// Optional certificate path
   pub ssl: Option<SslConfig>,
+  /// Optional token sent as `Authorization: Bearer <token>`
+  pub token: Option<String>,
 }
 
 #[derive(Clone)]
@@ -34,6 +36,7 @@ pub struct NanocldClient {
   pub version: String,
   pub unix_socket: Option<String>,
   pub ssl: Option<SslConfig>,
+  pub token: Option<String>,
 }
 
 impl Default for ConnectOpts {
@@ -42,6 +45,7 @@ impl Default for ConnectOpts {
       url: String::from("unix:///run/nanocl/nanocl.sock"),
       version: None,
       ssl: None,
+      token: None,
     }
   }
 }
@@ -59,6 +63,7 @@ impl NanocldClient {
       version: format!("v{NANOCLD_DEFAULT_VERSION}"),
       url: "http://localhost".to_owned(),
       ssl: None,
+      token: None,
     }
   }
 
@@ -71,6 +76,7 @@ impl NanocldClient {
           url: url.to_owned(),
           ssl: opts.ssl.clone(),
           unix_socket: None,
+          token: opts.token.clone(),
           version: version.unwrap_or(format!("v{NANOCLD_DEFAULT_VERSION}")),
         })
       }
@@ -80,6 +86,7 @@ impl NanocldClient {
           ssl: None,
           url: "http://localhost".to_owned(),
           unix_socket: Some(path.to_owned()),
+          token: opts.token.clone(),
           version: version.unwrap_or(format!("v{NANOCLD_DEFAULT_VERSION}")),
         })
       }
@@ -97,6 +104,7 @@ impl NanocldClient {
       version: version.to_owned(),
       url: String::from("http://localhost"),
       ssl: None,
+      token: None,
     }
   }
 
@@ -178,58 +186,63 @@ impl NanocldClient {
     format!("{}/{}{}", self.url, self.version, url)
   }
 
+  /// Add the `Authorization` header when a token is set
+  fn with_token(
+    &self,
+    req: http::client::ClientRequest,
+  ) -> http::client::ClientRequest {
+    match &self.token {
+      Some(token) => req.header("Authorization", format!("Bearer {token}")),
+      None => req,
+    }
+  }
+
   fn get(&self, url: &str) -> IoResult<http::client::ClientRequest> {
-    Ok(
-      self
-        .gen_client()?
-        .get(self.gen_url(url))
-        .header("User-Agent", "nanocld_client"),
-    )
+    let req = self
+      .gen_client()?
+      .get(self.gen_url(url))
+      .header("User-Agent", "nanocld_client");
+    Ok(self.with_token(req))
   }
 
   fn delete(&self, url: &str) -> IoResult<http::client::ClientRequest> {
-    Ok(
-      self
-        .gen_client()?
-        .delete(self.gen_url(url))
-        .header("User-Agent", "nanocld_client"),
-    )
+    let req = self
+      .gen_client()?
+      .delete(self.gen_url(url))
+      .header("User-Agent", "nanocld_client");
+    Ok(self.with_token(req))
   }
 
   fn post(&self, url: &str) -> IoResult<http::client::ClientRequest> {
-    Ok(
-      self
-        .gen_client()?
-        .post(self.gen_url(url))
-        .header("User-Agent", "nanocld_client"),
-    )
+    let req = self
+      .gen_client()?
+      .post(self.gen_url(url))
+      .header("User-Agent", "nanocld_client");
+    Ok(self.with_token(req))
   }
 
   fn patch(&self, url: &str) -> IoResult<http::client::ClientRequest> {
-    Ok(
-      self
-        .gen_client()?
-        .patch(self.gen_url(url))
-        .header("User-Agent", "nanocld_client"),
-    )
+    let req = self
+      .gen_client()?
+      .patch(self.gen_url(url))
+      .header("User-Agent", "nanocld_client");
+    Ok(self.with_token(req))
   }
 
   fn put(&self, url: &str) -> IoResult<http::client::ClientRequest> {
-    Ok(
-      self
-        .gen_client()?
-        .put(self.gen_url(url))
-        .header("User-Agent", "nanocld_client"),
-    )
+    let req = self
+      .gen_client()?
+      .put(self.gen_url(url))
+      .header("User-Agent", "nanocld_client");
+    Ok(self.with_token(req))
   }
 
   fn head(&self, url: &str) -> IoResult<http::client::ClientRequest> {
-    Ok(
-      self
-        .gen_client()?
-        .head(self.gen_url(url))
-        .header("User-Agent", "nanocld_client"),
-    )
+    let req = self
+      .gen_client()?
+      .head(self.gen_url(url))
+      .header("User-Agent", "nanocld_client");
+    Ok(self.with_token(req))
   }
 
   pub async fn send_get<Q>(
@@ -433,6 +446,19 @@ impl NanocldClient {
     Ok(body)
   }
 
+  /// Read the body of a response as a raw stream of bytes
+  pub fn res_bytes(
+    res: http::client::ClientResponse,
+  ) -> impl Stream<Item = Result<Bytes, HttpError>> {
+    res.into_stream().map(|item| {
+      item.map_err(|err| {
+        HttpError::internal_server_error(format!(
+          "Unable to read stream: {err}"
+        ))
+      })
+    })
+  }
+
   pub async fn res_stream<R>(
     res: http::client::ClientResponse,
   ) -> Receiver<Result<R, HttpError>>
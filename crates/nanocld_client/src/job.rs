@@ -2,7 +2,7 @@ use nanocl_error::http_client::HttpClientResult;
 
 use nanocl_stubs::{
   generic::GenericFilter,
-  job::{Job, JobInspect, JobPartial, JobSummary},
+  job::{Job, JobInspect, JobPartial, JobRun, JobSummary},
 };
 
 use super::http_client::NanocldClient;
@@ -50,6 +50,26 @@ impl NanocldClient {
     Self::res_json(res).await
   }
 
+  /// List the recent scheduled runs of a job by it's name
+  ///
+  /// ## Example
+  ///
+  /// ```no_run,ignore
+  /// use nanocld_client::NanocldClient;
+  ///
+  /// let client = NanocldClient::connect_to("http://localhost:8585", None);
+  /// let res = client.list_job_runs("my_job").await;
+  /// ```
+  pub async fn list_job_runs(
+    &self,
+    name: &str,
+  ) -> HttpClientResult<Vec<JobRun>> {
+    let res = self
+      .send_get(&format!("{}/{name}/runs", Self::JOB_PATH), None::<String>)
+      .await?;
+    Self::res_json(res).await
+  }
+
   /// Create a job from a [JobPartial](JobPartial)
   ///
   /// ## Example
@@ -134,6 +154,7 @@ mod tests {
         ttl: None,
         image_pull_secret: None,
         image_pull_policy: None,
+        ..Default::default()
       })
       .await
       .unwrap();
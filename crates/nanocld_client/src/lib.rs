@@ -1,5 +1,6 @@
 mod http_client;
 
+pub(crate) mod audit;
 pub(crate) mod cargo;
 pub(crate) mod exec;
 pub(crate) mod job;
@@ -9,10 +10,13 @@ pub(crate) mod node;
 pub(crate) mod process;
 pub(crate) mod resource;
 pub(crate) mod resource_kind;
+pub(crate) mod role;
 pub(crate) mod secret;
 pub(crate) mod system;
+pub(crate) mod token;
 pub(crate) mod vm;
 pub(crate) mod vm_image;
+pub(crate) mod volume;
 
 pub use bollard_next;
 pub mod error;
@@ -4,6 +4,7 @@ use nanocl_stubs::{
   generic::GenericFilter,
   namespace::{
     Namespace, NamespaceInspect, NamespacePartial, NamespaceSummary,
+    NamespaceUpdate,
   },
 };
 
@@ -40,6 +41,8 @@ impl NanocldClient {
     let new_item = NamespacePartial {
       name: name.to_owned(),
       metadata: None,
+      quota: None,
+      limit_range: None,
     };
     let res = self
       .send_post(Self::NAMESPACE_PATH, Some(new_item), None::<String>)
@@ -70,6 +73,39 @@ impl NanocldClient {
     Self::res_json(res).await
   }
 
+  /// Replace the quota and limit range of a namespace
+  ///
+  /// ## Example
+  ///
+  /// ```no_run,ignore
+  /// use nanocld_client::NanocldClient;
+  /// use nanocld_client::stubs::namespace::{NamespaceQuota, NamespaceUpdate};
+  ///
+  /// let client = NanocldClient::connect_to("http://localhost:8585", None);
+  /// let update = NamespaceUpdate {
+  ///   quota: Some(NamespaceQuota {
+  ///     instances: Some(10),
+  ///     ..Default::default()
+  ///   }),
+  ///   ..Default::default()
+  /// };
+  /// let res = client.put_namespace("my-namespace", &update).await;
+  /// ```
+  pub async fn put_namespace(
+    &self,
+    name: &str,
+    update: &NamespaceUpdate,
+  ) -> HttpClientResult<Namespace> {
+    let res = self
+      .send_put(
+        &format!("{}/{name}", Self::NAMESPACE_PATH),
+        Some(update),
+        None::<String>,
+      )
+      .await?;
+    Self::res_json(res).await
+  }
+
   /// Delete a namespace by it's name
   ///
   /// ## Example
@@ -107,6 +143,9 @@ mod tests {
     assert_eq!(namespace.name, NAMESPACE);
     let namespace = client.inspect_namespace(NAMESPACE).await.unwrap();
     assert_eq!(namespace.name, NAMESPACE);
+    let update = NamespaceUpdate::default();
+    let namespace = client.put_namespace(NAMESPACE, &update).await.unwrap();
+    assert_eq!(namespace.name, NAMESPACE);
     client.delete_namespace(NAMESPACE).await.unwrap();
   }
 }
@@ -23,6 +23,71 @@ impl NanocldClient {
     let res = self.send_get(Self::NODE_PATH, None::<String>).await?;
     Self::res_json(res).await
   }
+
+  /// Cordon a node so it receives no new instances
+  ///
+  /// ## Example
+  ///
+  /// ```no_run,ignore
+  /// use nanocld_client::NanocldClient;
+  ///
+  /// let client = NanocldClient::connect_to("http://localhost:8585", None);
+  /// let node = client.cordon_node("my-node").await.unwrap();
+  /// ```
+  ///
+  pub async fn cordon_node(&self, name: &str) -> HttpClientResult<Node> {
+    let res = self
+      .send_post(
+        &format!("{}/{name}/cordon", Self::NODE_PATH),
+        None::<String>,
+        None::<String>,
+      )
+      .await?;
+    Self::res_json(res).await
+  }
+
+  /// Uncordon a node so it receives new instances again and stop its drain
+  ///
+  /// ## Example
+  ///
+  /// ```no_run,ignore
+  /// use nanocld_client::NanocldClient;
+  ///
+  /// let client = NanocldClient::connect_to("http://localhost:8585", None);
+  /// let node = client.uncordon_node("my-node").await.unwrap();
+  /// ```
+  ///
+  pub async fn uncordon_node(&self, name: &str) -> HttpClientResult<Node> {
+    let res = self
+      .send_delete(
+        &format!("{}/{name}/cordon", Self::NODE_PATH),
+        None::<String>,
+      )
+      .await?;
+    Self::res_json(res).await
+  }
+
+  /// Cordon a node and move its instances to the other nodes in background
+  ///
+  /// ## Example
+  ///
+  /// ```no_run,ignore
+  /// use nanocld_client::NanocldClient;
+  ///
+  /// let client = NanocldClient::connect_to("http://localhost:8585", None);
+  /// let node = client.drain_node("my-node").await.unwrap();
+  /// ```
+  ///
+  pub async fn drain_node(&self, name: &str) -> HttpClientResult<Node> {
+    let res = self
+      .send_post(
+        &format!("{}/{name}/drain", Self::NODE_PATH),
+        None::<String>,
+        None::<String>,
+      )
+      .await?;
+    Self::res_json(res).await
+  }
 }
 
 #[cfg(test)]
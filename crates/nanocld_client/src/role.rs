@@ -0,0 +1,60 @@
+use nanocl_error::http_client::HttpClientResult;
+
+use nanocl_stubs::auth::{Role, RolePartial};
+use nanocl_stubs::generic::GenericFilter;
+
+use super::http_client::NanocldClient;
+
+impl NanocldClient {
+  /// ## Default path for roles
+  const ROLE_PATH: &'static str = "/roles";
+
+  /// List existing roles
+  ///
+  /// ## Example
+  ///
+  /// ```no_run,ignore
+  /// use nanocld_client::NanocldClient;
+  ///
+  /// let client = NanocldClient::connect_to("http://localhost:8585", None);
+  /// let roles = client.list_role(None).await?;
+  /// ```
+  pub async fn list_role(
+    &self,
+    query: Option<&GenericFilter>,
+  ) -> HttpClientResult<Vec<Role>> {
+    let query = Self::convert_query(query)?;
+    let res = self.send_get(Self::ROLE_PATH, Some(&query)).await?;
+    Self::res_json(res).await
+  }
+
+  /// Create a new role
+  pub async fn create_role(
+    &self,
+    item: &RolePartial,
+  ) -> HttpClientResult<Role> {
+    let res = self
+      .send_post(Self::ROLE_PATH, Some(item), None::<String>)
+      .await?;
+    Self::res_json(res).await
+  }
+
+  /// Inspect a role by its name
+  pub async fn inspect_role(&self, name: &str) -> HttpClientResult<Role> {
+    let res = self
+      .send_get(
+        &format!("{}/{name}/inspect", Self::ROLE_PATH),
+        None::<String>,
+      )
+      .await?;
+    Self::res_json(res).await
+  }
+
+  /// Delete a role by its name
+  pub async fn delete_role(&self, name: &str) -> HttpClientResult<()> {
+    self
+      .send_delete(&format!("{}/{name}", Self::ROLE_PATH), None::<String>)
+      .await?;
+    Ok(())
+  }
+}
@@ -1,7 +1,9 @@
 use nanocl_error::http_client::HttpClientResult;
 
 use nanocl_stubs::generic::GenericFilter;
-use nanocl_stubs::secret::{Secret, SecretPartial, SecretUpdate};
+use nanocl_stubs::secret::{
+  Secret, SecretPartial, SecretRotateKey, SecretRotateKeyResult, SecretUpdate,
+};
 
 use super::http_client::NanocldClient;
 
@@ -75,6 +77,50 @@ impl NanocldClient {
     Self::res_json(res).await
   }
 
+  /// Get a secret by it's key with its data decrypted
+  ///
+  /// ## Example
+  ///
+  /// ```no_run,ignore
+  /// use nanocld_client::NanocldClient;
+  ///
+  /// let client = NanocldClient::connect_to("http://localhost:8585", None);
+  /// let secret = client.reveal_secret("my-secret").await?;
+  /// ```
+  pub async fn reveal_secret(&self, key: &str) -> HttpClientResult<Secret> {
+    let res = self
+      .send_get(
+        &format!("{}/{key}/reveal", Self::SECRET_PATH),
+        None::<String>,
+      )
+      .await?;
+    Self::res_json(res).await
+  }
+
+  /// Rotate the master key used to encrypt the secrets
+  ///
+  /// ## Example
+  ///
+  /// ```no_run,ignore
+  /// use nanocld_client::NanocldClient;
+  ///
+  /// let client = NanocldClient::connect_to("http://localhost:8585", None);
+  /// let res = client.rotate_secret_key(&Default::default()).await?;
+  /// ```
+  pub async fn rotate_secret_key(
+    &self,
+    opts: &SecretRotateKey,
+  ) -> HttpClientResult<SecretRotateKeyResult> {
+    let res = self
+      .send_post(
+        &format!("{}/rotate-key", Self::SECRET_PATH),
+        Some(opts),
+        None::<String>,
+      )
+      .await?;
+    Self::res_json(res).await
+  }
+
   /// Delete a secret by it's key
   ///
   /// ## Example
@@ -119,6 +165,8 @@ mod tests {
     assert_eq!(secret.name, SECRET_NAME);
     let secret = client.inspect_secret(SECRET_NAME).await.unwrap();
     assert_eq!(secret.name, SECRET_NAME);
+    let secret = client.reveal_secret(SECRET_NAME).await.unwrap();
+    assert_eq!(secret.data, serde_json::json!({"key": "value"}));
     client.delete_secret(SECRET_NAME).await.unwrap();
   }
 }
@@ -3,7 +3,9 @@ use ntex::channel::mpsc::Receiver;
 use nanocl_error::http::HttpResult;
 use nanocl_error::http_client::HttpClientResult;
 
-use nanocl_stubs::system::{BinaryInfo, Event, EventCondition, HostInfo};
+use nanocl_stubs::system::{
+  BinaryInfo, Event, EventCondition, EventPartial, HostInfo, StoreSnapshot,
+};
 
 use super::http_client::NanocldClient;
 
@@ -47,6 +49,24 @@ impl NanocldClient {
     Ok(Self::res_stream(res).await)
   }
 
+  /// Report an event to the daemon
+  /// The reporting node default to the daemon one when empty
+  ///
+  /// ## Example
+  ///
+  /// ```no_run,ignore
+  /// use nanocld_client::NanocldClient;
+  ///
+  /// let client = NanocldClient::connect_to("http://localhost:8585", None);
+  /// let res = client.emit_event(&event).await;
+  /// ```
+  pub async fn emit_event(&self, event: &EventPartial) -> HttpClientResult<()> {
+    self
+      .send_post("/events", Some(event), None::<String>)
+      .await?;
+    Ok(())
+  }
+
   /// Check if the daemon is running
   ///
   /// ## Example
@@ -76,6 +96,22 @@ impl NanocldClient {
     let res = self.send_get("/info", None::<String>).await?;
     Self::res_json(res).await
   }
+
+  /// Dump every table of the daemon store into a new snapshot archive
+  pub async fn snapshot_store(&self) -> HttpClientResult<StoreSnapshot> {
+    let res = self
+      .send_post("/system/snapshot", None::<String>, None::<String>)
+      .await?;
+    Self::res_json(res).await
+  }
+
+  /// List the snapshots of the daemon store from the most recent
+  pub async fn list_store_snapshot(
+    &self,
+  ) -> HttpClientResult<Vec<StoreSnapshot>> {
+    let res = self.send_get("/system/snapshot", None::<String>).await?;
+    Self::res_json(res).await
+  }
 }
 
 #[cfg(test)]
@@ -0,0 +1,49 @@
+use nanocl_error::http_client::HttpClientResult;
+
+use nanocl_stubs::auth::{Token, TokenCreated, TokenPartial};
+use nanocl_stubs::generic::GenericFilter;
+
+use super::http_client::NanocldClient;
+
+impl NanocldClient {
+  /// ## Default path for tokens
+  const TOKEN_PATH: &'static str = "/tokens";
+
+  /// List existing tokens, their values are never returned
+  ///
+  /// ## Example
+  ///
+  /// ```no_run,ignore
+  /// use nanocld_client::NanocldClient;
+  ///
+  /// let client = NanocldClient::connect_to("http://localhost:8585", None);
+  /// let tokens = client.list_token(None).await?;
+  /// ```
+  pub async fn list_token(
+    &self,
+    query: Option<&GenericFilter>,
+  ) -> HttpClientResult<Vec<Token>> {
+    let query = Self::convert_query(query)?;
+    let res = self.send_get(Self::TOKEN_PATH, Some(&query)).await?;
+    Self::res_json(res).await
+  }
+
+  /// Create a new token, its value is only returned here
+  pub async fn create_token(
+    &self,
+    item: &TokenPartial,
+  ) -> HttpClientResult<TokenCreated> {
+    let res = self
+      .send_post(Self::TOKEN_PATH, Some(item), None::<String>)
+      .await?;
+    Self::res_json(res).await
+  }
+
+  /// Delete a token by its name
+  pub async fn delete_token(&self, name: &str) -> HttpClientResult<()> {
+    self
+      .send_delete(&format!("{}/{name}", Self::TOKEN_PATH), None::<String>)
+      .await?;
+    Ok(())
+  }
+}
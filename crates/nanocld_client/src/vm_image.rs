@@ -127,4 +127,27 @@ impl NanocldClient {
       .await?;
     Self::res_json(res).await
   }
+
+  /// Download the content of a base vm image by it's name
+  ///
+  /// ## Example
+  ///
+  /// ```no_run,ignore
+  /// use nanocld_client::NanocldClient;
+  ///
+  /// let client = NanocldClient::connect_to("http://localhost:8585", None);
+  /// let stream = client.export_vm_image("my-image").await?;
+  /// ```
+  pub async fn export_vm_image(
+    &self,
+    name: &str,
+  ) -> HttpClientResult<impl Stream<Item = HttpResult<Bytes>>> {
+    let res = self
+      .send_get(
+        &format!("{}/{name}/export", Self::VM_IMAGE_PATH),
+        None::<String>,
+      )
+      .await?;
+    Ok(Self::res_bytes(res))
+  }
 }
@@ -0,0 +1,188 @@
+use std::error::Error;
+
+use futures::Stream;
+use ntex::util::Bytes;
+
+use nanocl_error::http::HttpResult;
+use nanocl_error::http_client::HttpClientResult;
+
+use nanocl_stubs::generic::{GenericFilterNsp, GenericNspQuery};
+use nanocl_stubs::volume::{
+  Volume, VolumeInspect, VolumePartial, VolumeRestore, VolumeSnapshot,
+};
+
+use super::http_client::NanocldClient;
+
+impl NanocldClient {
+  /// ## Default path for volumes
+  const VOLUME_PATH: &'static str = "/volumes";
+
+  /// List existing volumes of a namespace
+  ///
+  /// ## Example
+  ///
+  /// ```no_run,ignore
+  /// use nanocld_client::NanocldClient;
+  ///
+  /// let client = NanocldClient::connect_to("http://localhost:8585", None);
+  /// let res = client.list_volume(None).await;
+  /// ```
+  pub async fn list_volume(
+    &self,
+    query: Option<&GenericFilterNsp>,
+  ) -> HttpClientResult<Vec<Volume>> {
+    let query = Self::convert_query(query)?;
+    let res = self.send_get(Self::VOLUME_PATH, Some(query)).await?;
+    Self::res_json(res).await
+  }
+
+  /// Create a new volume in a namespace
+  pub async fn create_volume(
+    &self,
+    item: &VolumePartial,
+    namespace: Option<&str>,
+  ) -> HttpClientResult<Volume> {
+    let res = self
+      .send_post(
+        Self::VOLUME_PATH,
+        Some(item),
+        Some(&GenericNspQuery::new(namespace)),
+      )
+      .await?;
+    Self::res_json(res).await
+  }
+
+  /// Inspect a volume by it's name and namespace
+  /// to get its size and the cargoes using it
+  ///
+  /// ## Example
+  ///
+  /// ```no_run,ignore
+  /// use nanocld_client::NanocldClient;
+  ///
+  /// let client = NanocldClient::connect_to("http://localhost:8585", None);
+  /// let res = client.inspect_volume("my-volume", None).await;
+  /// ```
+  pub async fn inspect_volume(
+    &self,
+    name: &str,
+    namespace: Option<&str>,
+  ) -> HttpClientResult<VolumeInspect> {
+    let res = self
+      .send_get(
+        &format!("{}/{name}/inspect", Self::VOLUME_PATH),
+        Some(&GenericNspQuery::new(namespace)),
+      )
+      .await?;
+    Self::res_json(res).await
+  }
+
+  /// Delete a volume by it's name and namespace
+  ///
+  /// ## Example
+  ///
+  /// ```no_run,ignore
+  /// use nanocld_client::NanocldClient;
+  ///
+  /// let client = NanocldClient::connect_to("http://localhost:8585", None);
+  /// let res = client.delete_volume("my-volume", None).await;
+  /// ```
+  pub async fn delete_volume(
+    &self,
+    name: &str,
+    namespace: Option<&str>,
+  ) -> HttpClientResult<()> {
+    self
+      .send_delete(
+        &format!("{}/{name}", Self::VOLUME_PATH),
+        Some(&GenericNspQuery::new(namespace)),
+      )
+      .await?;
+    Ok(())
+  }
+
+  /// Snapshot the data of a volume into a tarball
+  pub async fn snapshot_volume(
+    &self,
+    name: &str,
+    namespace: Option<&str>,
+  ) -> HttpClientResult<VolumeSnapshot> {
+    let res = self
+      .send_post(
+        &format!("{}/{name}/snapshots", Self::VOLUME_PATH),
+        None::<String>,
+        Some(&GenericNspQuery::new(namespace)),
+      )
+      .await?;
+    Self::res_json(res).await
+  }
+
+  /// List the snapshots of a volume newest first
+  pub async fn list_volume_snapshot(
+    &self,
+    name: &str,
+    namespace: Option<&str>,
+  ) -> HttpClientResult<Vec<VolumeSnapshot>> {
+    let res = self
+      .send_get(
+        &format!("{}/{name}/snapshots", Self::VOLUME_PATH),
+        Some(&GenericNspQuery::new(namespace)),
+      )
+      .await?;
+    Self::res_json(res).await
+  }
+
+  /// Replace the data of a volume with one of its snapshots
+  pub async fn restore_volume(
+    &self,
+    name: &str,
+    opts: &VolumeRestore,
+    namespace: Option<&str>,
+  ) -> HttpClientResult<()> {
+    self
+      .send_post(
+        &format!("{}/{name}/restore", Self::VOLUME_PATH),
+        Some(opts),
+        Some(&GenericNspQuery::new(namespace)),
+      )
+      .await?;
+    Ok(())
+  }
+
+  /// Download the tarball of a snapshot of a volume
+  pub async fn export_volume_snapshot(
+    &self,
+    name: &str,
+    snapshot: &str,
+    namespace: Option<&str>,
+  ) -> HttpClientResult<impl Stream<Item = HttpResult<Bytes>>> {
+    let res = self
+      .send_get(
+        &format!("{}/{name}/snapshots/{snapshot}/export", Self::VOLUME_PATH),
+        Some(&GenericNspQuery::new(namespace)),
+      )
+      .await?;
+    Ok(Self::res_bytes(res))
+  }
+
+  /// Upload a tarball as a new snapshot of a volume
+  pub async fn import_volume_snapshot<S, E>(
+    &self,
+    name: &str,
+    stream: S,
+    namespace: Option<&str>,
+  ) -> HttpClientResult<VolumeSnapshot>
+  where
+    S: Stream<Item = Result<Bytes, E>> + Unpin + 'static,
+    E: Error + 'static,
+  {
+    let res = self
+      .send_post_stream(
+        &format!("{}/{name}/snapshots/import", Self::VOLUME_PATH),
+        stream,
+        Some(&GenericNspQuery::new(namespace)),
+      )
+      .await?;
+    Self::res_json(res).await
+  }
+}